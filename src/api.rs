@@ -0,0 +1,611 @@
+//! Public API surface - the library as its users see it
+//!
+//! Lists every item of [`semver::public_api`] once, under its canonical
+//! path (the shortest public path, so `crate::Config` rather than
+//! `crate::config::Config` when both work), with the other paths as
+//! aliases. Declarations are rendered from the source with full signatures
+//! (generics, `where` clauses, qualifiers); structs list their public
+//! fields, enums their variants and traits their items. Types list the
+//! trait impls on them, derived or written out anywhere in the library.
+//! Files that don't parse fall back to the signatures the parser recorded.
+
+use crate::diff::{self, kind_label, signature};
+use crate::parser::is_cfg_test;
+use crate::resolve::PathResolver;
+use crate::semver;
+use crate::types::*;
+use quote::{ToTokens, quote};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use syn::visit::{self, Visit};
+
+/// A public item under its canonical path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiEntry {
+    /// Shortest public path, starting with the crate name
+    pub path: String,
+    /// Item kind (`fn`, `struct`, `trait`, ...)
+    pub kind: String,
+    /// Declaration with the path as its name (`pub fn my_crate::load(path: &Path) -> Config`)
+    pub declaration: String,
+    /// Public fields, variants or trait items
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<String>,
+    /// Trait impls on the type (`impl Clone for Config`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trait_impls: Vec<String>,
+    /// Other public paths of the same item
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// File relative to the analyzed root
+    pub file: PathBuf,
+    pub line: usize,
+}
+
+/// The public API of a library crate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiSurface {
+    pub crate_name: String,
+    pub items: Vec<ApiEntry>,
+}
+
+impl ApiSurface {
+    /// Collect the public API of the analyzed library. Paths are prefixed
+    /// with `crate_name`.
+    pub fn build(analysis: &ProjectAnalysis, crate_name: &str) -> Self {
+        let resolver = PathResolver::build(crate_name, &analysis.root, &analysis.files);
+        let api = semver::public_api(&resolver);
+
+        // Render every library file once
+        let mut rendered: HashMap<PathBuf, Vec<Rendered>> = HashMap::new();
+        let mut trait_impls: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for file in &analysis.files {
+            if !semver::is_library_file(&diff::relative_path(analysis, file)) {
+                continue;
+            }
+            let Some(syntax) = std::fs::read_to_string(&file.path)
+                .ok()
+                .and_then(|source| syn::parse_file(&source).ok())
+            else {
+                continue;
+            };
+            let mut renderer = Renderer::default();
+            renderer.visit_file(&syntax);
+            for (self_type, header) in renderer.impls {
+                trait_impls.entry(self_type).or_default().push(header);
+            }
+            rendered.insert(file.path.clone(), renderer.items);
+        }
+
+        // Paths of each item, shortest first
+        let mut paths: Vec<(&ParsedItem, PathBuf, Vec<String>)> = Vec::new();
+        for (path, api_item) in &api {
+            let path = format!("{}::{}", crate_name, path);
+            match paths
+                .iter_mut()
+                .find(|(item, _, _)| std::ptr::eq(*item, api_item.item))
+            {
+                Some((_, _, item_paths)) => item_paths.push(path),
+                None => paths.push((api_item.item, api_item.file.clone(), vec![path])),
+            }
+        }
+
+        let mut items = Vec::new();
+        for (item, file, mut item_paths) in paths {
+            item_paths.sort_by_key(|p| (p.matches("::").count(), p.clone()));
+            let path = item_paths.remove(0);
+
+            let found = rendered.get(&item.file_path).and_then(|items| {
+                items.iter().find(|r| {
+                    r.name == item.name
+                        && item.span.start_line <= r.line
+                        && r.line <= item.span.end_line
+                })
+            });
+            let (declaration, members) = match found {
+                Some(r) => (
+                    format!("{}{}{}", r.before, path, r.after),
+                    r.members.clone(),
+                ),
+                None => (fallback_declaration(item, &path), Vec::new()),
+            };
+            let trait_impls = match item.kind {
+                ItemKind::Struct { .. } | ItemKind::Enum { .. } => {
+                    let mut impls = trait_impls.get(&item.name).cloned().unwrap_or_default();
+                    impls.sort();
+                    impls.dedup();
+                    impls
+                }
+                _ => Vec::new(),
+            };
+
+            items.push(ApiEntry {
+                path,
+                kind: kind_label(&item.kind).to_string(),
+                declaration,
+                members,
+                trait_impls,
+                aliases: item_paths,
+                file,
+                line: item.span.start_line,
+            });
+        }
+        items.sort_by(|a, b| a.path.cmp(&b.path));
+
+        ApiSurface {
+            crate_name: crate_name.to_string(),
+            items,
+        }
+    }
+}
+
+/// Declaration from the parsed item alone (no generics or members)
+fn fallback_declaration(item: &ParsedItem, path: &str) -> String {
+    match signature(&item.kind) {
+        Some(sig) => {
+            let (qualifiers, rest) = sig.split_once("fn").unwrap_or(("", &sig));
+            format!("pub {}fn {}{}", qualifiers, path, rest)
+        }
+        None => format!("pub {} {}", kind_label(&item.kind), path),
+    }
+}
+
+/// Tokens as `quote` prints them (`& 'a str`, `Vec < u8 >`) in the usual
+/// style (`&'a str`, `Vec<u8>`), without trailing commas
+fn tidy(tokens: &str) -> String {
+    let is_word = |t: &str| t.starts_with(|c: char| c.is_alphanumeric() || c == '_' || c == '\'');
+
+    let mut out = String::new();
+    let mut previous = "";
+    for token in split_tokens(tokens) {
+        if matches!(token, ")" | "]" | ">" | "}") && previous == "," {
+            out.pop();
+        }
+        let attached = matches!(token, "," | ";" | ":" | "." | "?" | ")" | "]" | ">" | "::")
+            || matches!(previous, "(" | "[" | "<" | "::" | "&" | "*" | "#")
+            || (matches!(token, "(" | "<") && is_word(previous));
+        if !out.is_empty() && !attached {
+            out.push(' ');
+        }
+        out.push_str(token);
+        previous = token;
+    }
+    out
+}
+
+/// Split printed tokens on whitespace, with delimiters as tokens of their
+/// own (`quote` prints `(& str)` with the parentheses attached)
+fn split_tokens(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut in_string = false;
+    for (i, c) in text.char_indices() {
+        if in_string {
+            in_string = c != '"' || text[..i].ends_with('\\');
+            continue;
+        }
+        let delimiter = matches!(c, '(' | ')' | '[' | ']' | '{' | '}');
+        if c.is_whitespace() || delimiter {
+            if let Some(begin) = start.take() {
+                tokens.push(&text[begin..i]);
+            }
+            if delimiter {
+                tokens.push(&text[i..i + 1]);
+            }
+        } else {
+            in_string = c == '"';
+            start.get_or_insert(i);
+        }
+    }
+    if let Some(begin) = start {
+        tokens.push(&text[begin..]);
+    }
+    tokens
+}
+
+fn tokens(node: &impl ToTokens) -> String {
+    tidy(&node.to_token_stream().to_string())
+}
+
+/// Everything of a signature after the name: generics, parameters, return
+/// type and `where` clause
+fn signature_rest(sig: &syn::Signature) -> String {
+    let inputs = &sig.inputs;
+    let mut rest = format!("{}{}", tokens(&sig.generics), tokens(&quote!((#inputs))));
+    if let syn::ReturnType::Type(_, ty) = &sig.output {
+        rest.push_str(&format!(" -> {}", tokens(ty)));
+    }
+    rest.push_str(&where_clause(&sig.generics));
+    rest
+}
+
+/// Qualifiers and `fn` (`const unsafe fn `)
+fn signature_head(sig: &syn::Signature) -> String {
+    let syn::Signature {
+        constness,
+        asyncness,
+        unsafety,
+        abi,
+        ..
+    } = sig;
+    let qualifiers = tokens(&quote!(#constness #asyncness #unsafety #abi));
+    if qualifiers.is_empty() {
+        "fn ".to_string()
+    } else {
+        format!("{} fn ", qualifiers)
+    }
+}
+
+fn where_clause(generics: &syn::Generics) -> String {
+    match &generics.where_clause {
+        Some(clause) => format!(" {}", tokens(clause).trim_end_matches(',')),
+        None => String::new(),
+    }
+}
+
+/// Last path segment of a type, without generics (`&foo::Bar<T>` -> `Bar`)
+fn type_name(ty: &syn::Type) -> Option<String> {
+    match ty {
+        syn::Type::Path(path) => path.path.segments.last().map(|s| s.ident.to_string()),
+        syn::Type::Reference(reference) => type_name(&reference.elem),
+        syn::Type::Paren(paren) => type_name(&paren.elem),
+        syn::Type::Group(group) => type_name(&group.elem),
+        _ => None,
+    }
+}
+
+/// A declaration with the name left out
+struct Rendered {
+    name: String,
+    /// Line of the name
+    line: usize,
+    /// Text before the name (`pub struct `)
+    before: String,
+    /// Text after the name (`<T> where T: Clone`)
+    after: String,
+    members: Vec<String>,
+}
+
+#[derive(Default)]
+struct Renderer {
+    items: Vec<Rendered>,
+    /// (self type name, `impl Trait for Type`) for every trait impl
+    impls: Vec<(String, String)>,
+}
+
+impl Renderer {
+    fn push(&mut self, ident: &syn::Ident, before: String, after: String, members: Vec<String>) {
+        self.items.push(Rendered {
+            name: ident.to_string(),
+            line: ident.span().start().line,
+            before,
+            after,
+            members,
+        });
+    }
+
+    /// `impl Trait for Type` for each `#[derive(Trait)]`
+    fn derives(&mut self, attrs: &[syn::Attribute], ident: &syn::Ident, generics: &syn::Generics) {
+        let (_, type_generics, _) = generics.split_for_impl();
+        let self_type = tokens(&quote!(#ident #type_generics));
+        for attr in attrs.iter().filter(|a| a.path().is_ident("derive")) {
+            let Ok(paths) = attr.parse_args_with(
+                syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated,
+            ) else {
+                continue;
+            };
+            for path in paths {
+                self.impls.push((
+                    ident.to_string(),
+                    format!("impl {} for {}", tokens(&path), self_type),
+                ));
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for Renderer {
+    fn visit_item_mod(&mut self, item: &'ast syn::ItemMod) {
+        if !is_cfg_test(&item.attrs) {
+            visit::visit_item_mod(self, item);
+        }
+    }
+
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        let sig = &item.sig;
+        self.push(
+            &sig.ident,
+            format!("pub {}", signature_head(sig)),
+            signature_rest(sig),
+            Vec::new(),
+        );
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        if is_cfg_test(&item.attrs) {
+            return;
+        }
+        if let Some((negative, path, _)) = &item.trait_ {
+            if let Some(self_type) = type_name(&item.self_ty) {
+                let negative = if negative.is_some() { "!" } else { "" };
+                self.impls.push((
+                    self_type,
+                    format!(
+                        "{}impl{} {}{} for {}",
+                        if item.unsafety.is_some() {
+                            "unsafe "
+                        } else {
+                            ""
+                        },
+                        tokens(&item.generics),
+                        negative,
+                        tokens(path),
+                        tokens(&item.self_ty)
+                    ),
+                ));
+            }
+            return;
+        }
+        visit::visit_item_impl(self, item);
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        let sig = &item.sig;
+        self.push(
+            &sig.ident,
+            format!("pub {}", signature_head(sig)),
+            signature_rest(sig),
+            Vec::new(),
+        );
+    }
+
+    fn visit_item_struct(&mut self, item: &'ast syn::ItemStruct) {
+        let members = item
+            .fields
+            .iter()
+            .enumerate()
+            .filter(|(_, field)| matches!(field.vis, syn::Visibility::Public(_)))
+            .map(|(index, field)| {
+                let name = field
+                    .ident
+                    .as_ref()
+                    .map_or_else(|| index.to_string(), |ident| ident.to_string());
+                format!("pub {}: {}", name, tokens(&field.ty))
+            })
+            .collect();
+        self.push(
+            &item.ident,
+            "pub struct ".to_string(),
+            format!("{}{}", tokens(&item.generics), where_clause(&item.generics)),
+            members,
+        );
+        self.derives(&item.attrs, &item.ident, &item.generics);
+    }
+
+    fn visit_item_enum(&mut self, item: &'ast syn::ItemEnum) {
+        let members = item
+            .variants
+            .iter()
+            .map(|variant| {
+                // Fields without their attributes
+                let types = variant.fields.iter().map(|field| match &field.ident {
+                    Some(ident) => format!("{}: {}", ident, tokens(&field.ty)),
+                    None => tokens(&field.ty),
+                });
+                let types: Vec<String> = types.collect();
+                let mut text = variant.ident.to_string();
+                match &variant.fields {
+                    syn::Fields::Unit => {}
+                    syn::Fields::Unnamed(_) => text.push_str(&format!("({})", types.join(", "))),
+                    syn::Fields::Named(_) => text.push_str(&format!(" {{ {} }}", types.join(", "))),
+                }
+                if let Some((_, discriminant)) = &variant.discriminant {
+                    text.push_str(&format!(" = {}", tokens(discriminant)));
+                }
+                text
+            })
+            .collect();
+        self.push(
+            &item.ident,
+            "pub enum ".to_string(),
+            format!("{}{}", tokens(&item.generics), where_clause(&item.generics)),
+            members,
+        );
+        self.derives(&item.attrs, &item.ident, &item.generics);
+    }
+
+    fn visit_item_trait(&mut self, item: &'ast syn::ItemTrait) {
+        let mut before = String::from("pub ");
+        if item.unsafety.is_some() {
+            before.push_str("unsafe ");
+        }
+        if item.auto_token.is_some() {
+            before.push_str("auto ");
+        }
+        before.push_str("trait ");
+
+        let mut after = tokens(&item.generics);
+        if !item.supertraits.is_empty() {
+            let supertraits = &item.supertraits;
+            after.push_str(&format!(": {}", tokens(&quote!(#supertraits))));
+        }
+        after.push_str(&where_clause(&item.generics));
+
+        let members = item
+            .items
+            .iter()
+            .filter_map(|member| match member {
+                syn::TraitItem::Fn(f) => Some(format!(
+                    "{}{}{}{}",
+                    signature_head(&f.sig),
+                    f.sig.ident,
+                    signature_rest(&f.sig),
+                    if f.default.is_some() {
+                        " (provided)"
+                    } else {
+                        ""
+                    }
+                )),
+                syn::TraitItem::Type(t) => {
+                    let bounds = &t.bounds;
+                    Some(if bounds.is_empty() {
+                        format!("type {}", t.ident)
+                    } else {
+                        format!("type {}: {}", t.ident, tokens(&quote!(#bounds)))
+                    })
+                }
+                syn::TraitItem::Const(c) => Some(format!("const {}: {}", c.ident, tokens(&c.ty))),
+                _ => None,
+            })
+            .collect();
+        self.push(&item.ident, before, after, members);
+    }
+
+    fn visit_item_const(&mut self, item: &'ast syn::ItemConst) {
+        self.push(
+            &item.ident,
+            "pub const ".to_string(),
+            format!(": {}", tokens(&item.ty)),
+            Vec::new(),
+        );
+    }
+
+    fn visit_item_static(&mut self, item: &'ast syn::ItemStatic) {
+        let before = match item.mutability {
+            syn::StaticMutability::Mut(_) => "pub static mut ",
+            _ => "pub static ",
+        };
+        self.push(
+            &item.ident,
+            before.to_string(),
+            format!(": {}", tokens(&item.ty)),
+            Vec::new(),
+        );
+    }
+
+    fn visit_item_type(&mut self, item: &'ast syn::ItemType) {
+        self.push(
+            &item.ident,
+            "pub type ".to_string(),
+            format!("{} = {}", tokens(&item.generics), tokens(&item.ty)),
+            Vec::new(),
+        );
+    }
+
+    fn visit_item_macro(&mut self, item: &'ast syn::ItemMacro) {
+        if let Some(ident) = &item.ident {
+            self.push(
+                ident,
+                "macro_rules! ".to_string(),
+                String::new(),
+                Vec::new(),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::PartialParser;
+    use crate::snapshot::SNAPSHOT_VERSION;
+
+    #[test]
+    fn test_api_surface() {
+        let dir = std::env::temp_dir().join(format!("cargomap-api-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("src/config")).unwrap();
+        std::fs::write(
+            dir.join("src/lib.rs"),
+            "mod config;\npub mod io;\npub use config::{Config, Mode};\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("src/config.rs"),
+            r#"mod inner;
+pub use self::inner::Mode;
+
+#[derive(Debug, Clone)]
+pub struct Config<T> { pub port: u16, secret: T }
+
+impl<T: Default> Config<T> {
+    pub fn load<P: AsRef<std::path::Path>>(path: P) -> Result<Self, std::io::Error> where T: Clone { todo!() }
+    fn private(&self) {}
+}
+
+impl<T> std::fmt::Display for Config<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { todo!() }
+}
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("src/config/inner.rs"),
+            "pub enum Mode { Fast, Slow { delay: u32 } }\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("src/io.rs"),
+            "pub use crate::config::Config as Settings;\npub unsafe trait Sink: Send { fn write(&mut self, data: &[u8]) -> usize; fn flush(&mut self) {} }\n",
+        )
+        .unwrap();
+
+        let files = PartialParser::new().parse_project(&dir).unwrap();
+        let analysis = ProjectAnalysis {
+            version: SNAPSHOT_VERSION,
+            generator: String::new(),
+            root: dir.clone(),
+            files,
+            dependencies: Vec::new(),
+            call_graph: CallGraph::default(),
+            module_tree: ModuleTree::default(),
+            reference_map: ReferenceMap::default(),
+            crate_references: Vec::new(),
+        };
+        let surface = ApiSurface::build(&analysis, "demo");
+        std::fs::remove_dir_all(&dir).ok();
+
+        let paths: Vec<&str> = surface.items.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "demo::Config",
+                "demo::Config::load",
+                "demo::Mode",
+                "demo::io::Sink"
+            ]
+        );
+
+        let config = &surface.items[0];
+        assert_eq!(config.declaration, "pub struct demo::Config<T>");
+        assert_eq!(config.members, vec!["pub port: u16"]);
+        assert_eq!(config.aliases, vec!["demo::io::Settings"]);
+        assert_eq!(
+            config.trait_impls,
+            vec![
+                "impl Clone for Config<T>",
+                "impl Debug for Config<T>",
+                "impl<T> std::fmt::Display for Config<T>",
+            ]
+        );
+        assert_eq!(
+            surface.items[1].declaration,
+            "pub fn demo::Config::load<P: AsRef<std::path::Path>>(path: P) -> Result<Self, std::io::Error> where T: Clone"
+        );
+        assert_eq!(
+            surface.items[2].members,
+            vec!["Fast", "Slow { delay: u32 }"]
+        );
+        assert_eq!(
+            surface.items[3].declaration,
+            "pub unsafe trait demo::io::Sink: Send"
+        );
+        assert_eq!(
+            surface.items[3].members,
+            vec![
+                "fn write(&mut self, data: &[u8]) -> usize",
+                "fn flush(&mut self) (provided)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,302 @@
+//! Project configuration - scoring weights, ranking profiles and layering
+//! rules
+//!
+//! Configuration is read from `cargomap.toml` in the project root. The
+//! `[weights]` table overrides the compiled-in defaults from
+//! `gravity::weights`, and each `[profiles.<name>]` table layers further
+//! overrides on top of that:
+//!
+//! ```toml
+//! [weights]
+//! cross_module_usage = 20.0
+//!
+//! [profiles.tests]
+//! is_test_penalty = 40.0
+//! ```
+//!
+//! The "onboarding", "refactor" and "tests" profiles are built in; a
+//! profile of the same name in the config file extends the built-in one.
+//!
+//! `[[rules]]` tables declare architecture layering rules, checked by
+//! `cargomap check` (see [`crate::rules`]).
+
+use crate::gravity::weights;
+use crate::rules::LayerRule;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use thiserror::Error;
+
+/// Name of the config file looked up in the project root
+pub const CONFIG_FILE_NAME: &str = "cargomap.toml";
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("TOML parse error: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("Unknown profile '{name}' (available: {available})")]
+    UnknownProfile { name: String, available: String },
+    #[error("Invalid rule '{name}': {reason}")]
+    InvalidRule { name: String, reason: String },
+}
+
+/// Weights applied to each factor of the work-site score
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScoringWeights {
+    pub cross_module_usage: f64,
+    pub pub_visibility: f64,
+    pub generic_depth: f64,
+    pub is_test_penalty: f64,
+    pub site_bonus: f64,
+    pub utility_penalty: f64,
+    pub entry_distance_penalty: f64,
+    pub impl_richness: f64,
+    pub trait_impl: f64,
+    pub churn: f64,
+    pub recency: f64,
+    pub author_count: f64,
+    pub bug_fix: f64,
+    pub match_quality: f64,
+    pub semantic_similarity: f64,
+    pub complexity: f64,
+}
+
+impl Default for ScoringWeights {
+    fn default() -> Self {
+        Self {
+            cross_module_usage: weights::CROSS_MODULE_USAGE,
+            pub_visibility: weights::PUB_VISIBILITY,
+            generic_depth: weights::GENERIC_DEPTH,
+            is_test_penalty: weights::IS_TEST_PENALTY,
+            site_bonus: weights::SITE_BONUS,
+            utility_penalty: weights::UTILITY_PENALTY,
+            entry_distance_penalty: weights::ENTRY_DISTANCE_PENALTY,
+            impl_richness: weights::IMPL_RICHNESS,
+            trait_impl: weights::TRAIT_IMPL,
+            churn: weights::CHURN,
+            recency: weights::RECENCY,
+            author_count: weights::AUTHOR_COUNT,
+            bug_fix: weights::BUG_FIX,
+            match_quality: weights::MATCH_QUALITY,
+            semantic_similarity: weights::SEMANTIC_SIMILARITY,
+            complexity: weights::COMPLEXITY,
+        }
+    }
+}
+
+/// Partial set of weights; unset fields keep the value they are applied to
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WeightOverrides {
+    pub cross_module_usage: Option<f64>,
+    pub pub_visibility: Option<f64>,
+    pub generic_depth: Option<f64>,
+    pub is_test_penalty: Option<f64>,
+    pub site_bonus: Option<f64>,
+    pub utility_penalty: Option<f64>,
+    pub entry_distance_penalty: Option<f64>,
+    pub impl_richness: Option<f64>,
+    pub trait_impl: Option<f64>,
+    pub churn: Option<f64>,
+    pub recency: Option<f64>,
+    pub author_count: Option<f64>,
+    pub bug_fix: Option<f64>,
+    pub match_quality: Option<f64>,
+    pub semantic_similarity: Option<f64>,
+    pub complexity: Option<f64>,
+}
+
+impl WeightOverrides {
+    /// Apply the set fields on top of `weights`
+    pub fn apply(&self, weights: &mut ScoringWeights) {
+        let fields = [
+            (self.cross_module_usage, &mut weights.cross_module_usage),
+            (self.pub_visibility, &mut weights.pub_visibility),
+            (self.generic_depth, &mut weights.generic_depth),
+            (self.is_test_penalty, &mut weights.is_test_penalty),
+            (self.site_bonus, &mut weights.site_bonus),
+            (self.utility_penalty, &mut weights.utility_penalty),
+            (
+                self.entry_distance_penalty,
+                &mut weights.entry_distance_penalty,
+            ),
+            (self.impl_richness, &mut weights.impl_richness),
+            (self.trait_impl, &mut weights.trait_impl),
+            (self.churn, &mut weights.churn),
+            (self.recency, &mut weights.recency),
+            (self.author_count, &mut weights.author_count),
+            (self.bug_fix, &mut weights.bug_fix),
+            (self.match_quality, &mut weights.match_quality),
+            (self.semantic_similarity, &mut weights.semantic_similarity),
+            (self.complexity, &mut weights.complexity),
+        ];
+
+        for (value, slot) in fields {
+            if let Some(v) = value {
+                *slot = v;
+            }
+        }
+    }
+}
+
+/// Built-in ranking profiles
+fn builtin_profiles() -> HashMap<String, WeightOverrides> {
+    let mut profiles = HashMap::new();
+
+    // Newcomers want the public surface close to the entry point
+    profiles.insert(
+        "onboarding".to_string(),
+        WeightOverrides {
+            cross_module_usage: Some(20.0),
+            pub_visibility: Some(40.0),
+            entry_distance_penalty: Some(-15.0),
+            ..Default::default()
+        },
+    );
+
+    // Refactoring cares about blast radius, so heavily used and tangled
+    // items rise
+    profiles.insert(
+        "refactor".to_string(),
+        WeightOverrides {
+            cross_module_usage: Some(60.0),
+            utility_penalty: Some(25.0),
+            site_bonus: Some(0.0),
+            impl_richness: Some(10.0),
+            churn: Some(20.0),
+            bug_fix: Some(10.0),
+            complexity: Some(3.0),
+            ..Default::default()
+        },
+    );
+
+    // Finding test helpers: stop burying tests
+    profiles.insert(
+        "tests".to_string(),
+        WeightOverrides {
+            is_test_penalty: Some(80.0),
+            pub_visibility: Some(0.0),
+            ..Default::default()
+        },
+    );
+
+    profiles
+}
+
+/// Project-level cargomap configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectConfig {
+    /// Overrides applied to the compiled-in default weights
+    #[serde(default)]
+    pub weights: WeightOverrides,
+    /// Named profiles layered on top of `weights`
+    #[serde(default)]
+    pub profiles: HashMap<String, WeightOverrides>,
+    /// Architecture layering rules
+    #[serde(default)]
+    pub rules: Vec<LayerRule>,
+}
+
+impl ProjectConfig {
+    /// Load `cargomap.toml` from the project root, or defaults if it doesn't exist
+    pub fn load(project_root: &Path) -> Result<Self, ConfigError> {
+        let path = project_root.join(CONFIG_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        Self::parse(&content)
+    }
+
+    /// Parse configuration from TOML text
+    pub fn parse(content: &str) -> Result<Self, ConfigError> {
+        let config: Self = toml::from_str(content)?;
+        for (index, rule) in config.rules.iter().enumerate() {
+            if let Err(reason) = rule.validate() {
+                return Err(ConfigError::InvalidRule {
+                    name: rule.id(index),
+                    reason,
+                });
+            }
+        }
+        Ok(config)
+    }
+
+    /// Names of all available profiles (built-in and configured), sorted
+    pub fn profile_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = builtin_profiles()
+            .into_keys()
+            .chain(self.profiles.keys().cloned())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Resolve the weights for a profile (or the base weights if `None`)
+    pub fn weights_for(&self, profile: Option<&str>) -> Result<ScoringWeights, ConfigError> {
+        let mut weights = ScoringWeights::default();
+        self.weights.apply(&mut weights);
+
+        let Some(name) = profile else {
+            return Ok(weights);
+        };
+
+        let builtin = builtin_profiles();
+        let builtin_profile = builtin.get(name);
+        let configured_profile = self.profiles.get(name);
+
+        if builtin_profile.is_none() && configured_profile.is_none() {
+            return Err(ConfigError::UnknownProfile {
+                name: name.to_string(),
+                available: self.profile_names().join(", "),
+            });
+        }
+
+        if let Some(overrides) = builtin_profile {
+            overrides.apply(&mut weights);
+        }
+        if let Some(overrides) = configured_profile {
+            overrides.apply(&mut weights);
+        }
+
+        Ok(weights)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_layering() {
+        let config = ProjectConfig::parse(
+            r#"
+[weights]
+cross_module_usage = 10.0
+
+[profiles.tests]
+site_bonus = 1.0
+
+[profiles.custom]
+trait_impl = 7.0
+"#,
+        )
+        .unwrap();
+
+        let base = config.weights_for(None).unwrap();
+        assert_eq!(base.cross_module_usage, 10.0);
+        assert_eq!(base.is_test_penalty, weights::IS_TEST_PENALTY);
+
+        let tests = config.weights_for(Some("tests")).unwrap();
+        assert_eq!(tests.cross_module_usage, 10.0);
+        assert_eq!(tests.is_test_penalty, 80.0);
+        assert_eq!(tests.site_bonus, 1.0);
+
+        assert_eq!(config.weights_for(Some("custom")).unwrap().trait_impl, 7.0);
+        assert!(config.weights_for(Some("missing")).is_err());
+    }
+}
@@ -0,0 +1,237 @@
+//! Coverage reports - line hits from lcov or `cargo llvm-cov` JSON
+//!
+//! Reports are only parsed, never produced: point cargomap at the
+//! `lcov.info` or `llvm-cov export` JSON your CI already writes. Line hits
+//! are mapped onto item spans so each item gets a coverage percentage.
+
+use crate::types::*;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CoverageError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid coverage report: {0}")]
+    Format(String),
+}
+
+/// Per-line execution counts, keyed by file
+#[derive(Debug, Clone, Default)]
+pub struct CoverageReport {
+    /// file -> (line -> hit count), for instrumented lines only
+    files: HashMap<PathBuf, BTreeMap<usize, u64>>,
+}
+
+/// `llvm-cov export` JSON (only the parts we read)
+#[derive(Deserialize)]
+struct LlvmExport {
+    data: Vec<LlvmData>,
+}
+
+#[derive(Deserialize)]
+struct LlvmData {
+    files: Vec<LlvmFile>,
+}
+
+#[derive(Deserialize)]
+struct LlvmFile {
+    filename: PathBuf,
+    /// `[line, col, count, has_count, is_region_entry, (is_gap_region)]`
+    #[serde(default)]
+    segments: Vec<Vec<serde_json::Value>>,
+}
+
+impl CoverageReport {
+    /// Load an lcov or llvm-cov JSON report. Relative paths in the report
+    /// are resolved against `project_root`.
+    pub fn load(report: &Path, project_root: &Path) -> Result<Self, CoverageError> {
+        let content = std::fs::read_to_string(report)?;
+        let parsed = if content.trim_start().starts_with('{') {
+            Self::parse_llvm_json(&content)?
+        } else {
+            Self::parse_lcov(&content)?
+        };
+
+        // Key by canonical absolute path so lookups don't depend on how the
+        // report spelled them
+        let files = parsed
+            .files
+            .into_iter()
+            .map(|(path, lines)| {
+                let path = project_root.join(path);
+                (path.canonicalize().unwrap_or(path), lines)
+            })
+            .collect();
+
+        Ok(Self { files })
+    }
+
+    /// Parse lcov tracefile text (`SF:`, `DA:` and `end_of_record` lines)
+    pub fn parse_lcov(content: &str) -> Result<Self, CoverageError> {
+        let mut report = Self::default();
+        let mut current: Option<PathBuf> = None;
+
+        for line in content.lines().map(str::trim) {
+            if let Some(path) = line.strip_prefix("SF:") {
+                current = Some(PathBuf::from(path));
+            } else if let Some(data) = line.strip_prefix("DA:") {
+                let file = current
+                    .as_ref()
+                    .ok_or_else(|| CoverageError::Format("DA record before SF".to_string()))?;
+                let mut fields = data.split(',');
+                let (Some(line_no), Some(hits)) = (fields.next(), fields.next()) else {
+                    return Err(CoverageError::Format(format!("bad DA record '{}'", line)));
+                };
+                let line_no = line_no
+                    .parse()
+                    .map_err(|_| CoverageError::Format(format!("bad line in '{}'", line)))?;
+                // Some generators write negative or fractional counts
+                let hits = hits.parse::<f64>().unwrap_or(0.0).max(0.0) as u64;
+
+                let lines = report.files.entry(file.clone()).or_default();
+                *lines.entry(line_no).or_default() += hits;
+            } else if line == "end_of_record" {
+                current = None;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Parse `llvm-cov export` JSON, deriving line hits from region segments
+    /// the same way `llvm-cov report` does
+    pub fn parse_llvm_json(content: &str) -> Result<Self, CoverageError> {
+        let export: LlvmExport = serde_json::from_str(content)?;
+        let mut report = Self::default();
+
+        for file in export.data.into_iter().flat_map(|d| d.files) {
+            let segments: Vec<(usize, u64, bool, bool)> = file
+                .segments
+                .iter()
+                .filter_map(|s| {
+                    Some((
+                        s.first()?.as_u64()? as usize,
+                        s.get(2)?.as_u64()?,
+                        s.get(3)?.as_bool()?,
+                        s.get(4)?.as_bool()?,
+                    ))
+                })
+                .collect();
+            let Some(last_line) = segments.last().map(|s| s.0) else {
+                continue;
+            };
+
+            let lines = report.files.entry(file.filename).or_default();
+            // Count of the region still active when a line starts
+            let mut wrapped: Option<u64> = None;
+            let mut index = 0;
+
+            for line_no in segments[0].0..=last_line {
+                // A line is instrumented if a counted region wraps into it or
+                // starts on it; its count is the max of those regions
+                let mut hits = wrapped;
+
+                while let Some(&(line, count, has_count, is_entry)) = segments.get(index) {
+                    if line != line_no {
+                        break;
+                    }
+                    if has_count && is_entry {
+                        hits = Some(hits.map_or(count, |h| h.max(count)));
+                    }
+                    wrapped = has_count.then_some(count);
+                    index += 1;
+                }
+
+                if let Some(hits) = hits {
+                    lines.insert(line_no, hits);
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Line hits for a file, matching canonical paths
+    fn lines_for(&self, file: &Path) -> Option<&BTreeMap<usize, u64>> {
+        self.files.get(file).or_else(|| {
+            let canonical = file.canonicalize().ok()?;
+            self.files.get(&canonical)
+        })
+    }
+
+    /// Re-key the report by the given project file paths, dropping files the
+    /// project doesn't have
+    pub fn for_files(&self, files: &[PathBuf]) -> Self {
+        let files = files
+            .iter()
+            .filter_map(|f| Some((f.clone(), self.lines_for(f)?.clone())))
+            .collect();
+        Self { files }
+    }
+
+    /// Coverage of an item's lines, or `None` if none of them are instrumented
+    pub fn stats_for(&self, file: &Path, span: &Span) -> Option<CoverageFactors> {
+        let lines = self.files.get(file)?;
+        let end = span.end_line.max(span.start_line);
+
+        let (mut instrumented, mut covered) = (0, 0);
+        for (_, hits) in lines.range(span.start_line..=end) {
+            instrumented += 1;
+            if *hits > 0 {
+                covered += 1;
+            }
+        }
+
+        (instrumented > 0).then(|| CoverageFactors {
+            instrumented_lines: instrumented,
+            covered_lines: covered,
+            percent: 100.0 * covered as f64 / instrumented as f64,
+        })
+    }
+
+    /// Number of files in the report
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(start_line: usize, end_line: usize) -> Span {
+        Span {
+            start_line,
+            start_col: 0,
+            end_line,
+            end_col: 0,
+        }
+    }
+
+    #[test]
+    fn test_lcov_and_llvm_json_agree() {
+        let lcov = "TN:\nSF:src/lib.rs\nDA:1,1\nDA:2,1\nDA:3,0\nDA:4,0\nend_of_record\n";
+        // Region entered at 1:1 (count 1); an inner region starting at the
+        // end of line 2 never runs, and the outer region resumes at 4:10
+        let json = r#"{"data":[{"files":[{"filename":"src/lib.rs","segments":[
+            [1,1,1,true,true,false],[2,20,0,true,true,false],[4,10,1,true,false,false],[4,11,0,false,false,false]
+        ]}]}]}"#;
+
+        for report in [
+            CoverageReport::parse_lcov(lcov).unwrap(),
+            CoverageReport::parse_llvm_json(json).unwrap(),
+        ] {
+            let file = Path::new("src/lib.rs");
+            let stats = report.stats_for(file, &span(1, 2)).unwrap();
+            assert_eq!(stats.percent, 100.0);
+            let stats = report.stats_for(file, &span(3, 4)).unwrap();
+            assert_eq!(stats.covered_lines, 0);
+            assert!(report.stats_for(file, &span(10, 12)).is_none());
+        }
+    }
+}
@@ -4,8 +4,11 @@
 //! 1. Parsing Cargo.lock to get exact versions
 //! 2. Locating source files in ~/.cargo/registry/src/...
 //! 3. Extracting only the public API of those crates
+//! 4. Resolving paths through their re-exports, so `tokio::task::spawn` and
+//!    `tokio::spawn` find the same definition
 
 use crate::parser::PartialParser;
+use crate::resolve::{self, PathResolver};
 use crate::types::*;
 use cargo_metadata::{MetadataCommand, Package};
 use serde::Deserialize;
@@ -50,6 +53,8 @@ pub struct DependencyBridge {
     dependencies: HashMap<String, CrateDependency>,
     /// Parser for extracting APIs
     parser: PartialParser,
+    /// Path resolvers of the dependencies resolved so far
+    resolvers: HashMap<String, PathResolver>,
 }
 
 impl DependencyBridge {
@@ -62,6 +67,7 @@ impl DependencyBridge {
             registry_path,
             dependencies: HashMap::new(),
             parser: PartialParser::new(),
+            resolvers: HashMap::new(),
         })
     }
 
@@ -212,31 +218,45 @@ impl DependencyBridge {
         Ok(public_items)
     }
 
-    /// Resolve a path like `tokio::spawn` to its source location
+    /// Resolve a path like `tokio::spawn` to its source location. Paths
+    /// are followed through re-exports, into other crates if needed; if
+    /// that fails, the crate root's public items are searched by name.
     pub fn resolve_path(&mut self, path: &str) -> Option<ResolvedPath> {
         let parts: Vec<&str> = path.split("::").collect();
         if parts.is_empty() {
             return None;
         }
 
-        let crate_name = parts[0];
-
-        // Check if it's a known dependency
-        if !self.dependencies.contains_key(crate_name) {
-            // Try to load it
-            let _ = self.load_dependencies();
+        if let Some((defining, inner)) = self.locate(path) {
+            let resolver = self.resolvers.get(&defining)?;
+            let item = resolver.resolve(&inner)?.clone();
+            let canonical = resolver.canonical(&inner)?;
+            let key = self.dependency_key(&defining)?;
+            let registry_path = self.dependencies.get(&key)?.registry_path.clone()?;
+            return Some(ResolvedPath {
+                crate_name: defining,
+                item_name: item.name.clone(),
+                path: canonical,
+                file_path: item.file_path,
+                span: item.span,
+                kind: item.kind,
+                registry_path,
+            });
         }
 
+        let crate_name = parts[0];
+        let key = self.dependency_key(crate_name)?;
+
         // Extract public API if not cached
         {
-            let dep = self.dependencies.get(crate_name)?;
+            let dep = self.dependencies.get(&key)?;
             if dep.public_api.is_empty() {
                 let _ = dep; // Release borrow before mutable call
-                let _ = self.extract_public_api(crate_name);
+                let _ = self.extract_public_api(&key);
             }
         }
 
-        let dep = self.dependencies.get(crate_name)?;
+        let dep = self.dependencies.get(&key)?;
         let registry_path = dep.registry_path.clone()?;
 
         // Search for the item in the public API
@@ -246,6 +266,7 @@ impl DependencyBridge {
         Some(ResolvedPath {
             crate_name: crate_name.to_string(),
             item_name: item_name.to_string(),
+            path: path.to_string(),
             file_path: found_item.file_path.clone(),
             span: found_item.span,
             kind: found_item.kind.clone(),
@@ -253,6 +274,60 @@ impl DependencyBridge {
         })
     }
 
+    /// The canonical path of whatever a dependency path names: its
+    /// shortest public path in the crate defining it
+    /// (`tokio::task::spawn` -> `tokio::spawn`)
+    pub fn canonical_path(&mut self, path: &str) -> Option<String> {
+        let (defining, inner) = self.locate(path)?;
+        self.resolvers.get(&defining)?.canonical(&inner)
+    }
+
+    /// The crate defining what `path` names and the path there, following
+    /// re-exports of other crates (`clap::Parser` ->
+    /// `clap_builder::Parser`)
+    fn locate(&mut self, path: &str) -> Option<(String, String)> {
+        self.locate_within(path, resolve::MAX_DEPTH)
+    }
+
+    fn locate_within(&mut self, path: &str, depth: usize) -> Option<(String, String)> {
+        let crate_name = path.split("::").next()?.to_string();
+        let resolver = self.resolver(&crate_name)?;
+        if resolver.resolve(path).is_some() {
+            return Some((crate_name, path.to_string()));
+        }
+        if depth == 0 {
+            return None;
+        }
+        resolver
+            .forwards(path)
+            .into_iter()
+            .find_map(|forward| self.locate_within(&forward, depth - 1))
+    }
+
+    /// Path resolver for a dependency, parsing its sources on first use.
+    /// `crate_name` is the name used in paths (`serde_json`).
+    pub fn resolver(&mut self, crate_name: &str) -> Option<&PathResolver> {
+        if !self.resolvers.contains_key(crate_name) {
+            let key = self.dependency_key(crate_name)?;
+            let root = self.dependencies.get(&key)?.registry_path.clone()?;
+            let files = self.parser.parse_project(&root.join("src")).ok()?;
+            let resolver = PathResolver::build(crate_name, &root, &files);
+            self.resolvers.insert(crate_name.to_string(), resolver);
+        }
+        self.resolvers.get(crate_name)
+    }
+
+    /// Cargo.lock name of the dependency a path names (`serde_json`,
+    /// `proc_macro2` -> `proc-macro2`), loading the lock file if needed
+    fn dependency_key(&mut self, crate_name: &str) -> Option<String> {
+        if self.dependencies.is_empty() {
+            let _ = self.load_dependencies();
+        }
+        [crate_name.to_string(), crate_name.replace('_', "-")]
+            .into_iter()
+            .find(|name| self.dependencies.contains_key(name))
+    }
+
     /// Get all dependencies
     pub fn get_dependencies(&self) -> &HashMap<String, CrateDependency> {
         &self.dependencies
@@ -300,6 +375,8 @@ impl DependencyBridge {
 pub struct ResolvedPath {
     pub crate_name: String,
     pub item_name: String,
+    /// Canonical path of the item
+    pub path: String,
     pub file_path: PathBuf,
     pub span: Span,
     pub kind: ItemKind,
@@ -310,9 +387,8 @@ impl std::fmt::Display for ResolvedPath {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{}::{} at {}:{}",
-            self.crate_name,
-            self.item_name,
+            "{} at {}:{}",
+            self.path,
             self.file_path.display(),
             self.span.start_line
         )
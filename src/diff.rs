@@ -0,0 +1,776 @@
+//! Semantic diff - structural changes between two analyses
+//!
+//! Compares two `ProjectAnalysis` values (from snapshot files, git
+//! revisions or the working tree) item by item instead of line by line:
+//! - Items added, removed or moved to another module
+//! - Signature, field, variant, visibility and type changes
+//! - Trait impls gained or lost (manual, derived and blanket)
+//! - Call-graph edges gained or lost
+//!
+//! Items are matched by module path, kind and name; methods are qualified by
+//! their impl (`config::Config::load`). Spans, docs and bodies are ignored.
+//!
+//! Revisions are read from the git object store (`git ls-tree` and
+//! `git cat-file --batch`) into a scratch directory and analyzed there, so
+//! the working tree is never touched.
+
+use crate::gravity::{GravityError, SemanticGravity};
+use crate::snapshot::SnapshotError;
+use crate::traits::TraitMatrix;
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum DiffError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("git error: {0}")]
+    Git(String),
+    #[error("Analysis error: {0}")]
+    Gravity(#[from] GravityError),
+    #[error("Snapshot error: {0}")]
+    Snapshot(#[from] SnapshotError),
+}
+
+/// An item present on only one side
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemRef {
+    /// Qualified path (`config::Config::load`)
+    pub path: String,
+    pub kind: String,
+    /// File relative to the analyzed root
+    pub file: PathBuf,
+    pub line: usize,
+}
+
+/// An item present on both sides whose shape changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemChange {
+    pub path: String,
+    pub kind: String,
+    pub file: PathBuf,
+    pub line: usize,
+    /// One line per change (e.g. "field `port`: u16 -> u32")
+    pub changes: Vec<String>,
+}
+
+/// An unchanged item found under a different module
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemMove {
+    pub name: String,
+    pub kind: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// A (trait, type) implementation gained or lost
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ImplChange {
+    pub trait_name: String,
+    pub type_name: String,
+    /// `impl`, `derive` or `blanket`
+    pub source: String,
+}
+
+/// Structural differences from an old analysis to a new one
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnalysisDiff {
+    pub added: Vec<ItemRef>,
+    pub removed: Vec<ItemRef>,
+    pub changed: Vec<ItemChange>,
+    pub moved: Vec<ItemMove>,
+    pub impls_added: Vec<ImplChange>,
+    pub impls_removed: Vec<ImplChange>,
+    /// (caller, callee) edges only in the new analysis
+    pub edges_added: Vec<(String, String)>,
+    /// (caller, callee) edges only in the old analysis
+    pub edges_removed: Vec<(String, String)>,
+}
+
+impl AnalysisDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.moved.is_empty()
+            && self.impls_added.is_empty()
+            && self.impls_removed.is_empty()
+            && self.edges_added.is_empty()
+            && self.edges_removed.is_empty()
+    }
+
+    /// Moves grouped by (from, to) module, for "module moved" summaries
+    pub fn module_moves(&self) -> Vec<(&str, &str, usize)> {
+        let mut groups: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+        for m in &self.moved {
+            *groups.entry((m.from.as_str(), m.to.as_str())).or_default() += 1;
+        }
+        groups
+            .into_iter()
+            .map(|((from, to), count)| (from, to, count))
+            .collect()
+    }
+}
+
+/// An item with its qualified location
+struct Keyed<'a> {
+    module: String,
+    /// Name qualified by its impl for methods (`Config::load`)
+    name: String,
+    item: &'a ParsedItem,
+    file: PathBuf,
+}
+
+impl Keyed<'_> {
+    fn path(&self) -> String {
+        if self.module.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}::{}", self.module, self.name)
+        }
+    }
+
+    fn to_ref(&self) -> ItemRef {
+        ItemRef {
+            path: self.path(),
+            kind: kind_label(&self.item.kind).to_string(),
+            file: self.file.clone(),
+            line: self.item.span.start_line,
+        }
+    }
+}
+
+/// Compare two analyses
+pub fn diff(old: &ProjectAnalysis, new: &ProjectAnalysis) -> AnalysisDiff {
+    let mut result = AnalysisDiff::default();
+
+    let old_items = index_items(old);
+    let new_items = index_items(new);
+
+    let mut removed: Vec<&Keyed> = Vec::new();
+    let mut added: Vec<&Keyed> = Vec::new();
+    let keys: BTreeSet<&(String, &str)> = old_items.keys().chain(new_items.keys()).collect();
+    for key in keys {
+        let before = old_items.get(key).map_or(&[][..], Vec::as_slice);
+        let after = new_items.get(key).map_or(&[][..], Vec::as_slice);
+
+        // Same-named items (e.g. several `impl` blocks) pair up in order
+        for (a, b) in before.iter().zip(after) {
+            let changes = item_changes(a.item, b.item);
+            if !changes.is_empty() {
+                result.changed.push(ItemChange {
+                    path: b.path(),
+                    kind: kind_label(&b.item.kind).to_string(),
+                    file: b.file.clone(),
+                    line: b.item.span.start_line,
+                    changes,
+                });
+            }
+        }
+        removed.extend(before.iter().skip(after.len()));
+        added.extend(after.iter().skip(before.len()));
+    }
+
+    // A removed item reappearing unchanged elsewhere was moved
+    let mut moved_to: BTreeSet<usize> = BTreeSet::new();
+    for gone in removed {
+        let target = added.iter().enumerate().find(|(i, new)| {
+            !moved_to.contains(i)
+                && new.name == gone.name
+                && new.module != gone.module
+                && item_changes(gone.item, new.item).is_empty()
+        });
+        match target {
+            Some((i, new)) => {
+                moved_to.insert(i);
+                result.moved.push(ItemMove {
+                    name: new.name.clone(),
+                    kind: kind_label(&new.item.kind).to_string(),
+                    from: module_label(&gone.module),
+                    to: module_label(&new.module),
+                });
+            }
+            None => result.removed.push(gone.to_ref()),
+        }
+    }
+    result.added = added
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !moved_to.contains(i))
+        .map(|(_, k)| k.to_ref())
+        .collect();
+
+    let old_impls = impl_set(old);
+    let new_impls = impl_set(new);
+    result.impls_added = new_impls.difference(&old_impls).cloned().collect();
+    result.impls_removed = old_impls.difference(&new_impls).cloned().collect();
+
+    let old_edges = edge_set(old);
+    let new_edges = edge_set(new);
+    result.edges_added = new_edges.difference(&old_edges).cloned().collect();
+    result.edges_removed = old_edges.difference(&new_edges).cloned().collect();
+
+    result
+}
+
+/// Items keyed by (module, kind), name included in the module-qualified key
+fn index_items(analysis: &ProjectAnalysis) -> BTreeMap<(String, &'static str), Vec<Keyed<'_>>> {
+    let mut index: BTreeMap<(String, &'static str), Vec<Keyed>> = BTreeMap::new();
+
+    for file in &analysis.files {
+        let relative = relative_path(analysis, file);
+        let module = module_of(&relative);
+
+        for item in file.items_and_methods() {
+            // Impl blocks are covered by their methods and the trait impl set
+            if matches!(
+                item.kind,
+                ItemKind::Impl { .. } | ItemKind::Use { .. } | ItemKind::Unknown { .. }
+            ) {
+                continue;
+            }
+
+            let module = item_module(file, &module, item);
+            let name = match enclosing_impl(file, item) {
+                Some(ItemKind::Impl {
+                    self_type,
+                    trait_name: Some(trait_name),
+                    ..
+                }) => format!("<{} as {}>::{}", self_type, trait_name, item.name),
+                Some(ItemKind::Impl { self_type, .. }) => {
+                    format!("{}::{}", self_type, item.name)
+                }
+                _ => item.name.clone(),
+            };
+
+            let keyed = Keyed {
+                module: module.join("::"),
+                name,
+                item,
+                file: relative.clone(),
+            };
+            index
+                .entry((keyed.path(), kind_label(&item.kind)))
+                .or_default()
+                .push(keyed);
+        }
+    }
+
+    index
+}
+
+/// A file's path relative to the analyzed root
+pub(crate) fn relative_path(analysis: &ProjectAnalysis, file: &ParsedFile) -> PathBuf {
+    file.path
+        .strip_prefix(&analysis.root)
+        .unwrap_or(&file.path)
+        .to_path_buf()
+}
+
+/// Module of an item: its file's module plus any inline `mod` blocks
+/// around it
+pub(crate) fn item_module(
+    file: &ParsedFile,
+    file_module: &[String],
+    item: &ParsedItem,
+) -> Vec<String> {
+    let mut module = file_module.to_vec();
+    module.extend(
+        file.items
+            .iter()
+            .filter(|m| {
+                matches!(m.kind, ItemKind::Mod { inline: true })
+                    && !std::ptr::eq(*m, item)
+                    && contains(m, item)
+            })
+            .map(|m| m.name.clone()),
+    );
+    module
+}
+
+/// Module path of a root-relative file. Unlike `ParsedFile::module_path`
+/// this doesn't depend on where the root is, so both sides agree. Files of
+/// other members keep their directory (`crates/core`) and files outside
+/// `src/` (tests, examples) their whole path, so they don't collide.
+pub(crate) fn module_of(relative: &Path) -> Vec<String> {
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    let Some(src) = parts.iter().position(|p| p == "src") else {
+        return vec![relative.with_extension("").to_string_lossy().into_owned()];
+    };
+
+    let mut module = Vec::new();
+    if src > 0 {
+        module.push(parts[..src].join("/"));
+    }
+    module.extend(
+        parts[src + 1..]
+            .iter()
+            .filter(|p| !matches!(p.as_str(), "lib.rs" | "main.rs" | "mod.rs"))
+            .map(|p| p.strip_suffix(".rs").unwrap_or(p).to_string()),
+    );
+    module
+}
+
+pub(crate) fn contains(outer: &ParsedItem, inner: &ParsedItem) -> bool {
+    outer.span.start_line <= inner.span.start_line && inner.span.end_line <= outer.span.end_line
+}
+
+/// The impl block a method belongs to
+pub(crate) fn enclosing_impl<'a>(file: &'a ParsedFile, item: &ParsedItem) -> Option<&'a ItemKind> {
+    if !matches!(item.kind, ItemKind::Function { .. }) {
+        return None;
+    }
+    file.items
+        .iter()
+        .filter(|other| {
+            matches!(&other.kind, ItemKind::Impl { methods, .. } if methods.contains(&item.name))
+                && contains(other, item)
+        })
+        .max_by_key(|other| other.span.start_line)
+        .map(|other| &other.kind)
+}
+
+fn module_label(module: &str) -> String {
+    if module.is_empty() {
+        "crate".to_string()
+    } else {
+        module.to_string()
+    }
+}
+
+pub(crate) fn kind_label(kind: &ItemKind) -> &'static str {
+    match kind {
+        ItemKind::Function { .. } => "fn",
+        ItemKind::Struct { .. } => "struct",
+        ItemKind::Enum { .. } => "enum",
+        ItemKind::Trait { .. } => "trait",
+        ItemKind::Impl { .. } => "impl",
+        ItemKind::Mod { .. } => "mod",
+        ItemKind::Use { .. } => "use",
+        ItemKind::Const { .. } => "const",
+        ItemKind::Static { .. } => "static",
+        ItemKind::TypeAlias { .. } => "type",
+        ItemKind::Macro { .. } => "macro",
+        ItemKind::Unknown { .. } => "unknown",
+    }
+}
+
+pub(crate) fn visibility_label(vis: Visibility) -> &'static str {
+    match vis {
+        Visibility::Public => "pub",
+        Visibility::Crate => "pub(crate)",
+        Visibility::Super => "pub(super)",
+        Visibility::Restricted => "pub(in ..)",
+        Visibility::Private => "private",
+    }
+}
+
+pub(crate) fn signature(kind: &ItemKind) -> Option<String> {
+    let ItemKind::Function {
+        is_async,
+        parameters,
+        return_type,
+        ..
+    } = kind
+    else {
+        return None;
+    };
+    let params: Vec<String> = parameters
+        .iter()
+        .map(|p| {
+            if p.is_self {
+                p.ty.clone()
+            } else {
+                format!("{}: {}", p.name, p.ty)
+            }
+        })
+        .collect();
+    Some(format!(
+        "{}fn({}){}",
+        if *is_async { "async " } else { "" },
+        params.join(", "),
+        return_type
+            .as_ref()
+            .map(|r| format!(" -> {}", r))
+            .unwrap_or_default()
+    ))
+}
+
+fn field_label(field: &StructField, index: usize) -> String {
+    field.name.clone().unwrap_or_else(|| index.to_string())
+}
+
+/// Added, removed and retyped fields, matched by name (or position)
+fn field_changes(prefix: &str, old: &[StructField], new: &[StructField], out: &mut Vec<String>) {
+    let old: BTreeMap<String, &StructField> = old
+        .iter()
+        .enumerate()
+        .map(|(i, f)| (field_label(f, i), f))
+        .collect();
+    let new: BTreeMap<String, &StructField> = new
+        .iter()
+        .enumerate()
+        .map(|(i, f)| (field_label(f, i), f))
+        .collect();
+
+    for (name, field) in &new {
+        match old.get(name) {
+            None => out.push(format!("{}field `{}` added: {}", prefix, name, field.ty)),
+            Some(before) if before.ty != field.ty => out.push(format!(
+                "{}field `{}`: {} -> {}",
+                prefix, name, before.ty, field.ty
+            )),
+            Some(before) if before.visibility != field.visibility => out.push(format!(
+                "{}field `{}` visibility: {} -> {}",
+                prefix,
+                name,
+                visibility_label(before.visibility),
+                visibility_label(field.visibility)
+            )),
+            _ => {}
+        }
+    }
+    for name in old.keys().filter(|name| !new.contains_key(*name)) {
+        out.push(format!("{}field `{}` removed", prefix, name));
+    }
+}
+
+fn list_changes(what: &str, old: &[String], new: &[String], out: &mut Vec<String>) {
+    for name in new.iter().filter(|n| !old.contains(n)) {
+        out.push(format!("{} `{}` added", what, name));
+    }
+    for name in old.iter().filter(|n| !new.contains(n)) {
+        out.push(format!("{} `{}` removed", what, name));
+    }
+}
+
+/// Human-readable changes between two versions of an item
+fn item_changes(old: &ParsedItem, new: &ParsedItem) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    if old.visibility != new.visibility {
+        changes.push(format!(
+            "visibility: {} -> {}",
+            visibility_label(old.visibility),
+            visibility_label(new.visibility)
+        ));
+    }
+
+    match (&old.kind, &new.kind) {
+        (ItemKind::Function { .. }, ItemKind::Function { .. }) => {
+            let (before, after) = (signature(&old.kind), signature(&new.kind));
+            if before != after {
+                changes.push(format!(
+                    "signature: {} -> {}",
+                    before.unwrap_or_default(),
+                    after.unwrap_or_default()
+                ));
+            }
+        }
+        (
+            ItemKind::Struct {
+                fields: before,
+                is_tuple: was_tuple,
+            },
+            ItemKind::Struct {
+                fields: after,
+                is_tuple,
+            },
+        ) => {
+            if was_tuple != is_tuple {
+                changes.push(format!(
+                    "now a {} struct",
+                    if *is_tuple { "tuple" } else { "named-field" }
+                ));
+            }
+            field_changes("", before, after, &mut changes);
+        }
+        (ItemKind::Enum { variants: before }, ItemKind::Enum { variants: after }) => {
+            for variant in after {
+                match before.iter().find(|v| v.name == variant.name) {
+                    None => changes.push(format!("variant `{}` added", variant.name)),
+                    Some(old_variant) => field_changes(
+                        &format!("variant `{}` ", variant.name),
+                        &old_variant.fields,
+                        &variant.fields,
+                        &mut changes,
+                    ),
+                }
+            }
+            for variant in before
+                .iter()
+                .filter(|v| after.iter().all(|a| a.name != v.name))
+            {
+                changes.push(format!("variant `{}` removed", variant.name));
+            }
+        }
+        (
+            ItemKind::Trait {
+                methods: old_methods,
+                supertraits: old_supertraits,
+                ..
+            },
+            ItemKind::Trait {
+                methods,
+                supertraits,
+                ..
+            },
+        ) => {
+            list_changes("method", old_methods, methods, &mut changes);
+            list_changes("supertrait", old_supertraits, supertraits, &mut changes);
+        }
+        (ItemKind::Const { ty: before }, ItemKind::Const { ty: after })
+        | (ItemKind::TypeAlias { ty: before }, ItemKind::TypeAlias { ty: after })
+            if before != after =>
+        {
+            changes.push(format!("type: {} -> {}", before, after));
+        }
+        (
+            ItemKind::Static {
+                ty: before,
+                is_mut: was_mut,
+            },
+            ItemKind::Static { ty: after, is_mut },
+        ) => {
+            if before != after {
+                changes.push(format!("type: {} -> {}", before, after));
+            }
+            if was_mut != is_mut {
+                changes.push(format!("mutability: {} -> {}", was_mut, is_mut));
+            }
+        }
+        _ => {}
+    }
+
+    changes
+}
+
+/// Every (trait, type, source) implementation
+fn impl_set(analysis: &ProjectAnalysis) -> BTreeSet<ImplChange> {
+    TraitMatrix::build(&analysis.files)
+        .impls()
+        .iter()
+        .map(|i| ImplChange {
+            trait_name: i.trait_name.clone(),
+            type_name: i.type_name.clone(),
+            source: i.source.label().to_string(),
+        })
+        .collect()
+}
+
+fn edge_set(analysis: &ProjectAnalysis) -> BTreeSet<(String, String)> {
+    analysis
+        .call_graph
+        .callees
+        .iter()
+        .flat_map(|(caller, callees)| callees.iter().map(move |c| (caller.clone(), c.clone())))
+        .collect()
+}
+
+/// Load one side of a diff: a snapshot file if `spec` names one, otherwise
+/// a git revision of the repository containing `project_root`
+pub fn load_side(project_root: &Path, spec: &str) -> Result<ProjectAnalysis, DiffError> {
+    let path = project_root.join(spec);
+    if path.is_file() {
+        return Ok(ProjectAnalysis::load(&path)?);
+    }
+    analyze_revision(project_root, spec)
+}
+
+/// Analyze `project_root` as of a git revision, reading files straight from
+/// the object store
+pub fn analyze_revision(project_root: &Path, rev: &str) -> Result<ProjectAnalysis, DiffError> {
+    let scratch = std::env::temp_dir().join(format!(
+        "cargomap-rev-{}-{}",
+        std::process::id(),
+        rev.replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+    ));
+    if scratch.exists() {
+        std::fs::remove_dir_all(&scratch)?;
+    }
+
+    let result = export_revision(project_root, rev, &scratch).and_then(|()| {
+        let mut gravity = SemanticGravity::new();
+        gravity.analyze_project(&scratch)?;
+        Ok(gravity.snapshot(&scratch, Vec::new()))
+    });
+    let _ = std::fs::remove_dir_all(&scratch);
+
+    let mut analysis = result?;
+    analysis.root = PathBuf::from(rev);
+    for file in &mut analysis.files {
+        if let Ok(relative) = file.path.strip_prefix(&scratch) {
+            file.path = analysis.root.join(relative);
+        }
+    }
+    Ok(analysis)
+}
+
+/// Write the Rust sources and manifests under `project_root` at `rev` into
+/// `dest`
+fn export_revision(project_root: &Path, rev: &str, dest: &Path) -> Result<(), DiffError> {
+    // Paths are listed relative to `project_root` (a repo subdirectory is fine)
+    let listing = git(
+        project_root,
+        &[
+            "ls-tree",
+            "-r",
+            "-z",
+            &format!("{}^{{tree}}", rev),
+            "--",
+            ".",
+        ],
+    )?;
+    let blobs: Vec<(&str, &str)> = listing
+        .split('\0')
+        .filter_map(|entry| {
+            let (meta, path) = entry.split_once('\t')?;
+            let mut meta = meta.split(' ');
+            let (mode, kind, oid) = (meta.next()?, meta.next()?, meta.next()?);
+            let wanted = mode != "120000"
+                && kind == "blob"
+                && !path.starts_with("target/")
+                && (path.ends_with(".rs") || path.ends_with("Cargo.toml"));
+            wanted.then_some((oid, path))
+        })
+        .collect();
+
+    let mut child = Command::new("git")
+        .current_dir(project_root)
+        .args(["cat-file", "--batch"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // Feed object ids from another thread so a full stdout pipe can't stall us
+    let mut stdin = child.stdin.take().expect("piped stdin");
+    let request: String = blobs.iter().map(|(oid, _)| format!("{}\n", oid)).collect();
+    let writer = std::thread::spawn(move || stdin.write_all(request.as_bytes()));
+
+    let mut stdout = BufReader::new(child.stdout.take().expect("piped stdout"));
+    for (_, path) in &blobs {
+        let mut header = String::new();
+        stdout.read_line(&mut header)?;
+        let size: usize = header
+            .split_whitespace()
+            .nth(2)
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| {
+                DiffError::Git(format!("unexpected cat-file output '{}'", header.trim()))
+            })?;
+
+        let mut content = vec![0; size + 1]; // trailing newline
+        stdout.read_exact(&mut content)?;
+        content.pop();
+
+        let target = dest.join(path);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(target, content)?;
+    }
+
+    writer
+        .join()
+        .map_err(|_| DiffError::Git("cat-file writer panicked".to_string()))??;
+    child.wait()?;
+    std::fs::create_dir_all(dest)?;
+    Ok(())
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String, DiffError> {
+    let output = Command::new("git").current_dir(dir).args(args).output()?;
+    if !output.status.success() {
+        return Err(DiffError::Git(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::PartialParser;
+    use crate::snapshot::SNAPSHOT_VERSION;
+
+    fn analysis(files: &[(&str, &str)]) -> ProjectAnalysis {
+        let parser = PartialParser::new();
+        ProjectAnalysis {
+            version: SNAPSHOT_VERSION,
+            generator: String::new(),
+            root: PathBuf::from("/p"),
+            files: files
+                .iter()
+                .map(|(path, source)| {
+                    parser
+                        .parse_source(&Path::new("/p").join(path), source)
+                        .unwrap()
+                })
+                .collect(),
+            dependencies: Vec::new(),
+            call_graph: CallGraph::default(),
+            module_tree: ModuleTree::default(),
+            reference_map: ReferenceMap::default(),
+            crate_references: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_structural_diff() {
+        let old = analysis(&[
+            (
+                "src/config.rs",
+                "#[derive(Debug)]\npub struct Config { port: u16 }\nimpl Config {\n    fn load(path: &str) -> Config { todo!() }\n}\nfn helper() {}\n",
+            ),
+            ("src/util.rs", "pub fn shared(x: u32) -> u32 { x }\n"),
+        ]);
+        let new = analysis(&[
+            (
+                "src/config.rs",
+                "#[derive(Debug, Clone)]\npub struct Config { port: u32, pub host: String }\nimpl Config {\n    pub fn load(path: &Path) -> Config { todo!() }\n}\n",
+            ),
+            ("src/net/util.rs", "pub fn shared(x: u32) -> u32 { x }\n"),
+        ]);
+
+        let diff = diff(&old, &new);
+
+        let removed: Vec<&str> = diff.removed.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(removed, vec!["config::helper"]);
+        assert!(diff.added.is_empty());
+
+        let changed: BTreeMap<&str, &Vec<String>> = diff
+            .changed
+            .iter()
+            .map(|c| (c.path.as_str(), &c.changes))
+            .collect();
+        assert_eq!(
+            changed["config::Config"],
+            &vec![
+                "field `host` added: String".to_string(),
+                "field `port`: u16 -> u32".to_string()
+            ]
+        );
+        assert_eq!(
+            changed["config::Config::load"],
+            &vec![
+                "visibility: private -> pub".to_string(),
+                "signature: fn(path: & str) -> Config -> fn(path: & Path) -> Config".to_string()
+            ]
+        );
+
+        assert_eq!(diff.module_moves(), vec![("util", "net::util", 1)]);
+        assert_eq!(diff.impls_added.len(), 1);
+        assert_eq!(diff.impls_added[0].trait_name, "Clone");
+        assert!(diff.impls_removed.is_empty());
+    }
+}
@@ -0,0 +1,882 @@
+//! Error propagation map - which errors flow where
+//!
+//! Works out, for every function returning `Result<_, E>`:
+//! - Which variants of its error type it constructs itself
+//!   (`GravityError::Parse(..)`, including inside `map_err` closures)
+//! - Which calls it applies `?` to, and how the callee's error gets into `E`:
+//!   unchanged (same type), through a `From` conversion (a `#[from]` field
+//!   in a `thiserror` enum or a manual `impl From<F> for E`), or replaced by
+//!   `map_err`
+//! - Which `?` sites call outside the crate, whose errors can only arrive
+//!   through `E`'s `From` conversions from non-local types
+//!
+//! From that it builds a type-level error-flow graph and answers which
+//! error variants can reach a function, and along which call chain.
+//!
+//! Calls are matched by name (`Type::name` when written qualified), so
+//! same-named functions in different places are all considered.
+
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+/// Calls after which `?` doesn't propagate a callee error
+const NON_PROPAGATING: &[&str] = &["ok_or", "ok_or_else", "Some", "Ok", "Err"];
+
+/// A local error type and its variants
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorType {
+    pub name: String,
+    pub variants: Vec<ErrorVariant>,
+    pub file: PathBuf,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorVariant {
+    pub name: String,
+    /// Source type of a `#[from]` field
+    pub from: Option<String>,
+}
+
+/// How one error type converts into another
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConversionSource {
+    /// `#[from]` on a variant field
+    FromAttribute,
+    /// `impl From<F> for E`
+    FromImpl,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conversion {
+    pub from: String,
+    pub to: String,
+    /// Variant of `to` the error is wrapped in, if known
+    pub variant: Option<String>,
+    pub source: ConversionSource,
+    pub file: PathBuf,
+    pub line: usize,
+}
+
+/// How a callee's error reaches the caller at a `?`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Propagation {
+    /// Same error type, passed through unchanged
+    Unchanged,
+    /// Converted by `From`, into the given variant when known
+    Converted { variant: Option<String> },
+    /// `map_err` replaced it; the replacement is a construction site
+    Mapped,
+}
+
+/// A `?` applied to a call to a local function
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorCall {
+    /// Called name as written (`load` or `Type::load`)
+    pub callee: String,
+    pub callee_error: String,
+    pub propagation: Propagation,
+    pub line: usize,
+}
+
+/// Error behaviour of one function returning `Result`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionErrors {
+    /// `Type::name` for methods, `name` for free functions
+    pub key: String,
+    pub error_type: String,
+    pub is_public: bool,
+    pub file: PathBuf,
+    pub line: usize,
+    /// Variants constructed in the body, as `Type::Variant`, with lines
+    pub constructed: Vec<(String, usize)>,
+    /// `?` on local calls
+    pub calls: Vec<ErrorCall>,
+    /// `?` on calls outside the crate, with lines
+    pub external: Vec<(String, usize)>,
+}
+
+/// An error that can reach a function
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReachableError {
+    /// Where it originates, e.g. `DependencyError::Metadata` or
+    /// `GravityError::Io (from std::io::Error)`
+    pub error: String,
+    /// Variant of the queried function's error type it arrives as, when a
+    /// conversion wraps it on the way
+    pub surfaced_as: Option<String>,
+    /// Function keys from the queried function down to the origin
+    pub chain: Vec<String>,
+    pub file: PathBuf,
+    pub line: usize,
+}
+
+/// Error types, conversions and per-function error behaviour of a project
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ErrorFlow {
+    types: BTreeMap<String, ErrorType>,
+    conversions: Vec<Conversion>,
+    functions: Vec<FunctionErrors>,
+}
+
+impl ErrorFlow {
+    /// Analyze `files`, reading their sources for function bodies
+    pub fn build(files: &[ParsedFile]) -> Self {
+        let mut flow = ErrorFlow::default();
+        let sources: HashMap<&Path, String> = files
+            .iter()
+            .map(|f| {
+                let content = std::fs::read_to_string(&f.path).unwrap_or_default();
+                (f.path.as_path(), content)
+            })
+            .collect();
+
+        let aliases = result_aliases(files);
+        let signatures: Vec<(&ParsedFile, &ParsedItem, String)> = files
+            .iter()
+            .flat_map(|f| f.items_and_methods().map(move |item| (f, item)))
+            .filter_map(|(f, item)| match &item.kind {
+                ItemKind::Function {
+                    return_type: Some(ret),
+                    ..
+                } => result_error(ret, &aliases).map(|e| (f, item, e)),
+                _ => None,
+            })
+            .collect();
+
+        // Error types: anything a function returns as an error, derives
+        // `Error` or implements `std::error::Error`
+        let mut error_like: HashSet<String> =
+            signatures.iter().map(|(_, _, e)| base_name(e)).collect();
+        for item in files.iter().flat_map(|f| &f.items) {
+            if let ItemKind::Impl {
+                self_type,
+                trait_name: Some(trait_name),
+                ..
+            } = &item.kind
+                && trait_name.rsplit("::").next() == Some("Error")
+            {
+                error_like.insert(base_name(self_type));
+            }
+        }
+        for file in files {
+            for item in &file.items {
+                let (variants, is_enum) = match &item.kind {
+                    ItemKind::Enum { variants } => (variants.as_slice(), true),
+                    ItemKind::Struct { .. } => (&[][..], false),
+                    _ => continue,
+                };
+                let derives_error = item.attributes.iter().any(|a| {
+                    a.starts_with("#[derive(")
+                        && a.split(['(', ',', ')'])
+                            .any(|t| t.trim().rsplit("::").next() == Some("Error"))
+                });
+                if !(derives_error || error_like.contains(&item.name)) {
+                    continue;
+                }
+                let mut error_type = ErrorType {
+                    name: item.name.clone(),
+                    variants: Vec::new(),
+                    file: file.path.clone(),
+                    line: item.span.start_line,
+                };
+                if is_enum {
+                    for variant in variants {
+                        let from = variant
+                            .fields
+                            .iter()
+                            .find(|f| f.attributes.iter().any(|a| a == "#[from]"))
+                            .map(|f| compact(&f.ty));
+                        if let Some(from) = &from {
+                            flow.conversions.push(Conversion {
+                                from: from.clone(),
+                                to: item.name.clone(),
+                                variant: Some(variant.name.clone()),
+                                source: ConversionSource::FromAttribute,
+                                file: file.path.clone(),
+                                line: item.span.start_line,
+                            });
+                        }
+                        error_type.variants.push(ErrorVariant {
+                            name: variant.name.clone(),
+                            from,
+                        });
+                    }
+                }
+                flow.types.insert(item.name.clone(), error_type);
+            }
+        }
+
+        // Manual `impl From<F> for E`
+        let from_impl =
+            regex::Regex::new(r"From\s*<\s*(.+?)\s*>\s+for\s+(\w+)").expect("Invalid regex");
+        for file in files {
+            let lines: Vec<&str> = sources[file.path.as_path()].lines().collect();
+            for item in &file.items {
+                let ItemKind::Impl {
+                    trait_name: Some(trait_name),
+                    ..
+                } = &item.kind
+                else {
+                    continue;
+                };
+                if trait_name.rsplit("::").next() != Some("From") {
+                    continue;
+                }
+                let header_end = (item.span.start_line + 3).min(lines.len());
+                let header = lines
+                    .get(item.span.start_line.saturating_sub(1)..header_end)
+                    .unwrap_or_default()
+                    .join(" ");
+                if let Some(cap) = from_impl.captures(&header)
+                    && flow.types.contains_key(&cap[2])
+                {
+                    flow.conversions.push(Conversion {
+                        from: compact(&cap[1]),
+                        to: cap[2].to_string(),
+                        variant: None,
+                        source: ConversionSource::FromImpl,
+                        file: file.path.clone(),
+                        line: item.span.start_line,
+                    });
+                }
+            }
+        }
+
+        // Local callees by name and by `Type::name`
+        let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+        let keys: Vec<String> = signatures
+            .iter()
+            .map(|(file, item, _)| function_key(file, item))
+            .collect();
+        for (index, key) in keys.iter().enumerate() {
+            by_name.entry(key.clone()).or_default().push(index);
+            if let Some((_, name)) = key.rsplit_once("::") {
+                by_name.entry(name.to_string()).or_default().push(index);
+            }
+        }
+
+        let variant_pattern = regex::Regex::new(r"\b(\w+)::(\w+)\b").expect("Invalid regex");
+        for ((file, item, error), key) in signatures.iter().zip(&keys) {
+            let error_type = self_or(error, key);
+            let body = body_text(&sources[file.path.as_path()], &item.span);
+            let mut function = FunctionErrors {
+                key: key.clone(),
+                error_type: error_type.clone(),
+                is_public: item.visibility == Visibility::Public,
+                file: file.path.clone(),
+                line: item.span.start_line,
+                constructed: Vec::new(),
+                calls: Vec::new(),
+                external: Vec::new(),
+            };
+
+            for (offset, line) in body.lines().enumerate() {
+                for cap in variant_pattern.captures_iter(line) {
+                    let type_name = if &cap[1] == "Self" {
+                        key.split("::").next().unwrap_or("")
+                    } else {
+                        &cap[1]
+                    };
+                    let is_variant = flow
+                        .types
+                        .get(type_name)
+                        .is_some_and(|t| t.variants.iter().any(|v| v.name == cap[2]));
+                    let found = format!("{}::{}", type_name, &cap[2]);
+                    if is_variant
+                        && !function
+                            .constructed
+                            .iter()
+                            .any(|(v, l)| *v == found && *l == item.span.start_line + offset)
+                    {
+                        function
+                            .constructed
+                            .push((found, item.span.start_line + offset));
+                    }
+                }
+            }
+
+            for site in question_sites(&body) {
+                let line = item.span.start_line + site.line;
+                if NON_PROPAGATING.contains(&site.callee.as_str()) {
+                    continue;
+                }
+                let Some(candidates) = by_name.get(&site.callee) else {
+                    // A mapped external error is replaced, not propagated
+                    if !site.mapped {
+                        function.external.push((site.path, line));
+                    }
+                    continue;
+                };
+                // Prefer a same-file definition when the name is ambiguous
+                let same_file: Vec<usize> = candidates
+                    .iter()
+                    .copied()
+                    .filter(|&i| signatures[i].0.path == file.path)
+                    .collect();
+                let chosen = if same_file.is_empty() {
+                    candidates.clone()
+                } else {
+                    same_file
+                };
+                let mut seen = HashSet::new();
+                for index in chosen {
+                    let callee_error = self_or(&signatures[index].2, &keys[index]);
+                    if !seen.insert(callee_error.clone()) {
+                        continue;
+                    }
+                    let propagation = if site.mapped {
+                        Propagation::Mapped
+                    } else if base_name(&callee_error) == base_name(&error_type) {
+                        Propagation::Unchanged
+                    } else {
+                        Propagation::Converted {
+                            variant: flow.conversion_variant(&callee_error, &error_type),
+                        }
+                    };
+                    function.calls.push(ErrorCall {
+                        callee: site.callee.clone(),
+                        callee_error,
+                        propagation,
+                        line,
+                    });
+                }
+            }
+
+            flow.functions.push(function);
+        }
+
+        flow
+    }
+
+    /// Local error types, by name
+    pub fn error_types(&self) -> &BTreeMap<String, ErrorType> {
+        &self.types
+    }
+
+    /// Known `From` conversions into local error types
+    pub fn conversions(&self) -> &[Conversion] {
+        &self.conversions
+    }
+
+    /// Every function returning a `Result`
+    pub fn functions(&self) -> &[FunctionErrors] {
+        &self.functions
+    }
+
+    /// Functions matching `name` (`name` or `Type::name`)
+    pub fn find(&self, name: &str) -> Vec<&FunctionErrors> {
+        self.functions
+            .iter()
+            .filter(|f| f.key == name || f.key.rsplit("::").next() == Some(name))
+            .collect()
+    }
+
+    /// Variant of `to` that a `from` error converts into
+    fn conversion_variant(&self, from: &str, to: &str) -> Option<String> {
+        let from = base_name(from);
+        self.conversions
+            .iter()
+            .find(|c| c.to == base_name(to) && base_name(&c.from) == from)
+            .and_then(|c| c.variant.clone())
+    }
+
+    /// Type-level flows: (from, to, how) for every conversion and every
+    /// `?` that converts, deduplicated
+    pub fn type_edges(&self) -> Vec<(String, String, String)> {
+        let mut edges = BTreeSet::new();
+        for c in &self.conversions {
+            let how = match (&c.source, &c.variant) {
+                (ConversionSource::FromAttribute, Some(v)) => format!("#[from] {}", v),
+                _ => "impl From".to_string(),
+            };
+            edges.insert((c.from.clone(), c.to.clone(), how));
+        }
+        for f in &self.functions {
+            for call in &f.calls {
+                let how = match &call.propagation {
+                    Propagation::Unchanged => continue,
+                    Propagation::Converted { variant: Some(v) } => format!("? into {}", v),
+                    Propagation::Converted { variant: None } => "?".to_string(),
+                    Propagation::Mapped => "map_err".to_string(),
+                };
+                edges.insert((call.callee_error.clone(), f.error_type.clone(), how));
+            }
+        }
+        edges.into_iter().collect()
+    }
+
+    /// Every error that can reach `function` (all functions of that name),
+    /// along the shortest call chain to each origin
+    pub fn reachable(&self, function: &str) -> Vec<ReachableError> {
+        let mut reached = Vec::new();
+        let mut seen_origins = HashSet::new();
+
+        for start in self.find(function) {
+            let mut visited = HashSet::new();
+            let mut queue: VecDeque<(&FunctionErrors, Vec<String>, Option<String>)> =
+                VecDeque::from([(start, vec![start.key.clone()], None)]);
+
+            while let Some((current, chain, surfaced)) = queue.pop_front() {
+                if !visited.insert((current.key.as_str(), current.file.as_path())) {
+                    continue;
+                }
+
+                for (variant, line) in &current.constructed {
+                    if seen_origins.insert((variant.clone(), current.file.clone(), *line)) {
+                        reached.push(ReachableError {
+                            error: variant.clone(),
+                            surfaced_as: surfaced.clone(),
+                            chain: chain.clone(),
+                            file: current.file.clone(),
+                            line: *line,
+                        });
+                    }
+                }
+
+                for (call, line) in &current.external {
+                    for error in self.external_errors(&current.error_type, call) {
+                        if seen_origins.insert((error.clone(), current.file.clone(), *line)) {
+                            let mut chain = chain.clone();
+                            chain.push(call.clone());
+                            reached.push(ReachableError {
+                                surfaced_as: surfaced.clone(),
+                                error,
+                                chain,
+                                file: current.file.clone(),
+                                line: *line,
+                            });
+                        }
+                    }
+                }
+
+                for call in &current.calls {
+                    let surfaced = match (&call.propagation, &surfaced) {
+                        (Propagation::Mapped, _) => continue,
+                        (_, Some(s)) => Some(s.clone()),
+                        (Propagation::Converted { variant }, None) => Some(match variant {
+                            Some(v) => format!("{}::{}", base_name(&current.error_type), v),
+                            None => current.error_type.clone(),
+                        }),
+                        (Propagation::Unchanged, None) => None,
+                    };
+                    for callee in self.find(&call.callee) {
+                        if base_name(&callee.error_type) != base_name(&call.callee_error) {
+                            continue;
+                        }
+                        let mut chain = chain.clone();
+                        chain.push(callee.key.clone());
+                        queue.push_back((callee, chain, surfaced.clone()));
+                    }
+                }
+            }
+        }
+
+        reached
+    }
+
+    /// What a `?` on the external `call` can produce in `error_type`: the
+    /// type itself when it isn't local, otherwise its `#[from]` variants
+    /// with non-local sources - narrowed to the called crate's when the
+    /// call is path-qualified (`toml::from_str`, `fs::read`)
+    fn external_errors(&self, error_type: &str, call: &str) -> Vec<String> {
+        let Some(local) = self.types.get(&base_name(error_type)) else {
+            return vec![error_type.to_string()];
+        };
+        let external: Vec<(&ErrorVariant, &String)> = local
+            .variants
+            .iter()
+            .filter_map(|v| v.from.as_ref().map(|from| (v, from)))
+            .filter(|(_, from)| !self.types.contains_key(&base_name(from)))
+            .collect();
+
+        let narrowed: Vec<&(&ErrorVariant, &String)> = match call.rsplit_once("::") {
+            Some((path, _)) => external
+                .iter()
+                .filter(|(_, from)| crate_root(from) == crate_root(path))
+                .collect(),
+            None => Vec::new(),
+        };
+        let candidates = if narrowed.is_empty() {
+            external.iter().collect()
+        } else {
+            narrowed
+        };
+        candidates
+            .into_iter()
+            .map(|(v, from)| format!("{}::{} (from {})", local.name, v.name, from))
+            .collect()
+    }
+}
+
+/// `Type::name` for methods, `name` for free functions
+fn function_key(file: &ParsedFile, item: &ParsedItem) -> String {
+    match crate::diff::enclosing_impl(file, item) {
+        Some(ItemKind::Impl { self_type, .. }) => {
+            format!("{}::{}", base_name(self_type), item.name)
+        }
+        _ => item.name.clone(),
+    }
+}
+
+/// `Self` in an error position means the impl's type
+fn self_or(error: &str, key: &str) -> String {
+    if error == "Self" {
+        key.split("::").next().unwrap_or(error).to_string()
+    } else {
+        error.to_string()
+    }
+}
+
+/// First segment of a path, with `std`'s common modules folded into `std`
+fn crate_root(path: &str) -> &str {
+    match path.split("::").next().unwrap_or(path) {
+        "io" | "fs" | "fmt" | "num" | "env" | "net" | "core" | "alloc" => "std",
+        root => root,
+    }
+}
+
+/// Type text without token spacing (`Result < T , E >` -> `Result<T,E>`),
+/// keeping spaces that separate words (`dyn Error`)
+fn compact(ty: &str) -> String {
+    let mut out = String::new();
+    for word in ty.split_whitespace() {
+        let joins_words = out.ends_with(|c: char| c.is_alphanumeric() || c == '_')
+            && word.starts_with(|c: char| c.is_alphanumeric() || c == '_');
+        if joins_words {
+            out.push(' ');
+        }
+        out.push_str(word);
+    }
+    out
+}
+
+/// Last path segment without generics or references
+fn base_name(ty: &str) -> String {
+    let ty = compact(ty);
+    let ty = ty.trim_start_matches('&').trim_start_matches("mut");
+    let without_generics = ty.split('<').next().unwrap_or(ty);
+    without_generics
+        .rsplit("::")
+        .next()
+        .unwrap_or(without_generics)
+        .to_string()
+}
+
+/// Local `type Result<T> = Result<T, E>` aliases: alias name -> E
+fn result_aliases(files: &[ParsedFile]) -> HashMap<String, String> {
+    files
+        .iter()
+        .flat_map(|f| &f.items)
+        .filter_map(|item| match &item.kind {
+            ItemKind::TypeAlias { ty } if item.name == "Result" => generic_args(&compact(ty))
+                .get(1)
+                .map(|e| (item.name.clone(), e.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Error type of a `Result` return type, if it is one
+fn result_error(ret: &str, aliases: &HashMap<String, String>) -> Option<String> {
+    let ret = compact(ret);
+    let head = ret.split('<').next()?;
+    if head.rsplit("::").next()? != "Result" {
+        return None;
+    }
+    let args = generic_args(&ret);
+    match args.len() {
+        2 => Some(args[1].clone()),
+        1 if head.ends_with("io::Result") => Some("std::io::Error".to_string()),
+        1 if head.starts_with("anyhow::") => Some("anyhow::Error".to_string()),
+        1 => aliases.get("Result").cloned(),
+        _ => None,
+    }
+}
+
+/// Top-level generic arguments of a compact type (`A<B,C<D>>` -> [B, C<D>])
+fn generic_args(ty: &str) -> Vec<String> {
+    let Some(open) = ty.find('<') else {
+        return Vec::new();
+    };
+    let inner = &ty[open + 1..ty.rfind('>').unwrap_or(ty.len())];
+    let mut args = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    for c in inner.chars() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                args.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        args.push(current);
+    }
+    args
+}
+
+/// Source lines of a span, joined
+fn body_text(source: &str, span: &Span) -> String {
+    source
+        .lines()
+        .skip(span.start_line.saturating_sub(1))
+        .take(span.end_line + 1 - span.start_line.max(1))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A `?` applied to a call
+struct QuestionSite {
+    /// Called name (`name` or `Type::name`)
+    callee: String,
+    /// Called path as written (`std::fs::read`)
+    path: String,
+    /// Whether `map_err` sits between the call and the `?`
+    mapped: bool,
+    /// Line offset within the body
+    line: usize,
+}
+
+/// Find `call(..)?`, `call(..).await?` and `call(..).map_err(..)?` in a body
+fn question_sites(body: &str) -> Vec<QuestionSite> {
+    let chars: Vec<char> = body.chars().collect();
+    let mut sites = Vec::new();
+
+    for (pos, &c) in chars.iter().enumerate() {
+        if c != '?' {
+            continue;
+        }
+        let line = chars[..pos].iter().filter(|&&c| c == '\n').count();
+        let mut end = skip_back_whitespace(&chars, pos);
+        end = strip_suffix(&chars, end, ".await");
+
+        let mut mapped = false;
+        let Some((mut name, mut start)) = call_before(&chars, end) else {
+            continue;
+        };
+        if name == "map_err" {
+            mapped = true;
+            let before = skip_back_whitespace(&chars, start);
+            if before == 0 || chars[before - 1] != '.' {
+                continue;
+            }
+            let inner_end =
+                strip_suffix(&chars, skip_back_whitespace(&chars, before - 1), ".await");
+            let Some(inner) = call_before(&chars, inner_end) else {
+                continue;
+            };
+            (name, start) = inner;
+        }
+
+        // Path qualifiers, e.g. `std::fs::` or `Type::`
+        let mut segments = vec![name];
+        let mut path_start = start;
+        while path_start >= 2 && chars[path_start - 2..path_start] == [':', ':'] {
+            let mut q_start = path_start - 2;
+            while q_start > 0 && (chars[q_start - 1].is_alphanumeric() || chars[q_start - 1] == '_')
+            {
+                q_start -= 1;
+            }
+            if q_start == path_start - 2 {
+                break;
+            }
+            segments.insert(0, chars[q_start..path_start - 2].iter().collect());
+            path_start = q_start;
+        }
+
+        let name = &segments[segments.len() - 1];
+        let callee = match segments.len().checked_sub(2).map(|i| &segments[i]) {
+            Some(qualifier) if qualifier.starts_with(char::is_uppercase) => {
+                format!("{}::{}", qualifier, name)
+            }
+            _ => name.clone(),
+        };
+
+        sites.push(QuestionSite {
+            callee,
+            path: segments.join("::"),
+            mapped,
+            line,
+        });
+    }
+
+    sites
+}
+
+fn skip_back_whitespace(chars: &[char], mut end: usize) -> usize {
+    while end > 0 && chars[end - 1].is_whitespace() {
+        end -= 1;
+    }
+    end
+}
+
+fn strip_suffix(chars: &[char], end: usize, suffix: &str) -> usize {
+    let suffix: Vec<char> = suffix.chars().collect();
+    if end >= suffix.len() && chars[end - suffix.len()..end] == suffix[..] {
+        skip_back_whitespace(chars, end - suffix.len())
+    } else {
+        end
+    }
+}
+
+/// The call ending at `end` (just after its `)`): its name and where the
+/// name starts
+fn call_before(chars: &[char], end: usize) -> Option<(String, usize)> {
+    if end == 0 || chars[end - 1] != ')' {
+        return None;
+    }
+    let mut depth = 0;
+    let mut open = end - 1;
+    loop {
+        match chars[open] {
+            ')' => depth += 1,
+            '(' => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            _ => {}
+        }
+        open = open.checked_sub(1)?;
+    }
+
+    // Skip a turbofish, e.g. `parse::<u32>()`
+    let mut name_end = skip_back_whitespace(chars, open);
+    if name_end > 0 && chars[name_end - 1] == '>' {
+        let mut depth = 0;
+        while name_end > 0 {
+            name_end -= 1;
+            match chars[name_end] {
+                '>' => depth += 1,
+                '<' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        name_end = name_end.checked_sub(2)?;
+    }
+
+    let mut start = name_end;
+    while start > 0 && (chars[start - 1].is_alphanumeric() || chars[start - 1] == '_') {
+        start -= 1;
+    }
+    (start < name_end).then(|| (chars[start..name_end].iter().collect(), start))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::PartialParser;
+
+    #[test]
+    fn test_error_flow() {
+        let root = std::env::temp_dir().join(format!("cargomap-errors-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let path = root.join("lib.rs");
+        std::fs::write(
+            &path,
+            r#"
+#[derive(Debug, thiserror::Error)]
+pub enum DepError {
+    #[error("io")]
+    Io(#[from] std::io::Error),
+    #[error("missing")]
+    Missing(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    #[error("dep")]
+    Dep(#[from] DepError),
+    #[error("parse")]
+    Parse(String),
+}
+
+fn read_manifest() -> Result<String, DepError> {
+    let text = std::fs::read_to_string("Cargo.toml")?;
+    if text.is_empty() {
+        return Err(DepError::Missing("manifest".into()));
+    }
+    Ok(text)
+}
+
+pub fn load() -> Result<u32, AppError> {
+    let text = read_manifest()?;
+    let n = text.len().to_string().parse::<u32>().map_err(|e| AppError::Parse(e.to_string()))?;
+    Ok(n)
+}
+"#,
+        )
+        .unwrap();
+        let file = PartialParser::new().parse_file(&path).unwrap();
+        let flow = ErrorFlow::build(&[file]);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            flow.type_edges(),
+            vec![
+                (
+                    "DepError".to_string(),
+                    "AppError".to_string(),
+                    "#[from] Dep".to_string()
+                ),
+                (
+                    "DepError".to_string(),
+                    "AppError".to_string(),
+                    "? into Dep".to_string()
+                ),
+                (
+                    "std::io::Error".to_string(),
+                    "DepError".to_string(),
+                    "#[from] Io".to_string()
+                ),
+            ]
+        );
+
+        let load = &flow.find("load")[0];
+        assert_eq!(
+            load.calls[0].propagation,
+            Propagation::Converted {
+                variant: Some("Dep".to_string())
+            }
+        );
+        // The `map_err` replaces the external parse error
+        assert_eq!(load.calls.len(), 1);
+        assert!(load.external.is_empty());
+
+        let reached: Vec<_> = flow
+            .reachable("load")
+            .into_iter()
+            .map(|r| (r.error, r.surfaced_as, r.chain.join(" -> ")))
+            .collect();
+        assert_eq!(
+            reached,
+            vec![
+                ("AppError::Parse".to_string(), None, "load".to_string()),
+                (
+                    "DepError::Missing".to_string(),
+                    Some("AppError::Dep".to_string()),
+                    "load -> read_manifest".to_string()
+                ),
+                (
+                    "DepError::Io (from std::io::Error)".to_string(),
+                    Some("AppError::Dep".to_string()),
+                    "load -> read_manifest -> std::fs::read_to_string".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,779 @@
+//! Struct field access index - where fields are read, written, built and
+//! destructured
+//!
+//! Walks function bodies and records every use of a local struct's fields:
+//! - Reads (`c.count`, including inside `format!`-style macros)
+//! - Writes (`c.count = 1`, `c.count += 1`, `&mut c.count`)
+//! - Constructions (`Counter { count: 0, .. }`, `Wrapper(x)`)
+//! - Destructurings (`let Counter { count, .. } = c`, `Wrapper(x) => ..`)
+//!
+//! Accesses are attributed to a struct by the type of their receiver. A
+//! small body index types locals from signatures, `let` annotations,
+//! struct literals, the return types of local functions and methods and
+//! `for` loops over typed collections; `self` is the impl's type. When the
+//! receiver can't be typed, the access is attributed to the only struct
+//! declaring a field of that name, and marked as inferred; if several do,
+//! it is left out. Files that don't parse are skipped.
+
+use crate::diff;
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+
+/// How a field is used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum FieldAccessKind {
+    /// `value.field`
+    Read,
+    /// `value.field = ..`, `value.field += ..` or `&mut value.field`
+    Write,
+    /// Named in a struct literal or passed to a tuple struct constructor
+    Construct,
+    /// Bound by a struct or tuple struct pattern
+    Destructure,
+}
+
+impl FieldAccessKind {
+    pub const ALL: [FieldAccessKind; 4] = [
+        FieldAccessKind::Read,
+        FieldAccessKind::Write,
+        FieldAccessKind::Construct,
+        FieldAccessKind::Destructure,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FieldAccessKind::Read => "read",
+            FieldAccessKind::Write => "write",
+            FieldAccessKind::Construct => "construct",
+            FieldAccessKind::Destructure => "destructure",
+        }
+    }
+
+    /// Parse a label as printed by [`FieldAccessKind::label`]
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.label() == label)
+    }
+}
+
+/// One use of a struct field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldAccess {
+    pub struct_name: String,
+    /// Field name; tuple struct fields are `0`, `1`, ...
+    pub field: String,
+    pub kind: FieldAccessKind,
+    pub file: PathBuf,
+    pub line: usize,
+    /// Enclosing item (e.g. "Counter::bump")
+    pub context: String,
+    /// Trimmed source line
+    pub snippet: String,
+    /// The receiver's type was unknown; attributed because no other struct
+    /// has a field of this name
+    pub inferred: bool,
+}
+
+/// Signatures the body index types expressions with
+#[derive(Debug, Clone, Default)]
+pub struct TypeIndex {
+    /// Struct -> field -> type
+    fields: HashMap<String, HashMap<String, syn::Type>>,
+    /// Field name -> structs declaring it
+    owners: HashMap<String, Vec<String>>,
+    /// (type, method) -> return type, with `Self` replaced by the type
+    methods: HashMap<(String, String), syn::Type>,
+    /// Free function -> return type; `None` when several share the name
+    functions: HashMap<String, Option<syn::Type>>,
+    enums: HashSet<String>,
+}
+
+impl TypeIndex {
+    /// Index the structs and signatures of parsed files
+    pub fn build(files: &[ParsedFile]) -> Self {
+        let mut index = TypeIndex::default();
+        for file in files {
+            for item in file.items_and_methods() {
+                match &item.kind {
+                    ItemKind::Struct { fields, .. } => {
+                        let types = index.fields.entry(item.name.clone()).or_default();
+                        for (position, field) in fields.iter().enumerate() {
+                            let name = field.name.clone().unwrap_or_else(|| position.to_string());
+                            if let Ok(ty) = syn::parse_str::<syn::Type>(&field.ty) {
+                                types.insert(name.clone(), ty);
+                            }
+                            let owners = index.owners.entry(name).or_default();
+                            if !owners.contains(&item.name) {
+                                owners.push(item.name.clone());
+                            }
+                        }
+                    }
+                    ItemKind::Enum { .. } => {
+                        index.enums.insert(item.name.clone());
+                    }
+                    ItemKind::Function { return_type, .. } => {
+                        let owner = match diff::enclosing_impl(file, item) {
+                            Some(ItemKind::Impl { self_type, .. }) => {
+                                syn::parse_str::<syn::Type>(self_type)
+                                    .ok()
+                                    .and_then(|ty| type_name(&ty, None))
+                            }
+                            _ => None,
+                        };
+                        let returns = return_type.as_deref().and_then(|ty| {
+                            let ty = match &owner {
+                                Some(owner) => replace_self(ty, owner),
+                                None => ty.to_string(),
+                            };
+                            syn::parse_str::<syn::Type>(&ty).ok()
+                        });
+                        match owner {
+                            Some(owner) => {
+                                if let Some(returns) = returns {
+                                    index.methods.insert((owner, item.name.clone()), returns);
+                                }
+                            }
+                            None => {
+                                index
+                                    .functions
+                                    .entry(item.name.clone())
+                                    .and_modify(|existing| *existing = None)
+                                    .or_insert(returns);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        index
+    }
+
+    fn has_field(&self, struct_name: &str, field: &str) -> bool {
+        self.fields
+            .get(struct_name)
+            .is_some_and(|fields| fields.contains_key(field))
+    }
+}
+
+/// Find the field accesses in a source file, or `None` if it doesn't parse
+pub fn find_in_source(source: &str, path: &Path, index: &TypeIndex) -> Option<Vec<FieldAccess>> {
+    let file = syn::parse_file(source).ok()?;
+
+    let mut visitor = FieldVisitor {
+        path,
+        lines: source.lines().collect(),
+        index,
+        context: Vec::new(),
+        impl_type: None,
+        locals: HashMap::new(),
+        accesses: Vec::new(),
+    };
+    visitor.visit_file(&file);
+
+    let mut accesses = visitor.accesses;
+    let key = |a: &FieldAccess| (a.line, a.struct_name.clone(), a.field.clone(), a.kind);
+    accesses.sort_by_key(key);
+    accesses.dedup_by(|a, b| key(a) == key(b));
+    Some(accesses)
+}
+
+/// Smart pointers and guards whose fields are reached through `Deref`
+const WRAPPERS: &[&str] = &[
+    "Box",
+    "Rc",
+    "Arc",
+    "Ref",
+    "RefMut",
+    "MutexGuard",
+    "RwLockReadGuard",
+    "RwLockWriteGuard",
+    "Cow",
+];
+
+/// Collections whose items `for` loops and indexing give
+const COLLECTIONS: &[&str] = &[
+    "Vec",
+    "VecDeque",
+    "HashSet",
+    "BTreeSet",
+    "BinaryHeap",
+    "Option",
+];
+
+/// Methods returning (a reference to) their receiver's type
+const SAME_TYPE_METHODS: &[&str] = &[
+    "clone",
+    "to_owned",
+    "borrow",
+    "borrow_mut",
+    "as_ref",
+    "as_mut",
+    "iter",
+    "iter_mut",
+    "into_iter",
+];
+
+/// A type without references, parentheses and smart pointers
+fn strip(ty: &syn::Type) -> &syn::Type {
+    match ty {
+        syn::Type::Reference(r) => strip(&r.elem),
+        syn::Type::Paren(p) => strip(&p.elem),
+        syn::Type::Group(g) => strip(&g.elem),
+        syn::Type::Path(p) => {
+            let wrapped = p
+                .path
+                .segments
+                .last()
+                .filter(|s| WRAPPERS.contains(&s.ident.to_string().as_str()));
+            match wrapped.and_then(|_| type_arg(ty, 0)) {
+                Some(inner) => strip(inner),
+                None => ty,
+            }
+        }
+        _ => ty,
+    }
+}
+
+/// The `n`th generic type argument of a path type (`Vec<T>` -> `T`)
+fn type_arg(ty: &syn::Type, n: usize) -> Option<&syn::Type> {
+    let syn::Type::Path(p) = ty else {
+        return None;
+    };
+    let syn::PathArguments::AngleBracketed(args) = &p.path.segments.last()?.arguments else {
+        return None;
+    };
+    args.args
+        .iter()
+        .filter_map(|arg| match arg {
+            syn::GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        })
+        .nth(n)
+}
+
+/// Name of a type (`&mut Box<a::Counter>` -> `Counter`), with `Self` as
+/// `self_type`
+fn type_name(ty: &syn::Type, self_type: Option<&str>) -> Option<String> {
+    let syn::Type::Path(p) = strip(ty) else {
+        return None;
+    };
+    let name = p.path.segments.last()?.ident.to_string();
+    if name == "Self" {
+        return self_type.map(str::to_string);
+    }
+    Some(name)
+}
+
+/// Item type of a collection or slice
+fn element(ty: &syn::Type) -> Option<syn::Type> {
+    match strip(ty) {
+        syn::Type::Slice(s) => Some((*s.elem).clone()),
+        syn::Type::Array(a) => Some((*a.elem).clone()),
+        stripped @ syn::Type::Path(p) => {
+            let name = p.path.segments.last()?.ident.to_string();
+            if COLLECTIONS.contains(&name.as_str()) {
+                type_arg(stripped, 0).cloned()
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// `T` of an `Option<T>` or `Result<T, E>`; other types unchanged
+fn unwrapped(ty: syn::Type) -> syn::Type {
+    let inner = match strip(&ty) {
+        stripped @ syn::Type::Path(p)
+            if p.path
+                .segments
+                .last()
+                .is_some_and(|s| s.ident == "Option" || s.ident == "Result") =>
+        {
+            type_arg(stripped, 0).cloned()
+        }
+        _ => None,
+    };
+    inner.unwrap_or(ty)
+}
+
+/// Replace the `Self` tokens of a type string
+fn replace_self(ty: &str, owner: &str) -> String {
+    ty.split_whitespace()
+        .map(|token| if token == "Self" { owner } else { token })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn member_name(member: &syn::Member) -> String {
+    match member {
+        syn::Member::Named(ident) => ident.to_string(),
+        syn::Member::Unnamed(index) => index.index.to_string(),
+    }
+}
+
+struct FieldVisitor<'a> {
+    path: &'a Path,
+    lines: Vec<&'a str>,
+    index: &'a TypeIndex,
+    /// Enclosing item names
+    context: Vec<String>,
+    /// Self type of the enclosing impl block
+    impl_type: Option<String>,
+    /// Types of the innermost function's locals, where known
+    locals: HashMap<String, syn::Type>,
+    accesses: Vec<FieldAccess>,
+}
+
+impl FieldVisitor<'_> {
+    /// Visit inside a function, with its parameters as locals
+    fn function(&mut self, name: String, sig: &syn::Signature, f: impl FnOnce(&mut Self)) {
+        let mut locals = HashMap::new();
+        for input in &sig.inputs {
+            if let syn::FnArg::Typed(arg) = input
+                && let syn::Pat::Ident(ident) = &*arg.pat
+            {
+                locals.insert(ident.ident.to_string(), (*arg.ty).clone());
+            }
+        }
+        let outer = std::mem::replace(&mut self.locals, locals);
+        self.context.push(name);
+        f(self);
+        self.context.pop();
+        self.locals = outer;
+    }
+
+    fn context(&self) -> String {
+        self.context
+            .last()
+            .cloned()
+            .unwrap_or_else(|| "<module>".to_string())
+    }
+
+    fn snippet(&self, line: usize) -> String {
+        self.lines
+            .get(line.wrapping_sub(1))
+            .map(|l| l.trim().chars().take(120).collect())
+            .unwrap_or_default()
+    }
+
+    fn record(
+        &mut self,
+        struct_name: String,
+        field: String,
+        kind: FieldAccessKind,
+        line: usize,
+        inferred: bool,
+    ) {
+        self.accesses.push(FieldAccess {
+            struct_name,
+            field,
+            kind,
+            file: self.path.to_path_buf(),
+            line,
+            context: self.context(),
+            snippet: self.snippet(line),
+            inferred,
+        });
+    }
+
+    /// Record `base.field`, attributed by the type of `base`
+    fn record_field(&mut self, expr: &syn::ExprField, kind: FieldAccessKind) {
+        let field = member_name(&expr.member);
+        let line = expr.member.span().start().line;
+        let owner = self
+            .type_of(&expr.base)
+            .and_then(|ty| type_name(&ty, self.impl_type.as_deref()));
+        match owner {
+            Some(owner) if self.index.has_field(&owner, &field) => {
+                self.record(owner, field, kind, line, false);
+            }
+            // A type the index doesn't know, or doesn't have this field
+            Some(_) => {}
+            None => {
+                if let Some([owner]) = self.index.owners.get(&field).map(Vec::as_slice) {
+                    self.record(owner.clone(), field, kind, line, true);
+                }
+            }
+        }
+    }
+
+    /// Local struct a path names (`Counter`, `a::Counter`, `Self`), not
+    /// an enum variant
+    fn struct_of(&self, path: &syn::Path) -> Option<String> {
+        let segments: Vec<String> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+        let name = segments.last()?;
+        let name = if name == "Self" {
+            self.impl_type.clone()?
+        } else {
+            name.clone()
+        };
+        let variant =
+            segments.len() >= 2 && self.index.enums.contains(&segments[segments.len() - 2]);
+        (!variant && self.index.fields.contains_key(&name)).then_some(name)
+    }
+
+    /// Type of an expression, where the body index knows it
+    fn type_of(&self, expr: &syn::Expr) -> Option<syn::Type> {
+        match expr {
+            syn::Expr::Path(p) => {
+                let ident = p.path.get_ident()?.to_string();
+                if ident == "self" {
+                    return syn::parse_str(self.impl_type.as_deref()?).ok();
+                }
+                self.locals.get(&ident).cloned()
+            }
+            syn::Expr::Paren(p) => self.type_of(&p.expr),
+            syn::Expr::Group(g) => self.type_of(&g.expr),
+            syn::Expr::Reference(r) => self.type_of(&r.expr),
+            syn::Expr::Unary(u) if matches!(u.op, syn::UnOp::Deref(_)) => self.type_of(&u.expr),
+            syn::Expr::Cast(c) => Some((*c.ty).clone()),
+            syn::Expr::Field(f) => {
+                let owner = type_name(&self.type_of(&f.base)?, self.impl_type.as_deref())?;
+                self.index
+                    .fields
+                    .get(&owner)?
+                    .get(&member_name(&f.member))
+                    .cloned()
+            }
+            syn::Expr::Index(i) => element(&self.type_of(&i.expr)?),
+            syn::Expr::Try(t) => self.type_of(&t.expr).map(unwrapped),
+            syn::Expr::Struct(s) => syn::parse_str(&self.struct_of(&s.path)?).ok(),
+            syn::Expr::MethodCall(call) => {
+                let method = call.method.to_string();
+                let receiver = self.type_of(&call.receiver);
+                match method.as_str() {
+                    "unwrap" | "expect" => receiver.map(unwrapped),
+                    m if SAME_TYPE_METHODS.contains(&m) => receiver,
+                    _ => {
+                        let owner = type_name(&receiver?, self.impl_type.as_deref())?;
+                        self.index.methods.get(&(owner, method)).cloned()
+                    }
+                }
+            }
+            syn::Expr::Call(call) => {
+                let syn::Expr::Path(func) = &*call.func else {
+                    return None;
+                };
+                let segments: Vec<String> = func
+                    .path
+                    .segments
+                    .iter()
+                    .map(|s| s.ident.to_string())
+                    .collect();
+                match segments.as_slice() {
+                    [name] => self.index.functions.get(name)?.clone(),
+                    [.., owner, method] => {
+                        let owner = if owner == "Self" {
+                            self.impl_type.clone()?
+                        } else {
+                            owner.clone()
+                        };
+                        if let Some(returns) =
+                            self.index.methods.get(&(owner.clone(), method.clone()))
+                        {
+                            return Some(returns.clone());
+                        }
+                        // Tuple struct constructors
+                        self.struct_of(&func.path)
+                            .and_then(|name| syn::parse_str(&name).ok())
+                    }
+                    [] => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Bind a pattern's name to a type, or forget it when unknown
+    fn bind(&mut self, pat: &syn::Pat, ty: Option<syn::Type>) {
+        let (pat, ty) = match pat {
+            syn::Pat::Type(typed) => (&*typed.pat, Some((*typed.ty).clone())),
+            _ => (pat, ty),
+        };
+        if let syn::Pat::Ident(ident) = pat {
+            let name = ident.ident.to_string();
+            match ty {
+                Some(ty) => {
+                    self.locals.insert(name, ty);
+                }
+                None => {
+                    self.locals.remove(&name);
+                }
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for FieldVisitor<'_> {
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        self.function(item.sig.ident.to_string(), &item.sig, |v| {
+            visit::visit_item_fn(v, item)
+        });
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        let previous = std::mem::replace(&mut self.impl_type, type_name(&item.self_ty, None));
+        visit::visit_item_impl(self, item);
+        self.impl_type = previous;
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        let name = match &self.impl_type {
+            Some(ty) => format!("{}::{}", ty, item.sig.ident),
+            None => item.sig.ident.to_string(),
+        };
+        self.function(name, &item.sig, |v| visit::visit_impl_item_fn(v, item));
+    }
+
+    fn visit_item_trait(&mut self, item: &'ast syn::ItemTrait) {
+        let previous = self.impl_type.take();
+        self.context.push(item.ident.to_string());
+        visit::visit_item_trait(self, item);
+        self.context.pop();
+        self.impl_type = previous;
+    }
+
+    fn visit_trait_item_fn(&mut self, item: &'ast syn::TraitItemFn) {
+        let name = match self.context.last() {
+            Some(trait_name) => format!("{}::{}", trait_name, item.sig.ident),
+            None => item.sig.ident.to_string(),
+        };
+        self.function(name, &item.sig, |v| visit::visit_trait_item_fn(v, item));
+    }
+
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        let ty = local
+            .init
+            .as_ref()
+            .and_then(|init| self.type_of(&init.expr));
+        visit::visit_local(self, local);
+        self.bind(&local.pat, ty);
+    }
+
+    fn visit_expr_for_loop(&mut self, expr: &'ast syn::ExprForLoop) {
+        self.visit_expr(&expr.expr);
+        let item = self.type_of(&expr.expr).and_then(|ty| element(&ty));
+        let outer = self.locals.clone();
+        self.bind(&expr.pat, item);
+        self.visit_pat(&expr.pat);
+        self.visit_block(&expr.body);
+        self.locals = outer;
+    }
+
+    fn visit_expr_field(&mut self, expr: &'ast syn::ExprField) {
+        self.record_field(expr, FieldAccessKind::Read);
+        visit::visit_expr_field(self, expr);
+    }
+
+    fn visit_expr_assign(&mut self, expr: &'ast syn::ExprAssign) {
+        match &*expr.left {
+            syn::Expr::Field(field) => {
+                self.record_field(field, FieldAccessKind::Write);
+                self.visit_expr(&field.base);
+            }
+            left => self.visit_expr(left),
+        }
+        self.visit_expr(&expr.right);
+    }
+
+    fn visit_expr_binary(&mut self, expr: &'ast syn::ExprBinary) {
+        let compound = matches!(
+            expr.op,
+            syn::BinOp::AddAssign(_)
+                | syn::BinOp::SubAssign(_)
+                | syn::BinOp::MulAssign(_)
+                | syn::BinOp::DivAssign(_)
+                | syn::BinOp::RemAssign(_)
+                | syn::BinOp::BitXorAssign(_)
+                | syn::BinOp::BitAndAssign(_)
+                | syn::BinOp::BitOrAssign(_)
+                | syn::BinOp::ShlAssign(_)
+                | syn::BinOp::ShrAssign(_)
+        );
+        match &*expr.left {
+            syn::Expr::Field(field) if compound => {
+                self.record_field(field, FieldAccessKind::Write);
+                self.visit_expr(&field.base);
+            }
+            left => self.visit_expr(left),
+        }
+        self.visit_expr(&expr.right);
+    }
+
+    fn visit_expr_reference(&mut self, expr: &'ast syn::ExprReference) {
+        match &*expr.expr {
+            syn::Expr::Field(field) if expr.mutability.is_some() => {
+                self.record_field(field, FieldAccessKind::Write);
+                self.visit_expr(&field.base);
+            }
+            _ => visit::visit_expr_reference(self, expr),
+        }
+    }
+
+    fn visit_expr_struct(&mut self, expr: &'ast syn::ExprStruct) {
+        if let Some(name) = self.struct_of(&expr.path) {
+            for field in &expr.fields {
+                let line = field.member.span().start().line;
+                let member = member_name(&field.member);
+                self.record(
+                    name.clone(),
+                    member,
+                    FieldAccessKind::Construct,
+                    line,
+                    false,
+                );
+            }
+        }
+        visit::visit_expr_struct(self, expr);
+    }
+
+    fn visit_expr_call(&mut self, expr: &'ast syn::ExprCall) {
+        if let syn::Expr::Path(func) = &*expr.func
+            && let Some(name) = self.struct_of(&func.path)
+        {
+            for (position, arg) in expr.args.iter().enumerate() {
+                let line = arg.span().start().line;
+                self.record(
+                    name.clone(),
+                    position.to_string(),
+                    FieldAccessKind::Construct,
+                    line,
+                    false,
+                );
+            }
+        }
+        visit::visit_expr_call(self, expr);
+    }
+
+    fn visit_pat_struct(&mut self, pat: &'ast syn::PatStruct) {
+        if let Some(name) = self.struct_of(&pat.path) {
+            for field in &pat.fields {
+                let line = field.member.span().start().line;
+                let member = member_name(&field.member);
+                self.record(
+                    name.clone(),
+                    member,
+                    FieldAccessKind::Destructure,
+                    line,
+                    false,
+                );
+            }
+        }
+        visit::visit_pat_struct(self, pat);
+    }
+
+    fn visit_pat_tuple_struct(&mut self, pat: &'ast syn::PatTupleStruct) {
+        if let Some(name) = self.struct_of(&pat.path) {
+            for (position, elem) in pat.elems.iter().enumerate() {
+                if matches!(elem, syn::Pat::Wild(_) | syn::Pat::Rest(_)) {
+                    continue;
+                }
+                let line = elem.span().start().line;
+                self.record(
+                    name.clone(),
+                    position.to_string(),
+                    FieldAccessKind::Destructure,
+                    line,
+                    false,
+                );
+            }
+        }
+        visit::visit_pat_tuple_struct(self, pat);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        visit::visit_macro(self, mac);
+        if let Ok(args) = mac.parse_body_with(
+            syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated,
+        ) {
+            for arg in &args {
+                self.visit_expr(arg);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::PartialParser;
+
+    #[test]
+    fn test_find_field_accesses() {
+        let source = r#"
+pub struct Counter { count: u32, label: String }
+pub struct Tag { label: String }
+pub struct Id(u64);
+pub enum Event { Counted { count: u32 } }
+
+impl Counter {
+    pub fn new() -> Self {
+        Counter { count: 0, label: String::new() }
+    }
+    pub fn bump(&mut self) {
+        self.count += 1;
+    }
+}
+
+fn total(counters: &[Counter], tag: &Tag) -> u32 {
+    let mut sum = 0;
+    for c in counters {
+        println!("{} {}", c.label, tag.label);
+        sum += c.count;
+    }
+    sum
+}
+
+fn reset(id: Id) {
+    let mut counter = Counter::new();
+    let count = &mut counter.count;
+    let Counter { label, .. } = counter;
+    let Id(raw) = id;
+    let event = Event::Counted { count: 1 };
+    unknown().count;
+    unknown().label;
+}
+"#;
+        let file = PartialParser::new()
+            .parse_source(Path::new("lib.rs"), source)
+            .unwrap();
+        let index = TypeIndex::build(std::slice::from_ref(&file));
+        let accesses = find_in_source(source, Path::new("lib.rs"), &index).unwrap();
+
+        let found: Vec<(&str, &str, &str, usize, bool)> = accesses
+            .iter()
+            .map(|a| {
+                (
+                    a.struct_name.as_str(),
+                    a.field.as_str(),
+                    a.kind.label(),
+                    a.line,
+                    a.inferred,
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("Counter", "count", "construct", 9, false),
+                ("Counter", "label", "construct", 9, false),
+                ("Counter", "count", "write", 12, false),
+                ("Counter", "label", "read", 19, false),
+                ("Tag", "label", "read", 19, false),
+                ("Counter", "count", "read", 20, false),
+                ("Counter", "count", "write", 27, false),
+                ("Counter", "label", "destructure", 28, false),
+                ("Id", "0", "destructure", 29, false),
+                // Only `Counter` has a `count`; `label` is ambiguous
+                ("Counter", "count", "read", 31, true),
+            ]
+        );
+        assert_eq!(accesses[2].context, "Counter::bump");
+        assert_eq!(accesses[2].snippet, "self.count += 1;");
+    }
+}
@@ -0,0 +1,372 @@
+//! Fuzzy name matching for search
+//!
+//! Matches a query against an identifier using, from best to worst:
+//! - Exact, prefix and substring matches (case-insensitive)
+//! - Word prefixes: `SemGrav`, `sem_grav` and `sg` all match `SemanticGravity`
+//! - Edit distance: `analize_project` matches `analyze_project`
+//! - Subsequence: `smgrv` matches `SemanticGravity`, as long as the match
+//!   starts a word and isn't scattered across it
+//!
+//! Each match carries a quality in `0.0..=1.0` and the byte ranges of the
+//! candidate that matched, for highlighting.
+
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// How a query matched a candidate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatchKind {
+    Exact,
+    Prefix,
+    Substring,
+    /// Query is made of prefixes of the candidate's words (camelCase / snake_case)
+    WordPrefix,
+    /// Within a small edit distance
+    Typo,
+    Subsequence,
+    /// Only the doc comment mentions the query
+    DocComment,
+}
+
+/// Result of matching a query against a name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FuzzyMatch {
+    pub kind: MatchKind,
+    /// Match quality, 1.0 for an exact match
+    pub quality: f64,
+    /// Byte ranges of the candidate that matched
+    pub ranges: Vec<Range<usize>>,
+}
+
+impl FuzzyMatch {
+    fn new(kind: MatchKind, quality: f64, ranges: Vec<Range<usize>>) -> Self {
+        Self {
+            kind,
+            quality,
+            ranges,
+        }
+    }
+
+    /// A match on the doc comment rather than the name
+    pub fn doc_comment() -> Self {
+        Self::new(MatchKind::DocComment, 0.25, Vec::new())
+    }
+}
+
+/// Match `query` against `candidate`, returning the best match if any
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query.trim().chars().map(fold_case).collect();
+    if query.is_empty() {
+        return None;
+    }
+
+    // Work on (byte offset, lowercase char) pairs so ranges map back to `candidate`
+    let chars: Vec<(usize, char)> = candidate
+        .char_indices()
+        .map(|(i, c)| (i, fold_case(c)))
+        .collect();
+    let lower: Vec<char> = chars.iter().map(|&(_, c)| c).collect();
+    let byte_range = |start: usize, end: usize| {
+        let from = chars.get(start).map_or(candidate.len(), |&(i, _)| i);
+        let to = chars.get(end).map_or(candidate.len(), |&(i, _)| i);
+        from..to
+    };
+
+    if lower == query {
+        return Some(FuzzyMatch::new(MatchKind::Exact, 1.0, whole(candidate)));
+    }
+
+    if let Some(start) = find(&lower, &query) {
+        let (kind, quality) = if start == 0 {
+            (MatchKind::Prefix, 0.9)
+        } else {
+            (MatchKind::Substring, 0.8)
+        };
+        // Longer coverage of the candidate is a better match
+        let coverage = query.len() as f64 / lower.len() as f64;
+        return Some(FuzzyMatch::new(
+            kind,
+            quality + 0.05 * coverage,
+            vec![byte_range(start, start + query.len())],
+        ));
+    }
+
+    let words = split_words(candidate);
+    let compact: Vec<char> = query.iter().copied().filter(|c| *c != '_').collect();
+    if let Some(spans) = match_word_prefixes(&lower, &words, &compact) {
+        let used = spans.len() as f64 / words.len() as f64;
+        return Some(FuzzyMatch::new(
+            MatchKind::WordPrefix,
+            0.65 + 0.1 * used,
+            spans
+                .into_iter()
+                .map(|r| byte_range(r.start, r.end))
+                .collect(),
+        ));
+    }
+
+    let max_edits = (query.len() / 4).min(3);
+    if max_edits > 0 && lower.len().abs_diff(query.len()) <= max_edits {
+        let distance = edit_distance(&query, &lower);
+        if distance <= max_edits {
+            return Some(FuzzyMatch::new(
+                MatchKind::Typo,
+                0.6 - 0.1 * (distance - 1) as f64,
+                whole(candidate),
+            ));
+        }
+    }
+
+    if compact.len() >= 3
+        && let Some(positions) = subsequence(&lower, &compact)
+    {
+        // Reject scattered matches: the match must start a word, and at
+        // most half the query may land in runs that start mid-word
+        let runs = merge_positions(&positions);
+        let starts_word = |run: &Range<usize>| words.iter().any(|w| w.start == run.start);
+        let mid_word = runs.iter().filter(|run| !starts_word(run)).count();
+        if starts_word(&runs[0]) && mid_word <= compact.len() / 2 {
+            let scatter = (runs.len() - 1) as f64 / compact.len() as f64;
+            return Some(FuzzyMatch::new(
+                MatchKind::Subsequence,
+                0.3 + 0.2 * (1.0 - scatter),
+                runs.into_iter()
+                    .map(|r| byte_range(r.start, r.end))
+                    .collect(),
+            ));
+        }
+    }
+
+    None
+}
+
+/// Wrap the matched ranges of `text` in `open` / `close` markers
+pub fn highlight(text: &str, ranges: &[Range<usize>], open: &str, close: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+
+    for range in ranges {
+        if range.start < last || range.end > text.len() {
+            continue;
+        }
+        out.push_str(&text[last..range.start]);
+        out.push_str(open);
+        out.push_str(&text[range.clone()]);
+        out.push_str(close);
+        last = range.end;
+    }
+
+    out.push_str(&text[last..]);
+    out
+}
+
+/// Lowercase a char for comparison. Query and candidate fold the same way,
+/// one char to one char, so positions still map back to the candidate.
+fn fold_case(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// A single range covering the whole candidate
+fn whole(candidate: &str) -> Vec<Range<usize>> {
+    std::iter::once(0..candidate.len()).collect()
+}
+
+/// Lowercase words of an identifier (`SemanticGravity` -> `["semantic", "gravity"]`)
+pub fn words(ident: &str) -> Vec<String> {
+    let chars: Vec<char> = ident.chars().collect();
+    split_words(ident)
+        .into_iter()
+        .map(|r| chars[r].iter().copied().map(fold_case).collect())
+        .collect()
+}
+
+/// Char position of the first occurrence of `needle` in `haystack`
+fn find(haystack: &[char], needle: &[char]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Split an identifier into words (char ranges) on `_` and case changes
+fn split_words(ident: &str) -> Vec<Range<usize>> {
+    let chars: Vec<char> = ident.chars().collect();
+    let mut words = Vec::new();
+    let mut start = None;
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if let Some(s) = start.take() {
+                words.push(s..i);
+            }
+            continue;
+        }
+
+        let boundary = i > 0 && {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            // fooBar, HTTPServer -> HTTP|Server, v2Config
+            (prev.is_lowercase() && c.is_uppercase())
+                || (prev.is_uppercase() && c.is_uppercase() && next_lower)
+                || (prev.is_ascii_digit() != c.is_ascii_digit() && prev.is_alphanumeric())
+        };
+
+        match start {
+            Some(s) if boundary => {
+                words.push(s..i);
+                start = Some(i);
+            }
+            None => start = Some(i),
+            _ => {}
+        }
+    }
+
+    if let Some(s) = start {
+        words.push(s..chars.len());
+    }
+
+    words
+}
+
+/// Match `query` as a concatenation of non-empty prefixes of `words`, in order
+///
+/// Returns the matched char ranges, or `None` if the query can't be split
+/// that way. The first word must be matched.
+fn match_word_prefixes(
+    lower: &[char],
+    words: &[Range<usize>],
+    query: &[char],
+) -> Option<Vec<Range<usize>>> {
+    fn go(
+        lower: &[char],
+        words: &[Range<usize>],
+        query: &[char],
+        spans: &mut Vec<Range<usize>>,
+    ) -> bool {
+        if query.is_empty() {
+            return true;
+        }
+        let Some((word, rest)) = words.split_first() else {
+            return false;
+        };
+
+        let text = &lower[word.clone()];
+        let max = text.iter().zip(query).take_while(|(a, b)| a == b).count();
+
+        // Prefer consuming as much of this word as possible
+        for len in (1..=max).rev() {
+            spans.push(word.start..word.start + len);
+            if go(lower, rest, &query[len..], spans) {
+                return true;
+            }
+            spans.pop();
+        }
+
+        // Skip this word entirely (only after the first one)
+        !spans.is_empty() && go(lower, rest, query, spans)
+    }
+
+    let mut spans = Vec::new();
+    if words.len() > 1 && go(lower, words, query, &mut spans) {
+        Some(spans)
+    } else {
+        None
+    }
+}
+
+/// Optimal string alignment distance (Levenshtein with transpositions)
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut prev2: Vec<usize> = vec![0; b.len() + 1];
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (prev[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(prev2[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut prev2, &mut prev);
+        std::mem::swap(&mut prev, &mut current);
+    }
+
+    prev[b.len()]
+}
+
+/// Positions of `query`'s chars appearing in order in `lower`
+fn subsequence(lower: &[char], query: &[char]) -> Option<Vec<usize>> {
+    let mut positions = Vec::with_capacity(query.len());
+    let mut from = 0;
+
+    for q in query {
+        let offset = lower[from..].iter().position(|c| c == q)?;
+        positions.push(from + offset);
+        from += offset + 1;
+    }
+
+    Some(positions)
+}
+
+/// Merge sorted positions into contiguous ranges
+fn merge_positions(positions: &[usize]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for &p in positions {
+        match ranges.last_mut() {
+            Some(last) if last.end == p => last.end = p + 1,
+            _ => ranges.push(p..p + 1),
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_abbreviations() {
+        for query in ["SemGrav", "sem_grav", "sg"] {
+            let m = fuzzy_match(query, "SemanticGravity").unwrap();
+            assert_eq!(m.kind, MatchKind::WordPrefix, "{query}");
+        }
+
+        let m = fuzzy_match("SemGrav", "SemanticGravity").unwrap();
+        assert_eq!(m.ranges, vec![0..3, 8..12]);
+        assert_eq!(
+            highlight("SemanticGravity", &m.ranges, "[", "]"),
+            "[Sem]antic[Grav]ity"
+        );
+    }
+
+    #[test]
+    fn test_match_ordering() {
+        let exact = fuzzy_match("search", "search").unwrap();
+        let prefix = fuzzy_match("search", "search_code").unwrap();
+        let typo = fuzzy_match("analize_project", "analyze_project").unwrap();
+        let subseq = fuzzy_match("azpj", "analyze_project").unwrap();
+
+        assert_eq!(typo.kind, MatchKind::Typo);
+        assert_eq!(subseq.kind, MatchKind::Subsequence);
+        assert!(exact.quality > prefix.quality);
+        assert!(prefix.quality > typo.quality);
+        assert!(typo.quality > subseq.quality);
+        assert!(fuzzy_match("xyz", "analyze_project").is_none());
+    }
+
+    #[test]
+    fn test_scattered_near_misses() {
+        // Each letter is in the name, in order, but not as a usable match
+        assert!(fuzzy_match("navy", "SemanticGravity").is_none());
+        assert!(fuzzy_match("sv", "SemanticGravity").is_none());
+        assert!(fuzzy_match("smgrv", "SemanticGravity").is_some());
+
+        // Non-ASCII letters fold the same way on both sides
+        let m = fuzzy_match("ÉTAT", "état_courant").unwrap();
+        assert_eq!(m.kind, MatchKind::Prefix);
+        assert_eq!(m.ranges, vec![0..5]);
+    }
+}
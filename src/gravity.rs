@@ -7,10 +7,33 @@
 //! - Generic complexity depth
 //! - Test function detection (deprioritized)
 //! - Trait implementations for structs
-
-use crate::parser::PartialParser;
+//! - Git history of the item's lines (optional): churn, recency, authors, fixes
+//! - Fuzzy match quality against the search query
+//! - Embedding similarity to a natural-language query (semantic search)
+
+use crate::config::ScoringWeights;
+use crate::coverage::{CoverageError, CoverageReport};
+use crate::errorflow::ErrorFlow;
+use crate::fields::{self, FieldAccess, TypeIndex};
+use crate::fuzzy::{self, FuzzyMatch};
+use crate::hazards::{self, AsyncHazard, HazardKind};
+use crate::history::{GitHistory, HistoryError};
+use crate::panics::{self, RankedPanicSite};
+use crate::parser::{self, PartialParser, UseLeaf};
+use crate::query::{ItemContext, SearchQuery};
+use crate::resolve::PathResolver;
+use crate::semantic::{self, SemanticError, SemanticIndex};
+use crate::semver;
+use crate::snapshot;
+use crate::targets::TargetKind;
+use crate::testmap::{self, TestMap};
+use crate::traits::TraitMatrix;
+use crate::typegraph::TypeGraph;
 use crate::types::*;
-use std::collections::{HashMap, HashSet};
+use crate::unsafety::{self, Declarations, RankedUnsafeSite, UnsafeKind};
+use crate::usage::{self, UsageIndex};
+use crate::workspace::{Workspace, WorkspaceError};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
@@ -20,9 +43,20 @@ pub enum GravityError {
     Io(#[from] std::io::Error),
     #[error("Parse error: {0}")]
     Parse(String),
+    #[error("History error: {0}")]
+    History(#[from] HistoryError),
+    #[error("Semantic index error: {0}")]
+    Semantic(#[from] SemanticError),
+    #[error("Coverage error: {0}")]
+    Coverage(#[from] CoverageError),
+    #[error("Workspace error: {0}")]
+    Workspace(#[from] WorkspaceError),
 }
 
-/// Scoring weights based on the factor table
+/// Default scoring weights based on the factor table
+///
+/// These can be overridden per project through `cargomap.toml`
+/// (see [`crate::config`]).
 pub mod weights {
     pub const CROSS_MODULE_USAGE: f64 = 50.0;
     pub const PUB_VISIBILITY: f64 = 20.0;
@@ -33,8 +67,23 @@ pub mod weights {
     pub const ENTRY_DISTANCE_PENALTY: f64 = -5.0;
     pub const IMPL_RICHNESS: f64 = 5.0;
     pub const TRAIT_IMPL: f64 = 3.0;
+    /// Per ln(1 + commits touching the item)
+    pub const CHURN: f64 = 10.0;
+    /// Full bonus for a change today, halved after 30 days
+    pub const RECENCY: f64 = 20.0;
+    pub const AUTHOR_COUNT: f64 = 2.0;
+    pub const BUG_FIX: f64 = 5.0;
+    /// Per unit of fuzzy match quality (1.0 = exact name match)
+    pub const MATCH_QUALITY: f64 = 100.0;
+    /// Per unit of cosine similarity to a semantic query
+    pub const SEMANTIC_SIMILARITY: f64 = 300.0;
+    /// Per point of cognitive complexity (off unless a profile enables it)
+    pub const COMPLEXITY: f64 = 0.0;
 }
 
+/// Semantic search results below this cosine similarity are dropped
+const MIN_SEMANTIC_SIMILARITY: f32 = 0.2;
+
 /// Standard library / prelude methods to filter out
 const PRELUDE_METHODS: &[&str] = &[
     // Iterator methods
@@ -138,9 +187,38 @@ const PRELUDE_METHODS: &[&str] = &[
     "mem",
 ];
 
+/// How an incremental update changed a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileChange {
+    Added,
+    Modified,
+    Removed,
+}
+
+/// Result of re-analyzing one file in place
+#[derive(Debug, Clone)]
+pub struct FileUpdate {
+    pub path: PathBuf,
+    pub change: FileChange,
+    pub items_before: usize,
+    pub items_after: usize,
+}
+
+/// What one file contributes to the reference map, cross-crate references
+/// and module dependencies
+#[derive(Default)]
+struct FileReferences {
+    external: Vec<ExternalReference>,
+    crates: Vec<CrateReference>,
+    modules: Vec<ModuleDependency>,
+}
+
 /// Semantic gravity analyzer for ranking code elements
+#[derive(Clone)]
 pub struct SemanticGravity {
     parser: PartialParser,
+    /// Root passed to `analyze_project`
+    root: PathBuf,
     /// Module tree built from the project
     module_tree: ModuleTree,
     /// Call graph built from analysis
@@ -149,30 +227,97 @@ pub struct SemanticGravity {
     files: Vec<ParsedFile>,
     /// Map from type names to their impl blocks
     impl_map: HashMap<String, Vec<ParsedItem>>,
+    /// Trait implementations from impls, derives and blanket impls
+    trait_matrix: TraitMatrix,
+    /// Items and the local types they mention
+    type_graph: TypeGraph,
+    /// Tests and the functions they reach
+    test_map: TestMap,
+    /// Where each type is mentioned, per file
+    usage_index: UsageIndex,
     /// Distance cache from entry point
     distance_cache: HashMap<PathBuf, usize>,
     /// External reference map (crate::path -> local usages)
     reference_map: ReferenceMap,
+    /// Workspace members under the analyzed root
+    workspace: Workspace,
+    /// Paths from one workspace member into another
+    crate_references: Vec<CrateReference>,
+    /// Modules and crates each module refers to
+    module_dependencies: Vec<ModuleDependency>,
+    /// Target kinds whose roots count as entry points for distances
+    entry_kinds: Vec<TargetKind>,
     /// Module membership for cross-module analysis
     file_to_module: HashMap<PathBuf, String>,
+    /// Public paths of each member's library items, through re-exports
+    resolvers: Vec<PathResolver>,
+    /// Weights used by `score_item`
+    weights: ScoringWeights,
+    /// Line-level git history, if loaded
+    history: Option<GitHistory>,
+    /// Item embeddings for semantic search, if loaded
+    semantic_index: Option<SemanticIndex>,
+    /// Line coverage from an lcov / llvm-cov report, if loaded
+    coverage: Option<CoverageReport>,
 }
 
 impl SemanticGravity {
     pub fn new() -> Self {
         Self {
             parser: PartialParser::new(),
+            root: PathBuf::new(),
             module_tree: ModuleTree::default(),
             call_graph: CallGraph::default(),
             files: Vec::new(),
             impl_map: HashMap::new(),
+            trait_matrix: TraitMatrix::default(),
+            type_graph: TypeGraph::default(),
+            test_map: TestMap::default(),
+            usage_index: UsageIndex::default(),
             distance_cache: HashMap::new(),
             reference_map: ReferenceMap::default(),
+            workspace: Workspace::default(),
+            crate_references: Vec::new(),
+            module_dependencies: Vec::new(),
+            entry_kinds: vec![TargetKind::Lib, TargetKind::Bin],
             file_to_module: HashMap::new(),
+            resolvers: Vec::new(),
+            weights: ScoringWeights::default(),
+            history: None,
+            semantic_index: None,
+            coverage: None,
         }
     }
 
+    /// Use a custom set of scoring weights (e.g. from a ranking profile)
+    pub fn with_weights(mut self, weights: ScoringWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    /// Choose which build targets are entry points for entry distance
+    /// (default: libraries and binaries)
+    pub fn with_entry_targets(mut self, kinds: Vec<TargetKind>) -> Self {
+        self.entry_kinds = kinds;
+        self
+    }
+
+    /// Replace the scoring weights used for ranking
+    pub fn set_weights(&mut self, weights: ScoringWeights) {
+        self.weights = weights;
+    }
+
+    /// Get the scoring weights used for ranking
+    pub fn weights(&self) -> &ScoringWeights {
+        &self.weights
+    }
+
     /// Analyze a project and build the gravity model
     pub fn analyze_project(&mut self, root: &Path) -> Result<(), GravityError> {
+        // Find the crates to analyze
+        self.workspace = Workspace::discover(root)?;
+        self.root = root.to_path_buf();
+
         // Parse all files
         self.files = self
             .parser
@@ -181,92 +326,473 @@ impl SemanticGravity {
 
         // Build file -> module mapping
         self.build_file_module_map();
+        self.build_resolvers();
 
         // Build module tree
-        self.build_module_tree(root);
+        self.build_module_tree();
 
         // Build impl map
         self.build_impl_map();
+        self.trait_matrix = TraitMatrix::build(&self.files);
+        self.type_graph = TypeGraph::build(&self.files);
+        self.usage_index = UsageIndex::build(&self.files);
 
         // Build call graph with cross-module tracking
         self.build_call_graph()?;
+        self.test_map = TestMap::build(&self.files, &self.call_graph, &self.crate_roots());
 
         // Build external reference map
         self.build_reference_map()?;
 
-        // Compute distances from entry point
-        self.compute_distances(root);
+        // Compute distances from entry points
+        self.compute_distances();
+
+        // A loaded semantic index follows the re-parsed items
+        if self.semantic_index.is_some() {
+            self.refresh_semantic_index(root)?;
+        }
+
+        Ok(())
+    }
+
+    /// Re-analyze one changed, added or removed file in place, patching the
+    /// files, impl map, call graph, usage index, reference map and module
+    /// map instead of re-analyzing the whole project. `path` may be absolute
+    /// or relative to the analyzed root. Returns `None` for paths that
+    /// aren't analyzed Rust files. A loaded semantic index embeds the
+    /// file's new or changed items; git history and coverage are not
+    /// refreshed.
+    pub fn update_file(&mut self, path: &Path) -> Result<Option<FileUpdate>, GravityError> {
+        let path = self.project_path(path);
+        if path.extension().is_none_or(|ext| ext != "rs")
+            || parser::is_build_output(&self.root, &path)
+        {
+            return Ok(None);
+        }
+        let index = self.files.iter().position(|f| f.path == path);
+        let items_before = index.map_or(0, |i| self.files[i].items.len());
+
+        let parsed = if path.exists() {
+            Some(
+                self.parser
+                    .parse_file(&path)
+                    .map_err(|e| GravityError::Parse(e.to_string()))?,
+            )
+        } else {
+            None
+        };
+        let items_after = parsed.as_ref().map_or(0, |f| f.items.len());
+        let change = match (index, parsed) {
+            (Some(i), Some(file)) => {
+                self.files[i] = file;
+                FileChange::Modified
+            }
+            (None, Some(file)) => {
+                self.files.push(file);
+                FileChange::Added
+            }
+            (Some(i), None) => {
+                self.files.remove(i);
+                FileChange::Removed
+            }
+            (None, None) => return Ok(None),
+        };
+
+        // `mod` declarations may have changed; these views are cheap to
+        // rebuild from the parsed files
+        self.build_file_module_map();
+        self.build_resolvers();
+        self.build_module_tree();
+        self.compute_distances();
+
+        let file = self.files.iter().find(|f| f.path == path);
+
+        for impls in self.impl_map.values_mut() {
+            impls.retain(|item| item.file_path != path);
+        }
+        self.impl_map.retain(|_, impls| !impls.is_empty());
+        let impls: Vec<(String, ParsedItem)> = file
+            .into_iter()
+            .flat_map(|f| &f.items)
+            .filter_map(|item| match &item.kind {
+                ItemKind::Impl { self_type, .. } => {
+                    Some((self.normalize_type_name(self_type), item.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+        for (type_name, item) in impls {
+            self.impl_map.entry(type_name).or_default().push(item);
+        }
+        self.trait_matrix = TraitMatrix::build(&self.files);
+        self.type_graph = TypeGraph::build(&self.files);
+        self.usage_index.update(&path, file);
+
+        let calls = file.map(|f| self.scan_calls(f)).unwrap_or_default();
+        let scanned = file.map(|f| self.scan_references(f)).unwrap_or_default();
+
+        for sites in self.call_graph.callers.values_mut() {
+            sites.retain(|site| site.file != path);
+        }
+        self.call_graph.callers.retain(|_, sites| !sites.is_empty());
+        for (callee, site) in calls {
+            self.call_graph
+                .callers
+                .entry(callee)
+                .or_default()
+                .push(site);
+        }
+        self.rebuild_callees();
+        self.test_map = TestMap::build(&self.files, &self.call_graph, &self.crate_roots());
+
+        for references in self.reference_map.references.values_mut() {
+            references.retain(|r| r.file != path);
+        }
+        self.reference_map
+            .references
+            .retain(|_, references| !references.is_empty());
+        self.crate_references.retain(|r| r.file != path);
+        self.module_dependencies.retain(|d| d.file != path);
+        self.add_references(scanned);
+        self.module_dependencies
+            .sort_by(|a, b| (&a.file, a.line, &a.target).cmp(&(&b.file, b.line, &b.target)));
+        self.module_dependencies.dedup();
+
+        if self.semantic_index.is_some() {
+            let root = self.root.clone();
+            self.refresh_semantic_index(&root)?;
+        }
+
+        Ok(Some(FileUpdate {
+            path,
+            change,
+            items_before,
+            items_after,
+        }))
+    }
+
+    /// A path in the form `analyze_project` stored it: under the root as
+    /// given, even if `path` is absolute or canonical
+    fn project_path(&self, path: &Path) -> PathBuf {
+        if self.files.iter().any(|f| f.path == path) {
+            return path.to_path_buf();
+        }
+        let canonical_root = self
+            .root
+            .canonicalize()
+            .unwrap_or_else(|_| self.root.clone());
+        let canonical_path = path
+            .parent()
+            .and_then(|dir| dir.canonicalize().ok())
+            .zip(path.file_name())
+            .map(|(dir, name)| dir.join(name))
+            .unwrap_or_else(|| path.to_path_buf());
+        match canonical_path.strip_prefix(&canonical_root) {
+            Ok(relative) => self.root.join(relative),
+            Err(_) if path.is_relative() => self.root.join(path),
+            Err(_) => path.to_path_buf(),
+        }
+    }
+
+    /// Root passed to `analyze_project`
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Load local git history so scores include churn, recency, authorship and
+    /// bug-fix signals. Call after `analyze_project`.
+    pub fn load_git_history(&mut self, root: &Path) -> Result<(), GravityError> {
+        let paths: Vec<PathBuf> = self.files.iter().map(|f| f.path.clone()).collect();
+        self.history = Some(GitHistory::load(root, &paths)?);
+        Ok(())
+    }
 
+    /// Get the loaded git history
+    pub fn get_history(&self) -> Option<&GitHistory> {
+        self.history.as_ref()
+    }
+
+    /// Load an lcov or llvm-cov JSON coverage report so scores carry each
+    /// item's line coverage. Call after `analyze_project`.
+    pub fn load_coverage(&mut self, report: &Path, root: &Path) -> Result<(), GravityError> {
+        let paths: Vec<PathBuf> = self.files.iter().map(|f| f.path.clone()).collect();
+        self.coverage = Some(CoverageReport::load(report, root)?.for_files(&paths));
         Ok(())
     }
 
+    /// Get the loaded coverage report
+    pub fn get_coverage(&self) -> Option<&CoverageReport> {
+        self.coverage.as_ref()
+    }
+
+    /// Load the cached semantic index, embed items that are new or changed
+    /// since it was written, and save it back. Call after `analyze_project`
+    /// (and again after files change). Returns the number of items embedded.
+    pub fn load_semantic_index(&mut self, root: &Path) -> Result<usize, GravityError> {
+        let embedded = self.refresh_semantic_index(root)?;
+        if let Some(index) = &self.semantic_index {
+            index.save(root)?;
+        }
+        Ok(embedded)
+    }
+
+    /// Like [`SemanticGravity::load_semantic_index`], but never writes the
+    /// cache, for read-only callers
+    pub fn refresh_semantic_index(&mut self, root: &Path) -> Result<usize, GravityError> {
+        let mut index = self
+            .semantic_index
+            .take()
+            .unwrap_or_else(|| SemanticIndex::load(root));
+
+        let texts: Vec<String> = self
+            .semantic_items()
+            .map(|item| self.embedding_text(item))
+            .collect();
+        let refreshed = index.refresh(texts.iter().map(String::as_str));
+
+        // Keep whatever was embedded even if a later item failed
+        self.semantic_index = Some(index);
+        Ok(refreshed?)
+    }
+
+    /// Get the loaded semantic index
+    pub fn get_semantic_index(&self) -> Option<&SemanticIndex> {
+        self.semantic_index.as_ref()
+    }
+
+    /// Items included in the semantic index: everything but `use` items,
+    /// impl methods included
+    fn semantic_items(&self) -> impl Iterator<Item = &ParsedItem> {
+        self.files
+            .iter()
+            .flat_map(|f| f.items_and_methods())
+            .filter(|item| !matches!(item.kind, ItemKind::Use { .. }))
+    }
+
+    /// Text embedded for an item: kind, name, signature, breadcrumbs and docs
+    pub fn embedding_text(&self, item: &ParsedItem) -> String {
+        let mut text = format!(
+            "{} {} ({})",
+            self.item_kind_name(&item.kind),
+            item.name,
+            fuzzy::words(&item.name).join(" ")
+        );
+
+        let members: Vec<String> = match &item.kind {
+            ItemKind::Function {
+                parameters,
+                return_type,
+                ..
+            } => {
+                let params: Vec<String> = parameters
+                    .iter()
+                    .map(|p| {
+                        if p.is_self {
+                            p.ty.clone()
+                        } else {
+                            format!("{}: {}", p.name, p.ty)
+                        }
+                    })
+                    .collect();
+                let ret = return_type
+                    .as_ref()
+                    .map(|r| format!(" -> {}", r))
+                    .unwrap_or_default();
+                vec![format!("({}){}", params.join(", "), ret)]
+            }
+            ItemKind::Struct { fields, .. } => fields
+                .iter()
+                .map(|f| match &f.name {
+                    Some(name) => format!("{}: {}", name, f.ty),
+                    None => f.ty.clone(),
+                })
+                .collect(),
+            ItemKind::Enum { variants } => variants.iter().map(|v| v.name.clone()).collect(),
+            ItemKind::Trait { methods, .. } | ItemKind::Impl { methods, .. } => methods.clone(),
+            ItemKind::Const { ty } | ItemKind::Static { ty, .. } | ItemKind::TypeAlias { ty } => {
+                vec![ty.clone()]
+            }
+            _ => Vec::new(),
+        };
+        if !members.is_empty() {
+            text.push('\n');
+            text.push_str(&members.join(", "));
+        }
+
+        text.push('\n');
+        text.push_str(&self.get_breadcrumbs(item));
+
+        if let Some(doc) = &item.doc_comment {
+            text.push('\n');
+            text.push_str(doc.trim());
+        }
+
+        text
+    }
+
     /// Build mapping from file paths to their module names
     fn build_file_module_map(&mut self) {
         self.file_to_module.clear();
 
         for file in &self.files {
+            // In a multi-crate workspace, modules are rooted at their crate
+            let crate_root = self.crate_root_name(&file.path);
             let module_name = file.module_path.join("::");
             let module_name = if module_name.is_empty() {
-                "crate".to_string()
+                crate_root
             } else {
-                format!("crate::{}", module_name)
+                format!("{}::{}", crate_root, module_name)
             };
             self.file_to_module.insert(file.path.clone(), module_name);
         }
     }
 
-    /// Build the module tree from parsed files
-    fn build_module_tree(&mut self, root: &Path) {
-        let mut tree = ModuleTree::default();
-        tree.root.name = "crate".to_string();
-        tree.root.path = root.to_path_buf();
-        tree.root.depth = 0;
+    /// Resolve the public paths of each member's library, rooted like
+    /// `file_to_module`
+    fn build_resolvers(&mut self) {
+        self.resolvers = self
+            .workspace
+            .members()
+            .iter()
+            .map(|member| {
+                PathResolver::build(
+                    &self.crate_root_name(&member.root),
+                    &member.root,
+                    &self.files,
+                )
+            })
+            .collect();
+    }
+
+    /// Directories of the analyzed crates: the project root and every
+    /// workspace member
+    fn crate_roots(&self) -> Vec<&Path> {
+        let mut roots = vec![self.root.as_path()];
+        roots.extend(self.workspace.members().iter().map(|m| m.root.as_path()));
+        roots
+    }
+
+    /// Path root for a file's modules: `crate`, or the member's crate name
+    /// when analyzing several crates
+    fn crate_root_name(&self, path: &Path) -> String {
+        match self.workspace.member_for(path) {
+            Some(member) if self.workspace.is_multi_crate() => member.crate_name.clone(),
+            _ => "crate".to_string(),
+        }
+    }
+
+    /// Build the module tree: one subtree per build target (grouped by
+    /// workspace member when there are several), following `mod`
+    /// declarations from the target's root file
+    fn build_module_tree(&mut self) {
+        let mut crates: Vec<ModuleNode> = self
+            .workspace
+            .members()
+            .iter()
+            .map(|member| ModuleNode {
+                name: member.crate_name.clone(),
+                path: member.root.clone(),
+                children: member
+                    .targets
+                    .iter()
+                    .map(|target| {
+                        let name = format!("{} {}", target.kind.label(), target.name);
+                        self.module_node(name, &target.entry, 0, &mut HashSet::new())
+                    })
+                    .collect(),
+                depth: 0,
+            })
+            .collect();
 
-        let entry = if root.join("src/lib.rs").exists() {
-            root.join("src/lib.rs")
+        let mut tree = ModuleTree::default();
+        if crates.len() == 1 {
+            tree.root = crates.remove(0);
+            tree.root.name = "crate".to_string();
         } else {
-            root.join("src/main.rs")
-        };
+            tree.root.name = "workspace".to_string();
+            tree.root.children = crates;
+        }
 
-        tree.root.path = entry;
+        self.module_tree = tree;
+    }
 
-        for file in &self.files {
-            for item in &file.items {
-                if let ItemKind::Mod { inline } = &item.kind {
-                    let depth = file.module_path.len() + 1;
-                    let node = ModuleNode {
-                        name: item.name.clone(),
-                        path: if *inline {
-                            file.path.clone()
-                        } else {
-                            self.resolve_mod_path(&file.path, &item.name)
-                        },
-                        children: Vec::new(),
-                        depth,
-                    };
-                    tree.root.children.push(node);
+    /// Module node for a file and the modules it declares
+    fn module_node(
+        &self,
+        name: String,
+        path: &Path,
+        depth: usize,
+        visited: &mut HashSet<PathBuf>,
+    ) -> ModuleNode {
+        let mut node = ModuleNode {
+            name,
+            path: path.to_path_buf(),
+            children: Vec::new(),
+            depth,
+        };
+        if !visited.insert(path.to_path_buf()) {
+            return node;
+        }
+
+        let Some(file) = self.files.iter().find(|f| f.path == path) else {
+            return node;
+        };
+        for item in &file.items {
+            match &item.kind {
+                ItemKind::Mod { inline: true } => node.children.push(ModuleNode {
+                    name: item.name.clone(),
+                    path: file.path.clone(),
+                    children: Vec::new(),
+                    depth: depth + 1,
+                }),
+                ItemKind::Mod { inline: false } => {
+                    let mod_path = self.resolve_mod_path(&file.path, &item.name);
+                    node.children.push(self.module_node(
+                        item.name.clone(),
+                        &mod_path,
+                        depth + 1,
+                        visited,
+                    ));
                 }
+                _ => {}
             }
         }
-
-        self.module_tree = tree;
+        node
     }
 
-    /// Resolve a mod declaration to its file path
+    /// Resolve a mod declaration to its file path. Crate roots and `mod.rs`
+    /// files declare modules next to themselves; other files declare them in
+    /// a directory named after the file (`foo.rs` -> `foo/bar.rs`).
     fn resolve_mod_path(&self, parent: &Path, mod_name: &str) -> PathBuf {
         let parent_dir = parent.parent().unwrap_or(Path::new("."));
+        let owns_dir = parent
+            .file_name()
+            .is_some_and(|n| n == "mod.rs" || n == "lib.rs" || n == "main.rs")
+            || self
+                .workspace
+                .members()
+                .iter()
+                .any(|m| m.targets.iter().any(|t| t.entry == parent));
 
-        let direct = parent_dir.join(format!("{}.rs", mod_name));
-        if direct.exists() {
-            return direct;
+        let mut dirs = Vec::new();
+        if !owns_dir && let Some(stem) = parent.file_stem() {
+            dirs.push(parent_dir.join(stem));
         }
+        dirs.push(parent_dir.to_path_buf());
+
+        for dir in &dirs {
+            let direct = dir.join(format!("{}.rs", mod_name));
+            if direct.exists() {
+                return direct;
+            }
 
-        let nested = parent_dir.join(mod_name).join("mod.rs");
-        if nested.exists() {
-            return nested;
+            let nested = dir.join(mod_name).join("mod.rs");
+            if nested.exists() {
+                return nested;
+            }
         }
 
-        direct
+        dirs[0].join(format!("{}.rs", mod_name))
     }
 
     /// Build map from type names to impl blocks
@@ -303,133 +829,325 @@ impl SemanticGravity {
     fn build_call_graph(&mut self) -> Result<(), GravityError> {
         self.call_graph = CallGraph::default();
 
+        for file in &self.files {
+            for (callee, site) in self.scan_calls(file) {
+                self.call_graph
+                    .callers
+                    .entry(callee)
+                    .or_default()
+                    .push(site);
+            }
+        }
+        self.rebuild_callees();
+
+        Ok(())
+    }
+
+    /// Call sites in one file, as (callee, site)
+    fn scan_calls(&self, file: &ParsedFile) -> Vec<(String, CallSite)> {
         let call_pattern = regex::Regex::new(r"(\w+)\s*\(").expect("Invalid regex");
         let method_pattern = regex::Regex::new(r"\.(\w+)\s*\(").expect("Invalid regex");
 
-        for file in &self.files {
-            let content = std::fs::read_to_string(&file.path).unwrap_or_default();
-            let mut current_fn: Option<String> = None;
+        let content = std::fs::read_to_string(&file.path).unwrap_or_default();
+        let mut current_fn: Option<String> = None;
+        let mut calls = Vec::new();
 
-            for (line_num, line) in content.lines().enumerate() {
-                if line.contains("fn ") {
-                    if let Some(name) = self.extract_fn_name(line) {
-                        current_fn = Some(name);
-                    }
+        for (line_num, line) in content.lines().enumerate() {
+            if line.contains("fn ") {
+                if let Some(name) = self.extract_fn_name(line) {
+                    current_fn = Some(name);
                 }
+            }
 
-                if let Some(caller) = &current_fn {
-                    for cap in call_pattern.captures_iter(line) {
-                        if let Some(callee) = cap.get(1) {
-                            let callee_name = callee.as_str().to_string();
-
-                            if !self.is_keyword(&callee_name)
-                                && !self.is_prelude_method(&callee_name)
-                            {
-                                let call_site = CallSite {
-                                    caller: caller.clone(),
-                                    file: file.path.clone(),
-                                    line: line_num + 1,
-                                };
-
-                                self.call_graph
-                                    .callers
-                                    .entry(callee_name.clone())
-                                    .or_default()
-                                    .push(call_site);
-
-                                self.call_graph
-                                    .callees
-                                    .entry(caller.clone())
-                                    .or_default()
-                                    .push(callee_name);
-                            }
-                        }
-                    }
+            let Some(caller) = &current_fn else {
+                continue;
+            };
+            // Method calls match both patterns and get a site from each
+            let names = call_pattern
+                .captures_iter(line)
+                .chain(method_pattern.captures_iter(line))
+                .filter_map(|cap| cap.get(1))
+                .map(|m| m.as_str());
+            for name in names {
+                if !self.is_keyword(name) && !self.is_prelude_method(name) {
+                    calls.push((
+                        name.to_string(),
+                        CallSite {
+                            caller: caller.clone(),
+                            file: file.path.clone(),
+                            line: line_num + 1,
+                        },
+                    ));
+                }
+            }
+        }
 
-                    for cap in method_pattern.captures_iter(line) {
-                        if let Some(method) = cap.get(1) {
-                            let method_name = method.as_str().to_string();
-                            if !self.is_keyword(&method_name)
-                                && !self.is_prelude_method(&method_name)
-                            {
-                                self.call_graph
-                                    .callers
-                                    .entry(method_name.clone())
-                                    .or_default()
-                                    .push(CallSite {
-                                        caller: caller.clone(),
-                                        file: file.path.clone(),
-                                        line: line_num + 1,
-                                    });
-                            }
-                        }
-                    }
+        calls
+    }
+
+    /// Derive `callees` from `callers`, counting each call site once
+    fn rebuild_callees(&mut self) {
+        let mut seen: HashSet<(&str, &Path, usize)> = HashSet::new();
+        let mut callees: HashMap<String, Vec<String>> = HashMap::new();
+
+        for (callee, sites) in &self.call_graph.callers {
+            for site in sites {
+                if seen.insert((callee.as_str(), site.file.as_path(), site.line)) {
+                    callees
+                        .entry(site.caller.clone())
+                        .or_default()
+                        .push(callee.clone());
                 }
             }
         }
 
-        Ok(())
+        for names in callees.values_mut() {
+            names.sort();
+        }
+        self.call_graph.callees = callees;
     }
 
     /// Build the external reference map
     fn build_reference_map(&mut self) -> Result<(), GravityError> {
         self.reference_map = ReferenceMap::default();
+        self.crate_references.clear();
+        self.module_dependencies.clear();
+
+        let scanned: Vec<FileReferences> = self
+            .files
+            .iter()
+            .map(|file| self.scan_references(file))
+            .collect();
+        for file_references in scanned {
+            self.add_references(file_references);
+        }
+
+        self.module_dependencies
+            .sort_by(|a, b| (&a.file, a.line, &a.target).cmp(&(&b.file, b.line, &b.target)));
+        self.module_dependencies.dedup();
+
+        Ok(())
+    }
+
+    fn add_references(&mut self, scanned: FileReferences) {
+        for reference in scanned.external {
+            let key = self.reference_map.key(&reference.external_path).to_string();
+            self.reference_map
+                .references
+                .entry(key)
+                .or_default()
+                .push(reference);
+        }
+        self.crate_references.extend(scanned.crates);
+        self.module_dependencies.extend(scanned.modules);
+    }
+
+    /// External references, cross-crate references and module dependencies
+    /// in one file
+    fn scan_references(&self, file: &ParsedFile) -> FileReferences {
+        let mut scanned = FileReferences::default();
 
         // Pattern to match qualified paths like tokio::spawn, std::fs::read
         let qualified_pattern =
             regex::Regex::new(r"(\w+(?:::\w+)+)\s*[(\[{<]?").expect("Invalid regex");
+        let string_pattern = regex::Regex::new(r#""(?:[^"\\]|\\.)*""#).expect("Invalid regex");
 
-        for file in &self.files {
-            let content = std::fs::read_to_string(&file.path).unwrap_or_default();
-            let mut current_fn = String::from("<module>");
-            let mut brace_depth = 0;
-
-            for (line_num, line) in content.lines().enumerate() {
-                // Track function context
-                if line.contains("fn ") {
-                    if let Some(name) = self.extract_fn_name(line) {
-                        current_fn = name;
-                        brace_depth = 0;
-                    }
-                }
+        let content = std::fs::read_to_string(&file.path).unwrap_or_default();
+        let mut current_fn = String::from("<module>");
+        let mut brace_depth = 0;
+        let own_crate = self.workspace.member_for(&file.path);
+        let from_module = self
+            .file_to_module
+            .get(&file.path)
+            .cloned()
+            .unwrap_or_default();
+
+        // Cognitive complexity of the innermost function containing a line
+        let function_complexity = |line: usize| {
+            file.items
+                .iter()
+                .filter(|item| item.span.start_line <= line && line <= item.span.end_line)
+                .filter_map(|item| match &item.kind {
+                    ItemKind::Function {
+                        metrics: Some(metrics),
+                        ..
+                    } => Some((item.span.end_line - item.span.start_line, metrics.cognitive)),
+                    _ => None,
+                })
+                .min_by_key(|(length, _)| *length)
+                .map(|(_, cognitive)| cognitive)
+        };
 
-                // Track brace depth for complexity estimation
-                brace_depth += line.matches('{').count();
-                brace_depth = brace_depth.saturating_sub(line.matches('}').count());
+        for (line_num, line) in content.lines().enumerate() {
+            // Track function context
+            if line.contains("fn ") {
+                if let Some(name) = self.extract_fn_name(line) {
+                    current_fn = name;
+                    brace_depth = 0;
+                }
+            }
 
-                // Find qualified paths
-                for cap in qualified_pattern.captures_iter(line) {
-                    if let Some(path_match) = cap.get(1) {
-                        let path = path_match.as_str();
+            // Track brace depth for complexity estimation
+            brace_depth += line.matches('{').count();
+            brace_depth = brace_depth.saturating_sub(line.matches('}').count());
+
+            // Module dependencies from paths in code; `use` items are
+            // resolved from their parsed trees below
+            let trimmed = line.trim_start();
+            let is_use = trimmed.starts_with("use ")
+                || (trimmed.starts_with("pub") && trimmed.contains(" use "));
+            if !is_use && !trimmed.starts_with("//") {
+                let code = string_pattern.replace_all(line, "\"\"");
+                let code = code.split("//").next().unwrap_or_default();
+                for cap in qualified_pattern.captures_iter(code) {
+                    let segments: Vec<&str> = cap[1].split("::").collect();
+                    if let Some(target) = self.absolute_path(file, &segments) {
+                        scanned.modules.push(ModuleDependency {
+                            from_module: from_module.clone(),
+                            target,
+                            file: file.path.clone(),
+                            line: line_num + 1,
+                        });
+                    }
+                }
+            }
 
-                        // Skip local crate paths
-                        if path.starts_with("crate::") || path.starts_with("self::") {
-                            continue;
-                        }
+            // Find qualified paths
+            for cap in qualified_pattern.captures_iter(line) {
+                if let Some(path_match) = cap.get(1) {
+                    let path = path_match.as_str();
+
+                    // Skip local crate paths
+                    if path.starts_with("crate::")
+                        || path.starts_with("self::")
+                        || path.starts_with("super::")
+                    {
+                        continue;
+                    }
 
-                        // Check if first segment is an external crate
-                        let first_segment = path.split("::").next().unwrap_or("");
-                        if self.is_likely_external_crate(first_segment) {
-                            let reference = ExternalReference {
-                                external_path: path.to_string(),
+                    // Paths into workspace members are internal: either
+                    // the file's own crate (a binary using its library)
+                    // or a cross-crate edge
+                    let first_segment = path.split("::").next().unwrap_or("");
+                    if let Some(target) = self.workspace.member_named(first_segment) {
+                        if let Some(own) = own_crate.filter(|own| own.root != target.root) {
+                            scanned.crates.push(CrateReference {
+                                from_crate: own.crate_name.clone(),
+                                to_crate: target.crate_name.clone(),
+                                path: path.to_string(),
                                 file: file.path.clone(),
                                 line: line_num + 1,
                                 caller_context: current_fn.clone(),
-                                complexity: brace_depth + self.estimate_line_complexity(line),
-                            };
-
-                            self.reference_map
-                                .references
-                                .entry(path.to_string())
-                                .or_default()
-                                .push(reference);
+                            });
                         }
+                        continue;
+                    }
+
+                    // Check if first segment is an external crate
+                    if self.is_likely_external_crate(first_segment) {
+                        scanned.external.push(ExternalReference {
+                            external_path: path.to_string(),
+                            file: file.path.clone(),
+                            line: line_num + 1,
+                            caller_context: current_fn.clone(),
+                            complexity: function_complexity(line_num + 1).unwrap_or_else(|| {
+                                brace_depth + self.estimate_line_complexity(line)
+                            }),
+                        });
                     }
                 }
             }
         }
 
-        Ok(())
+        for item in &file.items {
+            let ItemKind::Use { path } = &item.kind else {
+                continue;
+            };
+            for (mut segments, leaf) in parser::use_leaves(path) {
+                if let UseLeaf::Name { name, .. } = leaf {
+                    segments.push(name);
+                }
+                let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+                if let Some(target) = self.absolute_path(file, &segments) {
+                    scanned.modules.push(ModuleDependency {
+                        from_module: from_module.clone(),
+                        target,
+                        file: file.path.clone(),
+                        line: item.span.start_line,
+                    });
+                }
+            }
+        }
+
+        scanned
+    }
+
+    /// Absolute form of a path written in `file`: `crate::`, `self::` and
+    /// `super::` become module paths, a workspace member's name its crate
+    /// root, and a leading child module name (2018 uniform paths) is
+    /// resolved against the file's module. Paths into the member's declared
+    /// dependencies and the standard crates are left as is; `None` for
+    /// anything else (local types, keywords).
+    fn absolute_path(&self, file: &ParsedFile, segments: &[&str]) -> Option<String> {
+        let (first, rest) = segments.split_first()?;
+        let module = self.file_to_module.get(&file.path)?;
+        let crate_root = self.crate_root_name(&file.path);
+
+        let mut resolved: Vec<&str> = match *first {
+            "crate" => vec![crate_root.as_str()],
+            "self" => module.split("::").collect(),
+            "super" => {
+                let mut parts: Vec<&str> = module.split("::").collect();
+                let mut rest = rest;
+                parts.pop();
+                while rest.first() == Some(&"super") {
+                    parts.pop();
+                    rest = &rest[1..];
+                }
+                if parts.is_empty() {
+                    return None;
+                }
+                parts.extend(rest);
+                return Some(parts.join("::"));
+            }
+            "Self" => return None,
+            name => match self.workspace.member_named(name) {
+                // Own library from a binary, or a sibling member
+                Some(member) => vec![if self.workspace.is_multi_crate() {
+                    member.crate_name.as_str()
+                } else {
+                    "crate"
+                }],
+                None => {
+                    let child = format!("{}::{}", module, name);
+                    if self.file_to_module.values().any(|m| *m == child) {
+                        module.split("::").chain([name]).collect()
+                    } else if self.is_dependency(&file.path, name) {
+                        vec![name]
+                    } else {
+                        // A local type or enum, e.g. `Kind::Variant`
+                        return None;
+                    }
+                }
+            },
+        };
+        if rest.is_empty() && resolved.len() == 1 && resolved[0] == *first {
+            // A bare single segment isn't a dependency
+            return None;
+        }
+        resolved.extend(rest);
+        Some(resolved.join("::"))
+    }
+
+    /// Whether a name at the start of a path in `file` is another crate: a
+    /// dependency its member declares, or one shipped with the toolchain
+    fn is_dependency(&self, file: &Path, name: &str) -> bool {
+        const SYSROOT_CRATES: [&str; 5] = ["std", "core", "alloc", "proc_macro", "test"];
+        SYSROOT_CRATES.contains(&name)
+            || self
+                .workspace
+                .member_for(file)
+                .is_some_and(|member| member.dependencies.iter().any(|d| d == name))
     }
 
     /// Check if a name is likely an external crate
@@ -560,20 +1278,26 @@ impl SemanticGravity {
         PRELUDE_METHODS.contains(&s)
     }
 
-    /// Compute distances from entry point
-    fn compute_distances(&mut self, root: &Path) {
+    /// Compute each file's distance to the nearest entry point: the roots
+    /// of the selected target kinds in every workspace member
+    fn compute_distances(&mut self) {
         self.distance_cache.clear();
 
-        let entry = if root.join("src/lib.rs").exists() {
-            root.join("src/lib.rs")
-        } else {
-            root.join("src/main.rs")
-        };
+        let members = self.workspace.members();
+        let mut entries: Vec<PathBuf> = members
+            .iter()
+            .flat_map(|m| m.targets_of(&self.entry_kinds))
+            .map(|t| t.entry.clone())
+            .collect();
+        if entries.is_empty() {
+            entries = members.iter().map(|m| m.entry.clone()).collect();
+        }
 
+        // Breadth-first, so the first visit is the shortest distance
         let mut visited: HashSet<PathBuf> = HashSet::new();
-        let mut queue: Vec<(PathBuf, usize)> = vec![(entry.clone(), 0)];
+        let mut queue: VecDeque<(PathBuf, usize)> = entries.into_iter().map(|e| (e, 0)).collect();
 
-        while let Some((path, dist)) = queue.pop() {
+        while let Some((path, dist)) = queue.pop_front() {
             if visited.contains(&path) {
                 continue;
             }
@@ -585,7 +1309,7 @@ impl SemanticGravity {
                     if let ItemKind::Mod { .. } = &item.kind {
                         let mod_path = self.resolve_mod_path(&path, &item.name);
                         if !visited.contains(&mod_path) {
-                            queue.push((mod_path, dist + 1));
+                            queue.push_back((mod_path, dist + 1));
                         }
                     }
                 }
@@ -660,7 +1384,7 @@ impl SemanticGravity {
     /// Check if an item is a test function
     fn is_test_item(&self, item: &ParsedItem) -> bool {
         // Check for #[test] attribute
-        item.attributes.iter().any(|attr| attr.contains("test"))
+        parser::has_test_attribute(&item.attributes)
             // Check if in a tests module
             || item.file_path.to_string_lossy().contains("/tests/")
             || item.name.starts_with("test_")
@@ -690,33 +1414,54 @@ impl SemanticGravity {
 
         let (impl_count, trait_impls) = self.get_impl_info(&item.name);
 
-        // Base score
-        let mut score = 100.0;
-
-        // Apply weights from the factor table
-        score += (cross_module_count as f64) * weights::CROSS_MODULE_USAGE;
+        let history = self
+            .history
+            .as_ref()
+            .and_then(|h| h.stats_for(&item.file_path, &item.span));
+
+        // Weighted contribution of each factor
+        let weights = &self.weights;
+        let mut breakdown = ScoreBreakdown {
+            base: 100.0,
+            cross_module_usage: (cross_module_count as f64) * weights.cross_module_usage,
+            generic_depth: (generic_depth as f64) * weights.generic_depth,
+            // Legacy factors (kept for continuity)
+            entry_distance: (entry_distance as f64) * weights.entry_distance_penalty,
+            impl_richness: (impl_count as f64) * weights.impl_richness,
+            trait_impls: (trait_impls.len() as f64) * weights.trait_impl,
+            ..Default::default()
+        };
 
         if matches!(item.visibility, Visibility::Public) {
-            score += weights::PUB_VISIBILITY;
+            breakdown.pub_visibility = weights.pub_visibility;
         }
 
-        score += (generic_depth as f64) * weights::GENERIC_DEPTH;
-
         if is_test {
-            score += weights::IS_TEST_PENALTY;
+            breakdown.is_test = weights.is_test_penalty;
         }
 
-        // Legacy factors (kept for continuity)
-        score += (entry_distance as f64) * weights::ENTRY_DISTANCE_PENALTY;
-
         if is_site {
-            score += weights::SITE_BONUS;
+            breakdown.call_pattern = weights.site_bonus;
         } else if call_count > 10 {
-            score += weights::UTILITY_PENALTY;
+            breakdown.call_pattern = weights.utility_penalty;
         }
 
-        score += (impl_count as f64) * weights::IMPL_RICHNESS;
-        score += (trait_impls.len() as f64) * weights::TRAIT_IMPL;
+        if let ItemKind::Function {
+            metrics: Some(metrics),
+            ..
+        } = &item.kind
+        {
+            breakdown.complexity = (metrics.cognitive as f64) * weights.complexity;
+        }
+
+        if let Some(h) = &history {
+            breakdown.churn = (h.churn as f64).ln_1p() * weights.churn;
+            breakdown.recency = weights.recency / (1.0 + h.days_since_change / 30.0);
+            breakdown.author_count = (h.author_count as f64) * weights.author_count;
+            breakdown.bug_fixes = (h.bug_fix_count as f64) * weights.bug_fix;
+        }
+
+        let score = breakdown.total();
 
         let factors = ScoreFactors {
             entry_distance,
@@ -727,6 +1472,12 @@ impl SemanticGravity {
             cross_module_count,
             generic_depth,
             is_test,
+            history,
+            semantic_similarity: None,
+            coverage: self
+                .coverage
+                .as_ref()
+                .and_then(|c| c.stats_for(&item.file_path, &item.span)),
         };
 
         // Build the context envelope
@@ -736,73 +1487,97 @@ impl SemanticGravity {
             item: item.clone(),
             score: score.max(0.0),
             factors,
+            breakdown,
             context,
+            matched: None,
         }
     }
 
+    /// Score an item that matched a search query, blending in match quality
+    fn score_match(&self, item: &ParsedItem, matched: FuzzyMatch) -> WorkSiteScore {
+        let mut result = self.score_item(item);
+        result.breakdown.match_quality = matched.quality * self.weights.match_quality;
+        result.score = result.breakdown.total().max(0.0);
+        result.matched = Some(matched);
+        result
+    }
+
     /// Build a ContextEnvelope for an item (Recursive Context Window)
     fn build_context_envelope(&self, item: &ParsedItem) -> ContextEnvelope {
         let breadcrumbs = self.get_breadcrumbs(item);
-        let siblings = self.get_siblings(item);
+        let related = self
+            .type_graph
+            .related(item)
+            .into_iter()
+            .take(5) // Strongest relations only, to avoid noise
+            .collect();
         let generic_bounds = self.extract_generic_bounds(item);
         let parent_context = self.get_parent_context(item);
 
         ContextEnvelope {
             breadcrumbs,
-            siblings,
+            related,
             generic_bounds,
             parent_context,
         }
     }
 
-    /// Get the full module path breadcrumb for an item
-    fn get_breadcrumbs(&self, item: &ParsedItem) -> String {
+    /// Get the full module path breadcrumb for an item, with the type of
+    /// methods and the public path when it differs (`crate::types::Span
+    /// (pub: crate::Span)`)
+    pub fn get_breadcrumbs(&self, item: &ParsedItem) -> String {
+        let declared = self.declared_path(item);
+        match self.get_public_path(item) {
+            Some(public) if public != declared => format!("{} (pub: {})", declared, public),
+            _ => declared,
+        }
+    }
+
+    /// Path an item is declared at; methods include their type
+    fn declared_path(&self, item: &ParsedItem) -> String {
         let module = self
             .file_to_module
             .get(&item.file_path)
             .cloned()
             .unwrap_or_else(|| "crate".to_string());
 
-        format!("{}::{}", module, item.name)
+        match self.parent_impl(item).map(|parent| &parent.kind) {
+            Some(ItemKind::Impl { self_type, .. }) => format!(
+                "{}::{}::{}",
+                module,
+                self.normalize_type_name(self_type),
+                item.name
+            ),
+            _ => format!("{}::{}", module, item.name),
+        }
     }
 
-    /// Find sibling items in the same file that share generics or are related
-    fn get_siblings(&self, item: &ParsedItem) -> Vec<SiblingInfo> {
-        let item_generics = self.extract_generic_params(item);
-
-        let Some(file) = self.files.iter().find(|f| f.path == item.file_path) else {
-            return Vec::new();
-        };
-
-        file.items
-            .iter()
-            .filter(|sibling| {
-                sibling.name != item.name
-                    && matches!(
-                        sibling.kind,
-                        ItemKind::Struct { .. }
-                            | ItemKind::Enum { .. }
-                            | ItemKind::Function { .. }
-                            | ItemKind::Trait { .. }
-                    )
-            })
-            .take(5) // Limit siblings to avoid noise
-            .map(|sibling| {
-                let sibling_generics = self.extract_generic_params(sibling);
-                let shared: Vec<String> = item_generics
-                    .iter()
-                    .filter(|g| sibling_generics.contains(g))
-                    .cloned()
-                    .collect();
-
-                SiblingInfo {
-                    name: sibling.name.clone(),
-                    kind: self.item_kind_name(&sibling.kind),
-                    line: sibling.span.start_line,
-                    shared_generics: shared,
-                }
+    /// Shortest path other crates can name a library item by, following
+    /// `pub use` re-exports; `None` for items outside public modules and
+    /// outside library targets
+    pub fn get_public_path(&self, item: &ParsedItem) -> Option<String> {
+        let member = self.workspace.member_for(&item.file_path)?;
+        let relative = item.file_path.strip_prefix(&member.root).ok()?;
+        if !semver::is_library_file(relative) {
+            return None;
+        }
+        // Methods are public if declared `pub` or part of a trait impl
+        let in_trait_impl = matches!(
+            self.parent_impl(item).map(|parent| &parent.kind),
+            Some(ItemKind::Impl {
+                trait_name: Some(_),
+                ..
             })
-            .collect()
+        );
+        if item.visibility != Visibility::Public && !in_trait_impl {
+            return None;
+        }
+        let crate_name = self.crate_root_name(&item.file_path);
+        let resolver = self
+            .resolvers
+            .iter()
+            .find(|r| r.crate_name() == crate_name)?;
+        resolver.public_path(&self.declared_path(item))
     }
 
     /// Get the kind name as a string
@@ -824,74 +1599,6 @@ impl SemanticGravity {
         .to_string()
     }
 
-    /// Extract generic parameter names from an item
-    fn extract_generic_params(&self, item: &ParsedItem) -> Vec<String> {
-        let text = self.get_item_signature_text(item);
-        self.parse_generic_params(&text)
-    }
-
-    /// Get the signature text for generic extraction
-    fn get_item_signature_text(&self, item: &ParsedItem) -> String {
-        match &item.kind {
-            ItemKind::Function {
-                parameters,
-                return_type,
-                ..
-            } => {
-                let params: String = parameters.iter().map(|p| p.ty.as_str()).collect();
-                let ret = return_type.as_deref().unwrap_or("");
-                format!("{} {}", params, ret)
-            }
-            ItemKind::Struct { fields, .. } => fields.iter().map(|f| f.ty.as_str()).collect(),
-            ItemKind::Impl { self_type, .. } => self_type.clone(),
-            ItemKind::Trait { supertraits, .. } => supertraits.join(" + "),
-            _ => String::new(),
-        }
-    }
-
-    /// Parse generic parameter names from text (e.g., "T", "K", "V" from "<T, K: Hash, V>")
-    fn parse_generic_params(&self, text: &str) -> Vec<String> {
-        let mut params = Vec::new();
-        let mut depth: usize = 0;
-        let mut current = String::new();
-
-        for c in text.chars() {
-            match c {
-                '<' => {
-                    depth += 1;
-                    if depth == 1 {
-                        current.clear();
-                    }
-                }
-                '>' => {
-                    if depth == 1 && !current.trim().is_empty() {
-                        // Extract param name (before any colon)
-                        let param = current.split(':').next().unwrap_or("").trim();
-                        if !param.is_empty()
-                            && param.chars().next().is_some_and(|c| c.is_uppercase())
-                        {
-                            params.push(param.to_string());
-                        }
-                    }
-                    depth = depth.saturating_sub(1);
-                }
-                ',' if depth == 1 => {
-                    let param = current.split(':').next().unwrap_or("").trim();
-                    if !param.is_empty() && param.chars().next().is_some_and(|c| c.is_uppercase()) {
-                        params.push(param.to_string());
-                    }
-                    current.clear();
-                }
-                _ if depth >= 1 => {
-                    current.push(c);
-                }
-                _ => {}
-            }
-        }
-
-        params
-    }
-
     /// Extract full generic bounds from item (the "Live Signature")
     fn extract_generic_bounds(&self, item: &ParsedItem) -> Vec<GenericBound> {
         // Read the source file to get the actual signature
@@ -1011,69 +1718,140 @@ impl SemanticGravity {
 
     /// Get parent context (e.g., impl block for a method)
     fn get_parent_context(&self, item: &ParsedItem) -> Option<String> {
-        // For now, if it's a method we'd look for the containing impl
-        // This requires positional analysis - check if there's an impl that contains this span
-        let Some(file) = self.files.iter().find(|f| f.path == item.file_path) else {
+        let ItemKind::Impl {
+            self_type,
+            trait_name,
+            ..
+        } = &self.parent_impl(item)?.kind
+        else {
             return None;
         };
 
-        for other in &file.items {
-            if let ItemKind::Impl {
-                self_type,
-                trait_name,
-                methods,
-            } = &other.kind
-            {
-                if methods.contains(&item.name) {
-                    return Some(if let Some(trait_n) = trait_name {
-                        format!("impl {} for {}", trait_n, self_type)
-                    } else {
-                        format!("impl {}", self_type)
-                    });
-                }
-            }
+        Some(if let Some(trait_n) = trait_name {
+            format!("impl {} for {}", trait_n, self_type)
+        } else {
+            format!("impl {}", self_type)
+        })
+    }
+
+    /// The impl block containing a method, found by span
+    pub fn parent_impl(&self, item: &ParsedItem) -> Option<&ParsedItem> {
+        if !matches!(item.kind, ItemKind::Function { .. }) {
+            return None;
         }
+        let file = self.files.iter().find(|f| f.path == item.file_path)?;
 
-        None
+        file.items.iter().find(|other| {
+            matches!(&other.kind, ItemKind::Impl { methods, .. } if methods.contains(&item.name))
+                && other.span.start_line <= item.span.start_line
+                && item.span.end_line <= other.span.end_line
+        })
     }
 
-    /// Get impl information for a type
+    /// Get impl information for a type: impl block count and implemented
+    /// traits (manual, derived and blanket)
     fn get_impl_info(&self, type_name: &str) -> (usize, Vec<String>) {
-        match self.impl_map.get(type_name) {
-            Some(impl_items) => {
-                let impl_count = impl_items.len();
-                let trait_impls: Vec<String> = impl_items
-                    .iter()
-                    .filter_map(|item| {
-                        if let ItemKind::Impl { trait_name, .. } = &item.kind {
-                            trait_name.clone()
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
-                (impl_count, trait_impls)
-            }
-            None => (0, Vec::new()),
-        }
+        let impl_count = self.impl_map.get(type_name).map_or(0, |items| items.len());
+        (impl_count, self.trait_matrix.trait_names_of(type_name))
+    }
+
+    /// Get the trait implementation matrix
+    pub fn trait_matrix(&self) -> &TraitMatrix {
+        &self.trait_matrix
+    }
+
+    /// Get the type relationship graph
+    pub fn type_graph(&self) -> &TypeGraph {
+        &self.type_graph
+    }
+
+    /// Get the static test-to-code map
+    pub fn test_map(&self) -> &TestMap {
+        &self.test_map
+    }
+
+    /// Build the error propagation map (reads function bodies from disk)
+    pub fn error_flow(&self) -> ErrorFlow {
+        ErrorFlow::build(&self.files)
     }
 
     /// Search for items and return ranked results
     pub fn search(&self, query: &str) -> Vec<WorkSiteScore> {
-        let query_lower = query.to_lowercase();
+        self.search_items(self.files.iter().flat_map(|f| &f.items), query)
+    }
 
-        let mut results: Vec<WorkSiteScore> = self
+    /// Search with a structured query: filters narrow the items and the
+    /// free text (if any) is matched and ranked as in `search`. Impl
+    /// methods are only searched when the query has filters, so plain text
+    /// ranks the same items as `search`.
+    pub fn search_query(&self, query: &SearchQuery) -> Vec<WorkSiteScore> {
+        let items = self
             .files
             .iter()
-            .flat_map(|f| &f.items)
-            .filter(|item| {
-                item.name.to_lowercase().contains(&query_lower)
-                    || item
-                        .doc_comment
+            .flat_map(|f| {
+                let methods = if query.has_filters() {
+                    f.methods.as_slice()
+                } else {
+                    &[]
+                };
+                f.items.iter().chain(methods)
+            })
+            .filter(|item| self.matches_query(query, item));
+
+        if !query.text.is_empty() {
+            return self.search_items(items, &query.text);
+        }
+
+        let mut results: Vec<WorkSiteScore> = items.map(|item| self.score_item(item)).collect();
+        results.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        results
+    }
+
+    /// Check an item against a structured query's filters
+    pub fn matches_query(&self, query: &SearchQuery, item: &ParsedItem) -> bool {
+        if !query.has_filters() {
+            return true;
+        }
+
+        let module = self
+            .file_to_module
+            .get(&item.file_path)
+            .map(String::as_str)
+            .unwrap_or("crate");
+        let owner = match item.kind {
+            ItemKind::Impl { .. } => Some(item),
+            _ => self.parent_impl(item),
+        };
+        let impl_type = owner.and_then(|owner| match &owner.kind {
+            ItemKind::Impl { self_type, .. } => Some(self_type.as_str()),
+            _ => None,
+        });
+
+        query.matches(item, &ItemContext { module, impl_type })
+    }
+
+    /// Fuzzy-match and rank a set of items against free text
+    fn search_items<'a>(
+        &self,
+        items: impl Iterator<Item = &'a ParsedItem>,
+        query: &str,
+    ) -> Vec<WorkSiteScore> {
+        let query_lower = query.to_lowercase();
+
+        let mut results: Vec<WorkSiteScore> = items
+            .filter_map(|item| {
+                let matched = fuzzy::fuzzy_match(query, &item.name).or_else(|| {
+                    item.doc_comment
                         .as_ref()
-                        .is_some_and(|d| d.to_lowercase().contains(&query_lower))
+                        .filter(|d| d.to_lowercase().contains(&query_lower))
+                        .map(|_| FuzzyMatch::doc_comment())
+                })?;
+                Some(self.score_match(item, matched))
             })
-            .map(|item| self.score_item(item))
             .collect();
 
         results.sort_by(|a, b| {
@@ -1085,11 +1863,442 @@ impl SemanticGravity {
         results
     }
 
+    /// Rank items by embedding similarity to a natural-language query,
+    /// blended with their gravity score. Requires `load_semantic_index`.
+    pub fn search_semantic(&self, query: &str) -> Result<Vec<WorkSiteScore>, GravityError> {
+        let Some(index) = &self.semantic_index else {
+            return Ok(Vec::new());
+        };
+        let query_vector = crate::embedding::embed(query).map_err(SemanticError::from)?;
+
+        let mut results: Vec<WorkSiteScore> = self
+            .semantic_items()
+            .filter_map(|item| {
+                let vector = index.vector(&self.embedding_text(item))?;
+                let similarity = semantic::cosine(&query_vector, vector);
+                (similarity >= MIN_SEMANTIC_SIMILARITY).then(|| {
+                    let mut result = self.score_item(item);
+                    result.factors.semantic_similarity = Some(similarity);
+                    result.breakdown.semantic_similarity =
+                        similarity as f64 * self.weights.semantic_similarity;
+                    result.score = result.breakdown.total().max(0.0);
+                    result
+                })
+            })
+            .collect();
+
+        results.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        Ok(results)
+    }
+
+    /// Find every place a type is mentioned: fields, variant payloads,
+    /// parameters, return types, impl headers, aliases, bounds and bodies.
+    /// Structural usages rank first, then usages from other modules.
+    pub fn find_type_usages(&self, type_name: &str) -> Vec<TypeUsage> {
+        let type_name = type_name.rsplit("::").next().unwrap_or(type_name);
+
+        // Module where the type is defined, for the cross-module bonus
+        let home_module = self
+            .files
+            .iter()
+            .flat_map(|f| &f.items)
+            .find(|item| {
+                item.name == type_name
+                    && matches!(
+                        item.kind,
+                        ItemKind::Struct { .. }
+                            | ItemKind::Enum { .. }
+                            | ItemKind::Trait { .. }
+                            | ItemKind::TypeAlias { .. }
+                    )
+            })
+            .and_then(|item| self.file_to_module.get(&item.file_path));
+
+        let mut usages: Vec<TypeUsage> = Vec::new();
+        for file in &self.files {
+            let mut file_usages = self.usage_index.in_file(&file.path, type_name).to_vec();
+
+            let module = self.file_to_module.get(&file.path);
+            let distance = self.distance_cache.get(&file.path).copied().unwrap_or(0);
+            for usage in &mut file_usages {
+                if home_module.is_some() && module != home_module {
+                    usage.score += self.weights.cross_module_usage / 5.0;
+                }
+                usage.score += (distance as f64) * self.weights.entry_distance_penalty;
+            }
+            usages.extend(file_usages);
+        }
+
+        usage::rank(&mut usages);
+
+        usages
+    }
+
+    /// Every place non-test code can panic, attributed to its enclosing
+    /// item and ranked by that item's score, then by entry distance. Files
+    /// that don't parse are skipped.
+    pub fn get_panic_sites(&self) -> Vec<RankedPanicSite> {
+        let roots = self.crate_roots();
+        let mut ranked = Vec::new();
+        for file in &self.files {
+            if testmap::is_integration_test_file(&file.path, &roots) {
+                continue;
+            }
+            let Some(sites) = std::fs::read_to_string(&file.path)
+                .ok()
+                .and_then(|source| panics::find_in_source(&source, &file.path))
+            else {
+                continue;
+            };
+
+            for site in sites {
+                // Innermost item around the site; impls and modules only
+                // enclose the items that get scored
+                let item = file
+                    .items_and_methods()
+                    .filter(|item| {
+                        !matches!(item.kind, ItemKind::Impl { .. } | ItemKind::Mod { .. })
+                            && item.span.start_line <= site.line
+                            && site.line <= item.span.end_line
+                    })
+                    .min_by_key(|item| item.span.end_line - item.span.start_line);
+                let (score, entry_distance) = match item {
+                    Some(item) => {
+                        let scored = self.score_item(item);
+                        (scored.score, scored.factors.entry_distance)
+                    }
+                    None => (
+                        0.0,
+                        self.distance_cache
+                            .get(&file.path)
+                            .copied()
+                            .unwrap_or(usize::MAX),
+                    ),
+                };
+                ranked.push(RankedPanicSite {
+                    site,
+                    score,
+                    entry_distance,
+                });
+            }
+        }
+
+        ranked.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.entry_distance.cmp(&b.entry_distance))
+                .then_with(|| a.site.file.cmp(&b.site.file))
+                .then_with(|| a.site.line.cmp(&b.site.line))
+        });
+        ranked
+    }
+
+    /// Blocking work in async code: blocking std calls, sync guards and
+    /// borrows held across `.await`, busy loops, and calls to local
+    /// functions that block. A synchronous function blocks if it uses a
+    /// blocking std path (per the reference map, or through its `use`
+    /// items) or calls a name whose every definition blocks. Files that
+    /// don't parse are skipped.
+    pub fn get_async_hazards(&self) -> Vec<AsyncHazard> {
+        let files_by_path: HashMap<&Path, &ParsedFile> =
+            self.files.iter().map(|f| (f.path.as_path(), f)).collect();
+
+        let roots = self.crate_roots();
+        let mut scans = Vec::new();
+        for file in &self.files {
+            if testmap::is_integration_test_file(&file.path, &roots) {
+                continue;
+            }
+            let uses: Vec<&str> = file
+                .items
+                .iter()
+                .filter_map(|item| match &item.kind {
+                    ItemKind::Use { path } => Some(path.as_str()),
+                    _ => None,
+                })
+                .collect();
+            let aliases = hazards::use_aliases(&uses);
+            if let Some(scan) = std::fs::read_to_string(&file.path)
+                .ok()
+                .and_then(|source| hazards::find_in_source(&source, &file.path, &aliases))
+            {
+                scans.push((file.path.as_path(), scan));
+            }
+        }
+
+        // Innermost function around a line, if it's synchronous
+        let sync_fn_at = |path: &Path, line: usize| -> Option<&ParsedItem> {
+            files_by_path
+                .get(path)?
+                .items_and_methods()
+                .filter(|item| {
+                    matches!(item.kind, ItemKind::Function { .. })
+                        && item.span.start_line <= line
+                        && line <= item.span.end_line
+                })
+                .min_by_key(|item| item.span.end_line - item.span.start_line)
+                .filter(|item| {
+                    matches!(
+                        item.kind,
+                        ItemKind::Function {
+                            is_async: false,
+                            ..
+                        }
+                    )
+                })
+        };
+
+        let mut definitions: HashMap<&str, usize> = HashMap::new();
+        for item in self.files.iter().flat_map(|f| f.items_and_methods()) {
+            if matches!(item.kind, ItemKind::Function { .. }) {
+                *definitions.entry(item.name.as_str()).or_default() += 1;
+            }
+        }
+
+        // Functions using blocking std paths directly
+        let mut pending: Vec<(&ParsedItem, String)> = self
+            .reference_map
+            .references
+            .values()
+            .flatten()
+            .filter(|r| hazards::async_alternative(&r.external_path).is_some())
+            .filter_map(|r| Some((sync_fn_at(&r.file, r.line)?, r.external_path.clone())))
+            .collect();
+        for (path, scan) in &scans {
+            for (_, blocking_path, line) in &scan.blocking_in_sync {
+                if let Some(item) = sync_fn_at(path, *line) {
+                    pending.push((item, blocking_path.clone()));
+                }
+            }
+        }
+
+        // Walk callers until no new name has all its definitions blocking.
+        // `blocking` maps each such name to what it blocks on.
+        let mut blocked: HashSet<(&Path, usize)> = HashSet::new();
+        let mut blocked_count: HashMap<&str, usize> = HashMap::new();
+        let mut blocking: HashMap<String, String> = HashMap::new();
+        let mut queue: VecDeque<String> = VecDeque::new();
+        loop {
+            for (item, via) in pending.drain(..) {
+                if !blocked.insert((item.file_path.as_path(), item.span.start_line)) {
+                    continue;
+                }
+                let count = blocked_count.entry(item.name.as_str()).or_default();
+                *count += 1;
+                if *count == definitions[item.name.as_str()] && !blocking.contains_key(&item.name) {
+                    blocking.insert(item.name.clone(), via);
+                    queue.push_back(item.name.clone());
+                }
+            }
+
+            let Some(name) = queue.pop_front() else {
+                break;
+            };
+            if self.is_prelude_method(&name) {
+                continue;
+            }
+            for site in self.call_graph.callers.get(&name).into_iter().flatten() {
+                if let Some(item) = sync_fn_at(&site.file, site.line)
+                    && item.name != name
+                {
+                    pending.push((item, name.clone()));
+                }
+            }
+        }
+
+        let mut found = Vec::new();
+        for (_, scan) in scans {
+            found.extend(scan.hazards);
+            for call in scan.calls {
+                let name = call.name.rsplit("::").next().unwrap_or(&call.name);
+                if self.is_prelude_method(name) || !blocking.contains_key(name) {
+                    continue;
+                }
+                let mut chain = vec![name.to_string()];
+                while let Some(via) = blocking.get(chain[chain.len() - 1].as_str()) {
+                    if chain.contains(via) || chain.len() > 8 {
+                        break;
+                    }
+                    chain.push(via.clone());
+                }
+                found.push(AsyncHazard {
+                    kind: HazardKind::BlockingCall,
+                    file: call.file,
+                    span: call.span,
+                    context: call.context,
+                    detail: chain.join(" -> "),
+                    suggestion: "run it in tokio::task::spawn_blocking".to_string(),
+                    snippet: call.snippet,
+                });
+            }
+        }
+
+        found.sort_by(|a, b| {
+            (&a.file, a.span.start_line, a.span.start_col).cmp(&(
+                &b.file,
+                b.span.start_line,
+                b.span.start_col,
+            ))
+        });
+        found.dedup_by(|a, b| a.file == b.file && a.span == b.span && a.kind == b.kind);
+        found
+    }
+
+    /// Every `unsafe` fn, impl, trait and block in non-test code, grouped
+    /// by module and ranked by distance from the public API: modules in
+    /// order of their closest site, sites by distance, undocumented first.
+    /// Binary-only crates have no public API and are measured from `main`.
+    /// Files that don't parse are skipped.
+    pub fn get_unsafe_sites(&self) -> Vec<RankedUnsafeSite> {
+        let roots = self.crate_roots();
+        let sources: Vec<(&ParsedFile, String)> = self
+            .files
+            .iter()
+            .filter(|file| !testmap::is_integration_test_file(&file.path, &roots))
+            .filter_map(|file| Some((file, std::fs::read_to_string(&file.path).ok()?)))
+            .collect();
+        let mut declarations = Declarations::default();
+        for (_, source) in &sources {
+            declarations.add_source(source);
+        }
+
+        // Distance of every function from the nearest public one
+        let api: Vec<semver::ApiItem> = self
+            .resolvers
+            .iter()
+            .flat_map(|resolver| semver::public_api(resolver).into_values())
+            .collect();
+        let public_types: HashSet<&str> = api
+            .iter()
+            .filter(|api| !matches!(api.item.kind, ItemKind::Function { .. }))
+            .map(|api| api.item.name.as_str())
+            .collect();
+        let mut distance: HashMap<&str, usize> = api
+            .iter()
+            .filter(|api| matches!(api.item.kind, ItemKind::Function { .. }))
+            .map(|api| (api.item.name.as_str(), 0))
+            .collect();
+        if distance.is_empty() {
+            distance.insert("main", 0);
+        }
+        let mut queue: VecDeque<&str> = distance.keys().copied().collect();
+        while let Some(name) = queue.pop_front() {
+            let next = distance[name] + 1;
+            for callee in self.call_graph.callees.get(name).into_iter().flatten() {
+                if !distance.contains_key(callee.as_str()) {
+                    distance.insert(callee, next);
+                    queue.push_back(callee);
+                }
+            }
+        }
+
+        let mut ranked = Vec::new();
+        for (file, source) in &sources {
+            let Some(sites) = unsafety::find_in_source(source, &file.path, &declarations) else {
+                continue;
+            };
+            let module = if file.module_path.is_empty() {
+                "crate".to_string()
+            } else {
+                file.module_path.join("::")
+            };
+            for site in sites {
+                let public_distance = match site.kind {
+                    UnsafeKind::Impl => {
+                        let self_type = site.name.rsplit(" for ").next().unwrap_or(&site.name);
+                        let base = self_type.split('<').next().unwrap_or(self_type);
+                        if public_types.contains(base) {
+                            0
+                        } else {
+                            usize::MAX
+                        }
+                    }
+                    UnsafeKind::Trait if public_types.contains(site.name.as_str()) => 0,
+                    _ => {
+                        let name = site.name.rsplit("::").next().unwrap_or(&site.name);
+                        distance.get(name).copied().unwrap_or(usize::MAX)
+                    }
+                };
+                ranked.push(RankedUnsafeSite {
+                    site,
+                    module: module.clone(),
+                    public_distance,
+                });
+            }
+        }
+
+        let mut closest: HashMap<String, usize> = HashMap::new();
+        for site in &ranked {
+            let entry = closest.entry(site.module.clone()).or_insert(usize::MAX);
+            *entry = (*entry).min(site.public_distance);
+        }
+        ranked.sort_by(|a, b| {
+            closest[&a.module]
+                .cmp(&closest[&b.module])
+                .then_with(|| a.module.cmp(&b.module))
+                .then_with(|| a.public_distance.cmp(&b.public_distance))
+                .then_with(|| a.site.documented.cmp(&b.site.documented))
+                .then_with(|| a.site.file.cmp(&b.site.file))
+                .then_with(|| a.site.line.cmp(&b.site.line))
+        });
+        ranked
+    }
+
+    /// Key the reference map by canonical paths: usages written as
+    /// `tokio::task::spawn` and `tokio::spawn` are merged under the path
+    /// `canonical` gives (e.g. `DependencyBridge::canonical_path`). Written
+    /// paths are kept on each reference and still find their usages.
+    pub fn canonicalize_references(&mut self, mut canonical: impl FnMut(&str) -> Option<String>) {
+        let references = std::mem::take(&mut self.reference_map.references);
+        for (path, usages) in references {
+            let key = match canonical(&path) {
+                Some(key) if key != path => {
+                    self.reference_map.aliases.insert(path, key.clone());
+                    key
+                }
+                _ => path,
+            };
+            self.reference_map
+                .references
+                .entry(key)
+                .or_default()
+                .extend(usages);
+        }
+        for usages in self.reference_map.references.values_mut() {
+            usages.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+        }
+    }
+
+    /// Canonicalize the references into the crate `path` starts with and
+    /// return `path`'s own canonical path, for looking up its usages
+    /// under any path to the same item
+    pub fn canonicalize_usages_of(
+        &mut self,
+        path: &str,
+        mut canonical: impl FnMut(&str) -> Option<String>,
+    ) -> String {
+        let crate_name = path.split("::").next().unwrap_or_default();
+        self.canonicalize_references(|written| {
+            if written.split("::").next() == Some(crate_name) {
+                canonical(written)
+            } else {
+                None
+            }
+        });
+        canonical(path).unwrap_or_else(|| path.to_string())
+    }
+
     /// Get local usages of an external symbol
     pub fn get_external_usages(&self, external_path: &str) -> Vec<&ExternalReference> {
         self.reference_map
             .references
-            .get(external_path)
+            .get(self.reference_map.key(external_path))
             .map(|refs| refs.iter().collect())
             .unwrap_or_default()
     }
@@ -1098,7 +2307,7 @@ impl SemanticGravity {
     pub fn get_most_complex_usage(&self, external_path: &str) -> Option<&ExternalReference> {
         self.reference_map
             .references
-            .get(external_path)?
+            .get(self.reference_map.key(external_path))?
             .iter()
             .max_by_key(|r| r.complexity)
     }
@@ -1132,6 +2341,27 @@ impl SemanticGravity {
             .unwrap_or_default()
     }
 
+    /// Reads, writes, constructions and destructurings of a struct's
+    /// fields (or of one field), by file and line. Receivers are typed by
+    /// the body index in [`fields`]; files that don't parse are skipped.
+    pub fn find_field_accesses(&self, struct_name: &str, field: Option<&str>) -> Vec<FieldAccess> {
+        let index = TypeIndex::build(&self.files);
+        let mut accesses: Vec<FieldAccess> = self
+            .files
+            .iter()
+            .filter_map(|file| {
+                let source = std::fs::read_to_string(&file.path).ok()?;
+                fields::find_in_source(&source, &file.path, &index)
+            })
+            .flatten()
+            .filter(|access| {
+                access.struct_name == struct_name && field.is_none_or(|f| access.field == f)
+            })
+            .collect();
+        accesses.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+        accesses
+    }
+
     /// Find what a function calls
     pub fn find_callees(&self, fn_name: &str) -> Vec<&String> {
         self.call_graph
@@ -1146,11 +2376,44 @@ impl SemanticGravity {
         self.distance_cache.get(path).copied()
     }
 
+    /// Get the workspace members found by `analyze_project`
+    pub fn get_workspace(&self) -> &Workspace {
+        &self.workspace
+    }
+
+    /// Get paths from one workspace member into another
+    pub fn get_crate_references(&self) -> &[CrateReference] {
+        &self.crate_references
+    }
+
+    /// Number of references along each cross-crate edge, busiest first
+    pub fn get_crate_edges(&self) -> Vec<(&str, &str, usize)> {
+        let mut counts: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+        for r in &self.crate_references {
+            *counts
+                .entry((r.from_crate.as_str(), r.to_crate.as_str()))
+                .or_default() += 1;
+        }
+
+        let mut edges: Vec<(&str, &str, usize)> = counts
+            .into_iter()
+            .map(|((from, to), count)| (from, to, count))
+            .collect();
+        edges.sort_by_key(|(_, _, count)| std::cmp::Reverse(*count));
+        edges
+    }
+
     /// Get the module tree
     pub fn get_module_tree(&self) -> &ModuleTree {
         &self.module_tree
     }
 
+    /// Get every module -> module/crate reference (`use` items and qualified
+    /// paths), for layering checks
+    pub fn get_module_dependencies(&self) -> &[ModuleDependency] {
+        &self.module_dependencies
+    }
+
     /// Get all parsed files
     pub fn get_files(&self) -> &[ParsedFile] {
         &self.files
@@ -1194,6 +2457,120 @@ impl SemanticGravity {
         all_scores
     }
 
+    /// Non-test functions with their complexity metrics, most complex
+    /// (by cognitive complexity) first
+    pub fn get_complex_functions(&self, n: usize) -> Vec<(&ParsedItem, FunctionMetrics)> {
+        let mut functions: Vec<(&ParsedItem, FunctionMetrics)> = self
+            .files
+            .iter()
+            .flat_map(|f| f.items_and_methods())
+            .filter(|item| !self.is_test_item(item))
+            .filter_map(|item| match &item.kind {
+                ItemKind::Function {
+                    metrics: Some(metrics),
+                    ..
+                } => Some((item, *metrics)),
+                _ => None,
+            })
+            .collect();
+
+        functions.sort_by(|(_, a), (_, b)| {
+            (b.cognitive, b.cyclomatic).cmp(&(a.cognitive, a.cyclomatic))
+        });
+        functions.truncate(n);
+        functions
+    }
+
+    /// Complexity metrics aggregated per module (non-test functions only),
+    /// highest total cognitive complexity first
+    pub fn get_module_metrics(&self) -> Vec<ModuleMetrics> {
+        let mut modules: Vec<ModuleMetrics> = self
+            .files
+            .iter()
+            .map(|file| {
+                let mut module = ModuleMetrics {
+                    module: self
+                        .file_to_module
+                        .get(&file.path)
+                        .cloned()
+                        .unwrap_or_else(|| "crate".to_string()),
+                    file: file.path.clone(),
+                    ..Default::default()
+                };
+
+                for item in file.items_and_methods() {
+                    let ItemKind::Function {
+                        metrics: Some(metrics),
+                        ..
+                    } = &item.kind
+                    else {
+                        continue;
+                    };
+                    if self.is_test_item(item) {
+                        continue;
+                    }
+
+                    module.functions += 1;
+                    module.total_cyclomatic += metrics.cyclomatic;
+                    module.total_cognitive += metrics.cognitive;
+                    module.statements += metrics.statements;
+                    module.max_nesting = module.max_nesting.max(metrics.max_nesting);
+                    if module.most_complex.is_none() || metrics.cognitive > module.max_cognitive {
+                        module.max_cognitive = metrics.cognitive;
+                        module.most_complex = Some(item.name.clone());
+                    }
+                }
+
+                module
+            })
+            .filter(|m| m.functions > 0)
+            .collect();
+
+        modules.sort_by(|a, b| {
+            b.total_cognitive
+                .cmp(&a.total_cognitive)
+                .then_with(|| a.module.cmp(&b.module))
+        });
+        modules
+    }
+
+    /// High-gravity items with low test coverage, ranked by how much
+    /// gravity is left uncovered. Requires `load_coverage`.
+    pub fn get_untested_hotspots(&self, n: usize, max_coverage: f64) -> Vec<WorkSiteScore> {
+        if self.coverage.is_none() {
+            return Vec::new();
+        }
+
+        let uncovered_gravity = |s: &WorkSiteScore| {
+            let percent = s.factors.coverage.as_ref().map_or(0.0, |c| c.percent);
+            s.score * (1.0 - percent / 100.0)
+        };
+
+        let mut hotspots: Vec<WorkSiteScore> = self
+            .files
+            .iter()
+            .flat_map(|f| f.items_and_methods())
+            .filter(|item| {
+                matches!(item.kind, ItemKind::Function { .. }) && !self.is_test_item(item)
+            })
+            .map(|item| self.score_item(item))
+            .filter(|s| {
+                s.factors
+                    .coverage
+                    .as_ref()
+                    .is_some_and(|c| c.percent <= max_coverage)
+            })
+            .collect();
+
+        hotspots.sort_by(|a, b| {
+            uncovered_gravity(b)
+                .partial_cmp(&uncovered_gravity(a))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        hotspots.truncate(n);
+        hotspots
+    }
+
     /// Get significant hub functions (filtered, cross-module usage prioritized)
     pub fn get_significant_hubs(&self, n: usize) -> Vec<(String, usize, usize)> {
         let mut hubs: Vec<_> = self
@@ -1214,6 +2591,25 @@ impl SemanticGravity {
         hubs
     }
 
+    /// Capture the full analysis as a serializable snapshot. Dependencies
+    /// come from the caller (e.g. a loaded `DependencyBridge`) and are
+    /// sorted by name.
+    pub fn snapshot(&self, root: &Path, mut dependencies: Vec<CrateDependency>) -> ProjectAnalysis {
+        dependencies.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+
+        ProjectAnalysis {
+            version: snapshot::SNAPSHOT_VERSION,
+            generator: format!("cargomap {}", env!("CARGO_PKG_VERSION")),
+            root: root.to_path_buf(),
+            files: self.files.clone(),
+            dependencies,
+            call_graph: self.call_graph.clone(),
+            module_tree: self.module_tree.clone(),
+            reference_map: self.reference_map.clone(),
+            crate_references: self.crate_references.clone(),
+        }
+    }
+
     /// Generate a summary of the project architecture
     pub fn summarize(&self) -> ProjectSummary {
         let mut summary = ProjectSummary::default();
@@ -1325,4 +2721,66 @@ mod tests {
         assert!(gravity.is_prelude_method("map"));
         assert!(!gravity.is_prelude_method("my_custom_function"));
     }
+
+    #[test]
+    fn test_methods_only_searched_with_filters() {
+        let root = std::env::temp_dir().join(format!("cargomap-methods-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src/lib.rs"),
+            "pub struct Config;\nimpl Config {\n    pub fn load() {}\n}\npub fn load() {}\n",
+        )
+        .unwrap();
+
+        let mut gravity = SemanticGravity::new();
+        gravity.analyze_project(&root).unwrap();
+
+        let lines = |results: Vec<WorkSiteScore>| -> Vec<usize> {
+            results
+                .iter()
+                .filter(|r| r.item.name == "load")
+                .map(|r| r.item.span.start_line)
+                .collect()
+        };
+        assert_eq!(lines(gravity.search("load")), vec![5]);
+        let text = SearchQuery::parse("load").unwrap();
+        assert_eq!(lines(gravity.search_query(&text)), vec![5]);
+        let filtered = SearchQuery::parse("load impl:Config").unwrap();
+        assert_eq!(lines(gravity.search_query(&filtered)), vec![3]);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_methods_embedded_with_their_type() {
+        let root = std::env::temp_dir().join(format!("cargomap-embed-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src/lib.rs"),
+            "pub struct Config;\nimpl Config {\n    /// Read the settings file\n    pub fn load(&self) -> bool { true }\n}\n",
+        )
+        .unwrap();
+
+        let mut gravity = SemanticGravity::new();
+        gravity.analyze_project(&root).unwrap();
+
+        let method = gravity
+            .semantic_items()
+            .find(|item| item.name == "load")
+            .expect("method is embedded");
+        let text = gravity.embedding_text(method);
+        assert!(text.contains("(&self) -> bool"), "{}", text);
+        assert!(text.contains("Config::load"), "{}", text);
+        assert!(text.contains("Read the settings file"), "{}", text);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
 }
@@ -0,0 +1,671 @@
+//! Async hazard detection - blocking work on async executors
+//!
+//! Walks `async fn` bodies, `async` blocks and async closures and records:
+//! - Blocking std calls (`std::fs`, `std::thread::sleep`, `std::net`,
+//!   `std::process::Command`, stdin), with paths resolved through the file's
+//!   `use` items, and the tokio counterpart to use instead
+//! - Synchronous lock guards (`.lock()` / `.read()` / `.write()` without
+//!   `.await`) and `RefCell` borrows bound with `let` and still alive at a
+//!   later `.await` in the same block
+//! - Nested loops with no `.await` inside, which hold the executor thread
+//!   for the whole iteration
+//! - Calls to local functions, returned for the caller to check against the
+//!   functions it knows to block (see
+//!   [`SemanticGravity::get_async_hazards`](crate::gravity::SemanticGravity::get_async_hazards))
+//!
+//! Closures handed to `spawn_blocking`, `block_in_place` or
+//! `std::thread::spawn` run off the executor and are skipped, as are tests.
+
+use crate::parser::{is_cfg_test, is_test_fn};
+use crate::types::Span;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+
+/// Calls whose closure argument runs outside the async executor
+const OFFLOADING: &[&str] = &["spawn_blocking", "block_in_place"];
+
+/// What blocks the executor at a site
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HazardKind {
+    /// Blocking std I/O (`std::fs`, `std::net`, `std::process`, stdin)
+    BlockingIo,
+    /// `std::thread::sleep`
+    Sleep,
+    /// A local function that blocks, directly or through its callees
+    BlockingCall,
+    /// A synchronous lock guard alive across `.await`
+    LockAcrossAwait,
+    /// A `RefCell` borrow alive across `.await`
+    BorrowAcrossAwait,
+    /// Nested loops without a yield point
+    BusyLoop,
+}
+
+impl HazardKind {
+    pub const ALL: [HazardKind; 6] = [
+        HazardKind::BlockingIo,
+        HazardKind::Sleep,
+        HazardKind::BlockingCall,
+        HazardKind::LockAcrossAwait,
+        HazardKind::BorrowAcrossAwait,
+        HazardKind::BusyLoop,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HazardKind::BlockingIo => "blocking-io",
+            HazardKind::Sleep => "sleep",
+            HazardKind::BlockingCall => "blocking-call",
+            HazardKind::LockAcrossAwait => "lock-across-await",
+            HazardKind::BorrowAcrossAwait => "borrow-across-await",
+            HazardKind::BusyLoop => "busy-loop",
+        }
+    }
+
+    /// Parse a label as printed by [`HazardKind::label`]
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.label() == label)
+    }
+}
+
+/// Blocking work found in async code
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AsyncHazard {
+    pub kind: HazardKind,
+    pub file: PathBuf,
+    pub span: Span,
+    /// Enclosing item (e.g. "AuditImpact::call_tool_async")
+    pub context: String,
+    /// What blocks, e.g. `std::fs::read_to_string` or a call chain
+    pub detail: String,
+    /// Async alternative
+    pub suggestion: String,
+    /// Trimmed source line
+    pub snippet: String,
+}
+
+/// A call to a possibly local function from async code
+#[derive(Debug, Clone)]
+pub struct AsyncCall {
+    /// Called name (`name`, or `Type::name` when written qualified)
+    pub name: String,
+    pub file: PathBuf,
+    pub span: Span,
+    pub context: String,
+    pub snippet: String,
+}
+
+/// Hazards and local calls found in one file's async code
+#[derive(Debug, Clone, Default)]
+pub struct FileScan {
+    pub hazards: Vec<AsyncHazard>,
+    pub calls: Vec<AsyncCall>,
+    /// Blocking std calls in synchronous functions: (function, path, line)
+    pub blocking_in_sync: Vec<(String, String, usize)>,
+}
+
+/// The async counterpart of a blocking std path, if it blocks
+pub fn async_alternative(path: &str) -> Option<(HazardKind, String)> {
+    if path == "std::thread::sleep" {
+        return Some((
+            HazardKind::Sleep,
+            "tokio::time::sleep(..).await".to_string(),
+        ));
+    }
+    for (blocking, alternative) in [
+        ("std::fs::", "tokio::fs::"),
+        ("std::net::", "tokio::net::"),
+        ("std::process::Command", "tokio::process::Command"),
+        ("std::io::stdin", "tokio::io::stdin"),
+    ] {
+        if let Some(rest) = path.strip_prefix(blocking) {
+            return Some((
+                HazardKind::BlockingIo,
+                format!("{}{} (or tokio::task::spawn_blocking)", alternative, rest),
+            ));
+        }
+    }
+    None
+}
+
+/// Names brought into scope by `use` items: alias -> full path
+pub fn use_aliases(use_paths: &[&str]) -> HashMap<String, String> {
+    let mut aliases = HashMap::new();
+    for path in use_paths {
+        for (segments, leaf) in crate::parser::use_leaves(path) {
+            let crate::parser::UseLeaf::Name { name, alias } = leaf else {
+                continue;
+            };
+            // `use std::fs::{self, File}` brings `fs` into scope
+            if name == "self" {
+                if let Some(last) = segments.last() {
+                    aliases.insert(last.clone(), segments.join("::"));
+                }
+            } else {
+                let mut full = segments;
+                full.push(name);
+                aliases.insert(alias, full.join("::"));
+            }
+        }
+    }
+    aliases
+}
+
+/// Scan a source file's async code, or `None` if it doesn't parse
+pub fn find_in_source(
+    source: &str,
+    path: &Path,
+    aliases: &HashMap<String, String>,
+) -> Option<FileScan> {
+    let file = syn::parse_file(source).ok()?;
+
+    let mut visitor = HazardVisitor {
+        path,
+        lines: source.lines().collect(),
+        aliases,
+        context: Vec::new(),
+        impl_type: None,
+        in_async: false,
+        scan: FileScan::default(),
+    };
+    visitor.visit_file(&file);
+
+    let mut scan = visitor.scan;
+    scan.hazards
+        .sort_by_key(|h| (h.span.start_line, h.span.start_col));
+    Some(scan)
+}
+
+fn span_of(span: proc_macro2::Span) -> Span {
+    Span {
+        start_line: span.start().line,
+        start_col: span.start().column,
+        end_line: span.end().line,
+        end_col: span.end().column,
+    }
+}
+
+/// `.lock()`, `.read()`, `.write()` or a RefCell borrow, optionally
+/// unwrapped, without `.await`: the kind and method
+fn sync_guard(expr: &syn::Expr) -> Option<(HazardKind, String)> {
+    let syn::Expr::MethodCall(call) = expr else {
+        return None;
+    };
+    let method = call.method.to_string();
+    match method.as_str() {
+        "unwrap" | "expect" => sync_guard(&call.receiver),
+        "lock" | "read" | "write" if call.args.is_empty() => {
+            Some((HazardKind::LockAcrossAwait, method))
+        }
+        "borrow" | "borrow_mut" if call.args.is_empty() => {
+            Some((HazardKind::BorrowAcrossAwait, method))
+        }
+        _ => None,
+    }
+}
+
+/// Finds `.await` and loops in an expression, not looking into nested
+/// async blocks or closures (they run separately)
+#[derive(Default)]
+struct Finder {
+    await_line: Option<usize>,
+    has_loop: bool,
+}
+
+impl<'ast> Visit<'ast> for Finder {
+    fn visit_expr_await(&mut self, expr: &'ast syn::ExprAwait) {
+        self.await_line
+            .get_or_insert(expr.await_token.span.start().line);
+        visit::visit_expr_await(self, expr);
+    }
+
+    fn visit_expr_loop(&mut self, expr: &'ast syn::ExprLoop) {
+        self.has_loop = true;
+        visit::visit_expr_loop(self, expr);
+    }
+
+    fn visit_expr_while(&mut self, expr: &'ast syn::ExprWhile) {
+        self.has_loop = true;
+        visit::visit_expr_while(self, expr);
+    }
+
+    fn visit_expr_for_loop(&mut self, expr: &'ast syn::ExprForLoop) {
+        self.has_loop = true;
+        visit::visit_expr_for_loop(self, expr);
+    }
+
+    fn visit_expr_async(&mut self, _: &'ast syn::ExprAsync) {}
+
+    fn visit_expr_closure(&mut self, _: &'ast syn::ExprClosure) {}
+
+    fn visit_item(&mut self, _: &'ast syn::Item) {}
+}
+
+fn find_in(f: impl FnOnce(&mut Finder)) -> Finder {
+    let mut finder = Finder::default();
+    f(&mut finder);
+    finder
+}
+
+struct HazardVisitor<'a> {
+    path: &'a Path,
+    lines: Vec<&'a str>,
+    aliases: &'a HashMap<String, String>,
+    /// Enclosing item names
+    context: Vec<String>,
+    /// Self type of the enclosing impl block
+    impl_type: Option<String>,
+    /// Whether the code being visited runs on the async executor
+    in_async: bool,
+    scan: FileScan,
+}
+
+impl HazardVisitor<'_> {
+    /// Visit inside a named function, async or not
+    fn function(&mut self, name: String, is_async: bool, f: impl FnOnce(&mut Self)) {
+        self.context.push(name);
+        let outer = std::mem::replace(&mut self.in_async, is_async);
+        f(self);
+        self.in_async = outer;
+        self.context.pop();
+    }
+
+    fn with_async(&mut self, in_async: bool, f: impl FnOnce(&mut Self)) {
+        let outer = std::mem::replace(&mut self.in_async, in_async);
+        f(self);
+        self.in_async = outer;
+    }
+
+    fn context(&self) -> String {
+        self.context
+            .last()
+            .cloned()
+            .unwrap_or_else(|| "<module>".to_string())
+    }
+
+    fn snippet(&self, line: usize) -> String {
+        self.lines
+            .get(line.wrapping_sub(1))
+            .map(|l| l.trim().chars().take(120).collect())
+            .unwrap_or_default()
+    }
+
+    fn record(&mut self, kind: HazardKind, span: Span, detail: String, suggestion: String) {
+        self.scan.hazards.push(AsyncHazard {
+            kind,
+            file: self.path.to_path_buf(),
+            snippet: self.snippet(span.start_line),
+            span,
+            context: self.context(),
+            detail,
+            suggestion,
+        });
+    }
+
+    /// Full path of a written path, through the file's `use` items
+    fn resolve(&self, path: &syn::Path) -> String {
+        let segments: Vec<String> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+        let Some(first) = segments.first() else {
+            return String::new();
+        };
+        match self.aliases.get(first) {
+            Some(full) => std::iter::once(full.clone())
+                .chain(segments[1..].iter().cloned())
+                .collect::<Vec<_>>()
+                .join("::"),
+            None => segments.join("::"),
+        }
+    }
+
+    /// Record a call made from async code that isn't awaited
+    fn call(&mut self, name: String, span: Span) {
+        self.scan.calls.push(AsyncCall {
+            name,
+            file: self.path.to_path_buf(),
+            snippet: self.snippet(span.start_line),
+            span,
+            context: self.context(),
+        });
+    }
+
+    /// Check a path call: blocking std work, or a candidate local call
+    fn path_call(&mut self, call: &syn::ExprCall, awaited: bool) {
+        let syn::Expr::Path(func) = &*call.func else {
+            return;
+        };
+        let full = self.resolve(&func.path);
+        let span = span_of(call.span());
+
+        if let Some((kind, suggestion)) = async_alternative(&full) {
+            if self.in_async {
+                self.record(kind, span, full, suggestion);
+            } else {
+                let context = self.context();
+                self.scan
+                    .blocking_in_sync
+                    .push((context, full, span.start_line));
+            }
+            return;
+        }
+
+        if self.in_async && !awaited {
+            let segments: Vec<&syn::PathSegment> = func.path.segments.iter().collect();
+            let name = match segments.as_slice() {
+                [.., qualifier, last]
+                    if qualifier.ident.to_string().starts_with(char::is_uppercase) =>
+                {
+                    format!("{}::{}", qualifier.ident, last.ident)
+                }
+                [.., last] => last.ident.to_string(),
+                [] => return,
+            };
+            self.call(name, span);
+        }
+    }
+
+    /// Visit a call; closures passed to an offloading call run off the
+    /// executor
+    fn visit_call(&mut self, call: &syn::ExprCall, awaited: bool) {
+        let offloads = matches!(&*call.func, syn::Expr::Path(func) if {
+            self.resolve(&func.path) == "std::thread::spawn"
+                || func
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|s| OFFLOADING.contains(&s.ident.to_string().as_str()))
+        });
+        if offloads {
+            self.with_async(false, |v| visit::visit_expr_call(v, call));
+            return;
+        }
+        self.path_call(call, awaited);
+        for arg in &call.args {
+            self.visit_expr(arg);
+        }
+    }
+
+    /// Flag `let` guards that are still alive at a later `.await`
+    fn check_guards(&mut self, block: &syn::Block) {
+        for (i, stmt) in block.stmts.iter().enumerate() {
+            let syn::Stmt::Local(local) = stmt else {
+                continue;
+            };
+            let Some(init) = &local.init else {
+                continue;
+            };
+            let Some((kind, method)) = sync_guard(&init.expr) else {
+                continue;
+            };
+            // `let _ = ...` drops the guard immediately
+            let syn::Pat::Ident(binding) = &local.pat else {
+                continue;
+            };
+            let name = binding.ident.to_string();
+
+            for later in &block.stmts[i + 1..] {
+                if is_drop_of(later, &name) {
+                    break;
+                }
+                let Some(await_line) = find_in(|f| f.visit_stmt(later)).await_line else {
+                    continue;
+                };
+                let suggestion = if kind == HazardKind::LockAcrossAwait {
+                    "drop the guard before .await, or use tokio::sync::Mutex / RwLock"
+                } else {
+                    "end the borrow before .await"
+                };
+                self.record(
+                    kind,
+                    span_of(local.span()),
+                    format!(
+                        "`{}` from .{}() is held across .await (line {})",
+                        name, method, await_line
+                    ),
+                    suggestion.to_string(),
+                );
+                break;
+            }
+        }
+    }
+
+    /// Flag a loop whose body holds another loop and never yields
+    fn check_loop(&mut self, span: proc_macro2::Span, body: &syn::Block) {
+        if !self.in_async {
+            return;
+        }
+        let finder = find_in(|f| f.visit_block(body));
+        if finder.has_loop && finder.await_line.is_none() {
+            self.record(
+                HazardKind::BusyLoop,
+                span_of(span),
+                "nested loops without a yield point".to_string(),
+                "add tokio::task::yield_now().await between iterations, or move the work to spawn_blocking"
+                    .to_string(),
+            );
+        }
+    }
+}
+
+/// `drop(name);`
+fn is_drop_of(stmt: &syn::Stmt, name: &str) -> bool {
+    let syn::Stmt::Expr(syn::Expr::Call(call), _) = stmt else {
+        return false;
+    };
+    let is_drop = matches!(&*call.func, syn::Expr::Path(p) if p.path.is_ident("drop"));
+    is_drop
+        && call
+            .args
+            .first()
+            .is_some_and(|arg| matches!(arg, syn::Expr::Path(p) if p.path.is_ident(name)))
+}
+
+impl<'ast> Visit<'ast> for HazardVisitor<'_> {
+    fn visit_item_mod(&mut self, item: &'ast syn::ItemMod) {
+        if !is_cfg_test(&item.attrs) {
+            visit::visit_item_mod(self, item);
+        }
+    }
+
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        if is_test_fn(&item.attrs) {
+            return;
+        }
+        let is_async = item.sig.asyncness.is_some();
+        self.function(item.sig.ident.to_string(), is_async, |v| {
+            visit::visit_item_fn(v, item)
+        });
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        if is_cfg_test(&item.attrs) {
+            return;
+        }
+        let self_ty = &item.self_ty;
+        let self_type = quote::quote!(#self_ty).to_string().replace(' ', "");
+        let previous = self.impl_type.replace(self_type);
+        visit::visit_item_impl(self, item);
+        self.impl_type = previous;
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        if is_test_fn(&item.attrs) {
+            return;
+        }
+        let name = match &self.impl_type {
+            Some(ty) => format!("{}::{}", ty, item.sig.ident),
+            None => item.sig.ident.to_string(),
+        };
+        let is_async = item.sig.asyncness.is_some();
+        self.function(name, is_async, |v| visit::visit_impl_item_fn(v, item));
+    }
+
+    fn visit_item_trait(&mut self, item: &'ast syn::ItemTrait) {
+        self.context.push(item.ident.to_string());
+        visit::visit_item_trait(self, item);
+        self.context.pop();
+    }
+
+    fn visit_trait_item_fn(&mut self, item: &'ast syn::TraitItemFn) {
+        let name = match self.context.last() {
+            Some(trait_name) => format!("{}::{}", trait_name, item.sig.ident),
+            None => item.sig.ident.to_string(),
+        };
+        let is_async = item.sig.asyncness.is_some();
+        self.function(name, is_async, |v| visit::visit_trait_item_fn(v, item));
+    }
+
+    fn visit_expr_async(&mut self, expr: &'ast syn::ExprAsync) {
+        self.with_async(true, |v| visit::visit_expr_async(v, expr));
+    }
+
+    fn visit_expr_closure(&mut self, expr: &'ast syn::ExprClosure) {
+        if expr.asyncness.is_some() {
+            self.with_async(true, |v| visit::visit_expr_closure(v, expr));
+        } else {
+            visit::visit_expr_closure(self, expr);
+        }
+    }
+
+    fn visit_block(&mut self, block: &'ast syn::Block) {
+        if self.in_async {
+            self.check_guards(block);
+        }
+        visit::visit_block(self, block);
+    }
+
+    fn visit_expr_await(&mut self, expr: &'ast syn::ExprAwait) {
+        // An awaited call is async; only its receiver and arguments can block
+        match &*expr.base {
+            syn::Expr::Call(call) => self.visit_call(call, true),
+            syn::Expr::MethodCall(call) => {
+                self.visit_expr(&call.receiver);
+                for arg in &call.args {
+                    self.visit_expr(arg);
+                }
+            }
+            base => self.visit_expr(base),
+        }
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        self.visit_call(call, false);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        if self.in_async {
+            self.call(call.method.to_string(), span_of(call.span()));
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_expr_loop(&mut self, expr: &'ast syn::ExprLoop) {
+        self.check_loop(expr.loop_token.span, &expr.body);
+        visit::visit_expr_loop(self, expr);
+    }
+
+    fn visit_expr_while(&mut self, expr: &'ast syn::ExprWhile) {
+        self.check_loop(expr.while_token.span, &expr.body);
+        visit::visit_expr_while(self, expr);
+    }
+
+    fn visit_expr_for_loop(&mut self, expr: &'ast syn::ExprForLoop) {
+        self.check_loop(expr.for_token.span, &expr.body);
+        visit::visit_expr_for_loop(self, expr);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        visit::visit_macro(self, mac);
+        if let Ok(args) = mac.parse_body_with(
+            syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated,
+        ) {
+            for arg in &args {
+                self.visit_expr(arg);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_async_hazards() {
+        let source = r#"
+use std::fs;
+use std::sync::Mutex;
+
+pub async fn handle(state: &Mutex<Vec<u32>>, client: &Client) -> usize {
+    let text = fs::read_to_string("config.toml").unwrap();
+    let guard = state.lock().unwrap();
+    client.send(guard.len()).await;
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    let rows = tokio::task::spawn_blocking(|| std::fs::read("data.bin")).await;
+    reindex(&text);
+    for row in rows {
+        for cell in row {
+            checksum(cell);
+        }
+    }
+    text.len()
+}
+
+pub async fn fine(state: &Mutex<u32>) {
+    {
+        let guard = state.lock().unwrap();
+        drop(guard);
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+}
+
+fn reindex(text: &str) {
+    std::fs::write("index", text).unwrap();
+}
+"#;
+        let aliases = use_aliases(&["std::fs", "std::sync::Mutex"]);
+        let scan = find_in_source(source, Path::new("src/server.rs"), &aliases).unwrap();
+
+        let found: Vec<_> = scan
+            .hazards
+            .iter()
+            .map(|h| (h.kind, h.span.start_line, h.detail.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (HazardKind::BlockingIo, 6, "std::fs::read_to_string"),
+                (
+                    HazardKind::LockAcrossAwait,
+                    7,
+                    "`guard` from .lock() is held across .await (line 8)"
+                ),
+                (HazardKind::Sleep, 9, "std::thread::sleep"),
+                (
+                    HazardKind::BusyLoop,
+                    12,
+                    "nested loops without a yield point"
+                ),
+            ]
+        );
+        assert_eq!(
+            scan.hazards[0].suggestion,
+            "tokio::fs::read_to_string (or tokio::task::spawn_blocking)"
+        );
+        assert!(
+            scan.calls
+                .iter()
+                .any(|c| c.name == "reindex" && c.span.start_line == 11)
+        );
+        assert!(!scan.calls.iter().any(|c| c.name == "send"));
+        assert_eq!(
+            scan.blocking_in_sync,
+            vec![
+                ("handle".to_string(), "std::fs::read".to_string(), 10),
+                ("reindex".to_string(), "std::fs::write".to_string(), 29),
+            ]
+        );
+    }
+}
@@ -0,0 +1,440 @@
+//! Git History - churn, recency, authorship and bug-fix signals
+//!
+//! Reads the local repository through the `git` CLI (no network access).
+//! Every commit is attributed to the lines it touched in the *current*
+//! version of each file: walking the log from newest to oldest, line
+//! positions are mapped back through each diff the same way `git blame`
+//! follows lines, so an item's span can be matched against its history.
+
+use crate::types::*;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::LazyLock;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum HistoryError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("git error: {0}")]
+    Git(String),
+}
+
+/// Metadata of a single commit
+#[derive(Debug, Clone)]
+struct CommitInfo {
+    author: String,
+    timestamp: i64,
+    is_fix: bool,
+}
+
+/// A commit's touched lines in one file, in current-file line numbers
+#[derive(Debug, Clone)]
+struct LineTouch {
+    commit: usize,
+    /// Inclusive (start, end) line ranges
+    ranges: Vec<(usize, usize)>,
+}
+
+/// One hunk header: `@@ -old_start,old_len +new_start,new_len @@`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Hunk {
+    old_start: usize,
+    old_len: usize,
+    new_start: usize,
+    new_len: usize,
+}
+
+/// Hunks of one commit, grouped by the file path they apply to
+#[derive(Debug, Default)]
+struct CommitDiff {
+    /// Author, timestamp and subject
+    header: Option<(String, i64, String)>,
+    files: HashMap<PathBuf, Vec<Hunk>>,
+}
+
+/// Line-level git history for the files of a project
+#[derive(Debug, Clone, Default)]
+pub struct GitHistory {
+    commits: Vec<CommitInfo>,
+    /// Keyed by the file paths passed to `load`
+    files: HashMap<PathBuf, Vec<LineTouch>>,
+    /// Unix timestamp used as "now" for recency
+    now: i64,
+}
+
+/// Commit subjects that indicate a bug fix
+const FIX_PATTERN: &str = r"(?i)\b(fix|fixes|fixed|fixing|bug|bugfix|hotfix|regression)\b";
+
+/// Hunk headers of a zero-context diff, compiled once for all commits
+static HUNK_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^@@ -(\d+)(?:,(\d+))? \+(\d+)(?:,(\d+))? @@").expect("Invalid regex")
+});
+
+/// Record separator placed before every commit header in `git log` output
+const RECORD_SEP: char = '\u{1e}';
+/// Field separator within a commit header
+const FIELD_SEP: char = '\u{1f}';
+
+impl GitHistory {
+    /// Load history for the given files of the project rooted at `project_root`
+    pub fn load(project_root: &Path, files: &[PathBuf]) -> Result<Self, HistoryError> {
+        let repo_root =
+            PathBuf::from(Self::git(project_root, &["rev-parse", "--show-toplevel"])?.trim());
+        let repo_root = repo_root.canonicalize()?;
+
+        let log = Self::git(
+            project_root,
+            &[
+                "log",
+                "--no-merges",
+                "--no-color",
+                "--no-ext-diff",
+                "-U0",
+                "-p",
+                "--format=%x1e%H%x1f%an%x1f%at%x1f%s",
+                "--",
+                ".",
+            ],
+        )?;
+        // Uncommitted edits shift lines relative to HEAD
+        let worktree = Self::git(
+            project_root,
+            &[
+                "diff",
+                "HEAD",
+                "--no-color",
+                "--no-ext-diff",
+                "-U0",
+                "--",
+                ".",
+            ],
+        )
+        .unwrap_or_default();
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+
+        // Current line count of each file, keyed by repo-relative path
+        let mut relative_paths = HashMap::new();
+        let mut line_counts = HashMap::new();
+        for file in files {
+            let Ok(canonical) = file.canonicalize() else {
+                continue;
+            };
+            let Ok(relative) = canonical.strip_prefix(&repo_root) else {
+                continue;
+            };
+            let content = std::fs::read_to_string(file).unwrap_or_default();
+            line_counts.insert(relative.to_path_buf(), content.lines().count());
+            relative_paths.insert(relative.to_path_buf(), file.clone());
+        }
+
+        let mut history = Self::from_diffs(&worktree, &log, &line_counts, now);
+
+        // Re-key from repo-relative paths to the caller's paths
+        history.files = history
+            .files
+            .into_iter()
+            .filter_map(|(relative, touches)| {
+                relative_paths
+                    .get(&relative)
+                    .map(|file| (file.clone(), touches))
+            })
+            .collect();
+
+        Ok(history)
+    }
+
+    /// Run a git command in `dir` and return its stdout
+    fn git(dir: &Path, args: &[&str]) -> Result<String, HistoryError> {
+        let output = Command::new("git").current_dir(dir).args(args).output()?;
+        if !output.status.success() {
+            return Err(HistoryError::Git(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Build history from a working-tree diff and a newest-first `git log -p -U0`,
+    /// for the files in `line_counts` (repo-relative path -> current line count)
+    fn from_diffs(
+        worktree: &str,
+        log: &str,
+        line_counts: &HashMap<PathBuf, usize>,
+        now: i64,
+    ) -> Self {
+        let fix_pattern = Regex::new(FIX_PATTERN).expect("Invalid regex");
+        let mut history = Self {
+            now,
+            ..Default::default()
+        };
+
+        // Current line -> line in the image being examined (None once the line
+        // was introduced by a newer commit than the one being examined)
+        let mut positions: HashMap<PathBuf, Vec<Option<usize>>> = line_counts
+            .iter()
+            .map(|(path, count)| (path.clone(), (0..=*count).map(Some).collect()))
+            .collect();
+
+        // Start from the working tree, mapped back to HEAD
+        for (path, hunks) in Self::parse_diff(worktree).files {
+            if let Some(lines) = positions.get_mut(&path) {
+                Self::map_back(lines, &hunks);
+            }
+        }
+
+        for commit in Self::split_log(log) {
+            let Some((author, timestamp, subject)) = commit.header else {
+                continue;
+            };
+            let commit_index = history.commits.len();
+            history.commits.push(CommitInfo {
+                author,
+                timestamp,
+                is_fix: fix_pattern.is_match(&subject),
+            });
+
+            for (path, hunks) in commit.files {
+                let Some(lines) = positions.get_mut(&path) else {
+                    continue;
+                };
+
+                let ranges = Self::touched_ranges(lines, &hunks);
+                if !ranges.is_empty() {
+                    history
+                        .files
+                        .entry(path.clone())
+                        .or_default()
+                        .push(LineTouch {
+                            commit: commit_index,
+                            ranges,
+                        });
+                }
+
+                Self::map_back(lines, &hunks);
+            }
+        }
+
+        history
+    }
+
+    /// Current lines whose position falls inside the post-image of `hunks`
+    fn touched_ranges(lines: &[Option<usize>], hunks: &[Hunk]) -> Vec<(usize, usize)> {
+        let mut touched: Vec<usize> = Vec::new();
+
+        for (current, pos) in lines.iter().enumerate().skip(1) {
+            let Some(pos) = *pos else {
+                continue;
+            };
+            let hit = hunks.iter().any(|h| {
+                if h.new_len == 0 {
+                    // Pure deletion: attribute it to the line right after the gap
+                    pos == h.new_start + 1
+                } else {
+                    pos >= h.new_start && pos < h.new_start + h.new_len
+                }
+            });
+            if hit {
+                touched.push(current);
+            }
+        }
+
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        for line in touched {
+            match ranges.last_mut() {
+                Some((_, end)) if *end + 1 == line => *end = line,
+                _ => ranges.push((line, line)),
+            }
+        }
+        ranges
+    }
+
+    /// Map positions from a diff's post-image back to its pre-image
+    fn map_back(lines: &mut [Option<usize>], hunks: &[Hunk]) {
+        for slot in lines.iter_mut() {
+            let Some(pos) = *slot else {
+                continue;
+            };
+
+            let mut shift: isize = 0;
+            let mut born_here = false;
+            for h in hunks {
+                let new_end = h.new_start + h.new_len; // exclusive
+                if h.new_len > 0 && pos >= h.new_start && pos < new_end {
+                    born_here = true;
+                    break;
+                }
+                let after = if h.new_len == 0 {
+                    pos > h.new_start
+                } else {
+                    pos >= new_end
+                };
+                if after {
+                    shift += h.old_len as isize - h.new_len as isize;
+                }
+            }
+
+            *slot = if born_here {
+                None
+            } else {
+                Some((pos as isize + shift).max(0) as usize)
+            };
+        }
+    }
+
+    /// Split `git log` output into per-commit diffs (newest first)
+    fn split_log(log: &str) -> Vec<CommitDiff> {
+        log.split(RECORD_SEP)
+            .filter(|record| !record.trim().is_empty())
+            .map(|record| {
+                let (header, body) = record.split_once('\n').unwrap_or((record, ""));
+                let mut diff = Self::parse_diff(body);
+
+                let fields: Vec<&str> = header.split(FIELD_SEP).collect();
+                if let [_hash, author, timestamp, subject] = fields[..] {
+                    diff.header = Some((
+                        author.to_string(),
+                        timestamp.trim().parse().unwrap_or(0),
+                        subject.to_string(),
+                    ));
+                }
+                diff
+            })
+            .collect()
+    }
+
+    /// Parse unified diff text with zero context lines
+    fn parse_diff(text: &str) -> CommitDiff {
+        let mut diff = CommitDiff::default();
+        let mut current: Option<PathBuf> = None;
+        // Body lines left in the current hunk (so content such as "+++" isn't
+        // mistaken for a file header)
+        let mut remaining = 0usize;
+
+        for line in text.lines() {
+            if remaining > 0 {
+                if line.starts_with('+') || line.starts_with('-') {
+                    remaining -= 1;
+                }
+                continue;
+            }
+
+            if line.starts_with("diff --git ") {
+                current = None;
+            } else if let Some(path) = line.strip_prefix("+++ ") {
+                current = path.strip_prefix("b/").map(PathBuf::from);
+            } else if let Some(caps) = HUNK_PATTERN.captures(line) {
+                let num = |i: usize, default: usize| {
+                    caps.get(i)
+                        .and_then(|m| m.as_str().parse().ok())
+                        .unwrap_or(default)
+                };
+                let hunk = Hunk {
+                    old_start: num(1, 0),
+                    old_len: num(2, 1),
+                    new_start: num(3, 0),
+                    new_len: num(4, 1),
+                };
+                remaining = hunk.old_len + hunk.new_len;
+                if let Some(path) = &current {
+                    diff.files.entry(path.clone()).or_default().push(hunk);
+                }
+            }
+        }
+
+        diff
+    }
+
+    /// History signals for the lines covered by `span` in `file`
+    pub fn stats_for(&self, file: &Path, span: &Span) -> Option<HistoryFactors> {
+        let touches = self.files.get(file)?;
+        let (start, end) = (span.start_line, span.end_line.max(span.start_line));
+
+        let commits: Vec<&CommitInfo> = touches
+            .iter()
+            .filter(|t| t.ranges.iter().any(|(s, e)| *s <= end && *e >= start))
+            .map(|t| &self.commits[t.commit])
+            .collect();
+
+        if commits.is_empty() {
+            return None;
+        }
+
+        let authors: HashSet<&str> = commits.iter().map(|c| c.author.as_str()).collect();
+        let last_change = commits.iter().map(|c| c.timestamp).max().unwrap_or(0);
+
+        Some(HistoryFactors {
+            churn: commits.len(),
+            days_since_change: ((self.now - last_change).max(0) as f64) / 86_400.0,
+            author_count: authors.len(),
+            bug_fix_count: commits.iter().filter(|c| c.is_fix).count(),
+        })
+    }
+
+    /// Number of commits loaded
+    pub fn commit_count(&self) -> usize {
+        self.commits.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(hash: &str, author: &str, ts: i64, subject: &str, body: &str) -> String {
+        format!("\u{1e}{hash}\u{1f}{author}\u{1f}{ts}\u{1f}{subject}\n{body}")
+    }
+
+    #[test]
+    fn test_lines_follow_history() {
+        // Newest first: "fix" inserted two lines at the top of a 3-line file
+        // created by "init"
+        let log = [
+            record(
+                "b",
+                "bob",
+                200,
+                "Fix off-by-one",
+                "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -0,0 +1,2 @@\n+fn a() {}\n++++ tricky\n",
+            ),
+            record(
+                "a",
+                "alice",
+                100,
+                "init",
+                "diff --git a/src/lib.rs b/src/lib.rs\n--- /dev/null\n+++ b/src/lib.rs\n@@ -0,0 +1,3 @@\n+x\n+y\n+z\n",
+            ),
+        ]
+        .concat();
+
+        let file = PathBuf::from("src/lib.rs");
+        let line_counts = HashMap::from([(file.clone(), 5)]);
+        let history = GitHistory::from_diffs("", &log, &line_counts, 200 + 86_400);
+        let span = |start_line, end_line| Span {
+            start_line,
+            end_line,
+            ..Default::default()
+        };
+
+        let top = history.stats_for(&file, &span(1, 2)).unwrap();
+        assert_eq!(top.churn, 1);
+        assert_eq!(top.bug_fix_count, 1);
+        assert_eq!(top.days_since_change, 1.0);
+
+        // Lines 3-5 are the original lines, shifted down by the fix
+        let original = history.stats_for(&file, &span(3, 5)).unwrap();
+        assert_eq!(original.churn, 1);
+        assert_eq!(original.author_count, 1);
+        assert_eq!(original.bug_fix_count, 0);
+
+        let all = history.stats_for(&file, &span(1, 5)).unwrap();
+        assert_eq!(all.churn, 2);
+        assert_eq!(all.author_count, 2);
+    }
+}
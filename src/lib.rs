@@ -4,16 +4,67 @@
 //! - Partial parsing (LSP-Lite) that handles broken code gracefully
 //! - Dependency bridge mapping Cargo.lock to registry sources
 //! - Semantic gravity ranking for intelligent result ordering
+//! - Fuzzy, abbreviation-aware search (`SemGrav` finds `SemanticGravity`)
+//! - Natural-language search over a cached item embedding index
+//! - Structured queries (`kind:fn vis:pub returns:Result load`)
+//! - Configurable scoring weights and named ranking profiles
+//! - Optional git history signals (churn, recency, authors, bug fixes)
+//! - Coverage-aware ranking from lcov / llvm-cov reports (untested hotspots)
+//! - Async hazards (blocking calls, locks held across `.await`, busy loops)
+//! - Panic site inventory (unwraps, panicking macros, indexing, division) ranked by gravity
+//! - Public API surface under canonical paths, with signatures and trait impls
+//! - Unsafe code audit (unsafe fns, impls and blocks, their operations, missing `SAFETY:` comments)
+//! - Per-function complexity metrics (cyclomatic, cognitive, nesting, length)
+//! - Cargo workspace support (per-member analysis, cross-crate edges)
+//! - Every build target as an entry point (lib, bins, examples, benches, tests)
 //! - Call-site teleportation (local usage mapping for external symbols)
+//! - Re-export resolution (`tokio::task::spawn` and `tokio::spawn` are one item)
+//! - Type usage index (fields, signatures, impl headers, bounds and bodies)
+//! - Struct field access index (reads, writes, constructions, destructurings)
+//! - Static test-to-code map (which tests reach a function, which none do)
+//! - Error propagation map (`?` conversions, `#[from]` variants, reachable errors)
+//! - Trait implementation matrix (manual impls, derives and blanket impls)
+//! - Type relationship graph (fields, variants, signatures, impls) for related-item context
+//! - Semantic diffs between snapshots or git revisions (items, impls, call edges)
+//! - Semver checks classifying public API changes as major, minor or patch
+//! - Architecture layering rules checked in CI (text or SARIF output)
+//! - Versioned analysis snapshots (JSON or binary) for other tools to consume
+//! - Watch mode that re-analyzes only changed files, in place
 //! - MCP server for LLM tool integration
 
+pub mod api;
+pub mod config;
+pub mod coverage;
 pub mod dependency;
+pub mod diff;
 pub mod embedding;
+pub mod errorflow;
+pub mod fields;
+pub mod fuzzy;
 pub mod gravity;
+pub mod hazards;
+pub mod history;
 pub mod mcp;
+pub mod metrics;
+pub mod panics;
 pub mod parser;
+pub mod query;
+pub mod resolve;
+pub mod rules;
+pub mod semantic;
+pub mod semver;
+pub mod snapshot;
+pub mod targets;
+pub mod testmap;
+pub mod traits;
+pub mod typegraph;
 pub mod types;
+pub mod unsafety;
+pub mod usage;
+pub mod watch;
+pub mod workspace;
 
+pub use config::ProjectConfig;
 pub use dependency::DependencyBridge;
 pub use gravity::SemanticGravity;
 pub use parser::PartialParser;
@@ -4,12 +4,50 @@
 //! - Resilient partial parsing (handles broken code)
 //! - Dependency bridge to cargo registry sources
 //! - Semantic gravity ranking for intelligent search
-//! - Call-site teleportation (local usage of external symbols)
+//! - Fuzzy, abbreviation-aware name matching
+//! - Natural-language search via item embeddings (`search --semantic`)
+//! - Structured search filters (`search kind:fn vis:pub returns:Result`)
+//! - Cargo workspace members and cross-crate references (`workspace`)
+//! - Build targets as entry points (`targets`, `--entry lib,bin,example`)
+//! - Call-site teleportation (local usage of external symbols, through re-exports)
+//! - Type usage lookup (`usages <Type>`)
+//! - Field reads, writes and constructions (`fields <Struct> --field count`)
+//! - Trait implementation matrix (`traits [Trait|Type]`)
+//! - Error propagation graph and errors reaching a function (`errors [fn] --dot`)
+//! - Tests reaching a function, and functions no test reaches (`tests [fn]`)
+//! - Coverage-aware ranking and untested hotspots (`--coverage lcov.info untested`)
+//! - Blocking work in async code, with async alternatives (`hazards`)
+//! - Panic sites in non-test code, ranked by gravity (`panics --kind unwrap`)
+//! - Unsafe code by module, closest to the public API first (`unsafe --undocumented`)
+//! - Complexity metrics per function and module (`metrics`)
+//! - Structural diffs between git revisions or snapshots (`diff main HEAD`)
+//! - Semver checks against the version bump (`semver v0.3.0`)
+//! - The library's public API with full signatures (`api --format json`)
+//! - Architecture layering rules from cargomap.toml (`check --format sarif`)
+//! - Versioned JSON or binary analysis snapshots (`snapshot analysis.json`),
+//!   readable by `api`, `tests`, `traits` and `usages` (`--snapshot analysis.json`)
+//! - Watch mode re-analyzing changed files in place (`watch [query]`)
 //! - MCP server for LLM tool integration
 
-use cargomap::{DependencyBridge, SemanticGravity};
+use cargomap::api::ApiSurface;
+use cargomap::fields::FieldAccessKind;
+use cargomap::gravity::FileChange;
+use cargomap::hazards::HazardKind;
+use cargomap::panics::PanicKind;
+use cargomap::query::SearchQuery;
+use cargomap::snapshot::SnapshotFormat;
+use cargomap::targets::TargetKind;
+use cargomap::testmap::TestMap;
+use cargomap::traits::{ImplSource, TraitMatrix};
+use cargomap::unsafety::UnsafeKind;
+use cargomap::watch::{self, ProjectWatcher};
+use cargomap::{
+    DependencyBridge, ItemKind, ModuleNode, ProjectAnalysis, ProjectConfig, SemanticGravity,
+    TypeUsage, TypeUsageKind, diff, fuzzy, rules, semver,
+};
 use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(name = "cargomap")]
@@ -23,6 +61,28 @@ struct Cli {
     #[arg(short, long)]
     quiet: bool,
 
+    /// Ranking profile from cargomap.toml or built-in (onboarding, refactor, tests)
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Include local git history (churn, recency, authors, bug fixes) in scores
+    #[arg(long, global = true)]
+    git_history: bool,
+
+    /// Target kinds that count as entry points for entry distance
+    /// (comma-separated: lib, bin, example, bench, test)
+    #[arg(long, global = true, value_delimiter = ',', default_value = "lib,bin")]
+    entry: Vec<String>,
+
+    /// lcov or llvm-cov JSON coverage report to attach to scores
+    #[arg(long, global = true)]
+    coverage: Option<PathBuf>,
+
+    /// Read the analysis from a snapshot file instead of the sources
+    /// (api, tests, traits and usages)
+    #[arg(long, global = true)]
+    snapshot: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -42,15 +102,26 @@ enum Commands {
 
     /// Search for items by name
     Search {
-        /// Search query
-        query: String,
+        /// Search query: fuzzy-matched text plus optional filters
+        /// (kind:, vis:, async:, returns:, module:, impl:, attr:, doc:; prefix with - to negate)
+        #[arg(required = true, num_args = 1..)]
+        query: Vec<String>,
 
         /// Maximum number of results
         #[arg(short, long, default_value = "10")]
         limit: usize,
+
+        /// Show the weighted score breakdown for each result
+        #[arg(long)]
+        explain: bool,
+
+        /// Treat the query as natural language and rank by embedding similarity
+        #[arg(long)]
+        semantic: bool,
     },
 
-    /// Resolve an external crate path and show local usages
+    /// Resolve an external crate path through re-exports and show local
+    /// usages under any path to the same item
     Resolve {
         /// External path to resolve (e.g., tokio::spawn)
         path: String,
@@ -60,6 +131,211 @@ enum Commands {
         limit: usize,
     },
 
+    /// Show where a local type is used (fields, signatures, impls, bounds, bodies)
+    Usages {
+        /// Type name (e.g., ParsedItem)
+        type_name: String,
+
+        /// Maximum number of usages to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Show where a struct's fields are read, written, constructed and
+    /// destructured
+    Fields {
+        /// Struct name (e.g., ParsedFile)
+        struct_name: String,
+
+        /// Only show one field (tuple struct fields are 0, 1, ...)
+        #[arg(short, long)]
+        field: Option<String>,
+
+        /// Only show one kind (read, write, construct, destructure)
+        #[arg(short, long)]
+        kind: Option<String>,
+
+        /// Maximum number of accesses to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Show the trait implementation matrix (impls, derives and blanket impls)
+    Traits {
+        /// Trait or type name; shows implementors of a trait and/or traits of a type
+        name: Option<String>,
+    },
+
+    /// Show how errors propagate, or which errors can reach a function
+    Errors {
+        /// Function to trace (omit for the whole error-flow graph)
+        function: Option<String>,
+
+        /// Print the error-flow graph in Graphviz DOT format
+        #[arg(long)]
+        dot: bool,
+
+        /// Maximum number of entries to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Show the tests that call a function, or functions no test calls
+    Tests {
+        /// Function to find tests for (omit to list functions without tests)
+        function: Option<String>,
+
+        /// Maximum number of entries to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Show high-gravity functions with low test coverage (requires --coverage)
+    Untested {
+        /// Maximum number of functions to show
+        #[arg(short, long, default_value = "10")]
+        limit: usize,
+
+        /// Only show functions at or below this line coverage percentage
+        #[arg(long, default_value = "50")]
+        max_coverage: f64,
+    },
+
+    /// Show blocking work in async code: blocking calls, locks held across
+    /// .await and loops without yield points
+    Hazards {
+        /// Only show one kind (blocking-io, sleep, blocking-call,
+        /// lock-across-await, borrow-across-await, busy-loop)
+        #[arg(short, long)]
+        kind: Option<String>,
+
+        /// Maximum number of findings to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Show where non-test code can panic, highest-gravity items first
+    Panics {
+        /// Only show one kind (unwrap, expect, panic, unreachable, todo,
+        /// unimplemented, assert, index, division)
+        #[arg(short, long)]
+        kind: Option<String>,
+
+        /// Maximum number of sites to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Audit unsafe code: unsafe fns, impls and blocks with the operations
+    /// they perform, by module, closest to the public API first
+    Unsafe {
+        /// Only show one kind (fn, impl, trait, block)
+        #[arg(short, long)]
+        kind: Option<String>,
+
+        /// Only show sites without a SAFETY comment
+        #[arg(short, long)]
+        undocumented: bool,
+
+        /// Maximum number of sites to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Show complexity metrics per module and the most complex functions
+    Metrics {
+        /// Only include modules whose path contains this text
+        #[arg(short, long)]
+        module: Option<String>,
+
+        /// Maximum number of functions to show
+        #[arg(short, long, default_value = "10")]
+        limit: usize,
+    },
+
+    /// Show build targets and the module tree of each
+    Targets,
+
+    /// Show workspace members and the references between them
+    Workspace {
+        /// Maximum number of cross-crate references to list per edge
+        #[arg(short, long, default_value = "3")]
+        limit: usize,
+    },
+
+    /// Structural diff between two git revisions or snapshot files
+    Diff {
+        /// Base revision (e.g. main, HEAD~3) or snapshot file
+        base: String,
+
+        /// Head revision or snapshot file (defaults to the working tree)
+        head: Option<String>,
+
+        /// Maximum number of entries to show per section
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+    },
+
+    /// Check public API changes against the version bump (exits 1 if breaking)
+    Semver {
+        /// Base revision or snapshot file (the last release)
+        base: String,
+
+        /// Head revision or snapshot file (defaults to the working tree)
+        head: Option<String>,
+
+        /// Version of the base (default: Cargo.toml at the base revision)
+        #[arg(long)]
+        from_version: Option<String>,
+
+        /// Version of the head (default: Cargo.toml at the head)
+        #[arg(long)]
+        to_version: Option<String>,
+
+        /// Maximum number of changes to show
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+    },
+
+    /// Print the library's public API: every public item under its
+    /// canonical path, with full signatures and trait impls
+    Api {
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Check the layering rules in cargomap.toml (exits 1 on violations)
+    Check {
+        /// Output format (text, sarif)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+
+        /// Write the report to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Save the full analysis as a versioned snapshot for other tools
+    Snapshot {
+        /// File to write (`.json` is written as JSON, anything else as binary)
+        output: PathBuf,
+
+        /// Encoding to use instead of the one implied by the file name (json, binary)
+        #[arg(short, long)]
+        format: Option<String>,
+    },
+
+    /// Watch the project and update the analysis in place as files change
+    Watch {
+        /// Search to re-run after every update
+        query: Vec<String>,
+
+        /// Maximum number of search results to show
+        #[arg(short, long, default_value = "10")]
+        limit: usize,
+    },
+
     /// List all dependencies
     Deps {
         /// Maximum number of dependencies to show
@@ -100,8 +376,72 @@ fn main() {
         return;
     }
 
+    // Commands that need only the stored analysis can run from a snapshot
+    if let Some(path) = &cli.snapshot {
+        let analysis = match ProjectAnalysis::load(path) {
+            Ok(analysis) => analysis,
+            Err(e) => {
+                eprintln!("Error loading snapshot {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        };
+        match cli.command {
+            Some(Commands::Usages { type_name, limit }) => {
+                cmd_usages(&analysis.find_type_usages(&type_name), &type_name, limit);
+            }
+            Some(Commands::Traits { name }) => {
+                cmd_traits(&analysis.trait_matrix(), name.as_deref());
+            }
+            Some(Commands::Tests { function, limit }) => {
+                cmd_tests(&analysis.test_map(), function.as_deref(), limit);
+            }
+            Some(Commands::Api { format }) => {
+                cmd_api(&analysis.api_surface(), &format);
+            }
+            _ => {
+                eprintln!(
+                    "Error: --snapshot works with api, tests, traits and usages; other commands analyze the sources"
+                );
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // Resolve scoring weights from cargomap.toml and the selected profile
+    let config = match ProjectConfig::load(&project_root) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error loading configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let weights = match config.weights_for(cli.profile.as_deref()) {
+        Ok(weights) => weights,
+        Err(e) => {
+            eprintln!("Error loading configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut entry_kinds = Vec::new();
+    for name in &cli.entry {
+        match TargetKind::parse(name) {
+            Some(kind) => entry_kinds.push(kind),
+            None => {
+                eprintln!(
+                    "Error: unknown target kind '{}' (expected lib, bin, example, bench or test)",
+                    name
+                );
+                std::process::exit(1);
+            }
+        }
+    }
+
     // Initialize components for non-MCP commands
-    let mut gravity = SemanticGravity::new();
+    let mut gravity = SemanticGravity::new()
+        .with_weights(weights)
+        .with_entry_targets(entry_kinds);
     let mut dep_bridge = match DependencyBridge::new(&project_root) {
         Ok(bridge) => Some(bridge),
         Err(e) => {
@@ -122,15 +462,157 @@ fn main() {
         std::process::exit(1);
     }
 
+    if cli.git_history
+        && let Err(e) = gravity.load_git_history(&project_root)
+    {
+        eprintln!("Warning: Could not load git history: {}", e);
+    }
+
+    if let Some(report) = &cli.coverage
+        && let Err(e) = gravity.load_coverage(report, &project_root)
+    {
+        eprintln!("Warning: Could not load coverage report: {}", e);
+    }
+
     match cli.command {
         Some(Commands::Analyze { externals, limit }) => {
             cmd_analyze(&gravity, &mut dep_bridge, externals, limit, cli.quiet);
         }
-        Some(Commands::Search { query, limit }) => {
-            cmd_search(&gravity, &query, limit);
+        Some(Commands::Search {
+            query,
+            limit,
+            explain,
+            semantic,
+        }) => {
+            if semantic {
+                if !cli.quiet {
+                    println!("Updating semantic index...");
+                }
+                match gravity.load_semantic_index(&project_root) {
+                    Ok(embedded) if !cli.quiet && embedded > 0 => {
+                        println!("Embedded {} new or changed items\n", embedded);
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        eprintln!("Error building semantic index: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            cmd_search(&gravity, &query.join(" "), limit, explain, semantic);
         }
         Some(Commands::Resolve { path, limit }) => {
-            cmd_resolve(&gravity, &mut dep_bridge, &path, limit);
+            cmd_resolve(&mut gravity, &mut dep_bridge, &path, limit);
+        }
+        Some(Commands::Usages { type_name, limit }) => {
+            cmd_usages(&gravity.find_type_usages(&type_name), &type_name, limit);
+        }
+        Some(Commands::Fields {
+            struct_name,
+            field,
+            kind,
+            limit,
+        }) => {
+            cmd_fields(
+                &gravity,
+                &struct_name,
+                field.as_deref(),
+                kind.as_deref(),
+                limit,
+            );
+        }
+        Some(Commands::Traits { name }) => {
+            cmd_traits(gravity.trait_matrix(), name.as_deref());
+        }
+        Some(Commands::Errors {
+            function,
+            dot,
+            limit,
+        }) => {
+            cmd_errors(&gravity, function.as_deref(), dot, limit);
+        }
+        Some(Commands::Tests { function, limit }) => {
+            cmd_tests(gravity.test_map(), function.as_deref(), limit);
+        }
+        Some(Commands::Untested {
+            limit,
+            max_coverage,
+        }) => {
+            cmd_untested(&gravity, limit, max_coverage);
+        }
+        Some(Commands::Hazards { kind, limit }) => {
+            cmd_hazards(&gravity, kind.as_deref(), limit);
+        }
+        Some(Commands::Panics { kind, limit }) => {
+            cmd_panics(&gravity, kind.as_deref(), limit);
+        }
+        Some(Commands::Unsafe {
+            kind,
+            undocumented,
+            limit,
+        }) => {
+            cmd_unsafe(&gravity, kind.as_deref(), undocumented, limit);
+        }
+        Some(Commands::Metrics { module, limit }) => {
+            cmd_metrics(&gravity, module.as_deref(), limit);
+        }
+        Some(Commands::Targets) => {
+            cmd_targets(&gravity);
+        }
+        Some(Commands::Workspace { limit }) => {
+            cmd_workspace(&gravity, limit);
+        }
+        Some(Commands::Diff { base, head, limit }) => {
+            cmd_diff(&gravity, &project_root, &base, head.as_deref(), limit);
+        }
+        Some(Commands::Semver {
+            base,
+            head,
+            from_version,
+            to_version,
+            limit,
+        }) => {
+            let from_version =
+                from_version.or_else(|| semver::package_version(&project_root, Some(&base)));
+            let to_version =
+                to_version.or_else(|| semver::package_version(&project_root, head.as_deref()));
+            let compatible = cmd_semver(
+                &gravity,
+                &project_root,
+                (&base, from_version.as_deref()),
+                (head.as_deref(), to_version.as_deref()),
+                limit,
+            );
+            if !compatible {
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Api { format }) => {
+            cmd_api(&api_surface(&gravity, &project_root), &format);
+        }
+        Some(Commands::Check { format, output }) => {
+            let passed = cmd_check(
+                &gravity,
+                &config.rules,
+                &project_root,
+                &format,
+                output.as_deref(),
+            );
+            if !passed {
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Snapshot { output, format }) => {
+            cmd_snapshot(
+                &gravity,
+                &mut dep_bridge,
+                &project_root,
+                &output,
+                format.as_deref(),
+            );
+        }
+        Some(Commands::Watch { query, limit }) => {
+            cmd_watch(&mut gravity, &query.join(" "), limit);
         }
         Some(Commands::Deps { limit }) => {
             cmd_deps(&mut dep_bridge, limit);
@@ -195,25 +677,57 @@ fn cmd_analyze(
     }
 }
 
-fn cmd_search(gravity: &SemanticGravity, query: &str, limit: usize) {
+fn cmd_search(gravity: &SemanticGravity, query: &str, limit: usize, explain: bool, semantic: bool) {
     println!("=== Search Results for '{}' ===\n", query);
 
-    let results = gravity.search(query);
+    let query = match SearchQuery::parse(query) {
+        Ok(query) => query,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return;
+        }
+    };
+
+    let results = if semantic && !query.text.is_empty() {
+        match gravity.search_semantic(&query.text) {
+            Ok(mut results) => {
+                results.retain(|r| gravity.matches_query(&query, &r.item));
+                results
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return;
+            }
+        }
+    } else {
+        gravity.search_query(&query)
+    };
     if results.is_empty() {
         println!("No results found.");
         return;
     }
 
+    // Highlight matched characters when writing to a terminal
+    let (open, close) = if std::io::stdout().is_terminal() {
+        ("\x1b[1;33m", "\x1b[0m")
+    } else {
+        ("", "")
+    };
+
     for (i, result) in results.iter().take(limit).enumerate() {
         let test_marker = if result.factors.is_test {
             " [TEST]"
         } else {
             ""
         };
+        let name = match &result.matched {
+            Some(matched) => fuzzy::highlight(&result.item.name, &matched.ranges, open, close),
+            None => result.item.name.clone(),
+        };
         println!(
             "{}. {}{} (score: {:.1})",
             i + 1,
-            result.item.name,
+            name,
             test_marker,
             result.score
         );
@@ -264,21 +778,57 @@ fn cmd_search(gravity: &SemanticGravity, query: &str, limit: usize) {
             );
         }
 
-        // Siblings with shared generics
-        let siblings_with_shared: Vec<_> = result
-            .context
-            .siblings
-            .iter()
-            .filter(|s| !s.shared_generics.is_empty())
-            .collect();
-        if !siblings_with_shared.is_empty() {
-            println!("   Related (shared generics):");
-            for sib in siblings_with_shared.iter().take(3) {
+        if let Some(history) = &result.factors.history {
+            println!(
+                "   History: {} commits, {} authors, {} fixes, last change {:.0} days ago",
+                history.churn,
+                history.author_count,
+                history.bug_fix_count,
+                history.days_since_change
+            );
+        }
+
+        if let Some(coverage) = &result.factors.coverage {
+            println!(
+                "   Coverage: {:.0}% ({}/{} lines)",
+                coverage.percent, coverage.covered_lines, coverage.instrumented_lines
+            );
+        }
+
+        if explain {
+            if let Some(matched) = &result.matched {
+                println!("   Match: {:?} ({:.2})", matched.kind, matched.quality);
+            }
+            if let Some(similarity) = result.factors.semantic_similarity {
+                println!("   Similarity: {:.3}", similarity);
+            }
+            if let ItemKind::Function {
+                metrics: Some(m), ..
+            } = &result.item.kind
+            {
+                println!(
+                    "   Metrics: cyclomatic={}, cognitive={}, nesting={}, statements={}",
+                    m.cyclomatic, m.cognitive, m.max_nesting, m.statements
+                );
+            }
+            let parts: Vec<String> = result
+                .breakdown
+                .contributions()
+                .iter()
+                .map(|(name, value)| format!("{}={:+.1}", name, value))
+                .collect();
+            println!("   Breakdown: {}", parts.join(", "));
+        }
+
+        // Strongest neighbors in the type graph
+        if !result.context.related.is_empty() {
+            println!("   Related:");
+            for related in result.context.related.iter().take(3) {
                 println!(
-                    "     - {} {} (shares: {})",
-                    sib.kind,
-                    sib.name,
-                    sib.shared_generics.join(", ")
+                    "     - {} {} ({})",
+                    related.kind,
+                    related.name,
+                    related.relations.join(", ")
                 );
             }
         }
@@ -292,13 +842,24 @@ fn cmd_search(gravity: &SemanticGravity, query: &str, limit: usize) {
 }
 
 fn cmd_resolve(
-    gravity: &SemanticGravity,
+    gravity: &mut SemanticGravity,
     dep_bridge: &mut Option<DependencyBridge>,
     path: &str,
     limit: usize,
 ) {
     println!("=== Call-Site Teleportation for '{}' ===", path);
 
+    // Merge usages written through other paths to the same item
+    // (`tokio::task::spawn` and `tokio::spawn`)
+    let canonical = match dep_bridge.as_mut() {
+        Some(bridge) => gravity.canonicalize_usages_of(path, |p| bridge.canonical_path(p)),
+        None => path.to_string(),
+    };
+    if canonical != path {
+        println!("Canonical path: {}", canonical);
+    }
+    let path = canonical.as_str();
+
     // Show local usages (the "bridge")
     let local_usages = gravity.get_external_usages(path);
     if !local_usages.is_empty() {
@@ -354,6 +915,1067 @@ fn cmd_resolve(
     }
 }
 
+fn cmd_usages(usages: &[TypeUsage], type_name: &str, limit: usize) {
+    println!("=== Usages of type '{}' ===\n", type_name);
+
+    if usages.is_empty() {
+        println!("No usages found.");
+        return;
+    }
+
+    // Per-category totals
+    let mut counts: Vec<(TypeUsageKind, usize)> = Vec::new();
+    for usage in usages {
+        match counts.iter_mut().find(|(kind, _)| *kind == usage.kind) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((usage.kind, 1)),
+        }
+    }
+    counts.sort();
+    let summary: Vec<String> = counts
+        .iter()
+        .map(|(kind, count)| format!("{} {}", count, kind.label()))
+        .collect();
+    println!("{} usages: {}\n", usages.len(), summary.join(", "));
+
+    for (i, usage) in usages.iter().take(limit).enumerate() {
+        println!(
+            "{}. [{}] in {} at {}:{}",
+            i + 1,
+            usage.kind.label(),
+            usage.context,
+            usage.file.display(),
+            usage.line
+        );
+        if !usage.snippet.is_empty() {
+            println!("   {}", usage.snippet);
+        }
+    }
+
+    if usages.len() > limit {
+        println!("\n... and {} more", usages.len() - limit);
+    }
+}
+
+fn cmd_fields(
+    gravity: &SemanticGravity,
+    struct_name: &str,
+    field: Option<&str>,
+    kind: Option<&str>,
+    limit: usize,
+) {
+    match field {
+        Some(field) => println!("=== Accesses of '{}.{}' ===\n", struct_name, field),
+        None => println!("=== Field accesses of '{}' ===\n", struct_name),
+    }
+
+    let kind = match kind.map(FieldAccessKind::from_label) {
+        Some(None) => {
+            let kinds: Vec<_> = FieldAccessKind::ALL.iter().map(|k| k.label()).collect();
+            eprintln!("Error: unknown kind (expected one of {})", kinds.join(", "));
+            return;
+        }
+        Some(kind) => kind,
+        None => None,
+    };
+    let accesses: Vec<_> = gravity
+        .find_field_accesses(struct_name, field)
+        .into_iter()
+        .filter(|a| kind.is_none_or(|k| a.kind == k))
+        .collect();
+    if accesses.is_empty() {
+        println!("No field accesses found.");
+        return;
+    }
+
+    let counts: Vec<String> = FieldAccessKind::ALL
+        .iter()
+        .filter_map(|k| {
+            let count = accesses.iter().filter(|a| a.kind == *k).count();
+            (count > 0).then(|| format!("{} {}", count, k.label()))
+        })
+        .collect();
+    println!("{} accesses: {}\n", accesses.len(), counts.join(", "));
+
+    for (i, access) in accesses.iter().take(limit).enumerate() {
+        println!(
+            "{}. [{}] .{} in {} at {}:{}{}",
+            i + 1,
+            access.kind.label(),
+            access.field,
+            access.context,
+            access.file.display(),
+            access.line,
+            if access.inferred {
+                " (inferred receiver)"
+            } else {
+                ""
+            }
+        );
+        if !access.snippet.is_empty() {
+            println!("   {}", access.snippet);
+        }
+    }
+
+    if accesses.len() > limit {
+        println!("\n... and {} more", accesses.len() - limit);
+    }
+}
+
+fn cmd_traits(matrix: &TraitMatrix, name: Option<&str>) {
+    let Some(name) = name else {
+        println!("=== Trait Implementation Matrix ===\n");
+        for (type_name, impls) in matrix.by_type() {
+            let traits: Vec<String> = impls
+                .iter()
+                .map(|i| format!("{} ({})", i.trait_name, i.source.label()))
+                .collect();
+            println!("{}: {}", type_name, traits.join(", "));
+        }
+        return;
+    };
+
+    let implementors = matrix.implementors(name);
+    let traits = matrix.traits_of(name);
+    if implementors.is_empty() && traits.is_empty() {
+        println!("No trait implementations found for '{}'.", name);
+        return;
+    }
+
+    if !implementors.is_empty() {
+        println!("=== Implementors of '{}' ===\n", name);
+        for i in &implementors {
+            println!(
+                "  {} [{}] {}:{}",
+                i.self_type,
+                i.source.label(),
+                i.file.display(),
+                i.line
+            );
+        }
+        println!();
+    }
+
+    if !traits.is_empty() {
+        println!("=== Traits implemented by '{}' ===\n", name);
+        for i in &traits {
+            let via = match &i.source {
+                ImplSource::Blanket { bounds } if !bounds.is_empty() => {
+                    format!(" via {}", bounds.join(" + "))
+                }
+                _ => String::new(),
+            };
+            println!(
+                "  {} [{}{}] {}:{}",
+                i.trait_name,
+                i.source.label(),
+                via,
+                i.file.display(),
+                i.line
+            );
+        }
+    }
+}
+
+fn cmd_errors(gravity: &SemanticGravity, function: Option<&str>, dot: bool, limit: usize) {
+    let flow = gravity.error_flow();
+
+    if dot {
+        println!("digraph errors {{");
+        println!("  rankdir=LR;");
+        for (from, to, how) in flow.type_edges() {
+            println!("  \"{}\" -> \"{}\" [label=\"{}\"];", from, to, how);
+        }
+        println!("}}");
+        return;
+    }
+
+    if let Some(function) = function {
+        println!("=== Errors reaching {} ===\n", function);
+        let matches = flow.find(function);
+        if matches.is_empty() {
+            println!("No function named {} returns a Result.", function);
+            return;
+        }
+        for f in &matches {
+            println!(
+                "{} -> Result<_, {}> {}:{}",
+                f.key,
+                f.error_type,
+                f.file.display(),
+                f.line
+            );
+        }
+        let reached = flow.reachable(function);
+        if reached.is_empty() {
+            println!("\nNo error origins found.");
+            return;
+        }
+        println!();
+        for r in reached.iter().take(limit) {
+            let surfaced = r
+                .surfaced_as
+                .as_ref()
+                .map(|s| format!(" as {}", s))
+                .unwrap_or_default();
+            println!("  {}{}", r.error, surfaced);
+            println!(
+                "    via {} ({}:{})",
+                r.chain.join(" -> "),
+                r.file.display(),
+                r.line
+            );
+        }
+        if reached.len() > limit {
+            println!("  ... and {} more", reached.len() - limit);
+        }
+        return;
+    }
+
+    println!("=== Error Flow ===\n");
+    println!("Error types:");
+    for error_type in flow.error_types().values() {
+        let variants: Vec<String> = error_type
+            .variants
+            .iter()
+            .map(|v| match &v.from {
+                Some(from) => format!("{}(from {})", v.name, from),
+                None => v.name.clone(),
+            })
+            .collect();
+        println!(
+            "  {} {}:{}",
+            error_type.name,
+            error_type.file.display(),
+            error_type.line
+        );
+        if !variants.is_empty() {
+            println!("    {}", variants.join(", "));
+        }
+    }
+
+    let edges = flow.type_edges();
+    if !edges.is_empty() {
+        println!("\nFlows:");
+        for (from, to, how) in edges.iter().take(limit) {
+            println!("  {} -> {} [{}]", from, to, how);
+        }
+        if edges.len() > limit {
+            println!("  ... and {} more", edges.len() - limit);
+        }
+    }
+
+    let public: Vec<_> = flow.functions().iter().filter(|f| f.is_public).collect();
+    if !public.is_empty() {
+        println!("\nPublic functions:");
+        for f in public.iter().take(limit) {
+            let mut errors: Vec<String> = flow
+                .reachable(&f.key)
+                .into_iter()
+                .map(|r| r.surfaced_as.unwrap_or(r.error))
+                .collect();
+            errors.sort();
+            errors.dedup();
+            if errors.is_empty() {
+                errors.push("(no origins found)".to_string());
+            }
+            println!("  {} -> {}: {}", f.key, f.error_type, errors.join(", "));
+        }
+        if public.len() > limit {
+            println!("  ... and {} more", public.len() - limit);
+        }
+    }
+}
+
+fn cmd_tests(map: &TestMap, function: Option<&str>, limit: usize) {
+    if let Some(function) = function {
+        println!("=== Tests reaching {} ===\n", function);
+        let tests = map.tests_for(function);
+        if tests.is_empty() {
+            println!("No test calls {}, directly or indirectly.", function);
+            return;
+        }
+        for (test, depth) in tests.iter().take(limit) {
+            let via = if *depth == 1 {
+                "direct".to_string()
+            } else {
+                format!("{} calls away", depth)
+            };
+            println!(
+                "  {} [{}, {}] {}:{}",
+                test.name,
+                test.kind.label(),
+                via,
+                test.file.display(),
+                test.line
+            );
+        }
+        if tests.len() > limit {
+            println!("  ... and {} more", tests.len() - limit);
+        }
+        return;
+    }
+
+    println!("=== Functions Without Tests ===\n");
+    let (total, reached) = map.function_counts();
+    println!(
+        "{} tests reach {} of {} functions\n",
+        map.tests().len(),
+        reached,
+        total
+    );
+    let untested = map.untested();
+    for f in untested.iter().take(limit) {
+        println!(
+            "  {}{} {}:{}",
+            if f.is_public { "pub " } else { "" },
+            f.name,
+            f.file.display(),
+            f.line
+        );
+    }
+    if untested.len() > limit {
+        println!("  ... and {} more", untested.len() - limit);
+    }
+}
+
+fn cmd_untested(gravity: &SemanticGravity, limit: usize, max_coverage: f64) {
+    println!(
+        "=== Untested Hotspots (<= {:.0}% covered) ===\n",
+        max_coverage
+    );
+
+    let Some(coverage) = gravity.get_coverage() else {
+        eprintln!("Error: pass a coverage report with --coverage <lcov.info|coverage.json>");
+        return;
+    };
+    if coverage.file_count() == 0 {
+        println!("The coverage report doesn't match any project files.");
+        return;
+    }
+
+    let hotspots = gravity.get_untested_hotspots(limit, max_coverage);
+    if hotspots.is_empty() {
+        println!("No functions at or below {:.0}% coverage.", max_coverage);
+        return;
+    }
+
+    for (i, hotspot) in hotspots.iter().enumerate() {
+        let coverage = hotspot.factors.coverage.as_ref();
+        println!(
+            "{}. {} (score: {:.1}, coverage: {:.0}%)",
+            i + 1,
+            hotspot.item.name,
+            hotspot.score,
+            coverage.map_or(0.0, |c| c.percent)
+        );
+        println!("   Path: {}", hotspot.context.breadcrumbs);
+        println!(
+            "   File: {}:{}",
+            hotspot.item.file_path.display(),
+            hotspot.item.span.start_line
+        );
+        if let Some(coverage) = coverage {
+            println!(
+                "   Lines: {}/{} covered, x-mod={}, calls={}",
+                coverage.covered_lines,
+                coverage.instrumented_lines,
+                hotspot.factors.cross_module_count,
+                hotspot.factors.call_count
+            );
+        }
+    }
+}
+
+fn cmd_hazards(gravity: &SemanticGravity, kind: Option<&str>, limit: usize) {
+    println!("=== Async Hazards ===\n");
+
+    let kind = match kind.map(HazardKind::from_label) {
+        Some(None) => {
+            let kinds: Vec<_> = HazardKind::ALL.iter().map(|k| k.label()).collect();
+            eprintln!("Error: unknown kind (expected one of {})", kinds.join(", "));
+            return;
+        }
+        Some(kind) => kind,
+        None => None,
+    };
+    let hazards: Vec<_> = gravity
+        .get_async_hazards()
+        .into_iter()
+        .filter(|h| kind.is_none_or(|k| h.kind == k))
+        .collect();
+    if hazards.is_empty() {
+        println!("No blocking work found in async code.");
+        return;
+    }
+
+    for (i, hazard) in hazards.iter().take(limit).enumerate() {
+        println!(
+            "  {}. [{}] {} {}:{}:{}",
+            i + 1,
+            hazard.kind.label(),
+            hazard.context,
+            hazard.file.display(),
+            hazard.span.start_line,
+            hazard.span.start_col + 1
+        );
+        println!("      {}", hazard.snippet);
+        println!("      {}", hazard.detail);
+        println!("      instead: {}", hazard.suggestion);
+    }
+    if hazards.len() > limit {
+        println!("  ... and {} more", hazards.len() - limit);
+    }
+}
+
+fn cmd_panics(gravity: &SemanticGravity, kind: Option<&str>, limit: usize) {
+    println!("=== Panic Sites ===\n");
+
+    let kind = match kind.map(PanicKind::from_label) {
+        Some(None) => {
+            let kinds: Vec<_> = PanicKind::ALL.iter().map(|k| k.label()).collect();
+            eprintln!("Error: unknown kind (expected one of {})", kinds.join(", "));
+            return;
+        }
+        Some(kind) => kind,
+        None => None,
+    };
+    let sites: Vec<_> = gravity
+        .get_panic_sites()
+        .into_iter()
+        .filter(|s| kind.is_none_or(|k| s.site.kind == k))
+        .collect();
+    if sites.is_empty() {
+        println!("No panic sites found.");
+        return;
+    }
+
+    let counts: Vec<String> = PanicKind::ALL
+        .iter()
+        .filter_map(|k| {
+            let count = sites.iter().filter(|s| s.site.kind == *k).count();
+            (count > 0).then(|| format!("{} {}", count, k.label()))
+        })
+        .collect();
+    println!("{} sites: {}\n", sites.len(), counts.join(", "));
+
+    for (i, ranked) in sites.iter().take(limit).enumerate() {
+        let site = &ranked.site;
+        let distance = if ranked.entry_distance == usize::MAX {
+            "-".to_string()
+        } else {
+            ranked.entry_distance.to_string()
+        };
+        println!(
+            "  {}. [{}] {} (score {:.1}, distance {}) {}:{}",
+            i + 1,
+            site.kind.label(),
+            site.context,
+            ranked.score,
+            distance,
+            site.file.display(),
+            site.line
+        );
+        println!("      {}", site.snippet);
+    }
+    if sites.len() > limit {
+        println!("  ... and {} more", sites.len() - limit);
+    }
+}
+
+fn cmd_unsafe(gravity: &SemanticGravity, kind: Option<&str>, undocumented: bool, limit: usize) {
+    println!("=== Unsafe Code ===\n");
+
+    let kind = match kind.map(UnsafeKind::from_label) {
+        Some(None) => {
+            let kinds: Vec<_> = UnsafeKind::ALL.iter().map(|k| k.label()).collect();
+            eprintln!("Error: unknown kind (expected one of {})", kinds.join(", "));
+            return;
+        }
+        Some(kind) => kind,
+        None => None,
+    };
+    let sites: Vec<_> = gravity
+        .get_unsafe_sites()
+        .into_iter()
+        .filter(|s| kind.is_none_or(|k| s.site.kind == k))
+        .filter(|s| !undocumented || !s.site.documented)
+        .collect();
+    if sites.is_empty() {
+        println!("No unsafe code found.");
+        return;
+    }
+
+    let counts: Vec<String> = UnsafeKind::ALL
+        .iter()
+        .filter_map(|k| {
+            let count = sites.iter().filter(|s| s.site.kind == *k).count();
+            (count > 0).then(|| format!("{} {}", count, k.label()))
+        })
+        .collect();
+    let missing = sites.iter().filter(|s| !s.site.documented).count();
+    println!(
+        "{} sites: {} ({} without SAFETY comments)",
+        sites.len(),
+        counts.join(", "),
+        missing
+    );
+
+    let mut module = None;
+    for ranked in sites.iter().take(limit) {
+        let site = &ranked.site;
+        if module != Some(&ranked.module) {
+            module = Some(&ranked.module);
+            println!("\n{}", ranked.module);
+        }
+        let distance = match ranked.public_distance {
+            0 => "public".to_string(),
+            usize::MAX => "not reachable from public API".to_string(),
+            1 => "1 call from public API".to_string(),
+            d => format!("{} calls from public API", d),
+        };
+        println!(
+            "  [{}] {} ({}) {}:{}{}",
+            site.kind.label(),
+            site.name,
+            distance,
+            site.file.display(),
+            site.line,
+            if site.documented {
+                ""
+            } else {
+                " - no SAFETY comment"
+            }
+        );
+        println!("      {}", site.snippet);
+        for op in &site.operations {
+            println!("      {} `{}` (line {})", op.op.label(), op.target, op.line);
+        }
+    }
+    if sites.len() > limit {
+        println!("\n  ... and {} more", sites.len() - limit);
+    }
+}
+
+fn cmd_metrics(gravity: &SemanticGravity, module: Option<&str>, limit: usize) {
+    println!("=== Complexity Metrics ===\n");
+
+    let in_scope = |path: &str| module.is_none_or(|m| path.contains(m));
+    let modules: Vec<_> = gravity
+        .get_module_metrics()
+        .into_iter()
+        .filter(|m| in_scope(&m.module))
+        .collect();
+    if modules.is_empty() {
+        println!("No functions found.");
+        return;
+    }
+
+    println!(
+        "{:<40} {:>5} {:>10} {:>10} {:>8} {:>6}",
+        "Module", "Fns", "Cyclo avg", "Cogn avg", "Cogn max", "Nest"
+    );
+    for m in &modules {
+        println!(
+            "{:<40} {:>5} {:>10.1} {:>10.1} {:>8} {:>6}",
+            m.module,
+            m.functions,
+            m.mean_cyclomatic(),
+            m.mean_cognitive(),
+            m.max_cognitive,
+            m.max_nesting
+        );
+    }
+
+    println!("\nMost complex functions:");
+    let functions = gravity
+        .get_complex_functions(usize::MAX)
+        .into_iter()
+        .filter(|(item, _)| in_scope(&gravity.get_breadcrumbs(item)))
+        .take(limit);
+    for (i, (item, m)) in functions.enumerate() {
+        println!(
+            "  {}. {} (cognitive {}, cyclomatic {})",
+            i + 1,
+            gravity.get_breadcrumbs(item),
+            m.cognitive,
+            m.cyclomatic
+        );
+        println!(
+            "     {}:{} - nesting {}, {} statements, {} params, {} early returns",
+            item.file_path.display(),
+            item.span.start_line,
+            m.max_nesting,
+            m.statements,
+            m.parameters,
+            m.early_returns
+        );
+    }
+}
+
+fn cmd_targets(gravity: &SemanticGravity) {
+    println!("=== Build Targets ===\n");
+
+    fn print_node(gravity: &SemanticGravity, node: &ModuleNode, indent: usize) {
+        let distance = gravity
+            .get_entry_distance(&node.path)
+            .map(|d| format!(" [distance {}]", d))
+            .unwrap_or_default();
+        println!(
+            "{}{} ({}){}",
+            "  ".repeat(indent),
+            node.name,
+            node.path.display(),
+            distance
+        );
+        for child in &node.children {
+            print_node(gravity, child, indent + 1);
+        }
+    }
+
+    let tree = gravity.get_module_tree();
+    let crates = if gravity.get_workspace().is_multi_crate() {
+        tree.root.children.iter().collect()
+    } else {
+        vec![&tree.root]
+    };
+    for krate in crates {
+        println!("{}:", krate.name);
+        for target in &krate.children {
+            print_node(gravity, target, 1);
+        }
+        println!();
+    }
+}
+
+fn cmd_workspace(gravity: &SemanticGravity, limit: usize) {
+    println!("=== Workspace ===\n");
+
+    let workspace = gravity.get_workspace();
+    println!("{} member crate(s):", workspace.members().len());
+    for member in workspace.members() {
+        let files: Vec<_> = gravity
+            .get_files()
+            .iter()
+            .filter(|f| workspace.member_for(&f.path) == Some(member))
+            .collect();
+        let items: usize = files.iter().map(|f| f.items.len()).sum();
+        println!(
+            "  {} ({}): {} files, {} items",
+            member.crate_name,
+            member.package,
+            files.len(),
+            items
+        );
+        println!("    Entry: {}", member.entry.display());
+    }
+
+    let edges = gravity.get_crate_edges();
+    if edges.is_empty() {
+        println!("\nNo cross-crate references.");
+        return;
+    }
+
+    println!("\nCross-crate references:");
+    for (from, to, count) in edges {
+        println!("  {} -> {} ({} references)", from, to, count);
+        let references = gravity
+            .get_crate_references()
+            .iter()
+            .filter(|r| r.from_crate == from && r.to_crate == to);
+        for r in references.take(limit) {
+            println!(
+                "    {} in {}() at {}:{}",
+                r.path,
+                r.caller_context,
+                r.file.display(),
+                r.line
+            );
+        }
+    }
+}
+
+fn cmd_diff(
+    gravity: &SemanticGravity,
+    project_root: &Path,
+    base: &str,
+    head: Option<&str>,
+    limit: usize,
+) {
+    let load = |spec: &str| match diff::load_side(project_root, spec) {
+        Ok(analysis) => analysis,
+        Err(e) => {
+            eprintln!("Error loading '{}': {}", spec, e);
+            std::process::exit(1);
+        }
+    };
+    let old = load(base);
+    let new = match head {
+        Some(spec) => load(spec),
+        None => gravity.snapshot(project_root, Vec::new()),
+    };
+    let changes = diff::diff(&old, &new);
+
+    println!(
+        "=== Semantic Diff: {} -> {} ===\n",
+        base,
+        head.unwrap_or("working tree")
+    );
+    if changes.is_empty() {
+        println!("No structural changes.");
+        return;
+    }
+
+    fn section<T>(title: &str, entries: &[T], limit: usize, line: impl Fn(&T) -> String) {
+        if entries.is_empty() {
+            return;
+        }
+        println!("{} ({}):", title, entries.len());
+        for entry in entries.iter().take(limit) {
+            println!("  {}", line(entry));
+        }
+        if entries.len() > limit {
+            println!("  ... and {} more", entries.len() - limit);
+        }
+        println!();
+    }
+
+    section("Added", &changes.added, limit, |r| {
+        format!("+ {} {} ({}:{})", r.kind, r.path, r.file.display(), r.line)
+    });
+    section("Removed", &changes.removed, limit, |r| {
+        format!("- {} {} ({}:{})", r.kind, r.path, r.file.display(), r.line)
+    });
+    section("Changed", &changes.changed, limit, |c| {
+        format!(
+            "~ {} {} ({}:{})\n      {}",
+            c.kind,
+            c.path,
+            c.file.display(),
+            c.line,
+            c.changes.join("\n      ")
+        )
+    });
+    section(
+        "Moved",
+        &changes.module_moves(),
+        limit,
+        |(from, to, count)| format!("{} -> {} ({} items)", from, to, count),
+    );
+    section("Trait impls added", &changes.impls_added, limit, |i| {
+        format!("+ {} for {} [{}]", i.trait_name, i.type_name, i.source)
+    });
+    section("Trait impls removed", &changes.impls_removed, limit, |i| {
+        format!("- {} for {} [{}]", i.trait_name, i.type_name, i.source)
+    });
+    section(
+        "Call edges added",
+        &changes.edges_added,
+        limit,
+        |(from, to)| format!("+ {} -> {}", from, to),
+    );
+    section(
+        "Call edges removed",
+        &changes.edges_removed,
+        limit,
+        |(from, to)| format!("- {} -> {}", from, to),
+    );
+}
+
+/// Returns false if the changes need a bigger bump than the versions allow
+fn cmd_semver(
+    gravity: &SemanticGravity,
+    project_root: &Path,
+    (base, from_version): (&str, Option<&str>),
+    (head, to_version): (Option<&str>, Option<&str>),
+    limit: usize,
+) -> bool {
+    let load = |spec: &str| match diff::load_side(project_root, spec) {
+        Ok(analysis) => analysis,
+        Err(e) => {
+            eprintln!("Error loading '{}': {}", spec, e);
+            std::process::exit(1);
+        }
+    };
+    let old = load(base);
+    let new = match head {
+        Some(spec) => load(spec),
+        None => gravity.snapshot(project_root, Vec::new()),
+    };
+    let report = semver::check(&old, &new);
+
+    println!(
+        "=== Semver Check: {} -> {} ===\n",
+        base,
+        head.unwrap_or("working tree")
+    );
+    if report.changes.is_empty() {
+        println!("No public API changes.\n");
+    }
+    for change in report.changes.iter().take(limit) {
+        println!(
+            "  [{}] {} {}: {}",
+            change.bump.label(),
+            change.kind,
+            change.path,
+            change.reason
+        );
+    }
+    if report.changes.len() > limit {
+        println!("  ... and {} more", report.changes.len() - limit);
+    }
+
+    let required = report.required();
+    println!("\nRequired bump: {}", required.label());
+
+    let (Some(from), Some(to)) = (from_version, to_version) else {
+        println!("Versions unknown; pass --from-version and --to-version to enforce");
+        return true;
+    };
+    let Some(allowed) = semver::allowed_bump(from, to) else {
+        eprintln!("Error: could not parse versions '{}' and '{}'", from, to);
+        return false;
+    };
+    println!("Version {} -> {} allows: {}", from, to, allowed.label());
+
+    if required > allowed {
+        eprintln!(
+            "Error: {} changes need a {} bump, but {} -> {} is only {}",
+            report.changes.iter().filter(|c| c.bump > allowed).count(),
+            required.label(),
+            from,
+            to,
+            allowed.label()
+        );
+        return false;
+    }
+    true
+}
+
+/// Public API of the library at `project_root`
+fn api_surface(gravity: &SemanticGravity, project_root: &Path) -> ApiSurface {
+    let crate_name = gravity
+        .get_workspace()
+        .members()
+        .iter()
+        .find(|m| m.root == project_root)
+        .map_or_else(|| "crate".to_string(), |m| m.crate_name.clone());
+    ApiSurface::build(&gravity.snapshot(project_root, Vec::new()), &crate_name)
+}
+
+fn cmd_api(surface: &ApiSurface, format: &str) {
+    match format {
+        "json" => match serde_json::to_string_pretty(&surface) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("Error serializing API: {}", e),
+        },
+        "text" => {
+            println!("=== Public API: {} ===\n", surface.crate_name);
+            if surface.items.is_empty() {
+                println!("No public API (is there a library target?)");
+            }
+            for entry in &surface.items {
+                println!("{}", entry.declaration);
+                for member in &entry.members {
+                    println!("    {}", member);
+                }
+                for trait_impl in &entry.trait_impls {
+                    println!("    {}", trait_impl);
+                }
+                for alias in &entry.aliases {
+                    println!("    (also {})", alias);
+                }
+            }
+        }
+        other => eprintln!("Error: unknown format '{}' (expected text or json)", other),
+    }
+}
+
+fn cmd_check(
+    gravity: &SemanticGravity,
+    layer_rules: &[rules::LayerRule],
+    project_root: &Path,
+    format: &str,
+    output: Option<&Path>,
+) -> bool {
+    let violations = rules::evaluate(layer_rules, gravity.get_module_dependencies());
+
+    let report = match format {
+        "sarif" => {
+            let sarif = rules::to_sarif(&violations, layer_rules, project_root);
+            serde_json::to_string_pretty(&sarif).unwrap_or_default()
+        }
+        "text" => {
+            let mut report = String::from("=== Architecture Check ===\n\n");
+            if layer_rules.is_empty() {
+                report.push_str("No rules configured (add [[rules]] to cargomap.toml).\n");
+            }
+            for v in &violations {
+                let file = &v.dependency.file;
+                report.push_str(&format!(
+                    "{}:{}: [{}] {}\n",
+                    file.strip_prefix(project_root).unwrap_or(file).display(),
+                    v.dependency.line,
+                    layer_rules[v.rule].id(v.rule),
+                    v.message
+                ));
+            }
+            if !layer_rules.is_empty() {
+                report.push_str(&format!(
+                    "\n{} rules, {} dependencies checked, {} violations\n",
+                    layer_rules.len(),
+                    gravity.get_module_dependencies().len(),
+                    violations.len()
+                ));
+            }
+            report
+        }
+        other => {
+            eprintln!(
+                "Error: unknown check format '{}' (expected text or sarif)",
+                other
+            );
+            std::process::exit(1);
+        }
+    };
+
+    match output {
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, report) {
+                eprintln!("Error writing report: {}", e);
+                std::process::exit(1);
+            }
+        }
+        None => print!("{}", report),
+    }
+
+    violations.is_empty()
+}
+
+fn cmd_watch(gravity: &mut SemanticGravity, query: &str, limit: usize) {
+    let root = gravity.root().to_path_buf();
+    let mut watcher = match ProjectWatcher::new(&root) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            eprintln!("Error starting watcher: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    println!("=== Watching {} (Ctrl-C to stop) ===\n", root.display());
+    if !query.is_empty() {
+        cmd_search(gravity, query, limit, false, false);
+    }
+
+    loop {
+        let changed = match watcher.next_batch(watch::DEBOUNCE) {
+            Ok(changed) => changed,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        };
+
+        let report = match watch::apply_changes(gravity, &changed) {
+            Ok(report) => report,
+            Err(e) => {
+                eprintln!("Warning: Could not update analysis: {}", e);
+                continue;
+            }
+        };
+
+        for (path, e) in &report.failures {
+            eprintln!("Warning: Could not update {}: {}", path.display(), e);
+        }
+
+        println!();
+        if report.reanalyzed {
+            println!(
+                "Manifest changed: re-analyzed {} files in {:.1?}",
+                gravity.get_files().len(),
+                report.elapsed
+            );
+        } else if report.updates.is_empty() {
+            continue;
+        } else {
+            for update in &report.updates {
+                let change = match update.change {
+                    FileChange::Added => "added",
+                    FileChange::Modified => "modified",
+                    FileChange::Removed => "removed",
+                };
+                println!(
+                    "{} {} ({} -> {} items)",
+                    change,
+                    update
+                        .path
+                        .strip_prefix(&root)
+                        .unwrap_or(&update.path)
+                        .display(),
+                    update.items_before,
+                    update.items_after
+                );
+            }
+            println!("Updated in {:.1?}", report.elapsed);
+        }
+
+        if !query.is_empty() {
+            println!();
+            cmd_search(gravity, query, limit, false, false);
+        }
+    }
+}
+
+fn cmd_snapshot(
+    gravity: &SemanticGravity,
+    dep_bridge: &mut Option<DependencyBridge>,
+    project_root: &Path,
+    output: &Path,
+    format: Option<&str>,
+) {
+    let format = match format {
+        Some(name) => match SnapshotFormat::parse(name) {
+            Ok(format) => format,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => SnapshotFormat::from_path(output),
+    };
+
+    let dependencies = dep_bridge
+        .as_mut()
+        .and_then(|bridge| bridge.load_dependencies().ok())
+        .map(|deps| deps.values().cloned().collect())
+        .unwrap_or_default();
+    let analysis = gravity.snapshot(project_root, dependencies);
+
+    if let Err(e) = analysis.save(output, format) {
+        eprintln!("Error writing snapshot: {}", e);
+        std::process::exit(1);
+    }
+
+    println!("=== Snapshot ===\n");
+    println!(
+        "Wrote {} ({:?}, version {})",
+        output.display(),
+        format,
+        analysis.version
+    );
+    println!("  Files: {}", analysis.files.len());
+    println!(
+        "  Items: {}",
+        analysis.files.iter().map(|f| f.items.len()).sum::<usize>()
+    );
+    println!("  Called functions: {}", analysis.call_graph.callers.len());
+    println!(
+        "  External symbols: {}",
+        analysis.reference_map.references.len()
+    );
+    println!("  Dependencies: {}", analysis.dependencies.len());
+}
+
 fn cmd_deps(dep_bridge: &mut Option<DependencyBridge>, limit: usize) {
     println!("=== Dependencies ===\n");
 
@@ -2,6 +2,11 @@
 //!
 //! Provides an MCP (Model Context Protocol) server that exposes cargomap's
 //! code analysis capabilities as tools for LLM clients.
+//!
+//! The server analyzes the project once and keeps that analysis current
+//! with a file watcher (see [`crate::watch`]). Tools read it in place; only
+//! those adding per-call state (coverage, git history, another ranking
+//! profile) or waiting on the client work on a copy.
 
 use async_trait::async_trait;
 use rust_mcp_sdk::McpServer;
@@ -13,10 +18,65 @@ use rust_mcp_sdk::schema::{
     SamplingMessageContent, TextContent, schema_utils::CallToolError,
 };
 use rust_mcp_sdk::tool_box;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLockReadGuard};
+
+use crate::dependency::DependencyBridge;
+use crate::hazards::HazardKind;
+use crate::panics::PanicKind;
+use crate::query::SearchQuery;
+use crate::unsafety::UnsafeKind;
+use crate::watch::{LiveAnalysis, WatchError};
+use crate::{ProjectConfig, SemanticGravity, diff, fuzzy, semver};
+
+/// Analysis kept current by the server's watcher
+static LIVE_ANALYSIS: OnceLock<LiveAnalysis> = OnceLock::new();
+
+/// An analysis for one tool call
+enum Analysis {
+    /// The live analysis, read-locked for the duration of the call
+    Live(RwLockReadGuard<'static, SemanticGravity>),
+    Fresh(Box<SemanticGravity>),
+}
+
+impl Analysis {
+    /// An analysis the caller may change: a copy of the live one
+    fn into_owned(self) -> SemanticGravity {
+        match self {
+            Analysis::Live(live) => live.clone(),
+            Analysis::Fresh(gravity) => *gravity,
+        }
+    }
+}
+
+impl Deref for Analysis {
+    type Target = SemanticGravity;
+
+    fn deref(&self) -> &SemanticGravity {
+        match self {
+            Analysis::Live(live) => live,
+            Analysis::Fresh(gravity) => gravity,
+        }
+    }
+}
+
+/// Analysis of `project_root`: the live analysis when the server is
+/// watching that root, otherwise a fresh one
+fn analyzed(project_root: &Path) -> Result<Analysis, CallToolError> {
+    if let Some(live) = LIVE_ANALYSIS.get() {
+        let current = live.read();
+        if current.root() == project_root {
+            return Ok(Analysis::Live(current));
+        }
+    }
 
-use crate::SemanticGravity;
+    let mut gravity = SemanticGravity::new();
+    gravity
+        .analyze_project(project_root)
+        .map_err(|e| CallToolError::from_message(e.to_string()))?;
+    Ok(Analysis::Fresh(Box::new(gravity)))
+}
 
 /// MCP Server handler for cargomap analysis tools
 pub struct CargomapServerHandler {
@@ -56,6 +116,18 @@ impl ServerHandler for CargomapServerHandler {
             CargomapTools::SearchCode(tool) => tool.call_tool(&self.project_root),
             CargomapTools::GetSummary(tool) => tool.call_tool(&self.project_root),
             CargomapTools::FindCallers(tool) => tool.call_tool(&self.project_root),
+            CargomapTools::FindTypeUsages(tool) => tool.call_tool(&self.project_root),
+            CargomapTools::GetUntestedHotspots(tool) => tool.call_tool(&self.project_root),
+            CargomapTools::FindTests(tool) => tool.call_tool(&self.project_root),
+            CargomapTools::GetErrorFlow(tool) => tool.call_tool(&self.project_root),
+            CargomapTools::FindPanicSites(tool) => tool.call_tool(&self.project_root),
+            CargomapTools::FindAsyncHazards(tool) => tool.call_tool(&self.project_root),
+            CargomapTools::AuditUnsafe(tool) => tool.call_tool(&self.project_root),
+            CargomapTools::GetComplexityMetrics(tool) => tool.call_tool(&self.project_root),
+            CargomapTools::GetWorkspace(tool) => tool.call_tool(&self.project_root),
+            CargomapTools::SemanticDiff(tool) => tool.call_tool(&self.project_root),
+            CargomapTools::CheckSemver(tool) => tool.call_tool(&self.project_root),
+            CargomapTools::GetTraitMatrix(tool) => tool.call_tool(&self.project_root),
             CargomapTools::GetExternalUsages(tool) => tool.call_tool(&self.project_root),
             CargomapTools::DiagnoseTraitBound(tool) => tool.call_tool(&self.project_root),
             // AuditImpact requires async + runtime for LLM sampling
@@ -86,10 +158,7 @@ pub struct AnalyzeStruct {
 
 impl AnalyzeStruct {
     pub fn call_tool(&self, project_root: &PathBuf) -> Result<CallToolResult, CallToolError> {
-        let mut gravity = SemanticGravity::new();
-        gravity
-            .analyze_project(project_root)
-            .map_err(|e| CallToolError::from_message(e.to_string()))?;
+        let gravity = analyzed(project_root)?;
 
         let results = gravity.search(&self.struct_name);
         let struct_results: Vec<_> = results
@@ -165,21 +234,16 @@ impl AnalyzeStruct {
             }
 
             // Show related items
-            let related: Vec<_> = result
-                .context
-                .siblings
-                .iter()
-                .filter(|s| !s.shared_generics.is_empty())
-                .take(5)
-                .collect();
-            if !related.is_empty() {
-                output.push_str("### Related (shared generics)\n");
-                for sib in related {
+            if !result.context.related.is_empty() {
+                output.push_str("### Related\n");
+                for related in &result.context.related {
                     output.push_str(&format!(
-                        "- {} `{}` (shares: {})\n",
-                        sib.kind,
-                        sib.name,
-                        sib.shared_generics.join(", ")
+                        "- {} `{}` ({}) at {}:{}\n",
+                        related.kind,
+                        related.name,
+                        related.relations.join(", "),
+                        related.file.display(),
+                        related.line
                     ));
                 }
                 output.push_str("\n");
@@ -204,155 +268,1220 @@ impl AnalyzeStruct {
     }
 }
 
-/// Search for code items by name or pattern
+/// Search for code items by name or pattern
+#[mcp_tool(
+    name = "search_code",
+    description = "Search for functions, structs, enums, traits, and other items in the Rust codebase by name. Matching is fuzzy: abbreviations (SemGrav, sem_grav), typos and subsequences all work; set semantic to search by meaning with a natural-language query. Narrow results with filters in the query (kind:fn vis:pub async:true returns:Result module:gravity impl:SemanticGravity attr:test doc:cache, - to negate) instead of filtering by hand. Returns ranked results with semantic gravity scoring; pick a ranking profile (onboarding, refactor, tests or one from cargomap.toml) to change what ranks first.",
+    read_only_hint = true
+)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct SearchCode {
+    /// Search query: free text fuzzy-matched against item names (then doc comments),
+    /// plus optional filters such as `kind:fn vis:pub async:true returns:Result
+    /// module:gravity impl:SemanticGravity attr:test doc:"cache"` (prefix with - to negate)
+    query: String,
+    /// Maximum number of results to return (default: 10)
+    #[serde(default = "default_limit")]
+    limit: Option<u32>,
+    /// Ranking profile: built-in "onboarding", "refactor", "tests", or one from cargomap.toml
+    #[serde(default)]
+    profile: Option<String>,
+    /// Include the weighted score breakdown for each result (default: false)
+    #[serde(default)]
+    explain: Option<bool>,
+    /// Factor local git history (churn, recency, authors, bug fixes) into scores (default: false)
+    #[serde(default)]
+    git_history: Option<bool>,
+    /// Treat the query as natural language and rank by embedding similarity (default: false)
+    #[serde(default)]
+    semantic: Option<bool>,
+    /// Path to an lcov or llvm-cov JSON report; adds line coverage to each result
+    #[serde(default)]
+    coverage_file: Option<String>,
+}
+
+fn default_limit() -> Option<u32> {
+    Some(10)
+}
+
+impl SearchCode {
+    pub fn call_tool(&self, project_root: &PathBuf) -> Result<CallToolResult, CallToolError> {
+        let weights = ProjectConfig::load(project_root)
+            .and_then(|config| config.weights_for(self.profile.as_deref()))
+            .map_err(|e| CallToolError::from_message(e.to_string()))?;
+
+        let query = SearchQuery::parse(&self.query)
+            .map_err(|e| CallToolError::from_message(e.to_string()))?;
+        let semantic = self.semantic.unwrap_or(false) && !query.text.is_empty();
+
+        // The live analysis keeps its semantic index between calls
+        if semantic
+            && let Some(live) = LIVE_ANALYSIS.get()
+            && live.root() == *project_root
+        {
+            live.load_semantic_index()
+                .map_err(|e| CallToolError::from_message(e.to_string()))?;
+        }
+
+        // Plain searches read the analysis in place; anything that changes
+        // how it scores works on a copy
+        let analysis = analyzed(project_root)?;
+        let customized = weights != *analysis.weights()
+            || self.git_history.unwrap_or(false)
+            || self.coverage_file.is_some()
+            || (semantic && analysis.get_semantic_index().is_none());
+        let mut copy = None;
+        let gravity: &SemanticGravity = if customized {
+            let mut gravity = analysis.into_owned().with_weights(weights);
+            if self.git_history.unwrap_or(false) {
+                gravity
+                    .load_git_history(project_root)
+                    .map_err(|e| CallToolError::from_message(e.to_string()))?;
+            }
+            if let Some(report) = &self.coverage_file {
+                gravity
+                    .load_coverage(&project_root.join(report), project_root)
+                    .map_err(|e| CallToolError::from_message(e.to_string()))?;
+            }
+            if semantic && gravity.get_semantic_index().is_none() {
+                gravity
+                    .refresh_semantic_index(project_root)
+                    .map_err(|e| CallToolError::from_message(e.to_string()))?;
+            }
+            copy.insert(gravity)
+        } else {
+            &analysis
+        };
+
+        let results = if semantic {
+            let mut results = gravity
+                .search_semantic(&query.text)
+                .map_err(|e| CallToolError::from_message(e.to_string()))?;
+            results.retain(|r| gravity.matches_query(&query, &r.item));
+            results
+        } else {
+            gravity.search_query(&query)
+        };
+        let limit = self.limit.unwrap_or(10) as usize;
+
+        if results.is_empty() {
+            return Ok(CallToolResult::text_content(vec![TextContent::from(
+                format!("No results found for '{}'.", self.query),
+            )]));
+        }
+
+        let mut output = format!("# Search Results for '{}'\n\n", self.query);
+        output.push_str(&format!(
+            "Found {} results (showing top {}):\n\n",
+            results.len(),
+            limit.min(results.len())
+        ));
+
+        for (i, result) in results.iter().take(limit).enumerate() {
+            let kind = match &result.item.kind {
+                crate::types::ItemKind::Function { .. } => "fn",
+                crate::types::ItemKind::Struct { .. } => "struct",
+                crate::types::ItemKind::Enum { .. } => "enum",
+                crate::types::ItemKind::Trait { .. } => "trait",
+                crate::types::ItemKind::Impl { .. } => "impl",
+                _ => "item",
+            };
+
+            let test_marker = if result.factors.is_test {
+                " [TEST]"
+            } else {
+                ""
+            };
+
+            output.push_str(&format!(
+                "{}. **{}** `{}`{}\n",
+                i + 1,
+                kind,
+                result.item.name,
+                test_marker
+            ));
+            output.push_str(&format!("   - Path: {}\n", result.context.breadcrumbs));
+            output.push_str(&format!(
+                "   - File: {}:{}\n",
+                result.item.file_path.display(),
+                result.item.span.start_line
+            ));
+            output.push_str(&format!(
+                "   - Score: {:.1} (x-mod: {}, generics: {})\n",
+                result.score, result.factors.cross_module_count, result.factors.generic_depth
+            ));
+            if let Some(matched) = &result.matched {
+                output.push_str(&format!(
+                    "   - Match: {:?} ({:.2}) `{}`\n",
+                    matched.kind,
+                    matched.quality,
+                    fuzzy::highlight(&result.item.name, &matched.ranges, "[", "]")
+                ));
+            }
+            if let Some(similarity) = result.factors.semantic_similarity {
+                output.push_str(&format!("   - Similarity: {:.3}\n", similarity));
+            }
+            if let Some(history) = &result.factors.history {
+                output.push_str(&format!(
+                    "   - History: {} commits, {} authors, {} fixes, last change {:.0} days ago\n",
+                    history.churn,
+                    history.author_count,
+                    history.bug_fix_count,
+                    history.days_since_change
+                ));
+            }
+            if let crate::types::ItemKind::Function {
+                metrics: Some(m), ..
+            } = &result.item.kind
+            {
+                output.push_str(&format!(
+                    "   - Complexity: cyclomatic {}, cognitive {}, nesting {}\n",
+                    m.cyclomatic, m.cognitive, m.max_nesting
+                ));
+            }
+            if let Some(coverage) = &result.factors.coverage {
+                output.push_str(&format!(
+                    "   - Coverage: {:.0}% ({}/{} lines)\n",
+                    coverage.percent, coverage.covered_lines, coverage.instrumented_lines
+                ));
+            }
+            if self.explain.unwrap_or(false) {
+                let parts: Vec<String> = result
+                    .breakdown
+                    .contributions()
+                    .iter()
+                    .map(|(name, value)| format!("{}: {:+.1}", name, value))
+                    .collect();
+                output.push_str(&format!("   - Breakdown: {}\n", parts.join(", ")));
+            }
+            output.push('\n');
+        }
+
+        Ok(CallToolResult::text_content(vec![TextContent::from(
+            output,
+        )]))
+    }
+}
+
+/// Get a summary of the project architecture
+#[mcp_tool(
+    name = "get_summary",
+    description = "Get an overview of the Rust project's architecture including file count, item counts, top work sites, and hub functions.",
+    read_only_hint = true
+)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct GetSummary {}
+
+impl GetSummary {
+    pub fn call_tool(&self, project_root: &PathBuf) -> Result<CallToolResult, CallToolError> {
+        let gravity = analyzed(project_root)?;
+
+        let summary = gravity.summarize();
+
+        let mut output = String::new();
+        output.push_str(&format!(
+            "# Project Summary: {}\n\n",
+            project_root.display()
+        ));
+        output.push_str("## Statistics\n\n");
+        output.push_str(&format!("| Metric | Count |\n"));
+        output.push_str(&format!("|--------|-------|\n"));
+        output.push_str(&format!("| Files | {} |\n", summary.total_files));
+        output.push_str(&format!("| Functions | {} |\n", summary.total_functions));
+        output.push_str(&format!("| Structs | {} |\n", summary.total_structs));
+        output.push_str(&format!("| Enums | {} |\n", summary.total_enums));
+        output.push_str(&format!("| Traits | {} |\n", summary.total_traits));
+        output.push_str(&format!("| Impl blocks | {} |\n", summary.total_impls));
+        output.push_str(&format!("| Modules | {} |\n", summary.total_modules));
+        output.push_str(&format!(
+            "| Parse errors | {} |\n",
+            summary.total_parse_errors
+        ));
+        output.push_str(&format!(
+            "| External symbols | {} |\n\n",
+            summary.external_usage_count
+        ));
+
+        if !summary.hotspots.is_empty() {
+            output.push_str("## Top Work Sites\n\n");
+            output.push_str("Items with highest semantic gravity scores:\n\n");
+            for (i, hs) in summary.hotspots.iter().take(5).enumerate() {
+                output.push_str(&format!(
+                    "{}. **{}** (score: {:.1}, x-mod: {}, generics: {})\n",
+                    i + 1,
+                    hs.item.name,
+                    hs.score,
+                    hs.factors.cross_module_count,
+                    hs.factors.generic_depth
+                ));
+            }
+            output.push_str("\n");
+        }
+
+        if !summary.hub_functions.is_empty() {
+            output.push_str("## Hub Functions\n\n");
+            output.push_str("Functions called from multiple modules:\n\n");
+            for (name, total, cross_mod) in summary.hub_functions.iter().take(5) {
+                output.push_str(&format!(
+                    "- **{}**: {} calls from {} modules\n",
+                    name, total, cross_mod
+                ));
+            }
+        }
+
+        Ok(CallToolResult::text_content(vec![TextContent::from(
+            output,
+        )]))
+    }
+}
+
+/// Find all callers of a function
+#[mcp_tool(
+    name = "find_callers",
+    description = "Find all locations where a function is called in the codebase. Useful for understanding how a function is used.",
+    read_only_hint = true
+)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct FindCallers {
+    /// Name of the function to find callers for
+    function_name: String,
+}
+
+impl FindCallers {
+    pub fn call_tool(&self, project_root: &PathBuf) -> Result<CallToolResult, CallToolError> {
+        let gravity = analyzed(project_root)?;
+
+        let callers = gravity.find_call_sites(&self.function_name);
+
+        if callers.is_empty() {
+            return Ok(CallToolResult::text_content(vec![TextContent::from(
+                format!("No callers found for function '{}'.", self.function_name),
+            )]));
+        }
+
+        let mut output = format!("# Callers of `{}`\n\n", self.function_name);
+        output.push_str(&format!("Found {} call site(s):\n\n", callers.len()));
+
+        for (i, site) in callers.iter().enumerate() {
+            output.push_str(&format!(
+                "{}. In `{}()` at {}:{}\n",
+                i + 1,
+                site.caller,
+                site.file.display(),
+                site.line
+            ));
+        }
+
+        Ok(CallToolResult::text_content(vec![TextContent::from(
+            output,
+        )]))
+    }
+}
+
+/// Find where a local type is mentioned
+#[mcp_tool(
+    name = "find_type_usages",
+    description = "List every place a local type is mentioned: struct fields, enum variant payloads, function parameters and return types, impl headers, type aliases, generic bounds and expressions in function bodies. Each hit is categorized and ranked (structural usages first). Unlike search_code, this finds references inside signatures and bodies, not item names.",
+    read_only_hint = true
+)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct FindTypeUsages {
+    /// Name of the type (e.g., "ParsedItem")
+    type_name: String,
+    /// Maximum number of usages to return (default: 30)
+    #[serde(default)]
+    limit: Option<u32>,
+}
+
+impl FindTypeUsages {
+    pub fn call_tool(&self, project_root: &PathBuf) -> Result<CallToolResult, CallToolError> {
+        let gravity = analyzed(project_root)?;
+
+        let usages = gravity.find_type_usages(&self.type_name);
+        if usages.is_empty() {
+            return Ok(CallToolResult::text_content(vec![TextContent::from(
+                format!("No usages found for type '{}'.", self.type_name),
+            )]));
+        }
+
+        let limit = self.limit.unwrap_or(30) as usize;
+        let mut output = format!("# Usages of `{}`\n\n", self.type_name);
+        output.push_str(&format!(
+            "Found {} usage(s) (showing top {}):\n\n",
+            usages.len(),
+            limit.min(usages.len())
+        ));
+
+        for (i, usage) in usages.iter().take(limit).enumerate() {
+            output.push_str(&format!(
+                "{}. **{}** in `{}` at {}:{}\n",
+                i + 1,
+                usage.kind.label(),
+                usage.context,
+                usage.file.display(),
+                usage.line
+            ));
+            if !usage.snippet.is_empty() {
+                output.push_str(&format!("   `{}`\n", usage.snippet));
+            }
+        }
+
+        Ok(CallToolResult::text_content(vec![TextContent::from(
+            output,
+        )]))
+    }
+}
+
+/// Find important code that tests don't reach
+#[mcp_tool(
+    name = "get_untested_hotspots",
+    description = "Rank high-gravity functions by how little of them is covered by tests, using an lcov or llvm-cov JSON report (e.g. from `cargo llvm-cov --lcov`). Use this to decide where new tests pay off most or which changes are risky because nothing exercises them.",
+    read_only_hint = true
+)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct GetUntestedHotspots {
+    /// Path to the coverage report (lcov.info or llvm-cov export JSON), relative to the project root
+    coverage_file: String,
+    /// Maximum number of functions to return (default: 10)
+    #[serde(default = "default_limit")]
+    limit: Option<u32>,
+    /// Only include functions at or below this line coverage percentage (default: 50)
+    #[serde(default)]
+    max_coverage: Option<f64>,
+}
+
+impl GetUntestedHotspots {
+    pub fn call_tool(&self, project_root: &PathBuf) -> Result<CallToolResult, CallToolError> {
+        let mut gravity = analyzed(project_root)?.into_owned();
+        gravity
+            .load_coverage(&project_root.join(&self.coverage_file), project_root)
+            .map_err(|e| CallToolError::from_message(e.to_string()))?;
+
+        let max_coverage = self.max_coverage.unwrap_or(50.0);
+        let limit = self.limit.unwrap_or(10) as usize;
+        let hotspots = gravity.get_untested_hotspots(limit, max_coverage);
+        if hotspots.is_empty() {
+            let matched = gravity.get_coverage().map_or(0, |c| c.file_count());
+            return Ok(CallToolResult::text_content(vec![TextContent::from(
+                format!(
+                    "No functions at or below {:.0}% coverage ({} project files in the report).",
+                    max_coverage, matched
+                ),
+            )]));
+        }
+
+        let mut output = format!("# Untested Hotspots (<= {:.0}% covered)\n\n", max_coverage);
+        output.push_str("| # | Function | Score | Coverage | Lines | Location |\n");
+        output.push_str("|---|----------|-------|----------|-------|----------|\n");
+        for (i, hotspot) in hotspots.iter().enumerate() {
+            let (percent, covered, instrumented) =
+                hotspot.factors.coverage.as_ref().map_or((0.0, 0, 0), |c| {
+                    (c.percent, c.covered_lines, c.instrumented_lines)
+                });
+            output.push_str(&format!(
+                "| {} | `{}` | {:.1} | {:.0}% | {}/{} | {}:{} |\n",
+                i + 1,
+                hotspot.item.name,
+                hotspot.score,
+                percent,
+                covered,
+                instrumented,
+                hotspot.item.file_path.display(),
+                hotspot.item.span.start_line
+            ));
+        }
+
+        Ok(CallToolResult::text_content(vec![TextContent::from(
+            output,
+        )]))
+    }
+}
+
+/// Map tests to the functions they call
+#[mcp_tool(
+    name = "find_tests",
+    description = "Statically map tests to production code by following the call graph from every #[test] function (unit tests in mod tests blocks, integration tests under tests/) and every doc comment code block. Pass a function name to list the tests that reach it, closest first, or omit it to list functions no test reaches (public ones first). Needs no coverage run.",
+    read_only_hint = true
+)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct FindTests {
+    /// Function to find tests for (e.g., "parse_source" or "PartialParser::parse_source")
+    #[serde(default)]
+    function: Option<String>,
+    /// Maximum number of entries to return (default: 10)
+    #[serde(default = "default_limit")]
+    limit: Option<u32>,
+}
+
+impl FindTests {
+    pub fn call_tool(&self, project_root: &PathBuf) -> Result<CallToolResult, CallToolError> {
+        let gravity = analyzed(project_root)?;
+        let map = gravity.test_map();
+        let limit = self.limit.unwrap_or(10) as usize;
+
+        let mut output = String::new();
+        if let Some(function) = &self.function {
+            let tests = map.tests_for(function);
+            output.push_str(&format!("# Tests reaching `{}`\n\n", function));
+            if tests.is_empty() {
+                output.push_str("No test calls this function, directly or indirectly.\n");
+            }
+            for (test, depth) in tests.iter().take(limit) {
+                output.push_str(&format!(
+                    "- `{}` ({}, depth {}) at {}:{}\n",
+                    test.name,
+                    test.kind.label(),
+                    depth,
+                    test.file.display(),
+                    test.line
+                ));
+            }
+            if tests.len() > limit {
+                output.push_str(&format!("\n... and {} more\n", tests.len() - limit));
+            }
+        } else {
+            let (total, reached) = map.function_counts();
+            let untested = map.untested();
+            output.push_str("# Functions Without Tests\n\n");
+            output.push_str(&format!(
+                "{} tests reach {} of {} functions.\n\n",
+                map.tests().len(),
+                reached,
+                total
+            ));
+            for f in untested.iter().take(limit) {
+                output.push_str(&format!(
+                    "- `{}`{} at {}:{}\n",
+                    f.name,
+                    if f.is_public { " (pub)" } else { "" },
+                    f.file.display(),
+                    f.line
+                ));
+            }
+            if untested.len() > limit {
+                output.push_str(&format!("\n... and {} more\n", untested.len() - limit));
+            }
+        }
+
+        Ok(CallToolResult::text_content(vec![TextContent::from(
+            output,
+        )]))
+    }
+}
+
+/// Trace which errors can reach a function
+#[mcp_tool(
+    name = "error_flow",
+    description = "Map how errors propagate through the project: which functions return which Result<_, E>, where `?` converts errors through From impls (including #[from] fields of thiserror enums), and which variants each function constructs. Pass a function name to answer \"what errors can this return and from where?\" with each origin, the variant it surfaces as and the call chain; omit it for the type-level error-flow graph.",
+    read_only_hint = true
+)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct GetErrorFlow {
+    /// Function to trace (e.g., "load_dependencies" or "DependencyBridge::load_dependencies")
+    #[serde(default)]
+    function: Option<String>,
+    /// Maximum number of entries to return (default: 10)
+    #[serde(default = "default_limit")]
+    limit: Option<u32>,
+}
+
+impl GetErrorFlow {
+    pub fn call_tool(&self, project_root: &PathBuf) -> Result<CallToolResult, CallToolError> {
+        let gravity = analyzed(project_root)?;
+        let flow = gravity.error_flow();
+        let limit = self.limit.unwrap_or(10) as usize;
+
+        let mut output = String::new();
+        if let Some(function) = &self.function {
+            let matches = flow.find(function);
+            if matches.is_empty() {
+                return Err(CallToolError::from_message(format!(
+                    "No function named '{}' returns a Result",
+                    function
+                )));
+            }
+            output.push_str(&format!("# Errors reaching `{}`\n\n", function));
+            for f in &matches {
+                output.push_str(&format!(
+                    "`{}` returns `Result<_, {}>` ({}:{})\n",
+                    f.key,
+                    f.error_type,
+                    f.file.display(),
+                    f.line
+                ));
+            }
+            output.push('\n');
+
+            let reached = flow.reachable(function);
+            if reached.is_empty() {
+                output.push_str("No error origins found.\n");
+            }
+            for r in reached.iter().take(limit) {
+                let surfaced = r
+                    .surfaced_as
+                    .as_ref()
+                    .map(|s| format!(" as `{}`", s))
+                    .unwrap_or_default();
+                output.push_str(&format!(
+                    "- `{}`{} via {} at {}:{}\n",
+                    r.error,
+                    surfaced,
+                    r.chain.join(" -> "),
+                    r.file.display(),
+                    r.line
+                ));
+            }
+            if reached.len() > limit {
+                output.push_str(&format!("\n... and {} more\n", reached.len() - limit));
+            }
+        } else {
+            output.push_str("# Error Flow\n\n## Error Types\n\n");
+            for error_type in flow.error_types().values() {
+                let variants: Vec<String> = error_type
+                    .variants
+                    .iter()
+                    .map(|v| match &v.from {
+                        Some(from) => format!("{} (#[from] {})", v.name, from),
+                        None => v.name.clone(),
+                    })
+                    .collect();
+                output.push_str(&format!(
+                    "- `{}` ({}:{}): {}\n",
+                    error_type.name,
+                    error_type.file.display(),
+                    error_type.line,
+                    variants.join(", ")
+                ));
+            }
+
+            let edges = flow.type_edges();
+            output.push_str("\n## Flows\n\n");
+            for (from, to, how) in edges.iter().take(limit) {
+                output.push_str(&format!("- `{}` -> `{}` ({})\n", from, to, how));
+            }
+            if edges.len() > limit {
+                output.push_str(&format!("\n... and {} more\n", edges.len() - limit));
+            }
+        }
+
+        Ok(CallToolResult::text_content(vec![TextContent::from(
+            output,
+        )]))
+    }
+}
+
+/// List places non-test code can panic
+#[mcp_tool(
+    name = "find_panic_sites",
+    description = "List every place non-test code can panic: unwrap/expect, panic!/unreachable!/todo!/unimplemented!, assert! macros, indexing and slicing, and integer division. Each site names its enclosing item and is ranked by that item's work-site score, then entry distance, so the riskiest panics in central code come first. Optionally filter by kind (unwrap, expect, panic, unreachable, todo, unimplemented, assert, index, division).",
+    read_only_hint = true
+)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct FindPanicSites {
+    /// Only list one kind of site (e.g., "unwrap" or "index")
+    #[serde(default)]
+    kind: Option<String>,
+    /// Maximum number of sites to return (default: 10)
+    #[serde(default = "default_limit")]
+    limit: Option<u32>,
+}
+
+impl FindPanicSites {
+    pub fn call_tool(&self, project_root: &PathBuf) -> Result<CallToolResult, CallToolError> {
+        let kind = match &self.kind {
+            Some(label) => Some(PanicKind::from_label(label).ok_or_else(|| {
+                CallToolError::from_message(format!("Unknown panic kind '{}'", label))
+            })?),
+            None => None,
+        };
+        let gravity = analyzed(project_root)?;
+        let limit = self.limit.unwrap_or(10) as usize;
+
+        let sites: Vec<_> = gravity
+            .get_panic_sites()
+            .into_iter()
+            .filter(|s| kind.is_none_or(|k| s.site.kind == k))
+            .collect();
+
+        let mut output = String::from("# Panic Sites\n\n");
+        let counts: Vec<String> = PanicKind::ALL
+            .iter()
+            .filter_map(|k| {
+                let count = sites.iter().filter(|s| s.site.kind == *k).count();
+                (count > 0).then(|| format!("{} {}", count, k.label()))
+            })
+            .collect();
+        if sites.is_empty() {
+            output.push_str("No panic sites found.\n");
+        } else {
+            output.push_str(&format!("{} sites: {}\n\n", sites.len(), counts.join(", ")));
+        }
+
+        for ranked in sites.iter().take(limit) {
+            let site = &ranked.site;
+            output.push_str(&format!(
+                "- **{}** in `{}` (score {:.1}) at {}:{}\n  `{}`\n",
+                site.kind.label(),
+                site.context,
+                ranked.score,
+                site.file.display(),
+                site.line,
+                site.snippet
+            ));
+        }
+        if sites.len() > limit {
+            output.push_str(&format!("\n... and {} more\n", sites.len() - limit));
+        }
+
+        Ok(CallToolResult::text_content(vec![TextContent::from(
+            output,
+        )]))
+    }
+}
+
+/// Find blocking work in async code
+#[mcp_tool(
+    name = "find_async_hazards",
+    description = "Find blocking work inside async functions and blocks: std::fs/std::net/std::process calls and thread::sleep, calls to local functions that reach such calls (with the call chain), Mutex/RwLock guards and RefCell borrows held across .await, and nested loops without a yield point. Each finding names the async alternative. Optionally filter by kind (blocking-io, sleep, blocking-call, lock-across-await, borrow-across-await, busy-loop).",
+    read_only_hint = true
+)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct FindAsyncHazards {
+    /// Only list one kind of hazard (e.g., "lock-across-await")
+    #[serde(default)]
+    kind: Option<String>,
+    /// Maximum number of hazards to return (default: 10)
+    #[serde(default = "default_limit")]
+    limit: Option<u32>,
+}
+
+impl FindAsyncHazards {
+    pub fn call_tool(&self, project_root: &PathBuf) -> Result<CallToolResult, CallToolError> {
+        let kind = match &self.kind {
+            Some(label) => Some(HazardKind::from_label(label).ok_or_else(|| {
+                CallToolError::from_message(format!("Unknown hazard kind '{}'", label))
+            })?),
+            None => None,
+        };
+        let gravity = analyzed(project_root)?;
+        let limit = self.limit.unwrap_or(10) as usize;
+
+        let hazards: Vec<_> = gravity
+            .get_async_hazards()
+            .into_iter()
+            .filter(|h| kind.is_none_or(|k| h.kind == k))
+            .collect();
+
+        let mut output = String::from("# Async Hazards\n\n");
+        if hazards.is_empty() {
+            output.push_str("No blocking work found in async code.\n");
+        } else {
+            output.push_str(&format!("{} hazards\n\n", hazards.len()));
+        }
+
+        for hazard in hazards.iter().take(limit) {
+            output.push_str(&format!(
+                "- **{}** in `{}` at {}:{}\n  `{}`\n  {}. Instead: {}\n",
+                hazard.kind.label(),
+                hazard.context,
+                hazard.file.display(),
+                hazard.span.start_line,
+                hazard.snippet,
+                hazard.detail,
+                hazard.suggestion
+            ));
+        }
+        if hazards.len() > limit {
+            output.push_str(&format!("\n... and {} more\n", hazards.len() - limit));
+        }
+
+        Ok(CallToolResult::text_content(vec![TextContent::from(
+            output,
+        )]))
+    }
+}
+
+/// Audit unsafe code
+#[mcp_tool(
+    name = "audit_unsafe",
+    description = "List every unsafe fn, unsafe impl (Send/Sync especially), unsafe trait and unsafe block in non-test code, with the operations inside (raw pointer derefs, FFI calls, transmute, static mut access, calls to unsafe functions, inline asm) and whether a SAFETY comment documents it. Grouped by module and ranked by call distance from the public API, so the most exposed unsafe code comes first. Use it to review unsafe code before changing it. Optionally filter by kind (fn, impl, trait, block) or to undocumented sites.",
+    read_only_hint = true
+)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct AuditUnsafe {
+    /// Only list one kind of site (e.g., "impl" or "block")
+    #[serde(default)]
+    kind: Option<String>,
+    /// Only list sites without a SAFETY comment
+    #[serde(default)]
+    undocumented: bool,
+    /// Maximum number of sites to return (default: 10)
+    #[serde(default = "default_limit")]
+    limit: Option<u32>,
+}
+
+impl AuditUnsafe {
+    pub fn call_tool(&self, project_root: &PathBuf) -> Result<CallToolResult, CallToolError> {
+        let kind = match &self.kind {
+            Some(label) => Some(UnsafeKind::from_label(label).ok_or_else(|| {
+                CallToolError::from_message(format!("Unknown unsafe kind '{}'", label))
+            })?),
+            None => None,
+        };
+        let gravity = analyzed(project_root)?;
+        let limit = self.limit.unwrap_or(10) as usize;
+
+        let sites: Vec<_> = gravity
+            .get_unsafe_sites()
+            .into_iter()
+            .filter(|s| kind.is_none_or(|k| s.site.kind == k))
+            .filter(|s| !self.undocumented || !s.site.documented)
+            .collect();
+
+        let mut output = String::from("# Unsafe Code\n");
+        if sites.is_empty() {
+            output.push_str("\nNo unsafe code found.\n");
+        } else {
+            let missing = sites.iter().filter(|s| !s.site.documented).count();
+            output.push_str(&format!(
+                "\n{} sites, {} without SAFETY comments\n",
+                sites.len(),
+                missing
+            ));
+        }
+
+        let mut module = None;
+        for ranked in sites.iter().take(limit) {
+            let site = &ranked.site;
+            if module != Some(&ranked.module) {
+                module = Some(&ranked.module);
+                output.push_str(&format!("\n## {}\n\n", ranked.module));
+            }
+            let distance = match ranked.public_distance {
+                0 => "public".to_string(),
+                usize::MAX => "not reachable from public API".to_string(),
+                d => format!("distance {} from public API", d),
+            };
+            output.push_str(&format!(
+                "- **unsafe {}** `{}` ({}) at {}:{}{}\n  `{}`\n",
+                site.kind.label(),
+                site.name,
+                distance,
+                site.file.display(),
+                site.line,
+                if site.documented {
+                    ""
+                } else {
+                    " - no SAFETY comment"
+                },
+                site.snippet
+            ));
+            for op in &site.operations {
+                output.push_str(&format!(
+                    "  - {} `{}` (line {})\n",
+                    op.op.label(),
+                    op.target,
+                    op.line
+                ));
+            }
+        }
+        if sites.len() > limit {
+            output.push_str(&format!("\n... and {} more\n", sites.len() - limit));
+        }
+
+        Ok(CallToolResult::text_content(vec![TextContent::from(
+            output,
+        )]))
+    }
+}
+
+/// Report function complexity metrics
+#[mcp_tool(
+    name = "get_complexity_metrics",
+    description = "Report AST-derived complexity for the project's functions and methods: cyclomatic and cognitive complexity, max nesting, statement and parameter counts and early returns. Returns per-module aggregates plus the most complex functions. Use it to find code that is hard to change safely or worth simplifying.",
+    read_only_hint = true
+)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct GetComplexityMetrics {
+    /// Only include modules whose path contains this text (e.g., "gravity")
+    #[serde(default)]
+    module: Option<String>,
+    /// Maximum number of functions to list (default: 10)
+    #[serde(default = "default_limit")]
+    limit: Option<u32>,
+}
+
+impl GetComplexityMetrics {
+    pub fn call_tool(&self, project_root: &PathBuf) -> Result<CallToolResult, CallToolError> {
+        let gravity = analyzed(project_root)?;
+
+        let in_scope = |path: &str| self.module.as_deref().is_none_or(|m| path.contains(m));
+        let modules: Vec<_> = gravity
+            .get_module_metrics()
+            .into_iter()
+            .filter(|m| in_scope(&m.module))
+            .collect();
+        if modules.is_empty() {
+            return Ok(CallToolResult::text_content(vec![TextContent::from(
+                "No functions found.".to_string(),
+            )]));
+        }
+
+        let mut output = String::from("# Complexity Metrics\n\n## Modules\n\n");
+        output.push_str("| Module | Functions | Avg cyclomatic | Avg cognitive | Max cognitive | Max nesting |\n");
+        output.push_str("|--------|-----------|----------------|---------------|---------------|-------------|\n");
+        for m in &modules {
+            output.push_str(&format!(
+                "| `{}` | {} | {:.1} | {:.1} | {} (`{}`) | {} |\n",
+                m.module,
+                m.functions,
+                m.mean_cyclomatic(),
+                m.mean_cognitive(),
+                m.max_cognitive,
+                m.most_complex.as_deref().unwrap_or("-"),
+                m.max_nesting
+            ));
+        }
+
+        output.push_str("\n## Most Complex Functions\n\n");
+        let limit = self.limit.unwrap_or(10) as usize;
+        let functions = gravity
+            .get_complex_functions(usize::MAX)
+            .into_iter()
+            .filter(|(item, _)| in_scope(&gravity.get_breadcrumbs(item)))
+            .take(limit);
+        for (i, (item, m)) in functions.enumerate() {
+            output.push_str(&format!(
+                "{}. **{}** at {}:{}\n   - cognitive {}, cyclomatic {}, nesting {}, {} statements, {} params, {} early returns\n",
+                i + 1,
+                gravity.get_breadcrumbs(item),
+                item.file_path.display(),
+                item.span.start_line,
+                m.cognitive,
+                m.cyclomatic,
+                m.max_nesting,
+                m.statements,
+                m.parameters,
+                m.early_returns
+            ));
+        }
+
+        Ok(CallToolResult::text_content(vec![TextContent::from(
+            output,
+        )]))
+    }
+}
+
+/// Describe the workspace members and their cross-crate references
+#[mcp_tool(
+    name = "get_workspace",
+    description = "List the crates of a Cargo workspace (package and crate names, entry points, file and item counts) and the references between them, e.g. `app -> core_lib (12 references)` with example call sites. Use it to understand how a multi-crate project is layered before changing a crate others depend on.",
+    read_only_hint = true
+)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct GetWorkspace {
+    /// Maximum number of example references to list per edge (default: 5)
+    #[serde(default)]
+    limit: Option<u32>,
+}
+
+impl GetWorkspace {
+    pub fn call_tool(&self, project_root: &PathBuf) -> Result<CallToolResult, CallToolError> {
+        let gravity = analyzed(project_root)?;
+
+        let workspace = gravity.get_workspace();
+        let mut output = String::from("# Workspace\n\n");
+        output.push_str("| Crate | Package | Files | Items | Entry |\n");
+        output.push_str("|-------|---------|-------|-------|-------|\n");
+        for member in workspace.members() {
+            let files: Vec<_> = gravity
+                .get_files()
+                .iter()
+                .filter(|f| workspace.member_for(&f.path) == Some(member))
+                .collect();
+            output.push_str(&format!(
+                "| `{}` | {} | {} | {} | {} |\n",
+                member.crate_name,
+                member.package,
+                files.len(),
+                files.iter().map(|f| f.items.len()).sum::<usize>(),
+                member.entry.display()
+            ));
+        }
+
+        output.push_str("\n## Build Targets\n\n");
+        for member in workspace.members() {
+            for target in &member.targets {
+                let distance = gravity
+                    .get_entry_distance(&target.entry)
+                    .map(|d| format!(" (distance {})", d))
+                    .unwrap_or_default();
+                output.push_str(&format!(
+                    "- `{}` {} `{}` at {}{}\n",
+                    member.crate_name,
+                    target.kind.label(),
+                    target.name,
+                    target.entry.display(),
+                    distance
+                ));
+            }
+        }
+
+        let edges = gravity.get_crate_edges();
+        if edges.is_empty() {
+            output.push_str("\nNo cross-crate references.\n");
+        } else {
+            output.push_str("\n## Cross-Crate References\n\n");
+            let limit = self.limit.unwrap_or(5) as usize;
+            for (from, to, count) in edges {
+                output.push_str(&format!(
+                    "- **{}** -> **{}** ({} references)\n",
+                    from, to, count
+                ));
+                let references = gravity
+                    .get_crate_references()
+                    .iter()
+                    .filter(|r| r.from_crate == from && r.to_crate == to);
+                for r in references.take(limit) {
+                    output.push_str(&format!(
+                        "  - `{}` in `{}()` at {}:{}\n",
+                        r.path,
+                        r.caller_context,
+                        r.file.display(),
+                        r.line
+                    ));
+                }
+            }
+        }
+
+        Ok(CallToolResult::text_content(vec![TextContent::from(
+            output,
+        )]))
+    }
+}
+
+/// Structural diff between two revisions or snapshots
 #[mcp_tool(
-    name = "search_code",
-    description = "Search for functions, structs, enums, traits, and other items in the Rust codebase by name. Returns ranked results with semantic gravity scoring.",
+    name = "semantic_diff",
+    description = "Summarize a change structurally instead of as a text diff: items added, removed or moved between modules, signature/field/variant/visibility changes, trait impls gained or lost (including derives), and call-graph edges gained or lost. Compares two git revisions (read from the object store, no checkout) or snapshot files; head defaults to the working tree. Ideal for reviewing a PR: base = the target branch.",
     read_only_hint = true
 )]
 #[derive(Debug, serde::Deserialize, serde::Serialize, JsonSchema)]
-pub struct SearchCode {
-    /// Search query (matches against item names and doc comments)
-    query: String,
-    /// Maximum number of results to return (default: 10)
-    #[serde(default = "default_limit")]
+pub struct SemanticDiff {
+    /// Base git revision (e.g., "main", "HEAD~1") or snapshot file
+    base: String,
+    /// Head git revision or snapshot file (default: the working tree)
+    #[serde(default)]
+    head: Option<String>,
+    /// Maximum entries per section (default: 30)
+    #[serde(default)]
     limit: Option<u32>,
 }
 
-fn default_limit() -> Option<u32> {
-    Some(10)
-}
-
-impl SearchCode {
+impl SemanticDiff {
     pub fn call_tool(&self, project_root: &PathBuf) -> Result<CallToolResult, CallToolError> {
-        let mut gravity = SemanticGravity::new();
-        gravity
-            .analyze_project(project_root)
+        let old = diff::load_side(project_root, &self.base)
             .map_err(|e| CallToolError::from_message(e.to_string()))?;
+        let new = match &self.head {
+            Some(head) => diff::load_side(project_root, head)
+                .map_err(|e| CallToolError::from_message(e.to_string()))?,
+            None => {
+                let gravity = analyzed(project_root)?;
+                gravity.snapshot(project_root, Vec::new())
+            }
+        };
+        let changes = diff::diff(&old, &new);
+        let limit = self.limit.unwrap_or(30) as usize;
 
-        let results = gravity.search(&self.query);
-        let limit = self.limit.unwrap_or(10) as usize;
-
-        if results.is_empty() {
+        let mut output = format!(
+            "# Semantic Diff: `{}` -> `{}`\n\n",
+            self.base,
+            self.head.as_deref().unwrap_or("working tree")
+        );
+        if changes.is_empty() {
+            output.push_str("No structural changes.\n");
             return Ok(CallToolResult::text_content(vec![TextContent::from(
-                format!("No results found for '{}'.", self.query),
+                output,
             )]));
         }
 
-        let mut output = format!("# Search Results for '{}'\n\n", self.query);
-        output.push_str(&format!(
-            "Found {} results (showing top {}):\n\n",
-            results.len(),
-            limit.min(results.len())
-        ));
-
-        for (i, result) in results.iter().take(limit).enumerate() {
-            let kind = match &result.item.kind {
-                crate::types::ItemKind::Function { .. } => "fn",
-                crate::types::ItemKind::Struct { .. } => "struct",
-                crate::types::ItemKind::Enum { .. } => "enum",
-                crate::types::ItemKind::Trait { .. } => "trait",
-                crate::types::ItemKind::Impl { .. } => "impl",
-                _ => "item",
-            };
-
-            let test_marker = if result.factors.is_test {
-                " [TEST]"
-            } else {
-                ""
-            };
-
-            output.push_str(&format!(
-                "{}. **{}** `{}`{}\n",
-                i + 1,
-                kind,
-                result.item.name,
-                test_marker
-            ));
-            output.push_str(&format!("   - Path: {}\n", result.context.breadcrumbs));
-            output.push_str(&format!(
-                "   - File: {}:{}\n",
-                result.item.file_path.display(),
-                result.item.span.start_line
-            ));
-            output.push_str(&format!(
-                "   - Score: {:.1} (x-mod: {}, generics: {})\n\n",
-                result.score, result.factors.cross_module_count, result.factors.generic_depth
-            ));
+        fn section(output: &mut String, title: &str, lines: Vec<String>, limit: usize) {
+            if lines.is_empty() {
+                return;
+            }
+            output.push_str(&format!("## {} ({})\n\n", title, lines.len()));
+            for line in lines.iter().take(limit) {
+                output.push_str(&format!("- {}\n", line));
+            }
+            if lines.len() > limit {
+                output.push_str(&format!("- ... and {} more\n", lines.len() - limit));
+            }
+            output.push('\n');
         }
 
+        let item_line = |r: &diff::ItemRef| {
+            format!("{} `{}` ({}:{})", r.kind, r.path, r.file.display(), r.line)
+        };
+        section(
+            &mut output,
+            "Added",
+            changes.added.iter().map(item_line).collect(),
+            limit,
+        );
+        section(
+            &mut output,
+            "Removed",
+            changes.removed.iter().map(item_line).collect(),
+            limit,
+        );
+        section(
+            &mut output,
+            "Changed",
+            changes
+                .changed
+                .iter()
+                .map(|c| {
+                    format!(
+                        "{} `{}` ({}:{})\n  - {}",
+                        c.kind,
+                        c.path,
+                        c.file.display(),
+                        c.line,
+                        c.changes.join("\n  - ")
+                    )
+                })
+                .collect(),
+            limit,
+        );
+        section(
+            &mut output,
+            "Moved",
+            changes
+                .module_moves()
+                .iter()
+                .map(|(from, to, count)| format!("`{}` -> `{}` ({} items)", from, to, count))
+                .collect(),
+            limit,
+        );
+        let impl_line = |i: &diff::ImplChange| {
+            format!("`{}` for `{}` ({})", i.trait_name, i.type_name, i.source)
+        };
+        section(
+            &mut output,
+            "Trait Impls Added",
+            changes.impls_added.iter().map(impl_line).collect(),
+            limit,
+        );
+        section(
+            &mut output,
+            "Trait Impls Removed",
+            changes.impls_removed.iter().map(impl_line).collect(),
+            limit,
+        );
+        let edge_line = |(from, to): &(String, String)| format!("`{}` -> `{}`", from, to);
+        section(
+            &mut output,
+            "Call Edges Added",
+            changes.edges_added.iter().map(edge_line).collect(),
+            limit,
+        );
+        section(
+            &mut output,
+            "Call Edges Removed",
+            changes.edges_removed.iter().map(edge_line).collect(),
+            limit,
+        );
+
         Ok(CallToolResult::text_content(vec![TextContent::from(
             output,
         )]))
     }
 }
 
-/// Get a summary of the project architecture
+/// Classify public API changes for a release
 #[mcp_tool(
-    name = "get_summary",
-    description = "Get an overview of the Rust project's architecture including file count, item counts, top work sites, and hub functions.",
+    name = "check_semver",
+    description = "Compare the crate's public API (pub items reachable through pub mod chains and pub use re-exports) between a base revision or snapshot and a head (default: working tree), classifying each change as major, minor or patch per Cargo's SemVer rules: removed items, changed signatures, new fields on exhaustive structs, new variants on non-#[non_exhaustive] enums, new required trait methods, lost trait impls. Reports the required bump and whether the given versions allow it.",
     read_only_hint = true
 )]
 #[derive(Debug, serde::Deserialize, serde::Serialize, JsonSchema)]
-pub struct GetSummary {}
+pub struct CheckSemver {
+    /// Base git revision (usually the last release tag) or snapshot file
+    base: String,
+    /// Head git revision or snapshot file (default: the working tree)
+    #[serde(default)]
+    head: Option<String>,
+    /// Version of the base (default: Cargo.toml at the base revision)
+    #[serde(default)]
+    from_version: Option<String>,
+    /// Version of the head (default: Cargo.toml at the head)
+    #[serde(default)]
+    to_version: Option<String>,
+}
 
-impl GetSummary {
+impl CheckSemver {
     pub fn call_tool(&self, project_root: &PathBuf) -> Result<CallToolResult, CallToolError> {
-        let mut gravity = SemanticGravity::new();
-        gravity
-            .analyze_project(project_root)
+        let old = diff::load_side(project_root, &self.base)
             .map_err(|e| CallToolError::from_message(e.to_string()))?;
+        let new = match &self.head {
+            Some(head) => diff::load_side(project_root, head)
+                .map_err(|e| CallToolError::from_message(e.to_string()))?,
+            None => {
+                let gravity = analyzed(project_root)?;
+                gravity.snapshot(project_root, Vec::new())
+            }
+        };
+        let report = semver::check(&old, &new);
+        let required = report.required();
 
-        let summary = gravity.summarize();
-
-        let mut output = String::new();
-        output.push_str(&format!(
-            "# Project Summary: {}\n\n",
-            project_root.display()
-        ));
-        output.push_str("## Statistics\n\n");
-        output.push_str(&format!("| Metric | Count |\n"));
-        output.push_str(&format!("|--------|-------|\n"));
-        output.push_str(&format!("| Files | {} |\n", summary.total_files));
-        output.push_str(&format!("| Functions | {} |\n", summary.total_functions));
-        output.push_str(&format!("| Structs | {} |\n", summary.total_structs));
-        output.push_str(&format!("| Enums | {} |\n", summary.total_enums));
-        output.push_str(&format!("| Traits | {} |\n", summary.total_traits));
-        output.push_str(&format!("| Impl blocks | {} |\n", summary.total_impls));
-        output.push_str(&format!("| Modules | {} |\n", summary.total_modules));
-        output.push_str(&format!(
-            "| Parse errors | {} |\n",
-            summary.total_parse_errors
-        ));
-        output.push_str(&format!(
-            "| External symbols | {} |\n\n",
-            summary.external_usage_count
-        ));
+        let mut output = format!(
+            "# Semver Check: `{}` -> `{}`\n\n**Required bump: {}**\n",
+            self.base,
+            self.head.as_deref().unwrap_or("working tree"),
+            required.label()
+        );
 
-        if !summary.hotspots.is_empty() {
-            output.push_str("## Top Work Sites\n\n");
-            output.push_str("Items with highest semantic gravity scores:\n\n");
-            for (i, hs) in summary.hotspots.iter().take(5).enumerate() {
-                output.push_str(&format!(
-                    "{}. **{}** (score: {:.1}, x-mod: {}, generics: {})\n",
-                    i + 1,
-                    hs.item.name,
-                    hs.score,
-                    hs.factors.cross_module_count,
-                    hs.factors.generic_depth
-                ));
+        let from = self
+            .from_version
+            .clone()
+            .or_else(|| semver::package_version(project_root, Some(&self.base)));
+        let to = self
+            .to_version
+            .clone()
+            .or_else(|| semver::package_version(project_root, self.head.as_deref()));
+        if let (Some(from), Some(to)) = (from, to) {
+            match semver::allowed_bump(&from, &to) {
+                Some(allowed) if required > allowed => output.push_str(&format!(
+                    "\n**Incompatible:** {} -> {} only allows a {} bump\n",
+                    from,
+                    to,
+                    allowed.label()
+                )),
+                Some(allowed) => output.push_str(&format!(
+                    "\n{} -> {} allows a {} bump: compatible\n",
+                    from,
+                    to,
+                    allowed.label()
+                )),
+                None => output.push_str(&format!(
+                    "\nCould not parse versions `{}` and `{}`\n",
+                    from, to
+                )),
             }
-            output.push_str("\n");
         }
 
-        if !summary.hub_functions.is_empty() {
-            output.push_str("## Hub Functions\n\n");
-            output.push_str("Functions called from multiple modules:\n\n");
-            for (name, total, cross_mod) in summary.hub_functions.iter().take(5) {
-                output.push_str(&format!(
-                    "- **{}**: {} calls from {} modules\n",
-                    name, total, cross_mod
-                ));
+        for bump in [
+            semver::Bump::Major,
+            semver::Bump::Minor,
+            semver::Bump::Patch,
+        ] {
+            let changes: Vec<_> = report.changes.iter().filter(|c| c.bump == bump).collect();
+            if changes.is_empty() {
+                continue;
+            }
+            output.push_str(&format!("\n## {} ({})\n\n", bump.label(), changes.len()));
+            for c in changes {
+                output.push_str(&format!("- {} `{}`: {}\n", c.kind, c.path, c.reason));
             }
         }
 
@@ -362,44 +1491,81 @@ impl GetSummary {
     }
 }
 
-/// Find all callers of a function
+/// Query the trait implementation matrix
 #[mcp_tool(
-    name = "find_callers",
-    description = "Find all locations where a function is called in the codebase. Useful for understanding how a function is used.",
+    name = "trait_matrix",
+    description = "Answer 'who implements this trait?' and 'which traits does this type implement?' from a trait-by-type matrix covering manual impls (including generic and reference self types), #[derive(...)] attributes and applicable blanket impls (impl<T: Foo> Bar for T). Pass trait_name, type_name, or neither for the full matrix.",
     read_only_hint = true
 )]
 #[derive(Debug, serde::Deserialize, serde::Serialize, JsonSchema)]
-pub struct FindCallers {
-    /// Name of the function to find callers for
-    function_name: String,
+pub struct GetTraitMatrix {
+    /// Trait to list implementors of (e.g., "Serialize")
+    #[serde(default)]
+    trait_name: Option<String>,
+    /// Type to list implemented traits of (e.g., "ParsedItem")
+    #[serde(default)]
+    type_name: Option<String>,
 }
 
-impl FindCallers {
+impl GetTraitMatrix {
     pub fn call_tool(&self, project_root: &PathBuf) -> Result<CallToolResult, CallToolError> {
-        let mut gravity = SemanticGravity::new();
-        gravity
-            .analyze_project(project_root)
-            .map_err(|e| CallToolError::from_message(e.to_string()))?;
+        let gravity = analyzed(project_root)?;
+        let matrix = gravity.trait_matrix();
 
-        let callers = gravity.find_call_sites(&self.function_name);
+        let mut output = String::new();
 
-        if callers.is_empty() {
-            return Ok(CallToolResult::text_content(vec![TextContent::from(
-                format!("No callers found for function '{}'.", self.function_name),
-            )]));
+        if let Some(trait_name) = &self.trait_name {
+            let implementors = matrix.implementors(trait_name);
+            output.push_str(&format!("# Implementors of `{}`\n\n", trait_name));
+            if implementors.is_empty() {
+                output.push_str("No local implementations found.\n");
+            }
+            for i in implementors {
+                output.push_str(&format!(
+                    "- `{}` ({}) at {}:{}\n",
+                    i.self_type,
+                    i.source.label(),
+                    i.file.display(),
+                    i.line
+                ));
+            }
+            output.push('\n');
         }
 
-        let mut output = format!("# Callers of `{}`\n\n", self.function_name);
-        output.push_str(&format!("Found {} call site(s):\n\n", callers.len()));
+        if let Some(type_name) = &self.type_name {
+            let traits = matrix.traits_of(type_name);
+            output.push_str(&format!("# Traits implemented by `{}`\n\n", type_name));
+            if traits.is_empty() {
+                output.push_str("No trait implementations found.\n");
+            }
+            for i in traits {
+                let via = match &i.source {
+                    crate::traits::ImplSource::Blanket { bounds } if !bounds.is_empty() => {
+                        format!(", via {}", bounds.join(" + "))
+                    }
+                    _ => String::new(),
+                };
+                output.push_str(&format!(
+                    "- `{}` ({}{}) at {}:{}\n",
+                    i.trait_name,
+                    i.source.label(),
+                    via,
+                    i.file.display(),
+                    i.line
+                ));
+            }
+            output.push('\n');
+        }
 
-        for (i, site) in callers.iter().enumerate() {
-            output.push_str(&format!(
-                "{}. In `{}()` at {}:{}\n",
-                i + 1,
-                site.caller,
-                site.file.display(),
-                site.line
-            ));
+        if self.trait_name.is_none() && self.type_name.is_none() {
+            output.push_str("# Trait Implementation Matrix\n\n");
+            for (type_name, impls) in matrix.by_type() {
+                let traits: Vec<String> = impls
+                    .iter()
+                    .map(|i| format!("{} ({})", i.trait_name, i.source.label()))
+                    .collect();
+                output.push_str(&format!("- **{}**: {}\n", type_name, traits.join(", ")));
+            }
         }
 
         Ok(CallToolResult::text_content(vec![TextContent::from(
@@ -411,7 +1577,7 @@ impl FindCallers {
 /// Get usages of external crate symbols
 #[mcp_tool(
     name = "get_external_usages",
-    description = "Find where external crate symbols (like tokio::spawn, serde::Serialize) are used in the project. Helps understand external dependencies usage.",
+    description = "Find where external crate symbols (like tokio::spawn, serde::Serialize) are used in the project, under any re-exported path to the same item. Helps understand external dependencies usage.",
     read_only_hint = true
 )]
 #[derive(Debug, serde::Deserialize, serde::Serialize, JsonSchema)]
@@ -422,12 +1588,16 @@ pub struct GetExternalUsages {
 
 impl GetExternalUsages {
     pub fn call_tool(&self, project_root: &PathBuf) -> Result<CallToolResult, CallToolError> {
-        let mut gravity = SemanticGravity::new();
-        gravity
-            .analyze_project(project_root)
-            .map_err(|e| CallToolError::from_message(e.to_string()))?;
+        let mut gravity = analyzed(project_root)?.into_owned();
 
-        let usages = gravity.get_external_usages(&self.external_path);
+        // Usages written through other paths to the same item count too
+        let canonical = match DependencyBridge::new(project_root) {
+            Ok(mut bridge) => {
+                gravity.canonicalize_usages_of(&self.external_path, |p| bridge.canonical_path(p))
+            }
+            Err(_) => self.external_path.clone(),
+        };
+        let usages = gravity.get_external_usages(&canonical);
 
         if usages.is_empty() {
             // Try to show available external symbols if exact match not found
@@ -488,7 +1658,7 @@ impl GetExternalUsages {
 /// Audit the impact of a proposed code change using LLM analysis
 #[mcp_tool(
     name = "audit_impact",
-    description = "Analyzes the semantic impact of a proposed change to a struct or function. Uses LLM sampling to reason about whether the change could break business logic at call sites. Example: 'Would changing field `count` from u32 to Option<u32> break callers?'",
+    description = "Analyzes the semantic impact of a proposed change to a struct or function. Uses LLM sampling to reason about whether the change could break business logic at call sites, or for structs at the exact sites where the changed fields are read, written, constructed or destructured. Example: 'Would changing field `count` from u32 to Option<u32> break callers?'",
     read_only_hint = true
 )]
 #[derive(Debug, serde::Deserialize, serde::Serialize, JsonSchema)]
@@ -497,7 +1667,7 @@ pub struct AuditImpact {
     target_name: String,
     /// Description of the proposed change (e.g., "change field `count` from u32 to Option<u32>")
     proposed_change: String,
-    /// Maximum number of call sites to analyze (default: 5)
+    /// Maximum number of call or field access sites to analyze (default: 5)
     #[serde(default = "default_max_sites")]
     max_sites: Option<u32>,
 }
@@ -524,91 +1694,142 @@ impl AuditImpact {
             )]));
         }
 
-        // First, analyze the project to find dependencies
-        let mut gravity = SemanticGravity::new();
-        gravity
-            .analyze_project(project_root)
-            .map_err(|e| CallToolError::from_message(e.to_string()))?;
-
-        // Find the target item
-        let results = gravity.search(&self.target_name);
-        if results.is_empty() {
-            return Ok(CallToolResult::text_content(vec![TextContent::from(
-                format!("No item named '{}' found in the project.", self.target_name),
-            )]));
-        }
-
-        let target = &results[0];
-
-        // Find all call sites for this item
-        let call_sites = gravity.find_call_sites(&self.target_name);
         let max_sites = self.max_sites.unwrap_or(5) as usize;
 
-        if call_sites.is_empty() {
-            return Ok(CallToolResult::text_content(vec![TextContent::from(
-                format!(
-                    "No call sites found for '{}'. The change appears safe from a usage perspective, \
-                     but manual review is still recommended.",
-                    self.target_name
-                ),
-            )]));
-        }
+        // Everything read from the live analysis happens in this block, so
+        // its lock is released before the client samples
+        let (site_label, sites, site_contexts, prompt) = {
+            let gravity = analyzed(project_root)?;
+
+            // Find the target item
+            let results = gravity.search(&self.target_name);
+            if results.is_empty() {
+                return Ok(CallToolResult::text_content(vec![TextContent::from(
+                    format!("No item named '{}' found in the project.", self.target_name),
+                )]));
+            }
 
-        // Collect context from call sites
-        let mut call_site_contexts = Vec::new();
-        for site in call_sites.iter().take(max_sites) {
-            // Read a few lines around each call site for context
-            let context = Self::read_call_site_context(&site.file, site.line);
-            call_site_contexts.push(format!(
-                "### Call site in `{}()` at {}:{}\n```rust\n{}\n```",
-                site.caller,
-                site.file.display(),
-                site.line,
-                context
-            ));
-        }
+            // Prefer the exact name over fuzzy matches
+            let target = results
+                .iter()
+                .find(|r| r.item.name == self.target_name)
+                .unwrap_or(&results[0]);
+
+            // Struct changes are audited where the fields they mention are
+            // read, written, built or destructured (all fields if none is
+            // named); anything else at the callers of its name
+            let (site_label, sites): (&str, Vec<(String, PathBuf, usize)>) = match &target.item.kind
+            {
+                crate::types::ItemKind::Struct { fields, .. } => {
+                    let mentioned: Vec<&str> = self
+                        .proposed_change
+                        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                        .filter(|word| fields.iter().any(|f| f.name.as_deref() == Some(*word)))
+                        .collect();
+                    let accesses = gravity
+                        .find_field_accesses(&target.item.name, None)
+                        .into_iter()
+                        .filter(|a| mentioned.is_empty() || mentioned.contains(&a.field.as_str()))
+                        .map(|a| {
+                            let heading = format!(
+                                "Field {} of `.{}` in `{}`{}",
+                                a.kind.label(),
+                                a.field,
+                                a.context,
+                                if a.inferred {
+                                    " (receiver type inferred)"
+                                } else {
+                                    ""
+                                }
+                            );
+                            (heading, a.file, a.line)
+                        })
+                        .collect();
+                    ("Field Access Sites", accesses)
+                }
+                _ => {
+                    let calls = gravity
+                        .find_call_sites(&self.target_name)
+                        .into_iter()
+                        .map(|site| {
+                            (
+                                format!("Call site in `{}()`", site.caller),
+                                site.file.clone(),
+                                site.line,
+                            )
+                        })
+                        .collect();
+                    ("Call Sites", calls)
+                }
+            };
 
-        // Build the target description
-        let target_description = match &target.item.kind {
-            crate::types::ItemKind::Struct { fields, .. } => {
-                let field_list: Vec<String> = fields
-                    .iter()
-                    .map(|f| format!("  {}: {}", f.name.as_deref().unwrap_or("_"), f.ty))
-                    .collect();
-                format!(
-                    "struct {} {{\n{}\n}}",
-                    target.item.name,
-                    field_list.join(",\n")
-                )
+            if sites.is_empty() {
+                return Ok(CallToolResult::text_content(vec![TextContent::from(
+                    format!(
+                        "No {} found for '{}'. The change appears safe from a usage perspective, \
+                     but manual review is still recommended.",
+                        site_label.to_lowercase(),
+                        self.target_name
+                    ),
+                )]));
             }
-            crate::types::ItemKind::Function {
-                parameters,
-                return_type,
-                is_async,
-            } => {
-                let params: Vec<String> = parameters
-                    .iter()
-                    .map(|p| format!("{}: {}", p.name, p.ty))
-                    .collect();
-                let ret = return_type
-                    .as_ref()
-                    .map(|r| format!(" -> {}", r))
-                    .unwrap_or_default();
-                let async_kw = if *is_async { "async " } else { "" };
-                format!(
-                    "{}fn {}({}){}",
-                    async_kw,
-                    target.item.name,
-                    params.join(", "),
-                    ret
-                )
+
+            // Collect context from the sites
+            let mut site_contexts = Vec::new();
+            for (heading, file, line) in sites.iter().take(max_sites) {
+                // Read a few lines around each site for context
+                let context = Self::read_call_site_context(file, *line);
+                site_contexts.push(format!(
+                    "### {} at {}:{}\n```rust\n{}\n```",
+                    heading,
+                    file.display(),
+                    line,
+                    context
+                ));
             }
-            _ => format!("{} ({})", target.item.name, target.context.breadcrumbs),
-        };
 
-        // Build the prompt for the LLM
-        let prompt = format!(
-            r#"You are a Rust code safety auditor. Analyze whether the following proposed change could break existing code.
+            // Build the target description
+            let target_description = match &target.item.kind {
+                crate::types::ItemKind::Struct { fields, .. } => {
+                    let field_list: Vec<String> = fields
+                        .iter()
+                        .map(|f| format!("  {}: {}", f.name.as_deref().unwrap_or("_"), f.ty))
+                        .collect();
+                    format!(
+                        "struct {} {{\n{}\n}}",
+                        target.item.name,
+                        field_list.join(",\n")
+                    )
+                }
+                crate::types::ItemKind::Function {
+                    parameters,
+                    return_type,
+                    is_async,
+                    ..
+                } => {
+                    let params: Vec<String> = parameters
+                        .iter()
+                        .map(|p| format!("{}: {}", p.name, p.ty))
+                        .collect();
+                    let ret = return_type
+                        .as_ref()
+                        .map(|r| format!(" -> {}", r))
+                        .unwrap_or_default();
+                    let async_kw = if *is_async { "async " } else { "" };
+                    format!(
+                        "{}fn {}({}){}",
+                        async_kw,
+                        target.item.name,
+                        params.join(", "),
+                        ret
+                    )
+                }
+                _ => format!("{} ({})", target.item.name, target.context.breadcrumbs),
+            };
+
+            // Build the prompt for the LLM
+            let prompt = format!(
+                r#"You are a Rust code safety auditor. Analyze whether the following proposed change could break existing code.
 
 ## Target Item
 ```rust
@@ -619,28 +1840,31 @@ Located at: {file}:{line}
 ## Proposed Change
 {proposed_change}
 
-## Call Sites ({count} of {total} shown)
-{call_sites}
+## {site_label} ({count} of {total} shown)
+{sites}
 
 ## Your Task
-1. For each call site, determine if the proposed change would:
+1. For each site, determine if the proposed change would:
    - Cause a compile error
    - Change runtime behavior in potentially breaking ways
    - Require updates to the calling code
 
 2. Rate the overall risk: LOW / MEDIUM / HIGH / CRITICAL
 
-3. Provide specific recommendations for each affected call site.
+3. Provide specific recommendations for each affected site.
 
 Respond in a structured format with clear assessments."#,
-            target_description = target_description,
-            file = target.item.file_path.display(),
-            line = target.item.span.start_line,
-            proposed_change = self.proposed_change,
-            count = call_site_contexts.len(),
-            total = call_sites.len(),
-            call_sites = call_site_contexts.join("\n\n"),
-        );
+                target_description = target_description,
+                file = target.item.file_path.display(),
+                line = target.item.span.start_line,
+                proposed_change = self.proposed_change,
+                site_label = site_label,
+                count = site_contexts.len(),
+                total = sites.len(),
+                sites = site_contexts.join("\n\n"),
+            );
+            (site_label, sites, site_contexts, prompt)
+        };
 
         // Create the sampling request
         let sampling_params = CreateMessageRequestParams {
@@ -696,19 +1920,20 @@ Respond in a structured format with clear assessments."#,
             self.proposed_change
         ));
         output.push_str(&format!(
-            "**Call Sites Analyzed:** {} of {}\n\n",
-            call_site_contexts.len(),
-            call_sites.len()
+            "**{} Analyzed:** {} of {}\n\n",
+            site_label,
+            site_contexts.len(),
+            sites.len()
         ));
         output.push_str("---\n\n");
         output.push_str("## LLM Analysis\n\n");
         output.push_str(&response_text);
 
-        if call_sites.len() > max_sites {
+        if sites.len() > max_sites {
             output.push_str(&format!(
-                "\n\n---\n*Note: {} additional call sites were not analyzed. \
+                "\n\n---\n*Note: {} additional sites were not analyzed. \
                  Consider increasing `max_sites` for a more comprehensive audit.*",
-                call_sites.len() - max_sites
+                sites.len() - max_sites
             ));
         }
 
@@ -1125,10 +2350,7 @@ mod trait_blockers {
 
 impl DiagnoseTraitBound {
     pub fn call_tool(&self, project_root: &PathBuf) -> Result<CallToolResult, CallToolError> {
-        let mut gravity = SemanticGravity::new();
-        gravity
-            .analyze_project(project_root)
-            .map_err(|e| CallToolError::from_message(e.to_string()))?;
+        let gravity = analyzed(project_root)?;
 
         // Search for the struct
         let results = gravity.search(&self.struct_name);
@@ -1408,11 +2630,9 @@ impl Introspect {
             )]));
         }
 
-        // Analyze the project
-        let mut gravity = SemanticGravity::new();
-        gravity
-            .analyze_project(project_root)
-            .map_err(|e| CallToolError::from_message(e.to_string()))?;
+        // Sampling takes a while: work on a copy rather than keep the live
+        // analysis locked
+        let gravity = analyzed(project_root)?.into_owned();
 
         // Find the target symbol
         let results = gravity.search(&self.symbol);
@@ -1422,7 +2642,11 @@ impl Introspect {
             )]));
         }
 
-        let target = &results[0];
+        // Prefer the exact name over fuzzy matches
+        let target = results
+            .iter()
+            .find(|r| r.item.name == self.symbol)
+            .unwrap_or(&results[0]);
         let max_loops = self.max_loops.unwrap_or(5) as usize;
         let threshold = self.convergence_threshold.unwrap_or(0.85);
 
@@ -1588,6 +2812,7 @@ impl Introspect {
                 parameters,
                 return_type,
                 is_async,
+                ..
             } => {
                 if *is_async {
                     context.push_str("**Async:** yes\n");
@@ -2003,6 +3228,18 @@ tool_box!(
         SearchCode,
         GetSummary,
         FindCallers,
+        FindTypeUsages,
+        GetUntestedHotspots,
+        FindTests,
+        GetErrorFlow,
+        FindPanicSites,
+        FindAsyncHazards,
+        AuditUnsafe,
+        GetComplexityMetrics,
+        GetWorkspace,
+        SemanticDiff,
+        CheckSemver,
+        GetTraitMatrix,
         GetExternalUsages,
         AuditImpact,
         DiagnoseTraitBound,
@@ -2037,6 +3274,22 @@ pub async fn run_mcp_server(project_root: PathBuf) -> Result<(), Box<dyn std::er
         protocol_version: ProtocolVersion::V2025_11_25.into(),
     };
 
+    // Analyze once up front, then patch the analysis as files change
+    let mut gravity = SemanticGravity::new();
+    match gravity
+        .analyze_project(&project_root)
+        .map_err(WatchError::from)
+        .and_then(|()| LiveAnalysis::start(gravity))
+    {
+        Ok(live) => {
+            let _ = LIVE_ANALYSIS.set(live);
+        }
+        Err(e) => eprintln!(
+            "Warning: Not watching project, analyzing per request: {}",
+            e
+        ),
+    }
+
     let transport = StdioTransport::new(TransportOptions::default())?;
     let handler = CargomapServerHandler::new(project_root);
 
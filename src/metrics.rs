@@ -0,0 +1,224 @@
+//! Function complexity metrics
+//!
+//! Measured from the syntax tree of each function and method body:
+//! - Cyclomatic complexity: 1 + branches (`if`, `while`, `for`, extra match
+//!   arms, match guards, `&&` / `||`)
+//! - Cognitive complexity (SonarSource): control flow costs 1 plus its
+//!   nesting level; `else`, labeled jumps and each run of like boolean
+//!   operators cost 1
+//! - Max nesting of control flow and closures
+//! - Statements, parameters (excluding `self`) and early returns
+//!   (`return` before the end, and `?`)
+//!
+//! Nested `fn` items are measured on their own, not as part of the body
+//! that contains them. Macro bodies are opaque.
+
+use crate::types::FunctionMetrics;
+use syn::visit::{self, Visit};
+
+/// Measure a function from its signature and body
+pub fn measure(sig: &syn::Signature, body: &syn::Block) -> FunctionMetrics {
+    let mut visitor = MetricsVisitor {
+        metrics: FunctionMetrics {
+            cyclomatic: 1,
+            parameters: sig
+                .inputs
+                .iter()
+                .filter(|arg| matches!(arg, syn::FnArg::Typed(_)))
+                .count(),
+            ..Default::default()
+        },
+        nesting: 0,
+    };
+    visitor.visit_block(body);
+
+    // A trailing `return` is the normal exit, not an early one
+    if let Some(syn::Stmt::Expr(syn::Expr::Return(_), _)) = body.stmts.last() {
+        visitor.metrics.early_returns -= 1;
+    }
+
+    visitor.metrics
+}
+
+struct MetricsVisitor {
+    metrics: FunctionMetrics,
+    /// Current control-flow nesting level
+    nesting: usize,
+}
+
+impl MetricsVisitor {
+    /// Control flow that costs 1 plus the current nesting
+    fn structural(&mut self) {
+        self.metrics.cognitive += 1 + self.nesting;
+    }
+
+    /// Visit one level deeper
+    fn nested(&mut self, f: impl FnOnce(&mut Self)) {
+        self.nesting += 1;
+        self.metrics.max_nesting = self.metrics.max_nesting.max(self.nesting);
+        f(self);
+        self.nesting -= 1;
+    }
+
+    /// `if` / `else if` / `else`; only the head of the chain pays for nesting
+    fn if_chain(&mut self, expr: &syn::ExprIf, is_else_if: bool) {
+        self.metrics.cyclomatic += 1;
+        if is_else_if {
+            self.metrics.cognitive += 1;
+        } else {
+            self.structural();
+        }
+
+        self.visit_expr(&expr.cond);
+        self.nested(|v| v.visit_block(&expr.then_branch));
+
+        match expr.else_branch.as_ref().map(|(_, e)| e.as_ref()) {
+            Some(syn::Expr::If(else_if)) => self.if_chain(else_if, true),
+            Some(other) => {
+                self.metrics.cognitive += 1;
+                self.nested(|v| v.visit_expr(other));
+            }
+            None => {}
+        }
+    }
+}
+
+fn is_logical(op: &syn::BinOp) -> bool {
+    matches!(op, syn::BinOp::And(_) | syn::BinOp::Or(_))
+}
+
+impl<'ast> Visit<'ast> for MetricsVisitor {
+    fn visit_stmt(&mut self, stmt: &'ast syn::Stmt) {
+        if !matches!(stmt, syn::Stmt::Item(_)) {
+            self.metrics.statements += 1;
+        }
+        visit::visit_stmt(self, stmt);
+    }
+
+    fn visit_item(&mut self, _item: &'ast syn::Item) {
+        // Nested items are measured separately
+    }
+
+    fn visit_expr_if(&mut self, expr: &'ast syn::ExprIf) {
+        self.if_chain(expr, false);
+    }
+
+    fn visit_expr_match(&mut self, expr: &'ast syn::ExprMatch) {
+        self.metrics.cyclomatic += expr.arms.len().saturating_sub(1);
+        self.metrics.cyclomatic += expr.arms.iter().filter(|a| a.guard.is_some()).count();
+        self.structural();
+
+        self.visit_expr(&expr.expr);
+        self.nested(|v| {
+            for arm in &expr.arms {
+                v.visit_arm(arm);
+            }
+        });
+    }
+
+    fn visit_expr_while(&mut self, expr: &'ast syn::ExprWhile) {
+        self.metrics.cyclomatic += 1;
+        self.structural();
+        self.visit_expr(&expr.cond);
+        self.nested(|v| v.visit_block(&expr.body));
+    }
+
+    fn visit_expr_for_loop(&mut self, expr: &'ast syn::ExprForLoop) {
+        self.metrics.cyclomatic += 1;
+        self.structural();
+        self.visit_expr(&expr.expr);
+        self.nested(|v| v.visit_block(&expr.body));
+    }
+
+    fn visit_expr_loop(&mut self, expr: &'ast syn::ExprLoop) {
+        // Exits are counted at the `if` / `match` that breaks
+        self.structural();
+        self.nested(|v| v.visit_block(&expr.body));
+    }
+
+    fn visit_expr_closure(&mut self, expr: &'ast syn::ExprClosure) {
+        self.nested(|v| visit::visit_expr_closure(v, expr));
+    }
+
+    fn visit_expr_binary(&mut self, expr: &'ast syn::ExprBinary) {
+        if is_logical(&expr.op) {
+            self.metrics.cyclomatic += 1;
+            // `a && b && c` is one run; `a && b || c` is two
+            let continues_run = matches!(
+                expr.left.as_ref(),
+                syn::Expr::Binary(left) if std::mem::discriminant(&left.op) == std::mem::discriminant(&expr.op)
+            );
+            if !continues_run {
+                self.metrics.cognitive += 1;
+            }
+        }
+        visit::visit_expr_binary(self, expr);
+    }
+
+    fn visit_expr_break(&mut self, expr: &'ast syn::ExprBreak) {
+        if expr.label.is_some() {
+            self.metrics.cognitive += 1;
+        }
+        visit::visit_expr_break(self, expr);
+    }
+
+    fn visit_expr_continue(&mut self, expr: &'ast syn::ExprContinue) {
+        if expr.label.is_some() {
+            self.metrics.cognitive += 1;
+        }
+        visit::visit_expr_continue(self, expr);
+    }
+
+    fn visit_expr_return(&mut self, expr: &'ast syn::ExprReturn) {
+        self.metrics.early_returns += 1;
+        visit::visit_expr_return(self, expr);
+    }
+
+    fn visit_expr_try(&mut self, expr: &'ast syn::ExprTry) {
+        self.metrics.early_returns += 1;
+        visit::visit_expr_try(self, expr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure() {
+        let f: syn::ItemFn = syn::parse_quote! {
+            fn check(&self, items: &[Item], strict: bool) -> Result<usize, Error> {
+                let mut count = 0;
+                for item in items {                        // +1 (nesting 0)
+                    if item.valid && (strict || item.x) {  // +2 (nesting 1), +2 runs
+                        count += 1;
+                    } else if item.skip {                  // +1
+                        continue;
+                    } else {                               // +1
+                        match item.kind {                  // +3 (nesting 2)
+                            Kind::A => return Err(Error),
+                            Kind::B if strict => {}
+                            _ => {}
+                        }
+                    }
+                }
+                let total = lookup(count)?;
+                Ok(total)
+            }
+        };
+
+        let metrics = measure(&f.sig, &f.block);
+        assert_eq!(
+            metrics,
+            FunctionMetrics {
+                // for, if, &&, ||, else if, two extra arms, one guard
+                cyclomatic: 9,
+                cognitive: 10,
+                max_nesting: 3,
+                statements: 8,
+                parameters: 2,
+                early_returns: 2,
+            }
+        );
+    }
+}
@@ -0,0 +1,418 @@
+//! Panic site inventory - where non-test code can panic
+//!
+//! Walks the syntax tree of each file and records:
+//! - `unwrap()` / `expect()` (and `unwrap_err()` / `expect_err()`)
+//! - `panic!`, `unreachable!`, `todo!`, `unimplemented!`
+//! - `assert!`, `assert_eq!`, `assert_ne!` (`debug_assert!`s are compiled
+//!   out of release builds and not listed)
+//! - Indexing and slicing (`v[i]`, `&s[1..]`)
+//! - Division and remainder, unless an operand is visibly a float or the
+//!   divisor is a non-zero literal
+//!
+//! `#[cfg(test)]` modules, `#[test]` functions and files under `tests/` are
+//! skipped. Macro arguments are visited when they parse as expressions
+//! (`format!("{}", x.unwrap())`). Types aren't resolved, so a `/` on a
+//! float variable is reported like an integer division.
+
+use crate::parser::{is_cfg_test, is_test_fn};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+
+/// What can panic at a site
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PanicKind {
+    Unwrap,
+    Expect,
+    Panic,
+    Unreachable,
+    Todo,
+    Unimplemented,
+    Assert,
+    Index,
+    Division,
+}
+
+impl PanicKind {
+    pub const ALL: [PanicKind; 9] = [
+        PanicKind::Unwrap,
+        PanicKind::Expect,
+        PanicKind::Panic,
+        PanicKind::Unreachable,
+        PanicKind::Todo,
+        PanicKind::Unimplemented,
+        PanicKind::Assert,
+        PanicKind::Index,
+        PanicKind::Division,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PanicKind::Unwrap => "unwrap",
+            PanicKind::Expect => "expect",
+            PanicKind::Panic => "panic",
+            PanicKind::Unreachable => "unreachable",
+            PanicKind::Todo => "todo",
+            PanicKind::Unimplemented => "unimplemented",
+            PanicKind::Assert => "assert",
+            PanicKind::Index => "index",
+            PanicKind::Division => "division",
+        }
+    }
+
+    /// Parse a label as printed by [`PanicKind::label`]
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.label() == label)
+    }
+
+    fn from_macro(name: &str) -> Option<Self> {
+        match name {
+            "panic" => Some(PanicKind::Panic),
+            "unreachable" => Some(PanicKind::Unreachable),
+            "todo" => Some(PanicKind::Todo),
+            "unimplemented" => Some(PanicKind::Unimplemented),
+            "assert" | "assert_eq" | "assert_ne" => Some(PanicKind::Assert),
+            _ => None,
+        }
+    }
+}
+
+/// A place non-test code can panic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PanicSite {
+    pub kind: PanicKind,
+    pub file: PathBuf,
+    pub line: usize,
+    /// Enclosing item (e.g. "SemanticGravity::search" or "<module>")
+    pub context: String,
+    /// Trimmed source line
+    pub snippet: String,
+}
+
+/// A panic site with the ranking of its enclosing item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankedPanicSite {
+    pub site: PanicSite,
+    /// Work-site score of the enclosing item
+    pub score: f64,
+    /// Entry distance of the file (`usize::MAX` if unreachable)
+    pub entry_distance: usize,
+}
+
+/// Find panic sites in a source file, or `None` if it doesn't parse
+pub fn find_in_source(source: &str, path: &Path) -> Option<Vec<PanicSite>> {
+    let file = syn::parse_file(source).ok()?;
+
+    let mut visitor = PanicVisitor {
+        path,
+        lines: source.lines().collect(),
+        context: Vec::new(),
+        impl_type: None,
+        sites: Vec::new(),
+    };
+    visitor.visit_file(&file);
+
+    let mut sites = visitor.sites;
+    sites.sort_by_key(|s| s.line);
+    sites.dedup_by(|a, b| a.line == b.line && a.kind == b.kind);
+    Some(sites)
+}
+
+/// Whether an expression is visibly a float (`2.0`, `x as f64`)
+fn is_float(expr: &syn::Expr) -> bool {
+    match expr {
+        syn::Expr::Lit(lit) => matches!(lit.lit, syn::Lit::Float(_)),
+        syn::Expr::Cast(cast) => {
+            matches!(&*cast.ty, syn::Type::Path(p) if p.path.is_ident("f64") || p.path.is_ident("f32"))
+        }
+        syn::Expr::Paren(paren) => is_float(&paren.expr),
+        syn::Expr::Unary(unary) => is_float(&unary.expr),
+        syn::Expr::Binary(binary) => is_float(&binary.left) || is_float(&binary.right),
+        syn::Expr::MethodCall(call) => is_float(&call.receiver),
+        _ => false,
+    }
+}
+
+/// Whether a divisor is a literal that can't be zero
+fn is_nonzero_literal(expr: &syn::Expr) -> bool {
+    match expr {
+        syn::Expr::Lit(lit) => match &lit.lit {
+            syn::Lit::Int(int) => int.base10_digits().chars().any(|c| c != '0'),
+            syn::Lit::Float(_) => true,
+            _ => false,
+        },
+        syn::Expr::Paren(paren) => is_nonzero_literal(&paren.expr),
+        _ => false,
+    }
+}
+
+struct PanicVisitor<'a> {
+    path: &'a Path,
+    lines: Vec<&'a str>,
+    /// Enclosing item names
+    context: Vec<String>,
+    /// Self type of the enclosing impl block
+    impl_type: Option<String>,
+    sites: Vec<PanicSite>,
+}
+
+impl PanicVisitor<'_> {
+    /// Visit inside a named item
+    fn nested(&mut self, name: String, f: impl FnOnce(&mut Self)) {
+        self.context.push(name);
+        f(self);
+        self.context.pop();
+    }
+
+    fn record(&mut self, kind: PanicKind, line: usize) {
+        let snippet = self
+            .lines
+            .get(line.wrapping_sub(1))
+            .map(|l| l.trim().chars().take(120).collect())
+            .unwrap_or_default();
+
+        self.sites.push(PanicSite {
+            kind,
+            file: self.path.to_path_buf(),
+            line,
+            context: self
+                .context
+                .last()
+                .cloned()
+                .unwrap_or_else(|| "<module>".to_string()),
+            snippet,
+        });
+    }
+}
+
+impl PanicVisitor<'_> {
+    /// Record `.unwrap()`, `.expect(..)` and indexing in macro tokens that
+    /// aren't expressions (`json!({ "id": ids[0] })`)
+    fn scan_tokens(&mut self, tokens: proc_macro2::TokenStream) {
+        use proc_macro2::{Delimiter, TokenTree};
+
+        let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+        for (i, token) in tokens.iter().enumerate() {
+            let previous = i.checked_sub(1).map(|p| &tokens[p]);
+            match token {
+                TokenTree::Ident(ident) => {
+                    let kind = match ident.to_string().as_str() {
+                        "unwrap" | "unwrap_err" => PanicKind::Unwrap,
+                        "expect" | "expect_err" => PanicKind::Expect,
+                        _ => continue,
+                    };
+                    let after_dot =
+                        matches!(previous, Some(TokenTree::Punct(p)) if p.as_char() == '.');
+                    let called = matches!(tokens.get(i + 1), Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis);
+                    if after_dot && called {
+                        self.record(kind, ident.span().start().line);
+                    }
+                }
+                TokenTree::Group(group) => {
+                    // `x[..]` and `f()[..]`, but not `vec![..]`, `&[..]` or `: [..]`
+                    let indexes = group.delimiter() == Delimiter::Bracket
+                        && match previous {
+                            Some(TokenTree::Ident(ident)) => !matches!(
+                                ident.to_string().as_str(),
+                                "in" | "return" | "break" | "else" | "mut"
+                            ),
+                            Some(TokenTree::Group(_)) => true,
+                            _ => false,
+                        };
+                    if indexes {
+                        self.record(PanicKind::Index, group.span_open().start().line);
+                    }
+                    self.scan_tokens(group.stream());
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for PanicVisitor<'_> {
+    fn visit_item_mod(&mut self, item: &'ast syn::ItemMod) {
+        if !is_cfg_test(&item.attrs) {
+            visit::visit_item_mod(self, item);
+        }
+    }
+
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        if is_test_fn(&item.attrs) {
+            return;
+        }
+        self.nested(item.sig.ident.to_string(), |v| {
+            visit::visit_item_fn(v, item)
+        });
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        if is_cfg_test(&item.attrs) {
+            return;
+        }
+        let self_ty = &item.self_ty;
+        let self_type = quote::quote!(#self_ty).to_string().replace(' ', "");
+        let previous = self.impl_type.replace(self_type);
+        visit::visit_item_impl(self, item);
+        self.impl_type = previous;
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        if is_test_fn(&item.attrs) {
+            return;
+        }
+        let name = match &self.impl_type {
+            Some(ty) => format!("{}::{}", ty, item.sig.ident),
+            None => item.sig.ident.to_string(),
+        };
+        self.nested(name, |v| visit::visit_impl_item_fn(v, item));
+    }
+
+    fn visit_item_trait(&mut self, item: &'ast syn::ItemTrait) {
+        self.nested(item.ident.to_string(), |v| visit::visit_item_trait(v, item));
+    }
+
+    fn visit_trait_item_fn(&mut self, item: &'ast syn::TraitItemFn) {
+        let name = match self.context.last() {
+            Some(trait_name) => format!("{}::{}", trait_name, item.sig.ident),
+            None => item.sig.ident.to_string(),
+        };
+        self.nested(name, |v| visit::visit_trait_item_fn(v, item));
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        let kind = match call.method.to_string().as_str() {
+            "unwrap" | "unwrap_err" => Some(PanicKind::Unwrap),
+            "expect" | "expect_err" => Some(PanicKind::Expect),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            self.record(kind, call.method.span().start().line);
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_expr_index(&mut self, index: &'ast syn::ExprIndex) {
+        self.record(
+            PanicKind::Index,
+            index.bracket_token.span.open().start().line,
+        );
+        visit::visit_expr_index(self, index);
+    }
+
+    fn visit_expr_binary(&mut self, binary: &'ast syn::ExprBinary) {
+        let divides = matches!(
+            binary.op,
+            syn::BinOp::Div(_)
+                | syn::BinOp::Rem(_)
+                | syn::BinOp::DivAssign(_)
+                | syn::BinOp::RemAssign(_)
+        );
+        if divides
+            && !is_float(&binary.left)
+            && !is_float(&binary.right)
+            && !is_nonzero_literal(&binary.right)
+        {
+            self.record(PanicKind::Division, binary.op.span().start().line);
+        }
+        visit::visit_expr_binary(self, binary);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        let name = mac
+            .path
+            .segments
+            .last()
+            .map(|s| s.ident.to_string())
+            .unwrap_or_default();
+        if let Some(kind) = PanicKind::from_macro(&name) {
+            self.record(kind, mac.path.span().start().line);
+        }
+
+        visit::visit_macro(self, mac);
+        match mac.parse_body_with(
+            syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated,
+        ) {
+            Ok(args) => {
+                for arg in &args {
+                    self.visit_expr(arg);
+                }
+            }
+            Err(_) => self.scan_tokens(mac.tokens.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_panic_sites() {
+        let source = r#"
+pub struct Cache { items: Vec<u32> }
+
+impl Cache {
+    pub fn first(&self) -> u32 {
+        self.items[0]
+    }
+
+    pub fn average(&self, count: usize) -> f64 {
+        let total: u32 = self.items.iter().sum();
+        let per_item = total as usize / count;
+        let ratio = total as f64 / 2.0;
+        println!("{}", self.items.first().unwrap());
+        per_item as f64 + ratio / (self.items.len() / 2) as f64
+    }
+}
+
+fn load(path: &str) -> String {
+    assert!(!path.is_empty());
+    let text = std::fs::read_to_string(path).expect("readable");
+    match text.len() {
+        0 => unreachable!(),
+        _ => todo!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_first() {
+        Some(1).unwrap();
+    }
+}
+
+#[test]
+fn loose_test() {
+    panic!("only in tests");
+}
+"#;
+        let sites = find_in_source(source, Path::new("src/lib.rs")).unwrap();
+        let found: Vec<_> = sites
+            .iter()
+            .map(|s| (s.kind, s.context.as_str(), s.line))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (PanicKind::Index, "Cache::first", 6),
+                (PanicKind::Division, "Cache::average", 11),
+                (PanicKind::Unwrap, "Cache::average", 13),
+                (PanicKind::Assert, "load", 19),
+                (PanicKind::Expect, "load", 20),
+                (PanicKind::Unreachable, "load", 22),
+                (PanicKind::Todo, "load", 23),
+            ]
+        );
+        assert_eq!(sites[0].snippet, "self.items[0]");
+
+        // Macro bodies that aren't expressions are scanned token by token
+        let source = "fn report(ids: &[u32]) -> String {\n    json!({ \"first\": ids[0], \"all\": [1, 2], \"n\": parse(x).unwrap() }).to_string()\n}\n";
+        let sites = find_in_source(source, Path::new("src/report.rs")).unwrap();
+        let kinds: Vec<_> = sites.iter().map(|s| s.kind).collect();
+        assert_eq!(kinds, vec![PanicKind::Index, PanicKind::Unwrap]);
+        assert_eq!(PanicKind::from_label("division"), Some(PanicKind::Division));
+    }
+}
@@ -4,9 +4,11 @@
 //! top-level items and parse each individually. If one function has a syntax
 //! error, we can still "see" the rest of the module.
 
+use crate::metrics;
 use crate::types::*;
 use regex::Regex;
-use std::path::Path;
+use std::path::{Component, Path};
+use syn::spanned::Spanned;
 use syn::visit::Visit;
 use syn::{self, Attribute, File, Item, Visibility as SynVisibility};
 use thiserror::Error;
@@ -20,7 +22,64 @@ pub enum ParserError {
     Parse { file: String, message: String },
 }
 
+/// Whether `path` is build output or VCS data of the project at `root`: in
+/// a `target/` directory next to a `Cargo.toml`, or in `.git/`. Only the
+/// part of the path below `root` counts, so projects checked out under a
+/// directory named `target` are still analyzed.
+pub fn is_build_output(root: &Path, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(root) else {
+        return false;
+    };
+    let mut dir = root.to_path_buf();
+    for component in relative.components() {
+        let Component::Normal(name) = component else {
+            continue;
+        };
+        if name == ".git" || (name == "target" && dir.join("Cargo.toml").is_file()) {
+            return true;
+        }
+        dir.push(name);
+    }
+    false
+}
+
+/// `#[cfg(test)]`, or a `cfg` that requires `test` (`#[cfg(all(test, ...))]`)
+pub fn is_cfg_test(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path().is_ident("cfg")
+            && attr.meta.require_list().is_ok_and(|list| {
+                let tokens = list.tokens.to_string();
+                !tokens.contains("not")
+                    && tokens
+                        .split(|c: char| !c.is_alphanumeric() && c != '_')
+                        .any(|t| t == "test")
+            })
+    })
+}
+
+/// Test code: `#[cfg(test)]` or a test attribute
+pub fn is_test_fn(attrs: &[Attribute]) -> bool {
+    is_cfg_test(attrs) || attrs.iter().any(|attr| is_test_path(attr.path()))
+}
+
+/// Whether a parsed item's attributes (as kept on [`ParsedItem`]) include
+/// a test attribute
+pub fn has_test_attribute(attributes: &[String]) -> bool {
+    attributes.iter().any(|attr| {
+        let path = attr.trim_start_matches("#[").trim_end_matches(']');
+        syn::parse_str::<syn::Path>(path).is_ok_and(|path| is_test_path(&path))
+    })
+}
+
+/// `#[test]`, `#[tokio::test]`, `#[rstest]`, ...
+fn is_test_path(path: &syn::Path) -> bool {
+    path.segments.last().is_some_and(|s| s.ident == "test")
+        || path.is_ident("rstest")
+        || path.is_ident("test_case")
+}
+
 /// Partial parser that handles broken code gracefully
+#[derive(Clone)]
 pub struct PartialParser {
     // Reserved for future regex-based optimizations
 }
@@ -42,11 +101,9 @@ impl PartialParser {
 
         for entry in WalkDir::new(root)
             .into_iter()
+            .filter_entry(|e| !is_build_output(root, e.path()))
             .filter_map(|e| e.ok())
-            .filter(|e| {
-                e.path().extension().is_some_and(|ext| ext == "rs")
-                    && !e.path().to_string_lossy().contains("/target/")
-            })
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "rs"))
         {
             let path = entry.path();
             match self.parse_file(path) {
@@ -63,22 +120,28 @@ impl PartialParser {
     /// Parse a single file with fallback to partial parsing
     pub fn parse_file(&self, path: &Path) -> Result<ParsedFile, ParserError> {
         let content = std::fs::read_to_string(path)?;
+        self.parse_source(path, &content)
+    }
+
+    /// Parse source text as if it were the file at `path`
+    pub fn parse_source(&self, path: &Path, content: &str) -> Result<ParsedFile, ParserError> {
         let module_path = self.derive_module_path(path);
 
         // First, try to parse the whole file
-        match syn::parse_file(&content) {
+        match syn::parse_file(content) {
             Ok(file) => {
-                let items = self.extract_items(&file, path);
+                let (items, methods) = self.extract_items(&file, path);
                 Ok(ParsedFile {
                     path: path.to_path_buf(),
                     items,
+                    methods,
                     parse_errors: Vec::new(),
                     module_path,
                 })
             }
             Err(_) => {
                 // File has errors - fall back to partial parsing
-                self.parse_partial(path, &content, module_path)
+                self.parse_partial(path, content, module_path)
             }
         }
     }
@@ -91,6 +154,7 @@ impl PartialParser {
         module_path: Vec<String>,
     ) -> Result<ParsedFile, ParserError> {
         let mut items = Vec::new();
+        let mut methods = Vec::new();
         let mut errors = Vec::new();
 
         // Split the file into chunks by top-level item boundaries
@@ -98,13 +162,16 @@ impl PartialParser {
 
         for chunk in chunks {
             match self.parse_chunk(&chunk.text, path) {
-                Ok(mut parsed_items) => {
-                    // Adjust line numbers based on chunk offset
-                    for item in &mut parsed_items {
-                        item.span.start_line += chunk.start_line;
-                        item.span.end_line += chunk.start_line;
+                Ok((mut parsed_items, mut parsed_methods)) => {
+                    // Adjust line numbers based on chunk offset. Chunks start on the
+                    // line that closed the previous item, which is line 1 of the chunk.
+                    let line_offset = chunk.start_line.saturating_sub(1);
+                    for item in parsed_items.iter_mut().chain(&mut parsed_methods) {
+                        item.span.start_line += line_offset;
+                        item.span.end_line += line_offset;
                     }
                     items.extend(parsed_items);
+                    methods.extend(parsed_methods);
                 }
                 Err(e) => {
                     errors.push(ParseError {
@@ -143,6 +210,7 @@ impl PartialParser {
         Ok(ParsedFile {
             path: path.to_path_buf(),
             items,
+            methods,
             parse_errors: errors,
             module_path,
         })
@@ -273,15 +341,12 @@ impl PartialParser {
     }
 
     /// Parse a single chunk of code
-    fn parse_chunk(&self, chunk: &str, path: &Path) -> Result<Vec<ParsedItem>, ParserError> {
+    fn parse_chunk(&self, chunk: &str, path: &Path) -> Result<ItemsAndMethods, ParserError> {
         // Wrap in a module context for parsing
         let wrapped = format!("mod __wrapper__ {{ {} }}", chunk);
 
         match syn::parse_file(&wrapped) {
-            Ok(file) => {
-                let items = self.extract_items(&file, path);
-                Ok(items)
-            }
+            Ok(file) => Ok(self.extract_items(&file, path)),
             Err(e) => Err(ParserError::Parse {
                 file: path.display().to_string(),
                 message: e.to_string(),
@@ -289,11 +354,11 @@ impl PartialParser {
         }
     }
 
-    /// Extract ParsedItems from a syn::File
-    fn extract_items(&self, file: &File, path: &Path) -> Vec<ParsedItem> {
+    /// Extract ParsedItems from a syn::File, with impl methods kept apart
+    fn extract_items(&self, file: &File, path: &Path) -> ItemsAndMethods {
         let mut visitor = ItemVisitor::new(path);
         visitor.visit_file(file);
-        visitor.items
+        (visitor.items, visitor.methods)
     }
 
     /// Derive module path from file path
@@ -322,14 +387,73 @@ impl PartialParser {
     }
 }
 
+/// Last element of a flattened use path
+pub(crate) enum UseLeaf {
+    /// `path::*`
+    Glob,
+    /// `path::name` or `path::name as alias`
+    Name { name: String, alias: String },
+}
+
+/// Flatten the use tree stored in `ItemKind::Use` into (module segments,
+/// leaf) pairs; empty if it doesn't parse
+pub(crate) fn use_leaves(path: &str) -> Vec<(Vec<String>, UseLeaf)> {
+    let mut leaves = Vec::new();
+    if let Ok(tree) = syn::parse_str::<syn::UseTree>(path) {
+        flatten_use_tree(&tree, Vec::new(), &mut leaves);
+    }
+    leaves
+}
+
+fn flatten_use_tree(
+    tree: &syn::UseTree,
+    prefix: Vec<String>,
+    out: &mut Vec<(Vec<String>, UseLeaf)>,
+) {
+    match tree {
+        syn::UseTree::Path(p) => {
+            let mut prefix = prefix;
+            prefix.push(p.ident.to_string());
+            flatten_use_tree(&p.tree, prefix, out);
+        }
+        syn::UseTree::Name(n) => {
+            let name = n.ident.to_string();
+            out.push((
+                prefix,
+                UseLeaf::Name {
+                    alias: name.clone(),
+                    name,
+                },
+            ));
+        }
+        syn::UseTree::Rename(r) => out.push((
+            prefix,
+            UseLeaf::Name {
+                name: r.ident.to_string(),
+                alias: r.rename.to_string(),
+            },
+        )),
+        syn::UseTree::Glob(_) => out.push((prefix, UseLeaf::Glob)),
+        syn::UseTree::Group(g) => {
+            for tree in &g.items {
+                flatten_use_tree(tree, prefix.clone(), out);
+            }
+        }
+    }
+}
+
 struct ItemChunk {
     text: String,
     start_line: usize,
 }
 
 /// Visitor to extract items from syn AST
+/// Top-level items and impl methods extracted from one syntax tree
+type ItemsAndMethods = (Vec<ParsedItem>, Vec<ParsedItem>);
+
 struct ItemVisitor {
     items: Vec<ParsedItem>,
+    methods: Vec<ParsedItem>,
     path: std::path::PathBuf,
 }
 
@@ -337,6 +461,7 @@ impl ItemVisitor {
     fn new(path: &Path) -> Self {
         Self {
             items: Vec::new(),
+            methods: Vec::new(),
             path: path.to_path_buf(),
         }
     }
@@ -387,73 +512,165 @@ impl ItemVisitor {
             .iter()
             .filter(|a| !a.path().is_ident("doc"))
             .map(|a| {
-                let path = a
-                    .path()
-                    .segments
-                    .iter()
-                    .map(|s| s.ident.to_string())
-                    .collect::<Vec<_>>()
-                    .join("::");
+                let path = self.path_to_string(a.path());
+
+                // Keep the derived trait list, e.g. #[derive(Debug, Clone)]
+                if a.path().is_ident("derive") {
+                    let derived = a
+                        .parse_args_with(
+                            syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated,
+                        )
+                        .map(|paths| {
+                            paths
+                                .iter()
+                                .map(|p| self.path_to_string(p))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        })
+                        .unwrap_or_default();
+                    return format!("#[derive({})]", derived);
+                }
+
                 format!("#[{}]", path)
             })
             .collect()
     }
 
+    fn path_to_string(&self, path: &syn::Path) -> String {
+        path.segments
+            .iter()
+            .map(|s| s.ident.to_string())
+            .collect::<Vec<_>>()
+            .join("::")
+    }
+
+    /// Type parameters with their trait bounds, merging `where` clauses
+    fn generic_bounds(&self, generics: &syn::Generics) -> Vec<GenericBound> {
+        let trait_bounds = |bounds: &syn::punctuated::Punctuated<syn::TypeParamBound, _>| {
+            bounds
+                .iter()
+                .filter_map(|b| match b {
+                    syn::TypeParamBound::Trait(t) => {
+                        let path = self.path_to_string(&t.path);
+                        Some(match t.modifier {
+                            syn::TraitBoundModifier::Maybe(_) => format!("?{}", path),
+                            syn::TraitBoundModifier::None => path,
+                        })
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut params: Vec<GenericBound> = generics
+            .type_params()
+            .map(|p| GenericBound {
+                param: p.ident.to_string(),
+                bounds: trait_bounds(&p.bounds),
+            })
+            .collect();
+
+        for predicate in generics.where_clause.iter().flat_map(|w| &w.predicates) {
+            if let syn::WherePredicate::Type(pred) = predicate {
+                let ty = &pred.bounded_ty;
+                let name = quote::quote!(#ty).to_string();
+                if let Some(param) = params.iter_mut().find(|p| p.param == name) {
+                    param.bounds.extend(trait_bounds(&pred.bounds));
+                }
+            }
+        }
+
+        params
+    }
+
     fn type_to_string(&self, ty: &syn::Type) -> String {
         quote::quote!(#ty).to_string()
     }
+
+    fn function_kind(&self, sig: &syn::Signature, body: &syn::Block) -> ItemKind {
+        let parameters: Vec<Parameter> = sig
+            .inputs
+            .iter()
+            .map(|arg| match arg {
+                syn::FnArg::Receiver(r) => Parameter {
+                    name: "self".to_string(),
+                    ty: if r.reference.is_some() {
+                        if r.mutability.is_some() {
+                            "&mut self"
+                        } else {
+                            "&self"
+                        }
+                    } else {
+                        "self"
+                    }
+                    .to_string(),
+                    is_self: true,
+                },
+                syn::FnArg::Typed(t) => Parameter {
+                    name: {
+                        let pat = &t.pat;
+                        quote::quote!(#pat).to_string()
+                    },
+                    ty: self.type_to_string(&t.ty),
+                    is_self: false,
+                },
+            })
+            .collect();
+
+        let return_type = match &sig.output {
+            syn::ReturnType::Default => None,
+            syn::ReturnType::Type(_, ty) => Some(self.type_to_string(ty)),
+        };
+
+        ItemKind::Function {
+            is_async: sig.asyncness.is_some(),
+            parameters,
+            return_type,
+            metrics: Some(metrics::measure(sig, body)),
+        }
+    }
+
+    /// Source span of an item (1-based lines), starting after its outer
+    /// attributes and doc comments so it points at the declaration itself
+    fn item_span(&self, item: &impl quote::ToTokens) -> Span {
+        let mut tokens = quote::ToTokens::to_token_stream(item)
+            .into_iter()
+            .peekable();
+        while matches!(tokens.peek(), Some(proc_macro2::TokenTree::Punct(p)) if p.as_char() == '#')
+        {
+            // `#` followed by the bracketed attribute body
+            tokens.next();
+            tokens.next();
+        }
+
+        let start = tokens
+            .next()
+            .map(|t| t.span().start())
+            .unwrap_or_else(|| item.span().start());
+        let end = item.span().end();
+
+        Span {
+            start_line: start.line,
+            start_col: start.column,
+            end_line: end.line,
+            end_col: end.column,
+        }
+    }
 }
 
 impl<'ast> Visit<'ast> for ItemVisitor {
     fn visit_item(&mut self, item: &'ast Item) {
+        let span = self.item_span(item);
         let parsed = match item {
-            Item::Fn(f) => {
-                let params: Vec<Parameter> = f
-                    .sig
-                    .inputs
-                    .iter()
-                    .map(|arg| match arg {
-                        syn::FnArg::Receiver(r) => Parameter {
-                            name: "self".to_string(),
-                            ty: if r.reference.is_some() {
-                                if r.mutability.is_some() {
-                                    "&mut self"
-                                } else {
-                                    "&self"
-                                }
-                            } else {
-                                "self"
-                            }
-                            .to_string(),
-                            is_self: true,
-                        },
-                        syn::FnArg::Typed(t) => Parameter {
-                            name: quote::quote!(#t.pat).to_string(),
-                            ty: self.type_to_string(&t.ty),
-                            is_self: false,
-                        },
-                    })
-                    .collect();
-
-                let return_type = match &f.sig.output {
-                    syn::ReturnType::Default => None,
-                    syn::ReturnType::Type(_, ty) => Some(self.type_to_string(ty)),
-                };
-
-                Some(ParsedItem {
-                    kind: ItemKind::Function {
-                        is_async: f.sig.asyncness.is_some(),
-                        parameters: params,
-                        return_type,
-                    },
-                    name: f.sig.ident.to_string(),
-                    visibility: self.convert_visibility(&f.vis),
-                    span: Span::default(),
-                    file_path: self.path.clone(),
-                    attributes: self.attrs_to_strings(&f.attrs),
-                    doc_comment: self.extract_doc_comment(&f.attrs),
-                })
-            }
+            Item::Fn(f) => Some(ParsedItem {
+                kind: self.function_kind(&f.sig, &f.block),
+                name: f.sig.ident.to_string(),
+                visibility: self.convert_visibility(&f.vis),
+                span,
+                file_path: self.path.clone(),
+                attributes: self.attrs_to_strings(&f.attrs),
+                doc_comment: self.extract_doc_comment(&f.attrs),
+            }),
 
             Item::Struct(s) => {
                 let (fields, is_tuple) = match &s.fields {
@@ -465,6 +682,7 @@ impl<'ast> Visit<'ast> for ItemVisitor {
                                 name: f.ident.as_ref().map(|i| i.to_string()),
                                 ty: self.type_to_string(&f.ty),
                                 visibility: self.convert_visibility(&f.vis),
+                                attributes: self.attrs_to_strings(&f.attrs),
                             })
                             .collect();
                         (fields, false)
@@ -477,6 +695,7 @@ impl<'ast> Visit<'ast> for ItemVisitor {
                                 name: None,
                                 ty: self.type_to_string(&f.ty),
                                 visibility: self.convert_visibility(&f.vis),
+                                attributes: self.attrs_to_strings(&f.attrs),
                             })
                             .collect();
                         (fields, true)
@@ -488,7 +707,7 @@ impl<'ast> Visit<'ast> for ItemVisitor {
                     kind: ItemKind::Struct { fields, is_tuple },
                     name: s.ident.to_string(),
                     visibility: self.convert_visibility(&s.vis),
-                    span: Span::default(),
+                    span,
                     file_path: self.path.clone(),
                     attributes: self.attrs_to_strings(&s.attrs),
                     doc_comment: self.extract_doc_comment(&s.attrs),
@@ -508,6 +727,7 @@ impl<'ast> Visit<'ast> for ItemVisitor {
                                     name: f.ident.as_ref().map(|i| i.to_string()),
                                     ty: self.type_to_string(&f.ty),
                                     visibility: self.convert_visibility(&f.vis),
+                                    attributes: self.attrs_to_strings(&f.attrs),
                                 })
                                 .collect(),
                             syn::Fields::Unnamed(unnamed) => unnamed
@@ -517,6 +737,7 @@ impl<'ast> Visit<'ast> for ItemVisitor {
                                     name: None,
                                     ty: self.type_to_string(&f.ty),
                                     visibility: self.convert_visibility(&f.vis),
+                                    attributes: self.attrs_to_strings(&f.attrs),
                                 })
                                 .collect(),
                             syn::Fields::Unit => Vec::new(),
@@ -532,7 +753,7 @@ impl<'ast> Visit<'ast> for ItemVisitor {
                     kind: ItemKind::Enum { variants },
                     name: e.ident.to_string(),
                     visibility: self.convert_visibility(&e.vis),
-                    span: Span::default(),
+                    span,
                     file_path: self.path.clone(),
                     attributes: self.attrs_to_strings(&e.attrs),
                     doc_comment: self.extract_doc_comment(&e.attrs),
@@ -566,10 +787,11 @@ impl<'ast> Visit<'ast> for ItemVisitor {
                         self_type: self_type.clone(),
                         trait_name,
                         methods,
+                        generics: self.generic_bounds(&i.generics),
                     },
                     name: format!("impl {}", self_type),
                     visibility: Visibility::Private,
-                    span: Span::default(),
+                    span,
                     file_path: self.path.clone(),
                     attributes: self.attrs_to_strings(&i.attrs),
                     doc_comment: None,
@@ -608,14 +830,26 @@ impl<'ast> Visit<'ast> for ItemVisitor {
                     })
                     .collect();
 
+                let required_methods: Vec<String> = t
+                    .items
+                    .iter()
+                    .filter_map(|item| match item {
+                        syn::TraitItem::Fn(m) if m.default.is_none() => {
+                            Some(m.sig.ident.to_string())
+                        }
+                        _ => None,
+                    })
+                    .collect();
+
                 Some(ParsedItem {
                     kind: ItemKind::Trait {
                         methods,
                         supertraits,
+                        required_methods,
                     },
                     name: t.ident.to_string(),
                     visibility: self.convert_visibility(&t.vis),
-                    span: Span::default(),
+                    span,
                     file_path: self.path.clone(),
                     attributes: self.attrs_to_strings(&t.attrs),
                     doc_comment: self.extract_doc_comment(&t.attrs),
@@ -628,19 +862,20 @@ impl<'ast> Visit<'ast> for ItemVisitor {
                 },
                 name: m.ident.to_string(),
                 visibility: self.convert_visibility(&m.vis),
-                span: Span::default(),
+                span,
                 file_path: self.path.clone(),
                 attributes: self.attrs_to_strings(&m.attrs),
                 doc_comment: self.extract_doc_comment(&m.attrs),
             }),
 
             Item::Use(u) => {
-                let path = quote::quote!(#u.tree).to_string();
+                let tree = &u.tree;
+                let path = quote::quote!(#tree).to_string();
                 Some(ParsedItem {
                     kind: ItemKind::Use { path: path.clone() },
                     name: path,
                     visibility: self.convert_visibility(&u.vis),
-                    span: Span::default(),
+                    span,
                     file_path: self.path.clone(),
                     attributes: self.attrs_to_strings(&u.attrs),
                     doc_comment: None,
@@ -653,7 +888,7 @@ impl<'ast> Visit<'ast> for ItemVisitor {
                 },
                 name: c.ident.to_string(),
                 visibility: self.convert_visibility(&c.vis),
-                span: Span::default(),
+                span,
                 file_path: self.path.clone(),
                 attributes: self.attrs_to_strings(&c.attrs),
                 doc_comment: self.extract_doc_comment(&c.attrs),
@@ -666,7 +901,7 @@ impl<'ast> Visit<'ast> for ItemVisitor {
                 },
                 name: s.ident.to_string(),
                 visibility: self.convert_visibility(&s.vis),
-                span: Span::default(),
+                span,
                 file_path: self.path.clone(),
                 attributes: self.attrs_to_strings(&s.attrs),
                 doc_comment: self.extract_doc_comment(&s.attrs),
@@ -678,7 +913,7 @@ impl<'ast> Visit<'ast> for ItemVisitor {
                 },
                 name: t.ident.to_string(),
                 visibility: self.convert_visibility(&t.vis),
-                span: Span::default(),
+                span,
                 file_path: self.path.clone(),
                 attributes: self.attrs_to_strings(&t.attrs),
                 doc_comment: self.extract_doc_comment(&t.attrs),
@@ -694,7 +929,7 @@ impl<'ast> Visit<'ast> for ItemVisitor {
                     .map(|i| i.to_string())
                     .unwrap_or_else(|| "<anonymous>".to_string()),
                 visibility: Visibility::Private,
-                span: Span::default(),
+                span,
                 file_path: self.path.clone(),
                 attributes: self.attrs_to_strings(&m.attrs),
                 doc_comment: None,
@@ -707,8 +942,35 @@ impl<'ast> Visit<'ast> for ItemVisitor {
             self.items.push(item);
         }
 
+        // Items declared inside macro calls (`cfg_rt! { pub use task::spawn; }`,
+        // `cfg_if!` arms) keep their spans, so they're visited like any other
+        let body = match item {
+            Item::Macro(m) if m.ident.is_none() => {
+                syn::parse2::<syn::File>(m.mac.tokens.clone()).ok()
+            }
+            _ => None,
+        };
+        for inner in body.iter().flat_map(|body| &body.items) {
+            self.visit_item(inner);
+        }
+
         syn::visit::visit_item(self, item);
     }
+
+    /// Methods in impl blocks are collected apart from top-level items
+    fn visit_impl_item_fn(&mut self, method: &'ast syn::ImplItemFn) {
+        self.methods.push(ParsedItem {
+            kind: self.function_kind(&method.sig, &method.block),
+            name: method.sig.ident.to_string(),
+            visibility: self.convert_visibility(&method.vis),
+            span: self.item_span(method),
+            file_path: self.path.clone(),
+            attributes: self.attrs_to_strings(&method.attrs),
+            doc_comment: self.extract_doc_comment(&method.attrs),
+        });
+
+        syn::visit::visit_impl_item_fn(self, method);
+    }
 }
 
 #[cfg(test)]
@@ -736,4 +998,37 @@ struct StillWorks {
         let chunks = parser.split_into_items(broken_code);
         assert!(chunks.len() >= 2, "Should split into multiple chunks");
     }
+
+    #[test]
+    fn test_skip_build_output() {
+        // A project checked out under a directory named `target`
+        let root = std::env::temp_dir()
+            .join(format!("cargomap-target-{}", std::process::id()))
+            .join("target/app");
+        for dir in ["src/target", "target/debug", ".git"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in [
+            "src/lib.rs",
+            "src/target/mod.rs",
+            "target/debug/build.rs",
+            ".git/hook.rs",
+        ] {
+            std::fs::write(root.join(file), "fn f() {}\n").unwrap();
+        }
+        std::fs::write(root.join("Cargo.toml"), "[package]\nname = \"app\"\n").unwrap();
+
+        let mut found: Vec<String> = PartialParser::new()
+            .parse_project(&root)
+            .unwrap()
+            .iter()
+            .map(|f| f.path.strip_prefix(&root).unwrap().display().to_string())
+            .collect();
+        found.sort();
+        assert_eq!(found, vec!["src/lib.rs", "src/target/mod.rs"]);
+        assert!(is_build_output(&root, &root.join("target/debug/build.rs")));
+        assert!(!is_build_output(&root, &root.join("src/target/mod.rs")));
+
+        std::fs::remove_dir_all(root.parent().unwrap().parent().unwrap()).unwrap();
+    }
 }
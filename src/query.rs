@@ -0,0 +1,282 @@
+//! Structured search queries
+//!
+//! A query mixes free text with `key:value` filters, for example:
+//!
+//! ```text
+//! kind:fn vis:pub async:true returns:Result module:gravity impl:SemanticGravity attr:test doc:"cache" load
+//! ```
+//!
+//! Filters narrow the candidate items; the free text (`load`) is matched
+//! and ranked as in a plain search. Prefix a filter with `-` to negate it
+//! (`-attr:test`).
+
+use crate::types::{ItemKind, ParsedItem, Visibility};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum QueryError {
+    #[error("Unknown filter '{0}' (expected kind, vis, async, returns, module, impl, attr or doc)")]
+    UnknownFilter(String),
+    #[error("Invalid value '{value}' for '{key}': {expected}")]
+    InvalidValue {
+        key: String,
+        value: String,
+        expected: &'static str,
+    },
+    #[error("Unterminated quote in query")]
+    UnterminatedQuote,
+}
+
+/// Item kinds accepted by `kind:`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KindFilter {
+    Function,
+    Struct,
+    Enum,
+    Trait,
+    Impl,
+    Mod,
+    Use,
+    Const,
+    Static,
+    TypeAlias,
+    Macro,
+}
+
+impl KindFilter {
+    fn parse(value: &str) -> Option<Self> {
+        Some(match value {
+            "fn" | "function" | "method" => Self::Function,
+            "struct" => Self::Struct,
+            "enum" => Self::Enum,
+            "trait" => Self::Trait,
+            "impl" => Self::Impl,
+            "mod" | "module" => Self::Mod,
+            "use" => Self::Use,
+            "const" => Self::Const,
+            "static" => Self::Static,
+            "type" => Self::TypeAlias,
+            "macro" => Self::Macro,
+            _ => return None,
+        })
+    }
+
+    fn matches(self, kind: &ItemKind) -> bool {
+        matches!(
+            (self, kind),
+            (Self::Function, ItemKind::Function { .. })
+                | (Self::Struct, ItemKind::Struct { .. })
+                | (Self::Enum, ItemKind::Enum { .. })
+                | (Self::Trait, ItemKind::Trait { .. })
+                | (Self::Impl, ItemKind::Impl { .. })
+                | (Self::Mod, ItemKind::Mod { .. })
+                | (Self::Use, ItemKind::Use { .. })
+                | (Self::Const, ItemKind::Const { .. })
+                | (Self::Static, ItemKind::Static { .. })
+                | (Self::TypeAlias, ItemKind::TypeAlias { .. })
+                | (Self::Macro, ItemKind::Macro { .. })
+        )
+    }
+}
+
+/// A single `key:value` condition
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    Kind(KindFilter),
+    Visibility(Visibility),
+    Async(bool),
+    /// Substring of the return type
+    Returns(String),
+    /// Substring of the module path (e.g. `gravity` for `crate::gravity`)
+    Module(String),
+    /// Self type of the impl block the item is (or belongs to)
+    Impl(String),
+    /// Attribute path (e.g. `test` for `#[test]`)
+    Attr(String),
+    /// Case-insensitive substring of the doc comment
+    Doc(String),
+}
+
+/// Where an item sits, for filters that look beyond the item itself
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ItemContext<'a> {
+    /// Module path of the item's file (e.g. `crate::gravity`)
+    pub module: &'a str,
+    /// Self type of the enclosing impl block (or of the item, if it is an impl)
+    pub impl_type: Option<&'a str>,
+}
+
+impl Filter {
+    /// Check whether an item satisfies the filter
+    pub fn matches(&self, item: &ParsedItem, context: &ItemContext) -> bool {
+        match self {
+            Filter::Kind(kind) => kind.matches(&item.kind),
+            Filter::Visibility(vis) => &item.visibility == vis,
+            Filter::Async(expected) => {
+                matches!(item.kind, ItemKind::Function { is_async, .. } if is_async == *expected)
+            }
+            Filter::Returns(ty) => matches!(
+                &item.kind,
+                ItemKind::Function { return_type: Some(ret), .. } if ret.contains(ty.as_str())
+            ),
+            Filter::Module(module) => context.module.contains(module.as_str()),
+            Filter::Impl(ty) => context.impl_type.is_some_and(|t| {
+                // Compare without generics: `Foo<T>` matches `impl:Foo`
+                let base = t.split('<').next().unwrap_or(t).trim();
+                base == ty || base.rsplit("::").next() == Some(ty.as_str())
+            }),
+            Filter::Attr(attr) => item.attributes.iter().any(|a| {
+                let body = a.trim_start_matches("#[").trim_end_matches(']');
+                // `#[derive(Debug)]` matches attr:derive
+                let path = body.split('(').next().unwrap_or(body);
+                path == attr || path.rsplit("::").next() == Some(attr.as_str())
+            }),
+            Filter::Doc(text) => item
+                .doc_comment
+                .as_ref()
+                .is_some_and(|d| d.to_lowercase().contains(&text.to_lowercase())),
+        }
+    }
+}
+
+/// A parsed search query: free text plus filters
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchQuery {
+    /// Free-text part, matched against names and docs
+    pub text: String,
+    /// Filters that must hold (`true`) or must not hold (`false`)
+    pub filters: Vec<(Filter, bool)>,
+}
+
+impl SearchQuery {
+    /// Parse a query string
+    pub fn parse(input: &str) -> Result<Self, QueryError> {
+        let mut query = SearchQuery::default();
+        let mut words = Vec::new();
+
+        for token in tokenize(input)? {
+            let Some((key, value)) = token.split_once(':').filter(|(k, v)| {
+                // `std::fs` is a path, not a filter
+                !k.is_empty()
+                    && !v.is_empty()
+                    && !v.starts_with(':')
+                    && k.chars().all(|c| c.is_alphabetic() || c == '-')
+            }) else {
+                words.push(token);
+                continue;
+            };
+
+            let (key, keep) = match key.strip_prefix('-') {
+                Some(key) => (key, false),
+                None => (key, true),
+            };
+            query.filters.push((parse_filter(key, value)?, keep));
+        }
+
+        query.text = words.join(" ");
+        Ok(query)
+    }
+
+    /// Whether the query has any filters
+    pub fn has_filters(&self) -> bool {
+        !self.filters.is_empty()
+    }
+
+    /// Check an item against every filter
+    pub fn matches(&self, item: &ParsedItem, context: &ItemContext) -> bool {
+        self.filters
+            .iter()
+            .all(|(filter, keep)| filter.matches(item, context) == *keep)
+    }
+}
+
+fn parse_filter(key: &str, value: &str) -> Result<Filter, QueryError> {
+    let invalid = |expected| QueryError::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+        expected,
+    };
+
+    Ok(match key {
+        "kind" => Filter::Kind(KindFilter::parse(value).ok_or_else(|| {
+            invalid("fn, struct, enum, trait, impl, mod, use, const, static, type or macro")
+        })?),
+        "vis" => Filter::Visibility(match value {
+            "pub" | "public" => Visibility::Public,
+            "crate" => Visibility::Crate,
+            "super" => Visibility::Super,
+            "private" | "priv" => Visibility::Private,
+            "restricted" => Visibility::Restricted,
+            _ => return Err(invalid("pub, crate, super, private or restricted")),
+        }),
+        "async" => Filter::Async(value.parse().map_err(|_| invalid("true or false"))?),
+        "returns" => Filter::Returns(value.to_string()),
+        "module" | "mod" => Filter::Module(value.to_string()),
+        "impl" => Filter::Impl(value.to_string()),
+        "attr" => Filter::Attr(value.to_string()),
+        "doc" => Filter::Doc(value.to_string()),
+        _ => return Err(QueryError::UnknownFilter(key.to_string())),
+    })
+}
+
+/// Split on whitespace, keeping double-quoted sections together
+fn tokenize(input: &str) -> Result<Vec<String>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(QueryError::UnterminatedQuote);
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query() {
+        let query = SearchQuery::parse(
+            r#"kind:fn vis:pub async:true returns:Result -attr:test doc:"disk cache" load"#,
+        )
+        .unwrap();
+
+        assert_eq!(query.text, "load");
+        assert_eq!(
+            query.filters,
+            vec![
+                (Filter::Kind(KindFilter::Function), true),
+                (Filter::Visibility(Visibility::Public), true),
+                (Filter::Async(true), true),
+                (Filter::Returns("Result".to_string()), true),
+                (Filter::Attr("test".to_string()), false),
+                (Filter::Doc("disk cache".to_string()), true),
+            ]
+        );
+
+        assert!(SearchQuery::parse("colour:red").is_err());
+        assert!(SearchQuery::parse("kind:banana").is_err());
+        assert_eq!(
+            SearchQuery::parse("std::fs").unwrap().text,
+            "std::fs",
+            "paths are free text"
+        );
+    }
+}
@@ -0,0 +1,526 @@
+//! Path resolution - which item a path names
+//!
+//! Expands the use trees stored in `ItemKind::Use` (nested groups, globs,
+//! `as` renames and `self` / `super` / `crate` prefixes) and follows
+//! `pub use` re-exports and `pub mod` chains from the crate root, giving
+//! every public path of every item. `tokio::spawn` and `tokio::task::spawn`
+//! name the same definition, so both resolve to it and share one canonical
+//! path: the shortest public one. Items are also found under the path they
+//! are declared at, public or not.
+//!
+//! Only the library target is resolved (`src/` without `main.rs` and
+//! `src/bin`). Uses of other crates are skipped and re-exports are followed
+//! through at most [`MAX_DEPTH`] hops. Re-exports of other crates
+//! (`pub use clap_builder::*`) are recorded as forwards for the caller to
+//! follow into that crate. Items generated by macros aren't seen.
+
+use crate::diff;
+use crate::parser::{UseLeaf, use_leaves};
+use crate::semver;
+use crate::types::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Re-export hops and nested re-exported modules followed per path
+pub const MAX_DEPTH: usize = 6;
+
+/// What a name in a module refers to
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Target {
+    Item(usize),
+    Module(Vec<String>),
+    /// A path in another crate (a module, for globs)
+    External(String),
+}
+
+/// Name glob re-exports of other crates are recorded under
+const GLOB: &str = "*";
+
+/// Every path the items of one crate can be named by
+#[derive(Debug, Clone, Default)]
+pub struct PathResolver {
+    crate_name: String,
+    root: PathBuf,
+    items: Vec<ParsedItem>,
+    /// Module each item is declared in
+    modules: Vec<Vec<String>>,
+    /// Declared path -> item
+    declared: HashMap<String, usize>,
+    /// Public path -> item
+    public: BTreeMap<String, usize>,
+    /// Shortest public path of each item
+    canonical: HashMap<usize, String>,
+    /// Public path (or module, for globs) -> paths in the crates it is
+    /// re-exported from
+    forwards: BTreeMap<String, Vec<String>>,
+    /// `pub` methods of inherent impls, with their type's name
+    methods: Vec<(String, ParsedItem)>,
+}
+
+impl PathResolver {
+    /// Resolve the library files of the crate rooted at `root` (the
+    /// directory holding its `src/`). Paths start with `crate_name`.
+    pub fn build(crate_name: &str, root: &Path, files: &[ParsedFile]) -> Self {
+        let mut resolver = PathResolver {
+            crate_name: crate_name.to_string(),
+            root: root.to_path_buf(),
+            ..Default::default()
+        };
+
+        let mut declared_modules = BTreeSet::new();
+        for file in files {
+            let Ok(relative) = file.path.strip_prefix(root) else {
+                continue;
+            };
+            if !semver::is_library_file(relative) {
+                continue;
+            }
+            let file_module = diff::module_of(relative);
+            // Methods are reached through their type
+            for method in &file.methods {
+                if let Some(ItemKind::Impl {
+                    trait_name: None,
+                    self_type,
+                    ..
+                }) = diff::enclosing_impl(file, method)
+                    && method.visibility == Visibility::Public
+                {
+                    resolver
+                        .methods
+                        .push((base_name(self_type), method.clone()));
+                }
+            }
+            for item in &file.items {
+                let module = diff::item_module(file, &file_module, item);
+                if matches!(item.kind, ItemKind::Mod { .. }) {
+                    let mut path = module.clone();
+                    path.push(item.name.clone());
+                    declared_modules.insert(path);
+                }
+                resolver.items.push(item.clone());
+                resolver.modules.push(module);
+            }
+        }
+
+        for (index, item) in resolver.items.iter().enumerate() {
+            if is_nameable(item) {
+                let path = resolver.join(&resolver.modules[index], &item.name);
+                resolver.declared.entry(path).or_insert(index);
+            }
+        }
+
+        let modules = Modules {
+            resolver: &resolver,
+            declared: &declared_modules,
+        };
+        let mut reachable = BTreeMap::new();
+        modules.walk(&[], crate_name, MAX_DEPTH, &mut reachable);
+        let mut public = BTreeMap::new();
+        for (path, target) in reachable {
+            match target {
+                Target::Item(index) => {
+                    public.insert(path, index);
+                }
+                Target::External(external) => {
+                    let path = match path.strip_suffix(GLOB) {
+                        Some(module) => module.trim_end_matches(':').to_string(),
+                        None => path,
+                    };
+                    resolver.forwards.entry(path).or_default().push(external);
+                }
+                Target::Module(_) => {}
+            }
+        }
+        for (index, item) in resolver.items.iter().enumerate() {
+            let exported = matches!(item.kind, ItemKind::Macro { .. })
+                && item.attributes.iter().any(|a| a.contains("macro_export"));
+            if exported {
+                public
+                    .entry(format!("{}::{}", crate_name, item.name))
+                    .or_insert(index);
+            }
+        }
+
+        for (path, &index) in &public {
+            let shorter = resolver.canonical.get(&index).is_none_or(|current| {
+                (path.matches("::").count(), path) < (current.matches("::").count(), current)
+            });
+            if shorter {
+                resolver.canonical.insert(index, path.clone());
+            }
+        }
+        resolver.public = public;
+        resolver
+    }
+
+    pub fn crate_name(&self) -> &str {
+        &self.crate_name
+    }
+
+    /// Directory holding the crate's `src/`
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Number of public paths
+    pub fn len(&self) -> usize {
+        self.public.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.public.is_empty()
+    }
+
+    /// Public paths and the items they name, sorted by path
+    pub fn public_paths(&self) -> impl Iterator<Item = (&str, &ParsedItem)> {
+        self.public
+            .iter()
+            .map(|(path, &index)| (path.as_str(), &self.items[index]))
+    }
+
+    /// `pub` methods of inherent impls under every public path of their
+    /// type (`tokio::Runtime::new`), sorted by path
+    pub fn public_methods(&self) -> Vec<(String, &ParsedItem)> {
+        let mut methods = Vec::new();
+        for (path, item) in self.public_paths() {
+            if !matches!(item.kind, ItemKind::Struct { .. } | ItemKind::Enum { .. }) {
+                continue;
+            }
+            for (_, method) in self.methods.iter().filter(|(owner, _)| *owner == item.name) {
+                methods.push((format!("{}::{}", path, method.name), method));
+            }
+        }
+        methods.sort_by(|a, b| a.0.cmp(&b.0));
+        methods
+    }
+
+    /// The item a path names, by public or declared path. Longer paths
+    /// resolve to their longest known prefix, so `Runtime::new` gives
+    /// `Runtime`.
+    pub fn resolve(&self, path: &str) -> Option<&ParsedItem> {
+        self.lookup(path).map(|(index, _)| &self.items[index])
+    }
+
+    /// The path to report for whatever `path` names: the shortest public
+    /// path of its item (else its declared path), with any segments past
+    /// the item kept (`tokio::task::spawn` -> `tokio::spawn`)
+    pub fn canonical(&self, path: &str) -> Option<String> {
+        let (index, rest) = self.lookup(path)?;
+        let base = match self.canonical.get(&index) {
+            Some(public) => public.clone(),
+            None => self.join(&self.modules[index], &self.items[index].name),
+        };
+        Some(format!("{}{}", base, rest))
+    }
+
+    /// Like [`canonical`](Self::canonical), but `None` for items other
+    /// crates can't name
+    pub fn public_path(&self, path: &str) -> Option<String> {
+        let (index, rest) = self.lookup(path)?;
+        Some(format!("{}{}", self.canonical.get(&index)?, rest))
+    }
+
+    /// Where a path this crate doesn't define may be re-exported from:
+    /// paths in other crates, most specific first (`clap::Parser` ->
+    /// `clap_derive::Parser`, then `clap_builder::Parser` through
+    /// `pub use clap_builder::*`)
+    pub fn forwards(&self, path: &str) -> Vec<String> {
+        let path = path.strip_prefix("::").unwrap_or(path);
+        let mut forwards = Vec::new();
+        let mut end = path.len();
+        loop {
+            let prefix = &path[..end];
+            for external in self.forwards.get(prefix).into_iter().flatten() {
+                forwards.push(format!("{}{}", external, &path[end..]));
+            }
+            match prefix.rfind("::") {
+                Some(shorter) => end = shorter,
+                None => return forwards,
+            }
+        }
+    }
+
+    /// Item of the longest known prefix of `path`, and the rest
+    /// (`"::new"`)
+    fn lookup<'p>(&self, path: &'p str) -> Option<(usize, &'p str)> {
+        let path = path.strip_prefix("::").unwrap_or(path);
+        let mut end = path.len();
+        loop {
+            let prefix = &path[..end];
+            if let Some(&index) = self
+                .public
+                .get(prefix)
+                .or_else(|| self.declared.get(prefix))
+            {
+                return Some((index, &path[end..]));
+            }
+            end = prefix.rfind("::")?;
+        }
+    }
+
+    fn join(&self, module: &[String], name: &str) -> String {
+        let mut path = vec![self.crate_name.as_str()];
+        path.extend(module.iter().map(String::as_str));
+        path.push(name);
+        path.join("::")
+    }
+}
+
+/// Items a path can name (modules and imports are followed instead)
+fn is_nameable(item: &ParsedItem) -> bool {
+    !matches!(
+        item.kind,
+        ItemKind::Mod { .. }
+            | ItemKind::Use { .. }
+            | ItemKind::Impl { .. }
+            | ItemKind::Unknown { .. }
+    )
+}
+
+/// `& 'a mut foo :: Bar < T >` -> `Bar`
+fn base_name(ty: &str) -> String {
+    let without_generics = ty.split('<').next().unwrap_or(ty);
+    without_generics
+        .rsplit("::")
+        .next()
+        .unwrap_or(without_generics)
+        .split_whitespace()
+        .filter(|t| *t != "&" && *t != "mut" && !t.starts_with('\''))
+        .collect::<String>()
+        .trim_start_matches('&')
+        .to_string()
+}
+
+/// Module structure of a crate being resolved
+struct Modules<'a> {
+    resolver: &'a PathResolver,
+    /// Every module path, declared inline or as a file
+    declared: &'a BTreeSet<Vec<String>>,
+}
+
+impl Modules<'_> {
+    /// Record the items and other crates' paths under `module`, reached
+    /// as `prefix`
+    fn walk(
+        &self,
+        module: &[String],
+        prefix: &str,
+        depth: usize,
+        out: &mut BTreeMap<String, Target>,
+    ) {
+        for (name, target) in self.exports(module, depth) {
+            let path = format!("{}::{}", prefix, name);
+            match target {
+                Target::Module(child) if depth > 0 => self.walk(&child, &path, depth - 1, out),
+                Target::Module(_) => {}
+                target => {
+                    out.entry(path).or_insert(target);
+                }
+            }
+        }
+    }
+
+    /// Names `module` exposes to other crates: its `pub` items and modules,
+    /// and what its `pub use` items bring in
+    fn exports(&self, module: &[String], depth: usize) -> BTreeSet<(String, Target)> {
+        let mut exports = BTreeSet::new();
+        let items = &self.resolver.items;
+        for (index, item) in items.iter().enumerate() {
+            if self.resolver.modules[index] != module || item.visibility != Visibility::Public {
+                continue;
+            }
+            match &item.kind {
+                ItemKind::Mod { .. } => {
+                    let mut child = module.to_vec();
+                    child.push(item.name.clone());
+                    exports.insert((item.name.clone(), Target::Module(child)));
+                }
+                ItemKind::Use { path } if depth > 0 => {
+                    for (segments, leaf) in use_leaves(path) {
+                        exports.extend(self.import(module, &segments, leaf, depth - 1));
+                    }
+                }
+                _ if is_nameable(item) => {
+                    exports.insert((item.name.clone(), Target::Item(index)));
+                }
+                _ => {}
+            }
+        }
+        exports
+    }
+
+    /// What one leaf of a use tree written in `module` brings in
+    fn import(
+        &self,
+        module: &[String],
+        segments: &[String],
+        leaf: UseLeaf,
+        depth: usize,
+    ) -> Vec<(String, Target)> {
+        let Some(target) = self.resolve_module(module, segments) else {
+            return self.import_external(segments, leaf);
+        };
+        match leaf {
+            UseLeaf::Glob => self.exports(&target, depth).into_iter().collect(),
+            // `use a::b::{self}` names the module `b`
+            UseLeaf::Name { name, alias } if name == "self" => {
+                let alias = if alias == "self" {
+                    target.last().cloned().unwrap_or_default()
+                } else {
+                    alias
+                };
+                vec![(alias, Target::Module(target))]
+            }
+            UseLeaf::Name { name, alias } => {
+                // Private items and modules can't be re-exported, so only
+                // exports count
+                self.exports(&target, depth)
+                    .into_iter()
+                    .filter(|(exported, _)| *exported == name)
+                    .map(|(_, target)| (alias.clone(), target))
+                    .collect()
+            }
+        }
+    }
+
+    /// A leaf of a use tree into another crate
+    fn import_external(&self, segments: &[String], leaf: UseLeaf) -> Vec<(String, Target)> {
+        let relative = matches!(
+            segments.first().map(String::as_str),
+            None | Some("crate" | "self" | "super")
+        );
+        if relative {
+            return Vec::new();
+        }
+        let module = segments.join("::");
+        match leaf {
+            UseLeaf::Glob => vec![(GLOB.to_string(), Target::External(module))],
+            UseLeaf::Name { name, alias } if name == "self" => {
+                let alias = if alias == "self" {
+                    segments.last().cloned().unwrap_or_default()
+                } else {
+                    alias
+                };
+                vec![(alias, Target::External(module))]
+            }
+            UseLeaf::Name { name, alias } => {
+                vec![(alias, Target::External(format!("{}::{}", module, name)))]
+            }
+        }
+    }
+
+    /// Module a use path written in `current` points into; `None` for
+    /// other crates
+    fn resolve_module(&self, current: &[String], segments: &[String]) -> Option<Vec<String>> {
+        let mut module = current.to_vec();
+        let mut rest = segments;
+        match segments.first().map(String::as_str) {
+            Some("crate") => {
+                module.clear();
+                rest = &segments[1..];
+            }
+            Some("self") => rest = &segments[1..],
+            Some("super") => {
+                while rest.first().map(String::as_str) == Some("super") {
+                    module.pop()?;
+                    rest = &rest[1..];
+                }
+            }
+            // The crate naming itself, as in doc examples
+            Some(name) if name == self.resolver.crate_name => {
+                module.clear();
+                rest = &segments[1..];
+            }
+            // Uniform paths: relative to the current module, else another crate
+            Some(first) => {
+                let mut child = module.clone();
+                child.push(first.to_string());
+                if !self.declared.contains(&child) {
+                    return None;
+                }
+            }
+            None => {}
+        }
+        module.extend(rest.iter().cloned());
+        Some(module)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::PartialParser;
+
+    #[test]
+    fn test_resolve_reexports() {
+        let parser = PartialParser::new();
+        let root = Path::new("/deps/tokio");
+        let files: Vec<ParsedFile> = [
+            (
+                "src/lib.rs",
+                "pub mod task;\nmod runtime;\ncfg_rt! { pub use task::spawn; }\npub use runtime::{Builder as RuntimeBuilder, *};\npub use tokio_macros::main;\npub mod io { pub use bytes::*; }\n#[macro_export]\nmacro_rules! select { () => {} }\n",
+            ),
+            (
+                "src/task/mod.rs",
+                "mod spawn;\npub use self::spawn::spawn;\npub(crate) fn internal() {}\n",
+            ),
+            ("src/task/spawn.rs", "pub fn spawn() {}\n"),
+            (
+                "src/runtime.rs",
+                "pub struct Runtime;\nimpl Runtime { pub fn new() -> Self { Runtime } }\npub struct Builder;\nfn hidden() {}\n",
+            ),
+        ]
+        .iter()
+        .map(|(path, source)| parser.parse_source(&root.join(path), source).unwrap())
+        .collect();
+
+        let resolver = PathResolver::build("tokio", root, &files);
+        let paths: Vec<&str> = resolver.public_paths().map(|(path, _)| path).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "tokio::Builder",
+                "tokio::Runtime",
+                "tokio::RuntimeBuilder",
+                "tokio::select",
+                "tokio::spawn",
+                "tokio::task::spawn",
+            ]
+        );
+
+        // Both public paths and the declared one name the same function
+        let spawn = resolver.resolve("tokio::spawn").unwrap();
+        assert_eq!(spawn.file_path, root.join("src/task/spawn.rs"));
+        for path in ["tokio::task::spawn", "tokio::task::spawn::spawn"] {
+            assert_eq!(resolver.canonical(path).as_deref(), Some("tokio::spawn"));
+        }
+        assert_eq!(
+            resolver
+                .canonical("tokio::runtime::Runtime::new")
+                .as_deref(),
+            Some("tokio::Runtime::new")
+        );
+
+        // Private items resolve by their declared path only
+        assert_eq!(
+            resolver.canonical("tokio::runtime::hidden").as_deref(),
+            Some("tokio::runtime::hidden")
+        );
+        assert!(resolver.public_path("tokio::runtime::hidden").is_none());
+        assert!(resolver.resolve("tokio::hidden").is_none());
+        assert!(resolver.canonical("serde::Serialize").is_none());
+
+        // Re-exports of other crates are forwarded
+        assert_eq!(resolver.forwards("tokio::main"), vec!["tokio_macros::main"]);
+        assert_eq!(resolver.forwards("tokio::io::Buf"), vec!["bytes::Buf"]);
+        assert!(resolver.forwards("tokio::spawn").is_empty());
+
+        // Inherent methods are listed under each path of their type
+        let methods: Vec<String> = resolver
+            .public_methods()
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(methods, vec!["tokio::Runtime::new"]);
+    }
+}
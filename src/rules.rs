@@ -0,0 +1,296 @@
+//! Architecture layering rules
+//!
+//! Rules are declared as `[[rules]]` tables in `cargomap.toml` and checked
+//! against the module dependency graph (`use` items and qualified paths,
+//! including paths into other workspace members and external crates):
+//!
+//! ```toml
+//! # crate::types must not depend on crate::mcp
+//! [[rules]]
+//! name = "types-stay-pure"
+//! from = "crate::types"
+//! deny = ["crate::mcp"]
+//!
+//! # only crate::db may use sqlx
+//! [[rules]]
+//! to = "sqlx"
+//! only = ["crate::db"]
+//! ```
+//!
+//! `from` / `to` / list entries are module or crate paths and match
+//! themselves and everything below them. A `from` rule with `allow`
+//! restricts the module to the listed targets (plus itself and
+//! `std` / `core` / `alloc`).
+
+use crate::types::ModuleDependency;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Crates every module may use, even under an `allow` list
+const ALWAYS_ALLOWED: &[&str] = &["std", "core", "alloc"];
+
+/// One layering rule from `cargomap.toml`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LayerRule {
+    /// Identifier used in reports; defaults to `layer-rule-<n>`
+    pub name: Option<String>,
+    /// Modules the rule constrains the dependencies of
+    pub from: Option<String>,
+    /// Targets `from` must not depend on
+    #[serde(default)]
+    pub deny: Vec<String>,
+    /// If non-empty, the only targets `from` may depend on
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Module or crate whose users the rule constrains
+    pub to: Option<String>,
+    /// The only modules that may depend on `to`
+    #[serde(default)]
+    pub only: Vec<String>,
+}
+
+impl LayerRule {
+    /// Check the rule is one of the supported shapes: `from` with `deny`
+    /// and/or `allow`, or `to` with `only`
+    pub fn validate(&self) -> Result<(), String> {
+        match (&self.from, &self.to) {
+            (Some(_), None) if self.only.is_empty() => {
+                if self.deny.is_empty() && self.allow.is_empty() {
+                    Err("a `from` rule needs `deny` or `allow`".to_string())
+                } else {
+                    Ok(())
+                }
+            }
+            (None, Some(_)) if self.deny.is_empty() && self.allow.is_empty() => {
+                if self.only.is_empty() {
+                    Err("a `to` rule needs `only`".to_string())
+                } else {
+                    Ok(())
+                }
+            }
+            (None, None) => Err("a rule needs `from` or `to`".to_string()),
+            _ => Err("use either `from` with `deny`/`allow`, or `to` with `only`".to_string()),
+        }
+    }
+
+    /// Name shown in reports for the rule at `index`
+    pub fn id(&self, index: usize) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("layer-rule-{}", index + 1))
+    }
+
+    /// Why `dep` breaks this rule, if it does
+    fn violated_by(&self, dep: &ModuleDependency) -> Option<String> {
+        if let Some(from) = &self.from {
+            if !covers(from, &dep.from_module) || covers(from, &dep.target) {
+                return None;
+            }
+            if let Some(denied) = self.deny.iter().find(|d| covers(d, &dep.target)) {
+                return Some(format!(
+                    "{} must not depend on {} (uses {})",
+                    dep.from_module, denied, dep.target
+                ));
+            }
+            let allowed = self.allow.is_empty()
+                || ALWAYS_ALLOWED.iter().any(|c| covers(c, &dep.target))
+                || self.allow.iter().any(|a| covers(a, &dep.target));
+            if !allowed {
+                return Some(format!(
+                    "{} may only depend on {} (uses {})",
+                    dep.from_module,
+                    self.allow.join(", "),
+                    dep.target
+                ));
+            }
+        }
+
+        if let Some(to) = &self.to
+            && covers(to, &dep.target)
+            && !covers(to, &dep.from_module)
+            && !self.only.iter().any(|o| covers(o, &dep.from_module))
+        {
+            return Some(format!(
+                "only {} may depend on {} (used by {})",
+                self.only.join(", "),
+                to,
+                dep.from_module
+            ));
+        }
+
+        None
+    }
+}
+
+/// Whether `path` is `pattern` or lies below it
+fn covers(pattern: &str, path: &str) -> bool {
+    path == pattern
+        || path
+            .strip_prefix(pattern)
+            .is_some_and(|rest| rest.starts_with("::"))
+}
+
+/// A dependency that breaks a rule
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    /// Index of the rule in the configuration
+    pub rule: usize,
+    pub message: String,
+    pub dependency: ModuleDependency,
+}
+
+/// Check every dependency against every rule
+pub fn evaluate(rules: &[LayerRule], dependencies: &[ModuleDependency]) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for dep in dependencies {
+        for (index, rule) in rules.iter().enumerate() {
+            if let Some(message) = rule.violated_by(dep) {
+                violations.push(Violation {
+                    rule: index,
+                    message,
+                    dependency: dep.clone(),
+                });
+            }
+        }
+    }
+    violations
+}
+
+/// Render violations as a SARIF 2.1.0 log, with file locations relative to
+/// `project_root`
+pub fn to_sarif(
+    violations: &[Violation],
+    rules: &[LayerRule],
+    project_root: &Path,
+) -> serde_json::Value {
+    let rule_descriptors: Vec<_> = rules
+        .iter()
+        .enumerate()
+        .map(|(index, rule)| {
+            let description = match (&rule.from, &rule.to) {
+                (Some(from), _) => format!("Layering constraints on {}", from),
+                (_, Some(to)) => format!("Restricted users of {}", to),
+                _ => "Layering rule".to_string(),
+            };
+            serde_json::json!({
+                "id": rule.id(index),
+                "shortDescription": { "text": description },
+            })
+        })
+        .collect();
+
+    let results: Vec<_> = violations
+        .iter()
+        .map(|v| {
+            let file = v.dependency.file.as_path();
+            let uri = file
+                .strip_prefix(project_root)
+                .unwrap_or(file)
+                .to_string_lossy()
+                .replace('\\', "/");
+            serde_json::json!({
+                "ruleId": rules[v.rule].id(v.rule),
+                "ruleIndex": v.rule,
+                "level": "error",
+                "message": { "text": v.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": uri, "uriBaseId": "%SRCROOT%" },
+                        "region": { "startLine": v.dependency.line.max(1) },
+                    }
+                }],
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "cargomap",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rule_descriptors,
+                }
+            },
+            "results": results,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ProjectConfig;
+
+    fn dep(from_module: &str, target: &str) -> ModuleDependency {
+        ModuleDependency {
+            from_module: from_module.to_string(),
+            target: target.to_string(),
+            file: "/project/src/lib.rs".into(),
+            line: 1,
+        }
+    }
+
+    #[test]
+    fn test_layer_rules() {
+        let config = ProjectConfig::parse(
+            r#"
+[[rules]]
+from = "crate::types"
+deny = ["crate::mcp"]
+
+[[rules]]
+name = "db-only"
+to = "sqlx"
+only = ["crate::db"]
+
+[[rules]]
+from = "crate::parser"
+allow = ["crate::types", "syn"]
+"#,
+        )
+        .unwrap();
+        let deps = [
+            dep("crate::types", "crate::mcp::Server"),
+            dep("crate::types::inner", "crate::mcp"),
+            dep("crate::types", "crate::mcpx::Thing"),
+            dep("crate::db::pool", "sqlx::PgPool"),
+            dep("crate::api", "sqlx::query"),
+            dep("crate::parser", "syn::File"),
+            dep("crate::parser", "std::fs::read"),
+            dep("crate::parser", "crate::parser::chunk"),
+            dep("crate::parser", "crate::gravity::SemanticGravity"),
+        ];
+
+        let violations = evaluate(&config.rules, &deps);
+        let broken: Vec<_> = violations
+            .iter()
+            .map(|v| (v.rule, v.dependency.target.as_str()))
+            .collect();
+        assert_eq!(
+            broken,
+            vec![
+                (0, "crate::mcp::Server"),
+                (0, "crate::mcp"),
+                (1, "sqlx::query"),
+                (2, "crate::gravity::SemanticGravity"),
+            ]
+        );
+
+        let sarif = to_sarif(&violations, &config.rules, Path::new("/project"));
+        let result = &sarif["runs"][0]["results"][2];
+        assert_eq!(result["ruleId"], "db-only");
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "src/lib.rs"
+        );
+
+        assert!(
+            ProjectConfig::parse("[[rules]]\nfrom = \"crate::a\"\nonly = [\"crate::b\"]\n")
+                .is_err()
+        );
+    }
+}
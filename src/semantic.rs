@@ -0,0 +1,167 @@
+//! Semantic index - item embeddings for natural-language search
+//!
+//! Each item is embedded from its kind, name, signature, breadcrumbs and doc
+//! comment (see `SemanticGravity::embedding_text`). Vectors are keyed by a
+//! hash of that text and cached in `target/cargomap/semantic-index.json`, so
+//! a refresh only embeds items whose text changed since the last run.
+
+use crate::embedding::{self, EmbeddingError};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Bumped when the embedded text or model changes, invalidating old caches
+const INDEX_VERSION: u32 = 1;
+
+#[derive(Error, Debug)]
+pub enum SemanticError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Embedding error: {0}")]
+    Embedding(#[from] EmbeddingError),
+}
+
+/// Embedding vectors for item texts, keyed by text hash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SemanticIndex {
+    version: u32,
+    vectors: HashMap<String, Vec<f32>>,
+}
+
+impl Default for SemanticIndex {
+    fn default() -> Self {
+        Self {
+            version: INDEX_VERSION,
+            vectors: HashMap::new(),
+        }
+    }
+}
+
+impl SemanticIndex {
+    /// Location of the cached index for a project
+    pub fn cache_path(project_root: &Path) -> PathBuf {
+        project_root
+            .join("target")
+            .join("cargomap")
+            .join("semantic-index.json")
+    }
+
+    /// Load the cached index, or an empty one if missing or outdated
+    pub fn load(project_root: &Path) -> Self {
+        std::fs::read_to_string(Self::cache_path(project_root))
+            .ok()
+            .and_then(|content| serde_json::from_str::<Self>(&content).ok())
+            .filter(|index| index.version == INDEX_VERSION)
+            .unwrap_or_default()
+    }
+
+    /// Write the index to the project's cache directory
+    pub fn save(&self, project_root: &Path) -> Result<(), SemanticError> {
+        let path = Self::cache_path(project_root);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Bring the index in line with `texts`: embed new texts and drop
+    /// vectors no longer used. Returns the number of texts embedded.
+    pub fn refresh<'a>(
+        &mut self,
+        texts: impl IntoIterator<Item = &'a str>,
+    ) -> Result<usize, SemanticError> {
+        self.refresh_with(texts, embedding::embed)
+    }
+
+    fn refresh_with<'a, E>(
+        &mut self,
+        texts: impl IntoIterator<Item = &'a str>,
+        mut embed: impl FnMut(&str) -> Result<Vec<f32>, E>,
+    ) -> Result<usize, SemanticError>
+    where
+        SemanticError: From<E>,
+    {
+        let mut used = HashSet::new();
+        let mut embedded = 0;
+
+        // Vectors go straight into the index, so a failed embedding keeps
+        // the cache and everything embedded before it
+        for text in texts {
+            let key = text_key(text);
+            if !self.vectors.contains_key(&key) {
+                let vector = embed(text)?;
+                embedded += 1;
+                self.vectors.insert(key.clone(), vector);
+            }
+            used.insert(key);
+        }
+
+        self.vectors.retain(|key, _| used.contains(key));
+        Ok(embedded)
+    }
+
+    /// Embedding for an item text, if indexed
+    pub fn vector(&self, text: &str) -> Option<&[f32]> {
+        self.vectors.get(&text_key(text)).map(Vec::as_slice)
+    }
+
+    /// Number of indexed texts
+    pub fn len(&self) -> usize {
+        self.vectors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vectors.is_empty()
+    }
+}
+
+/// Cosine similarity of two L2-normalized embeddings
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Stable cache key for an item text (FNV-1a, hex)
+fn text_key(text: &str) -> String {
+    let hash = text.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_only_embeds_changed_texts() {
+        let mut index = SemanticIndex::default();
+        let fake = |text: &str| Ok::<_, SemanticError>(vec![text.len() as f32]);
+
+        let embedded = index.refresh_with(["fn a", "fn bb"], fake).unwrap();
+        assert_eq!(embedded, 2);
+
+        let embedded = index.refresh_with(["fn bb", "fn ccc"], fake).unwrap();
+        assert_eq!(embedded, 1);
+        assert_eq!(index.len(), 2);
+        assert!(index.vector("fn a").is_none());
+        assert_eq!(index.vector("fn ccc"), Some(&[6.0][..]));
+
+        // A failure keeps cached vectors and those embedded before it
+        let failing = |text: &str| match text {
+            "fn dddd" => Ok(vec![7.0]),
+            _ => Err(SemanticError::Io(std::io::Error::other("offline"))),
+        };
+        assert!(
+            index
+                .refresh_with(["fn bb", "fn dddd", "fn eeeee"], failing)
+                .is_err()
+        );
+        assert_eq!(index.vector("fn bb"), Some(&[5.0][..]));
+        assert_eq!(index.vector("fn dddd"), Some(&[7.0][..]));
+        assert!(index.vector("fn ccc").is_some());
+    }
+}
@@ -0,0 +1,541 @@
+//! Semver checks - classify public API changes per Cargo's SemVer rules
+//!
+//! The public API is every public path [`PathResolver`] finds in the
+//! library: `pub` items reachable through `pub mod` chains and `pub use`
+//! re-exports. Methods of inherent impls count under each public path of
+//! their type.
+//!
+//! Changes are classified the way the Cargo book's SemVer compatibility
+//! chapter does:
+//! - Major: removed or hidden items, changed signatures and types, removed
+//!   or retyped public fields, new fields on structs without private fields,
+//!   new variants on enums without `#[non_exhaustive]`, new required trait
+//!   methods or supertraits, adding `#[non_exhaustive]`, lost trait impls
+//! - Minor: new items, new provided trait methods, new public fields or
+//!   variants where allowed, new trait impls
+//! - Patch: new private fields on structs that already had private ones
+
+use crate::diff::{kind_label, signature, visibility_label};
+use crate::resolve::PathResolver;
+use crate::traits::TraitMatrix;
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Size of a version bump, ordered from smallest to largest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl Bump {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Patch => "patch",
+            Self::Minor => "minor",
+            Self::Major => "major",
+        }
+    }
+}
+
+/// One classified change to the public API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiChange {
+    /// Public path (`config::Config::load`)
+    pub path: String,
+    pub kind: String,
+    pub bump: Bump,
+    pub reason: String,
+}
+
+/// Public API changes between two analyses
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SemverReport {
+    pub changes: Vec<ApiChange>,
+}
+
+impl SemverReport {
+    /// Smallest bump that covers every change
+    pub fn required(&self) -> Bump {
+        self.changes
+            .iter()
+            .map(|c| c.bump)
+            .max()
+            .unwrap_or(Bump::Patch)
+    }
+}
+
+/// A public item under the path users name it by
+#[derive(Debug, Clone)]
+pub struct ApiItem<'a> {
+    pub item: &'a ParsedItem,
+    /// File relative to the analyzed root
+    pub file: PathBuf,
+}
+
+/// The public API of the crate `resolver` resolved, keyed by public path
+/// without the crate name
+pub fn public_api(resolver: &PathResolver) -> BTreeMap<String, ApiItem<'_>> {
+    let items = resolver
+        .public_paths()
+        .map(|(path, item)| (path.to_string(), item))
+        .chain(resolver.public_methods());
+
+    let mut api = BTreeMap::new();
+    for (path, item) in items {
+        let Some(path) = path
+            .strip_prefix(resolver.crate_name())
+            .and_then(|p| p.strip_prefix("::"))
+        else {
+            continue;
+        };
+        let file = item
+            .file_path
+            .strip_prefix(resolver.root())
+            .unwrap_or(&item.file_path)
+            .to_path_buf();
+        api.entry(path.to_string())
+            .or_insert(ApiItem { item, file });
+    }
+    api
+}
+
+/// Whether a root-relative file belongs to the library target of the root
+/// package
+pub(crate) fn is_library_file(relative: &Path) -> bool {
+    relative.starts_with("src")
+        && relative != Path::new("src/main.rs")
+        && !relative.starts_with("src/bin")
+}
+
+fn is_non_exhaustive(item: &ParsedItem) -> bool {
+    item.attributes.iter().any(|a| a.contains("non_exhaustive"))
+}
+
+/// Classify the public API changes from `old` to `new`
+pub fn check(old: &ProjectAnalysis, new: &ProjectAnalysis) -> SemverReport {
+    let old_resolver = PathResolver::build("crate", &old.root, &old.files);
+    let new_resolver = PathResolver::build("crate", &new.root, &new.files);
+    let before = public_api(&old_resolver);
+    let after = public_api(&new_resolver);
+    let mut changes = Vec::new();
+
+    for (path, old_item) in &before {
+        match after.get(path) {
+            None => changes.push(ApiChange {
+                path: path.clone(),
+                kind: kind_label(&old_item.item.kind).to_string(),
+                bump: Bump::Major,
+                reason: "removed or no longer public".to_string(),
+            }),
+            Some(new_item) => {
+                for (bump, reason) in item_changes(old_item.item, new_item.item) {
+                    changes.push(ApiChange {
+                        path: path.clone(),
+                        kind: kind_label(&new_item.item.kind).to_string(),
+                        bump,
+                        reason,
+                    });
+                }
+            }
+        }
+    }
+    for (path, new_item) in after.iter().filter(|(p, _)| !before.contains_key(*p)) {
+        changes.push(ApiChange {
+            path: path.clone(),
+            kind: kind_label(&new_item.item.kind).to_string(),
+            bump: Bump::Minor,
+            reason: "added".to_string(),
+        });
+    }
+
+    // Trait impls on public types
+    let public_types = |api: &BTreeMap<String, ApiItem>| -> BTreeSet<String> {
+        api.values()
+            .filter(|a| matches!(a.item.kind, ItemKind::Struct { .. } | ItemKind::Enum { .. }))
+            .map(|a| a.item.name.clone())
+            .collect()
+    };
+    let impls =
+        |analysis: &ProjectAnalysis, types: &BTreeSet<String>| -> BTreeSet<(String, String)> {
+            TraitMatrix::build(&analysis.files)
+                .impls()
+                .iter()
+                .filter(|i| types.contains(&i.type_name))
+                .map(|i| (i.type_name.clone(), i.trait_name.clone()))
+                .collect()
+        };
+    let (old_types, new_types) = (public_types(&before), public_types(&after));
+    let old_impls = impls(old, &old_types);
+    let new_impls = impls(new, &new_types);
+    for (type_name, trait_name) in old_impls.difference(&new_impls) {
+        if new_types.contains(type_name) {
+            changes.push(ApiChange {
+                path: type_name.clone(),
+                kind: "impl".to_string(),
+                bump: Bump::Major,
+                reason: format!("no longer implements `{}`", trait_name),
+            });
+        }
+    }
+    for (type_name, trait_name) in new_impls.difference(&old_impls) {
+        if old_types.contains(type_name) {
+            changes.push(ApiChange {
+                path: type_name.clone(),
+                kind: "impl".to_string(),
+                bump: Bump::Minor,
+                reason: format!("now implements `{}`", trait_name),
+            });
+        }
+    }
+
+    changes.sort_by(|a, b| b.bump.cmp(&a.bump).then_with(|| a.path.cmp(&b.path)));
+    SemverReport { changes }
+}
+
+/// Classified changes between two versions of a public item
+fn item_changes(old: &ParsedItem, new: &ParsedItem) -> Vec<(Bump, String)> {
+    let mut changes = Vec::new();
+    let (old_kind, new_kind) = (kind_label(&old.kind), kind_label(&new.kind));
+    if old_kind != new_kind {
+        changes.push((
+            Bump::Major,
+            format!("changed from {} to {}", old_kind, new_kind),
+        ));
+        return changes;
+    }
+
+    match (is_non_exhaustive(old), is_non_exhaustive(new)) {
+        (false, true) => changes.push((Bump::Major, "became #[non_exhaustive]".to_string())),
+        (true, false) => changes.push((Bump::Minor, "no longer #[non_exhaustive]".to_string())),
+        _ => {}
+    }
+
+    match (&old.kind, &new.kind) {
+        (ItemKind::Function { .. }, ItemKind::Function { .. }) => {
+            let (before, after) = (signature(&old.kind), signature(&new.kind));
+            if before != after {
+                changes.push((
+                    Bump::Major,
+                    format!(
+                        "signature: {} -> {}",
+                        before.unwrap_or_default(),
+                        after.unwrap_or_default()
+                    ),
+                ));
+            }
+        }
+        (
+            ItemKind::Struct {
+                fields: before,
+                is_tuple: was_tuple,
+            },
+            ItemKind::Struct {
+                fields: after,
+                is_tuple,
+            },
+        ) => {
+            if was_tuple != is_tuple {
+                changes.push((Bump::Major, "changed struct form".to_string()));
+            }
+            // Without private fields (or #[non_exhaustive]) users can build
+            // the struct with a literal, so any new field breaks them
+            let constructible = !is_non_exhaustive(old)
+                && before.iter().all(|f| f.visibility == Visibility::Public);
+            field_changes("", before, after, constructible, &mut changes);
+        }
+        (ItemKind::Enum { variants: before }, ItemKind::Enum { variants: after }) => {
+            for variant in before {
+                match after.iter().find(|v| v.name == variant.name) {
+                    None => {
+                        changes.push((Bump::Major, format!("variant `{}` removed", variant.name)))
+                    }
+                    // Variant fields are public and constructible
+                    Some(new_variant) => field_changes(
+                        &format!("variant `{}` ", variant.name),
+                        &variant.fields,
+                        &new_variant.fields,
+                        true,
+                        &mut changes,
+                    ),
+                }
+            }
+            for variant in after
+                .iter()
+                .filter(|v| before.iter().all(|b| b.name != v.name))
+            {
+                let bump = if is_non_exhaustive(old) {
+                    Bump::Minor
+                } else {
+                    Bump::Major
+                };
+                changes.push((bump, format!("variant `{}` added", variant.name)));
+            }
+        }
+        (
+            ItemKind::Trait {
+                methods: old_methods,
+                supertraits: old_supertraits,
+                required_methods: old_required,
+            },
+            ItemKind::Trait {
+                methods,
+                supertraits,
+                required_methods,
+            },
+        ) => {
+            for method in methods.iter().filter(|m| !old_methods.contains(m)) {
+                if required_methods.contains(method) {
+                    changes.push((Bump::Major, format!("required method `{}` added", method)));
+                } else {
+                    changes.push((Bump::Minor, format!("provided method `{}` added", method)));
+                }
+            }
+            for method in old_methods.iter().filter(|m| !methods.contains(m)) {
+                changes.push((Bump::Major, format!("method `{}` removed", method)));
+            }
+            for method in required_methods
+                .iter()
+                .filter(|m| old_methods.contains(m) && !old_required.contains(m))
+            {
+                changes.push((Bump::Major, format!("method `{}` lost its default", method)));
+            }
+            for supertrait in supertraits.iter().filter(|s| !old_supertraits.contains(s)) {
+                changes.push((Bump::Major, format!("supertrait `{}` added", supertrait)));
+            }
+            for supertrait in old_supertraits.iter().filter(|s| !supertraits.contains(s)) {
+                changes.push((Bump::Major, format!("supertrait `{}` removed", supertrait)));
+            }
+        }
+        (ItemKind::Const { ty: before }, ItemKind::Const { ty: after })
+        | (ItemKind::TypeAlias { ty: before }, ItemKind::TypeAlias { ty: after })
+            if before != after =>
+        {
+            changes.push((Bump::Major, format!("type: {} -> {}", before, after)));
+        }
+        (
+            ItemKind::Static {
+                ty: before,
+                is_mut: was_mut,
+            },
+            ItemKind::Static { ty: after, is_mut },
+        ) => {
+            if before != after {
+                changes.push((Bump::Major, format!("type: {} -> {}", before, after)));
+            }
+            if was_mut != is_mut {
+                changes.push((
+                    Bump::Major,
+                    format!("mutability: {} -> {}", was_mut, is_mut),
+                ));
+            }
+        }
+        _ => {}
+    }
+
+    changes
+}
+
+/// Field changes; `constructible` types break on any new field
+fn field_changes(
+    prefix: &str,
+    old: &[StructField],
+    new: &[StructField],
+    constructible: bool,
+    out: &mut Vec<(Bump, String)>,
+) {
+    let label = |f: &StructField, i: usize| f.name.clone().unwrap_or_else(|| i.to_string());
+    let old: BTreeMap<String, &StructField> = old
+        .iter()
+        .enumerate()
+        .map(|(i, f)| (label(f, i), f))
+        .collect();
+    let new: BTreeMap<String, &StructField> = new
+        .iter()
+        .enumerate()
+        .map(|(i, f)| (label(f, i), f))
+        .collect();
+
+    for (name, field) in &old {
+        if prefix.is_empty() && field.visibility != Visibility::Public {
+            continue;
+        }
+        match new.get(name) {
+            None => out.push((Bump::Major, format!("{}field `{}` removed", prefix, name))),
+            Some(after) if after.ty != field.ty => out.push((
+                Bump::Major,
+                format!("{}field `{}`: {} -> {}", prefix, name, field.ty, after.ty),
+            )),
+            Some(after) if prefix.is_empty() && after.visibility != Visibility::Public => {
+                out.push((
+                    Bump::Major,
+                    format!(
+                        "field `{}` visibility: pub -> {}",
+                        name,
+                        visibility_label(after.visibility)
+                    ),
+                ))
+            }
+            _ => {}
+        }
+    }
+
+    for (name, field) in new.iter().filter(|(name, _)| !old.contains_key(*name)) {
+        let public = !prefix.is_empty() || field.visibility == Visibility::Public;
+        let bump = if constructible {
+            Bump::Major
+        } else if public {
+            Bump::Minor
+        } else {
+            Bump::Patch
+        };
+        out.push((
+            bump,
+            format!(
+                "{}{} field `{}` added",
+                prefix,
+                if public { "public" } else { "private" },
+                name
+            ),
+        ));
+    }
+}
+
+/// Largest change a version bump allows under Cargo's rules: the left-most
+/// non-zero component is the "major" one, so `0.3.1 -> 0.3.2` allows only
+/// minor changes and `0.0.1 -> 0.0.2` allows anything. `None` if either
+/// version can't be parsed.
+pub fn allowed_bump(from: &str, to: &str) -> Option<Bump> {
+    let parse = |v: &str| -> Option<[u64; 3]> {
+        let core = v.trim().split(['-', '+']).next()?;
+        let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+        Some([parts.next()??, parts.next()??, parts.next()??])
+    };
+    let (from, to) = (parse(from)?, parse(to)?);
+
+    // Index of the component that acts as "major"
+    let major = from.iter().position(|&c| c != 0).unwrap_or(2);
+    let changed = (0..3).find(|&i| from[i] != to[i]);
+    Some(match changed {
+        None => Bump::Patch,
+        Some(i) if i <= major => Bump::Major,
+        Some(i) if i == major + 1 => Bump::Minor,
+        Some(_) => Bump::Patch,
+    })
+}
+
+/// `[package] version` of the crate at `spec`: a git revision, or the
+/// working tree if `None`. Snapshot files don't record it.
+pub fn package_version(project_root: &Path, spec: Option<&str>) -> Option<String> {
+    let manifest = match spec {
+        None => std::fs::read_to_string(project_root.join("Cargo.toml")).ok()?,
+        Some(spec) if project_root.join(spec).is_file() => return None,
+        Some(rev) => {
+            let output = Command::new("git")
+                .current_dir(project_root)
+                .args(["show", &format!("{}:./Cargo.toml", rev)])
+                .output()
+                .ok()?;
+            if !output.status.success() {
+                return None;
+            }
+            String::from_utf8(output.stdout).ok()?
+        }
+    };
+    let manifest: toml::Value = toml::from_str(&manifest).ok()?;
+    manifest
+        .get("package")?
+        .get("version")?
+        .as_str()
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::PartialParser;
+    use crate::snapshot::SNAPSHOT_VERSION;
+
+    fn analysis(files: &[(&str, &str)]) -> ProjectAnalysis {
+        let parser = PartialParser::new();
+        ProjectAnalysis {
+            version: SNAPSHOT_VERSION,
+            generator: String::new(),
+            root: PathBuf::from("/p"),
+            files: files
+                .iter()
+                .map(|(path, source)| {
+                    parser
+                        .parse_source(&Path::new("/p").join(path), source)
+                        .unwrap()
+                })
+                .collect(),
+            dependencies: Vec::new(),
+            call_graph: CallGraph::default(),
+            module_tree: ModuleTree::default(),
+            reference_map: ReferenceMap::default(),
+            crate_references: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_classify_changes() {
+        let old = analysis(&[
+            (
+                "src/lib.rs",
+                "mod inner;\npub use inner::{Config, Mode as Kind};\npub trait Plugin { fn name(&self) -> String; }\n",
+            ),
+            (
+                "src/inner.rs",
+                "pub struct Config { pub port: u16, secret: String }\npub enum Mode { A }\nimpl Config { pub fn load() -> Config { todo!() } }\n",
+            ),
+        ]);
+        let new = analysis(&[
+            (
+                "src/lib.rs",
+                "mod inner;\npub use inner::{Config, Mode as Kind};\npub trait Plugin { fn name(&self) -> String; fn init(&self) {} fn stop(&self); }\npub fn helper() {}\n",
+            ),
+            (
+                "src/inner.rs",
+                "pub struct Config { pub port: u16, secret: String, cache: bool }\npub enum Mode { A, B }\nimpl Config { pub fn load(path: &str) -> Config { todo!() } }\n",
+            ),
+        ]);
+
+        let resolver = PathResolver::build("crate", &old.root, &old.files);
+        let api = public_api(&resolver);
+        let paths: Vec<&str> = api.keys().map(String::as_str).collect();
+        assert_eq!(paths, vec!["Config", "Config::load", "Kind", "Plugin"]);
+
+        let report = check(&old, &new);
+        let found: Vec<(Bump, &str, &str)> = report
+            .changes
+            .iter()
+            .map(|c| (c.bump, c.path.as_str(), c.reason.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    Bump::Major,
+                    "Config::load",
+                    "signature: fn() -> Config -> fn(path: & str) -> Config"
+                ),
+                (Bump::Major, "Kind", "variant `B` added"),
+                (Bump::Major, "Plugin", "required method `stop` added"),
+                (Bump::Minor, "Plugin", "provided method `init` added"),
+                (Bump::Minor, "helper", "added"),
+                (Bump::Patch, "Config", "private field `cache` added"),
+            ]
+        );
+        assert_eq!(report.required(), Bump::Major);
+
+        assert_eq!(allowed_bump("0.3.1", "0.3.2"), Some(Bump::Minor));
+        assert_eq!(allowed_bump("0.3.1", "0.4.0"), Some(Bump::Major));
+        assert_eq!(allowed_bump("1.2.3", "1.2.4"), Some(Bump::Patch));
+        assert_eq!(allowed_bump("0.0.3", "0.0.4"), Some(Bump::Major));
+    }
+}
@@ -0,0 +1,295 @@
+//! Analysis snapshots - a `ProjectAnalysis` saved to disk
+//!
+//! A snapshot holds everything the analyzer derived from a project (files
+//! and items, call graph, module tree, reference map, dependencies) so other
+//! tools can consume it without linking cargomap, and `diff` and `semver`
+//! can compare against it in place of a git revision. The views that need
+//! nothing else (public API, trait matrix, test map, type usages) are built
+//! straight from a loaded snapshot, so those commands skip re-analysis.
+//! Scoring also needs the workspace manifests, which a snapshot doesn't
+//! carry, so it is not loaded back into a `SemanticGravity`.
+//!
+//! Two encodings are supported:
+//! - JSON, for dashboards and scripts
+//! - Binary: an 8-byte magic, the format version as a little-endian `u32`,
+//!   then the analysis as MessagePack
+//!
+//! Both carry [`SNAPSHOT_VERSION`]; loading a snapshot written with a
+//! different version fails instead of misreading it.
+
+use crate::api::ApiSurface;
+use crate::testmap::TestMap;
+use crate::traits::TraitMatrix;
+use crate::types::{ModuleNode, ProjectAnalysis, TypeUsage};
+use crate::usage::{self, UsageIndex};
+use serde::Deserialize;
+use std::path::Path;
+use thiserror::Error;
+
+/// Version of the snapshot layout; bump when `ProjectAnalysis` or anything
+/// it contains changes shape
+pub const SNAPSHOT_VERSION: u32 = 5;
+
+/// Leading bytes of a binary snapshot
+const MAGIC: &[u8; 8] = b"CARGOMAP";
+
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Binary encode error: {0}")]
+    Encode(#[from] rmp_serde::encode::Error),
+    #[error("Binary decode error: {0}")]
+    Decode(#[from] rmp_serde::decode::Error),
+    #[error("Snapshot version {found} is not supported (expected {expected})")]
+    Version { found: u32, expected: u32 },
+    #[error("Unknown snapshot format '{0}' (expected json or binary)")]
+    Format(String),
+}
+
+/// On-disk encoding of a snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotFormat {
+    Json,
+    Binary,
+}
+
+impl SnapshotFormat {
+    /// Parse a format name (`json`, `binary` / `bin`)
+    pub fn parse(name: &str) -> Result<Self, SnapshotError> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "binary" | "bin" | "msgpack" => Ok(Self::Binary),
+            other => Err(SnapshotError::Format(other.to_string())),
+        }
+    }
+
+    /// Format implied by a file name: `.json` is JSON, anything else binary
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::Json,
+            _ => Self::Binary,
+        }
+    }
+}
+
+/// Just the version, read before decoding the rest of a JSON snapshot
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+fn check_version(found: u32) -> Result<(), SnapshotError> {
+    if found == SNAPSHOT_VERSION {
+        Ok(())
+    } else {
+        Err(SnapshotError::Version {
+            found,
+            expected: SNAPSHOT_VERSION,
+        })
+    }
+}
+
+impl ProjectAnalysis {
+    /// Encode the analysis
+    pub fn to_bytes(&self, format: SnapshotFormat) -> Result<Vec<u8>, SnapshotError> {
+        match format {
+            SnapshotFormat::Json => Ok(serde_json::to_vec_pretty(self)?),
+            SnapshotFormat::Binary => {
+                let mut bytes = MAGIC.to_vec();
+                bytes.extend_from_slice(&self.version.to_le_bytes());
+                // Named fields keep `#[serde(default)]` fields readable
+                bytes.extend(rmp_serde::to_vec_named(self)?);
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Decode a snapshot, detecting the format from its first bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        if let Some(rest) = bytes.strip_prefix(MAGIC) {
+            let (version, body) = rest
+                .split_at_checked(4)
+                .ok_or_else(|| SnapshotError::Format("truncated binary snapshot".to_string()))?;
+            check_version(u32::from_le_bytes(version.try_into().unwrap()))?;
+            return Ok(rmp_serde::from_slice(body)?);
+        }
+
+        let header: Header = serde_json::from_slice(bytes)?;
+        check_version(header.version)?;
+        Ok(serde_json::from_slice(bytes)?)
+    }
+
+    /// Write the analysis to `path`
+    pub fn save(&self, path: &Path, format: SnapshotFormat) -> Result<(), SnapshotError> {
+        std::fs::write(path, self.to_bytes(format)?)?;
+        Ok(())
+    }
+
+    /// Read a snapshot written by [`ProjectAnalysis::save`], in either format
+    pub fn load(path: &Path) -> Result<Self, SnapshotError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+}
+
+/// Views built from a loaded snapshot alone
+impl ProjectAnalysis {
+    /// Crate nodes of the module tree: the root for a single crate, its
+    /// children for a workspace
+    fn crate_nodes(&self) -> &[ModuleNode] {
+        let root = &self.module_tree.root;
+        if root.name == "workspace" {
+            &root.children
+        } else {
+            std::slice::from_ref(root)
+        }
+    }
+
+    /// Directories of the analyzed crates: the root and every crate
+    pub fn crate_roots(&self) -> Vec<&Path> {
+        let mut roots = vec![self.root.as_path()];
+        roots.extend(self.crate_nodes().iter().map(|node| node.path.as_path()));
+        roots
+    }
+
+    /// Name paths use for the crate at the analyzed root: its library
+    /// target, else its binary, like a workspace member's crate name
+    pub fn crate_name(&self) -> Option<String> {
+        let node = self
+            .crate_nodes()
+            .iter()
+            .find(|node| node.path == self.root)?;
+        let target = |kind: &str| {
+            node.children
+                .iter()
+                .find_map(|target| target.name.strip_prefix(kind))
+        };
+        target("lib ")
+            .or_else(|| target("bin "))
+            .map(|name| name.replace('-', "_"))
+    }
+
+    /// Public API of the library at the analyzed root. Declarations come
+    /// from the sources when they are still on disk, otherwise from the
+    /// recorded signatures.
+    pub fn api_surface(&self) -> ApiSurface {
+        let crate_name = self.crate_name().unwrap_or_else(|| "crate".to_string());
+        ApiSurface::build(self, &crate_name)
+    }
+
+    /// Trait implementations of the analyzed items
+    pub fn trait_matrix(&self) -> TraitMatrix {
+        TraitMatrix::build(&self.files)
+    }
+
+    /// Tests and the functions they reach through the recorded call graph
+    pub fn test_map(&self) -> TestMap {
+        TestMap::build(&self.files, &self.call_graph, &self.crate_roots())
+    }
+
+    /// Usages of a local type, structural ones first. Unlike
+    /// `SemanticGravity::find_type_usages` they are ranked by kind alone:
+    /// the cross-module and entry-distance adjustments need the workspace.
+    /// Function bodies are read from the sources when they are still on
+    /// disk; otherwise only the recorded signatures are searched.
+    pub fn find_type_usages(&self, type_name: &str) -> Vec<TypeUsage> {
+        let type_name = type_name.rsplit("::").next().unwrap_or(type_name);
+        let index = UsageIndex::build(&self.files);
+
+        let mut usages: Vec<TypeUsage> = self
+            .files
+            .iter()
+            .flat_map(|file| index.in_file(&file.path, type_name).iter().cloned())
+            .collect();
+        usage::rank(&mut usages);
+        usages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::PartialParser;
+    use crate::types::*;
+
+    #[test]
+    fn test_round_trip() {
+        let file = PartialParser::new()
+            .parse_source(
+                Path::new("src/lib.rs"),
+                "/// Docs\npub fn run(x: u32) -> Result<u32, Error> { if x > 1 { helper()?; } Ok(x) }\n",
+            )
+            .unwrap();
+        let analysis = ProjectAnalysis {
+            version: SNAPSHOT_VERSION,
+            generator: "cargomap test".to_string(),
+            root: "/project".into(),
+            files: vec![file],
+            dependencies: Vec::new(),
+            call_graph: CallGraph::default(),
+            module_tree: ModuleTree::default(),
+            reference_map: ReferenceMap::default(),
+            crate_references: Vec::new(),
+        };
+        let expected = serde_json::to_value(&analysis).unwrap();
+
+        for format in [SnapshotFormat::Json, SnapshotFormat::Binary] {
+            let bytes = analysis.to_bytes(format).unwrap();
+            let loaded = ProjectAnalysis::from_bytes(&bytes).unwrap();
+            assert_eq!(serde_json::to_value(&loaded).unwrap(), expected);
+        }
+
+        let mut stale = analysis.to_bytes(SnapshotFormat::Binary).unwrap();
+        stale[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&99u32.to_le_bytes());
+        assert!(matches!(
+            ProjectAnalysis::from_bytes(&stale),
+            Err(SnapshotError::Version { found: 99, .. })
+        ));
+    }
+
+    #[test]
+    fn test_views_without_sources() {
+        let root = std::env::temp_dir().join(format!("cargomap-snapshot-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"my-demo\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src/lib.rs"),
+            "#[derive(Clone)]\npub struct Config;\npub fn load(config: Config) -> u32 { 1 }\n\
+             #[test]\nfn loads() { load(Config); }\n",
+        )
+        .unwrap();
+
+        let mut gravity = crate::SemanticGravity::new();
+        gravity.analyze_project(&root).unwrap();
+        let bytes = gravity
+            .snapshot(&root, Vec::new())
+            .to_bytes(SnapshotFormat::Binary)
+            .unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        let analysis = ProjectAnalysis::from_bytes(&bytes).unwrap();
+
+        assert_eq!(analysis.crate_name().as_deref(), Some("my_demo"));
+        let api: Vec<String> = analysis
+            .api_surface()
+            .items
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect();
+        assert_eq!(api, vec!["my_demo::Config", "my_demo::load"]);
+        assert_eq!(analysis.trait_matrix().traits_of("Config").len(), 1);
+        let tests = analysis.test_map();
+        assert_eq!(tests.tests_for("load").len(), 1);
+        let usages = analysis.find_type_usages("Config");
+        assert!(
+            usages.iter().any(|u| u.kind == TypeUsageKind::Parameter),
+            "{:?}",
+            usages
+        );
+    }
+}
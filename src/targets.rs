@@ -0,0 +1,259 @@
+//! Cargo build targets - the entry points of a crate
+//!
+//! A package can have a library, any number of binaries, examples, benches
+//! and integration tests. Each is its own crate root: entry distances and
+//! module trees are computed from these roots instead of assuming a single
+//! `src/lib.rs` or `src/main.rs`.
+//!
+//! Targets come from `cargo metadata` when available; otherwise they are read
+//! from the manifest's `[lib]`, `[[bin]]`, `[[example]]`, `[[bench]]` and
+//! `[[test]]` tables and auto-discovered the way cargo does (`src/main.rs`,
+//! `src/bin/*.rs`, `examples/*.rs`, `tests/*/main.rs`, ...) unless turned off
+//! with `autobins = false` and friends.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Kind of build target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum TargetKind {
+    Lib,
+    Bin,
+    Example,
+    Bench,
+    Test,
+}
+
+impl TargetKind {
+    pub const ALL: [TargetKind; 5] = [
+        TargetKind::Lib,
+        TargetKind::Bin,
+        TargetKind::Example,
+        TargetKind::Bench,
+        TargetKind::Test,
+    ];
+
+    /// Parse a kind name (`lib`, `bin`, `example`, `bench`, `test`)
+    pub fn parse(name: &str) -> Option<Self> {
+        Some(match name.trim() {
+            "lib" => Self::Lib,
+            "bin" => Self::Bin,
+            "example" | "examples" => Self::Example,
+            "bench" | "benches" => Self::Bench,
+            "test" | "tests" => Self::Test,
+            _ => return None,
+        })
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Lib => "lib",
+            Self::Bin => "bin",
+            Self::Example => "example",
+            Self::Bench => "bench",
+            Self::Test => "test",
+        }
+    }
+
+    /// Manifest table, auto-discovery switch and directory for the kind
+    fn manifest_keys(self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            Self::Lib => ("lib", "autolib", "src"),
+            Self::Bin => ("bin", "autobins", "src/bin"),
+            Self::Example => ("example", "autoexamples", "examples"),
+            Self::Bench => ("bench", "autobenches", "benches"),
+            Self::Test => ("test", "autotests", "tests"),
+        }
+    }
+}
+
+/// One build target of a package
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrateTarget {
+    pub kind: TargetKind,
+    pub name: String,
+    /// Crate root file
+    pub entry: PathBuf,
+}
+
+/// Targets reported by `cargo metadata`, with paths re-rooted by `rebase`
+pub fn from_metadata(
+    targets: &[cargo_metadata::Target],
+    rebase: impl Fn(&Path) -> Option<PathBuf>,
+) -> Vec<CrateTarget> {
+    targets
+        .iter()
+        .filter_map(|target| {
+            let kind = if target.is_bin() {
+                TargetKind::Bin
+            } else if target.is_example() {
+                TargetKind::Example
+            } else if target.is_bench() {
+                TargetKind::Bench
+            } else if target.is_test() {
+                TargetKind::Test
+            } else if target.is_custom_build() {
+                return None;
+            } else {
+                // lib, rlib, proc-macro, cdylib, ...
+                TargetKind::Lib
+            };
+
+            Some(CrateTarget {
+                kind,
+                name: target.name.clone(),
+                entry: rebase(target.src_path.as_std_path())?,
+            })
+        })
+        .collect()
+}
+
+/// Targets of the package in `dir`, from its manifest (if any) plus cargo's
+/// auto-discovery rules
+pub fn from_manifest(dir: &Path, manifest: Option<&toml::Value>) -> Vec<CrateTarget> {
+    let package = manifest.and_then(|m| m.get("package"));
+    let package_name = package
+        .and_then(|p| p.get("name"))
+        .and_then(|n| n.as_str())
+        .map(str::to_string)
+        .or_else(|| dir.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "crate".to_string());
+    let auto = |key: &str| {
+        package
+            .and_then(|p| p.get(key))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true)
+    };
+
+    let mut targets: Vec<CrateTarget> = Vec::new();
+    for kind in TargetKind::ALL {
+        let (table, auto_key, auto_dir) = kind.manifest_keys();
+
+        // Explicit targets: `[lib]` is a table, the rest are arrays of tables
+        let explicit: Vec<&toml::Value> = match manifest.and_then(|m| m.get(table)) {
+            Some(toml::Value::Array(entries)) => entries.iter().collect(),
+            Some(entry @ toml::Value::Table(_)) => vec![entry],
+            _ => Vec::new(),
+        };
+        for entry in explicit {
+            let name = entry
+                .get("name")
+                .and_then(|n| n.as_str())
+                .unwrap_or(&package_name);
+            let path = match entry.get("path").and_then(|p| p.as_str()) {
+                Some(path) => dir.join(path),
+                None if kind == TargetKind::Lib => dir.join("src/lib.rs"),
+                None => dir.join(auto_dir).join(format!("{}.rs", name)),
+            };
+            push_target(&mut targets, kind, name, path);
+        }
+
+        if !auto(auto_key) {
+            continue;
+        }
+        match kind {
+            TargetKind::Lib => {
+                let lib = dir.join("src/lib.rs");
+                if lib.exists() && !targets.iter().any(|t| t.kind == TargetKind::Lib) {
+                    push_target(&mut targets, kind, &package_name, lib);
+                }
+            }
+            _ => {
+                if kind == TargetKind::Bin && dir.join("src/main.rs").exists() {
+                    push_target(&mut targets, kind, &package_name, dir.join("src/main.rs"));
+                }
+                for (name, path) in scan_target_dir(&dir.join(auto_dir)) {
+                    push_target(&mut targets, kind, &name, path);
+                }
+            }
+        }
+    }
+
+    targets
+}
+
+/// Add a target unless one of the same kind and name (or entry) exists;
+/// explicit targets are added first, so they win
+fn push_target(targets: &mut Vec<CrateTarget>, kind: TargetKind, name: &str, entry: PathBuf) {
+    let exists = targets
+        .iter()
+        .any(|t| t.kind == kind && (t.name == name || t.entry == entry));
+    if !exists {
+        targets.push(CrateTarget {
+            kind,
+            name: name.to_string(),
+            entry,
+        });
+    }
+}
+
+/// `<dir>/*.rs` and `<dir>/*/main.rs`, named by file stem or directory
+fn scan_target_dir(dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut found: Vec<(String, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_string_lossy().into_owned();
+            if path.is_dir() {
+                let main = path.join("main.rs");
+                main.exists().then_some((name, main))
+            } else {
+                (path.extension()? == "rs").then_some((name, path))
+            }
+        })
+        .collect();
+    found.sort();
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_targets() {
+        let dir = std::env::temp_dir().join(format!("cargomap-targets-{}", std::process::id()));
+        for file in [
+            "src/lib.rs",
+            "src/main.rs",
+            "src/bin/tool.rs",
+            "src/bin/multi/main.rs",
+            "examples/demo.rs",
+            "tests/it.rs",
+            "benches/speed.rs",
+        ] {
+            let path = dir.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        let manifest: toml::Value = toml::from_str(
+            "[package]\nname = \"app\"\nautobenches = false\n[[bin]]\nname = \"custom\"\npath = \"tools/custom.rs\"\n",
+        )
+        .unwrap();
+
+        let targets = from_manifest(&dir, Some(&manifest));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let found: Vec<(&str, &str)> = targets
+            .iter()
+            .map(|t| (t.kind.label(), t.name.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("lib", "app"),
+                ("bin", "custom"),
+                ("bin", "app"),
+                ("bin", "multi"),
+                ("bin", "tool"),
+                ("example", "demo"),
+                ("test", "it"),
+            ]
+        );
+        assert_eq!(targets[3].entry, dir.join("src/bin/multi/main.rs"));
+    }
+}
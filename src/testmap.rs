@@ -0,0 +1,390 @@
+//! Static test-to-code mapping
+//!
+//! Finds the tests in a project and follows the call graph from each one,
+//! answering "which tests reach this function?" and "which functions does
+//! no test reach?" without running anything. Three kinds of test count:
+//! - `#[test]` functions (and `#[tokio::test]` and friends) in `mod tests`
+//!   blocks or anywhere else in `src/`
+//! - The same in integration test files under `tests/`
+//! - Doc comment code blocks, which call into the item they document
+//!
+//! A function's calls are the call sites inside its span. Callees are
+//! known by name only, so a test reaching `new` is taken to reach every
+//! `new`.
+
+use crate::parser;
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Component, Path, PathBuf};
+
+/// Doc code block languages rustdoc doesn't run
+const SKIPPED_DOC_BLOCKS: &[&str] = &["text", "ignore", "no_run", "compile_fail", "toml", "sh"];
+
+/// Where a test lives
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TestKind {
+    /// `#[test]` inside the crate's sources
+    Unit,
+    /// `#[test]` in a file under `tests/`
+    Integration,
+    /// Code block in a doc comment
+    Doctest,
+}
+
+impl TestKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TestKind::Unit => "unit",
+            TestKind::Integration => "integration",
+            TestKind::Doctest => "doctest",
+        }
+    }
+}
+
+/// A function (or doc comment) that runs as a test
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestFunction {
+    /// Function name, or `<item> (doctest)`
+    pub name: String,
+    pub kind: TestKind,
+    pub file: PathBuf,
+    pub line: usize,
+}
+
+/// A non-test function tests may reach
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeFunction {
+    pub name: String,
+    pub file: PathBuf,
+    pub line: usize,
+    pub is_public: bool,
+}
+
+/// A test reaching a function, `depth` calls away (1 = called directly)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TestHit {
+    /// Index into [`TestMap::tests`]
+    pub test: usize,
+    pub depth: usize,
+}
+
+/// Tests and the production functions each one reaches
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TestMap {
+    tests: Vec<TestFunction>,
+    functions: Vec<CodeFunction>,
+    /// Function name -> tests reaching it
+    reached_by: HashMap<String, Vec<TestHit>>,
+}
+
+impl TestMap {
+    /// Find the tests in `files` and walk `call_graph` from each.
+    /// `crate_roots` are the analyzed crates' directories, whose `tests/`
+    /// hold integration tests.
+    pub fn build(files: &[ParsedFile], call_graph: &CallGraph, crate_roots: &[&Path]) -> Self {
+        let mut map = TestMap::default();
+        // Callees of each test; doctests get theirs from the code block
+        let mut roots: Vec<Vec<String>> = Vec::new();
+        // Function name -> names called inside any function of that name
+        let mut edges: HashMap<String, Vec<String>> = HashMap::new();
+
+        let mut sites_by_file: HashMap<&Path, Vec<(usize, &str)>> = HashMap::new();
+        for (callee, sites) in &call_graph.callers {
+            for site in sites {
+                sites_by_file
+                    .entry(site.file.as_path())
+                    .or_default()
+                    .push((site.line, callee.as_str()));
+            }
+        }
+
+        let function_names: HashSet<&str> = files
+            .iter()
+            .flat_map(|f| f.items_and_methods())
+            .filter(|item| matches!(item.kind, ItemKind::Function { .. }))
+            .map(|item| item.name.as_str())
+            .collect();
+
+        for file in files {
+            let in_tests_dir = is_integration_test_file(&file.path, crate_roots);
+            let test_modules: Vec<&Span> = file
+                .items
+                .iter()
+                .filter(|item| {
+                    matches!(item.kind, ItemKind::Mod { .. })
+                        && (item.name == "tests" || item.name == "test")
+                })
+                .map(|item| &item.span)
+                .collect();
+
+            for item in file.items_and_methods() {
+                if let Some(doc) = &item.doc_comment {
+                    let calls = doctest_calls(doc, &function_names);
+                    if !calls.is_empty() {
+                        map.tests.push(TestFunction {
+                            name: format!("{} (doctest)", item.name),
+                            kind: TestKind::Doctest,
+                            file: file.path.clone(),
+                            line: item.span.start_line,
+                        });
+                        roots.push(calls);
+                    }
+                }
+
+                if !matches!(item.kind, ItemKind::Function { .. }) {
+                    continue;
+                }
+
+                let mut calls: Vec<String> = sites_by_file
+                    .get(file.path.as_path())
+                    .into_iter()
+                    .flatten()
+                    .filter(|(line, _)| {
+                        item.span.start_line <= *line && *line <= item.span.end_line
+                    })
+                    .map(|(_, callee)| callee.to_string())
+                    .collect();
+                calls.sort();
+                calls.dedup();
+
+                if parser::has_test_attribute(&item.attributes) {
+                    map.tests.push(TestFunction {
+                        name: item.name.clone(),
+                        kind: if in_tests_dir {
+                            TestKind::Integration
+                        } else {
+                            TestKind::Unit
+                        },
+                        file: file.path.clone(),
+                        line: item.span.start_line,
+                    });
+                    roots.push(calls);
+                    continue;
+                }
+                edges.entry(item.name.clone()).or_default().extend(calls);
+
+                // Helpers in test modules and test files aren't production code
+                let in_test_module = test_modules.iter().any(|span| {
+                    span.start_line <= item.span.start_line && item.span.end_line <= span.end_line
+                });
+                if !in_tests_dir && !in_test_module {
+                    map.functions.push(CodeFunction {
+                        name: item.name.clone(),
+                        file: file.path.clone(),
+                        line: item.span.start_line,
+                        is_public: item.visibility == Visibility::Public,
+                    });
+                }
+            }
+        }
+
+        for (index, callees) in roots.into_iter().enumerate() {
+            for (name, depth) in reachable(callees, &edges) {
+                map.reached_by
+                    .entry(name)
+                    .or_default()
+                    .push(TestHit { test: index, depth });
+            }
+        }
+        for hits in map.reached_by.values_mut() {
+            hits.sort_by_key(|hit| (hit.depth, hit.test));
+        }
+
+        map
+    }
+
+    /// All tests found
+    pub fn tests(&self) -> &[TestFunction] {
+        &self.tests
+    }
+
+    /// Tests reaching a function (`name` or `Type::name`), closest first
+    pub fn tests_for(&self, function: &str) -> Vec<(&TestFunction, usize)> {
+        let name = function.rsplit("::").next().unwrap_or(function);
+        self.reached_by
+            .get(name)
+            .map(|hits| {
+                hits.iter()
+                    .map(|hit| (&self.tests[hit.test], hit.depth))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Production functions no test reaches, public ones first
+    pub fn untested(&self) -> Vec<&CodeFunction> {
+        let mut untested: Vec<&CodeFunction> = self
+            .functions
+            .iter()
+            .filter(|f| !self.reached_by.contains_key(&f.name))
+            .collect();
+        untested.sort_by_key(|f| (!f.is_public, f.file.clone(), f.line));
+        untested
+    }
+
+    /// Number of production functions, and how many some test reaches
+    pub fn function_counts(&self) -> (usize, usize) {
+        let reached = self
+            .functions
+            .iter()
+            .filter(|f| self.reached_by.contains_key(&f.name))
+            .count();
+        (self.functions.len(), reached)
+    }
+}
+
+/// Whether a file is under `tests/` in the innermost of `roots` (crate
+/// directories) holding it. Directories above the crate don't count, so a
+/// project checked out under some `tests/` is still analyzed.
+pub(crate) fn is_integration_test_file(path: &Path, roots: &[&Path]) -> bool {
+    roots
+        .iter()
+        .filter(|root| path.starts_with(root))
+        .max_by_key(|root| root.components().count())
+        .and_then(|root| path.strip_prefix(root).ok()?.components().next())
+        .is_some_and(|first| matches!(first, Component::Normal(name) if name == "tests"))
+}
+
+/// Project functions called from the runnable code blocks of a doc comment
+fn doctest_calls(doc: &str, function_names: &HashSet<&str>) -> Vec<String> {
+    let call_pattern = regex::Regex::new(r"(\w+)\s*\(").expect("Invalid regex");
+    let mut calls = Vec::new();
+    let mut in_block = false;
+    let mut runnable = false;
+
+    for line in doc.lines() {
+        let trimmed = line.trim();
+        if let Some(lang) = trimmed.strip_prefix("```") {
+            if !in_block {
+                runnable = !lang
+                    .split(',')
+                    .any(|tag| SKIPPED_DOC_BLOCKS.contains(&tag.trim()));
+            }
+            in_block = !in_block;
+            continue;
+        }
+        if in_block && runnable {
+            for cap in call_pattern.captures_iter(trimmed) {
+                if function_names.contains(&cap[1]) && !calls.iter().any(|c| c == &cap[1]) {
+                    calls.push(cap[1].to_string());
+                }
+            }
+        }
+    }
+
+    calls
+}
+
+/// Every function reachable from `roots` (called at depth 1), with its
+/// shortest depth
+fn reachable(roots: Vec<String>, edges: &HashMap<String, Vec<String>>) -> HashMap<String, usize> {
+    let mut depths = HashMap::new();
+    let mut queue: VecDeque<(String, usize)> = roots.into_iter().map(|name| (name, 1)).collect();
+
+    while let Some((name, depth)) = queue.pop_front() {
+        if depths.contains_key(&name) {
+            continue;
+        }
+        if let Some(callees) = edges.get(&name) {
+            for callee in callees {
+                if !depths.contains_key(callee) {
+                    queue.push_back((callee.clone(), depth + 1));
+                }
+            }
+        }
+        depths.insert(name, depth);
+    }
+
+    depths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::PartialParser;
+
+    #[test]
+    fn test_map_tests_to_functions() {
+        let parser = PartialParser::new();
+        // Directories above the project named `tests` don't matter
+        let root = Path::new("/home/u/tests/app");
+        let lib = parser
+            .parse_source(
+                &root.join("src/lib.rs"),
+                r#"
+/// ```
+/// let n = documented();
+/// ```
+pub fn documented() -> u32 { helper() }
+fn helper() -> u32 { 1 }
+pub fn orphan() {}
+
+#[cfg(test)]
+mod tests {
+    fn fixture() -> u32 { 2 }
+
+    #[test]
+    fn test_helper() { assert_eq!(helper(), 1); }
+}
+"#,
+            )
+            .unwrap();
+        let integration = parser
+            .parse_source(
+                &root.join("tests/api.rs"),
+                "#[tokio::test]\nasync fn calls_documented() { documented(); }\n",
+            )
+            .unwrap();
+
+        let mut call_graph = CallGraph::default();
+        for (callee, file, line) in [
+            ("helper", "src/lib.rs", 5),
+            ("helper", "src/lib.rs", 14),
+            ("documented", "tests/api.rs", 2),
+        ] {
+            call_graph
+                .callers
+                .entry(callee.to_string())
+                .or_default()
+                .push(CallSite {
+                    caller: String::new(),
+                    file: root.join(file),
+                    line,
+                });
+        }
+
+        let map = TestMap::build(&[lib, integration], &call_graph, &[root]);
+        let kinds: Vec<_> = map
+            .tests()
+            .iter()
+            .map(|t| (t.name.as_str(), t.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("documented (doctest)", TestKind::Doctest),
+                ("test_helper", TestKind::Unit),
+                ("calls_documented", TestKind::Integration),
+            ]
+        );
+
+        let helper: Vec<_> = map
+            .tests_for("helper")
+            .iter()
+            .map(|(t, depth)| (t.name.as_str(), *depth))
+            .collect();
+        assert_eq!(
+            helper,
+            vec![
+                ("test_helper", 1),
+                ("documented (doctest)", 2),
+                ("calls_documented", 2),
+            ]
+        );
+
+        let untested: Vec<_> = map.untested().iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(untested, vec!["orphan"]);
+        assert_eq!(map.function_counts(), (3, 2));
+    }
+}
@@ -0,0 +1,349 @@
+//! Trait implementation matrix
+//!
+//! Collects which local types implement which traits, from three sources:
+//! - Manual `impl Trait for Type` blocks (including generic and reference
+//!   self types such as `Wrapper<T>` or `&Type`)
+//! - `#[derive(...)]` attributes
+//! - Blanket impls (`impl<T: Foo> Bar for T`), applied to every local type
+//!   that satisfies the bounds
+//!
+//! References are types of their own: `impl Trait for &Type` and
+//! `impl<T: Foo> Trait for &T` are listed under `&Type`, and don't make
+//! `Type` itself implement the trait.
+//!
+//! Traits are keyed by their last path segment, so `serde::Serialize` and
+//! `Serialize` are the same trait.
+
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+/// Traits every type is assumed to have when checking blanket bounds
+const AUTO_TRAITS: &[&str] = &["Sized", "Send", "Sync", "Unpin"];
+
+/// How a type came to implement a trait
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImplSource {
+    /// `impl Trait for Type`
+    Manual,
+    /// `#[derive(Trait)]`
+    Derive,
+    /// `impl<T: Bounds> Trait for T`, with the bounds the type satisfied
+    Blanket { bounds: Vec<String> },
+}
+
+impl ImplSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ImplSource::Manual => "impl",
+            ImplSource::Derive => "derive",
+            ImplSource::Blanket { .. } => "blanket",
+        }
+    }
+}
+
+/// One (trait, type) cell of the matrix
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraitImpl {
+    /// Trait name (last path segment)
+    pub trait_name: String,
+    /// Implementing type (base name without generics, after `&` or
+    /// `&mut ` for reference impls)
+    pub type_name: String,
+    /// Self type as written (e.g. `Wrapper < T >`, `& Config`)
+    pub self_type: String,
+    pub source: ImplSource,
+    pub file: PathBuf,
+    pub line: usize,
+}
+
+/// Trait-by-type implementation matrix
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TraitMatrix {
+    impls: Vec<TraitImpl>,
+}
+
+/// A blanket impl waiting to be applied
+struct Blanket<'a> {
+    /// `&` or `&mut ` for impls on references to the parameter
+    reference: &'static str,
+    trait_name: String,
+    bounds: Vec<String>,
+    item: &'a ParsedItem,
+}
+
+impl TraitMatrix {
+    /// Build the matrix from parsed files
+    pub fn build(files: &[ParsedFile]) -> Self {
+        let items: Vec<&ParsedItem> = files.iter().flat_map(|f| &f.items).collect();
+        let mut impls = Vec::new();
+        let mut blankets = Vec::new();
+
+        // Derives on local types
+        for item in &items {
+            if !matches!(item.kind, ItemKind::Struct { .. } | ItemKind::Enum { .. }) {
+                continue;
+            }
+            for trait_name in derived_traits(item) {
+                impls.push(TraitImpl {
+                    trait_name,
+                    type_name: item.name.clone(),
+                    self_type: item.name.clone(),
+                    source: ImplSource::Derive,
+                    file: item.file_path.clone(),
+                    line: item.span.start_line,
+                });
+            }
+        }
+
+        // Manual and blanket impls
+        for item in &items {
+            let ItemKind::Impl {
+                self_type,
+                trait_name: Some(trait_path),
+                generics,
+                ..
+            } = &item.kind
+            else {
+                continue;
+            };
+            let trait_name = last_segment(trait_path).to_string();
+            let (reference, referenced) = split_reference(self_type);
+
+            // Only the bare parameter (or a reference to it) is a blanket;
+            // `Wrapper<T>` is an impl on `Wrapper`
+            match generics.iter().find(|g| g.param == referenced) {
+                Some(param) => blankets.push(Blanket {
+                    reference,
+                    trait_name,
+                    bounds: param.bounds.clone(),
+                    item,
+                }),
+                None => impls.push(TraitImpl {
+                    trait_name,
+                    type_name: type_key(self_type),
+                    self_type: self_type.clone(),
+                    source: ImplSource::Manual,
+                    file: item.file_path.clone(),
+                    line: item.span.start_line,
+                }),
+            }
+        }
+
+        let mut matrix = Self { impls };
+        matrix.apply_blankets(&items, &blankets);
+        matrix.impls.sort_by(|a, b| {
+            (&a.trait_name, &a.type_name, a.source.label()).cmp(&(
+                &b.trait_name,
+                &b.type_name,
+                b.source.label(),
+            ))
+        });
+        matrix
+    }
+
+    /// Apply blanket impls to local types until nothing new is implied
+    /// (a blanket impl can satisfy the bounds of another)
+    fn apply_blankets(&mut self, items: &[&ParsedItem], blankets: &[Blanket]) {
+        let local_types: BTreeSet<&str> = items
+            .iter()
+            .filter(|item| matches!(item.kind, ItemKind::Struct { .. } | ItemKind::Enum { .. }))
+            .map(|item| item.name.as_str())
+            .collect();
+
+        loop {
+            let mut added = Vec::new();
+            for ty in &local_types {
+                let implemented = self.trait_set(ty);
+                for blanket in blankets {
+                    let satisfied = blanket.bounds.iter().all(|bound| {
+                        // `?Sized` relaxes rather than requires
+                        let name = last_segment(bound);
+                        bound.starts_with('?')
+                            || AUTO_TRAITS.contains(&name)
+                            || implemented.contains(name)
+                    });
+                    let target = format!("{}{}", blanket.reference, ty);
+                    if satisfied
+                        && !self
+                            .trait_set(&target)
+                            .contains(blanket.trait_name.as_str())
+                    {
+                        added.push(TraitImpl {
+                            trait_name: blanket.trait_name.clone(),
+                            type_name: target.clone(),
+                            self_type: target,
+                            source: ImplSource::Blanket {
+                                bounds: blanket.bounds.clone(),
+                            },
+                            file: blanket.item.file_path.clone(),
+                            line: blanket.item.span.start_line,
+                        });
+                    }
+                }
+            }
+
+            if added.is_empty() {
+                break;
+            }
+            added.sort_by(|a, b| (&a.type_name, &a.trait_name).cmp(&(&b.type_name, &b.trait_name)));
+            added.dedup_by(|a, b| a.type_name == b.type_name && a.trait_name == b.trait_name);
+            self.impls.extend(added);
+        }
+    }
+
+    fn trait_set(&self, type_name: &str) -> BTreeSet<&str> {
+        self.impls
+            .iter()
+            .filter(|i| i.type_name == type_name)
+            .map(|i| i.trait_name.as_str())
+            .collect()
+    }
+
+    /// Every (trait, type) entry
+    pub fn impls(&self) -> &[TraitImpl] {
+        &self.impls
+    }
+
+    /// Types implementing a trait ("who implements `Trait`?")
+    pub fn implementors(&self, trait_name: &str) -> Vec<&TraitImpl> {
+        let trait_name = last_segment(trait_name);
+        self.impls
+            .iter()
+            .filter(|i| i.trait_name == trait_name)
+            .collect()
+    }
+
+    /// Traits implemented by a type ("which traits does `Type` implement?")
+    pub fn traits_of(&self, type_name: &str) -> Vec<&TraitImpl> {
+        let type_name = type_key(type_name);
+        self.impls
+            .iter()
+            .filter(|i| i.type_name == type_name)
+            .collect()
+    }
+
+    /// Trait names implemented by a type, deduplicated and sorted
+    pub fn trait_names_of(&self, type_name: &str) -> Vec<String> {
+        self.trait_set(&type_key(type_name))
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Type -> implemented traits, for every type in the matrix
+    pub fn by_type(&self) -> BTreeMap<&str, Vec<&TraitImpl>> {
+        let mut rows: BTreeMap<&str, Vec<&TraitImpl>> = BTreeMap::new();
+        for i in &self.impls {
+            rows.entry(i.type_name.as_str()).or_default().push(i);
+        }
+        rows
+    }
+}
+
+/// Trait names listed in an item's `#[derive(...)]` attributes
+fn derived_traits(item: &ParsedItem) -> Vec<String> {
+    item.attributes
+        .iter()
+        .filter_map(|a| a.strip_prefix("#[derive(")?.strip_suffix(")]"))
+        .flat_map(|list| list.split(','))
+        .map(|t| last_segment(t.trim()).to_string())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+fn last_segment(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path).trim()
+}
+
+/// Matrix key of a self type: its base name, after the reference if any
+/// (`& 'a mut foo :: Bar < T >` -> `&mut Bar`)
+fn type_key(ty: &str) -> String {
+    let (reference, referenced) = split_reference(ty);
+    format!("{}{}", reference, base_type_name(referenced))
+}
+
+/// Base name of a type: `foo :: Bar < T >` -> `Bar`
+fn base_type_name(ty: &str) -> String {
+    let without_generics = ty.split('<').next().unwrap_or(ty);
+    let base = without_generics
+        .split_whitespace()
+        .filter(|t| *t != "dyn")
+        .collect::<String>();
+    last_segment(&base).to_string()
+}
+
+/// Split a reference type into `&` / `&mut ` and the referenced type, with
+/// any lifetime dropped (`& 'a mut Bar < T >` -> (`&mut `, `Bar < T >`));
+/// other types have no prefix
+fn split_reference(ty: &str) -> (&'static str, &str) {
+    let Some(rest) = ty.trim().strip_prefix('&') else {
+        return ("", ty.trim());
+    };
+    let mut rest = rest.trim_start();
+    if rest.starts_with('\'') {
+        rest = rest
+            .split_once(char::is_whitespace)
+            .map_or("", |(_, after)| after.trim_start());
+    }
+    match rest.strip_prefix("mut ") {
+        Some(referenced) => ("&mut ", referenced.trim_start()),
+        None => ("&", rest),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::PartialParser;
+    use std::path::Path;
+
+    #[test]
+    fn test_matrix_sources() {
+        let source = r#"
+#[derive(Debug, Clone)]
+struct Config;
+struct Wrapper<T>(T);
+trait Describe {}
+trait Loud {}
+trait Shared {}
+impl<T> Describe for Wrapper<T> {}
+impl Describe for Config {}
+impl Describe for &Wrapper<u8> {}
+impl<T: Describe + Clone> Loud for T {}
+impl<T: Clone> Shared for &T {}
+"#;
+        let file = PartialParser::new()
+            .parse_source(Path::new("lib.rs"), source)
+            .unwrap();
+        let matrix = TraitMatrix::build(&[file]);
+
+        let config: Vec<(&str, &str)> = matrix
+            .traits_of("Config")
+            .iter()
+            .map(|i| (i.trait_name.as_str(), i.source.label()))
+            .collect();
+        assert_eq!(
+            config,
+            vec![
+                ("Clone", "derive"),
+                ("Debug", "derive"),
+                ("Describe", "impl"),
+                ("Loud", "blanket"),
+            ]
+        );
+
+        let describers: Vec<&str> = matrix
+            .implementors("Describe")
+            .iter()
+            .map(|i| i.type_name.as_str())
+            .collect();
+        assert_eq!(describers, vec!["&Wrapper", "Config", "Wrapper"]);
+        assert!(matrix.trait_names_of("Wrapper").iter().all(|t| t != "Loud"));
+
+        // Impls on references belong to the reference, not the type
+        assert_eq!(matrix.trait_names_of("& Config"), vec!["Shared"]);
+        assert_eq!(matrix.trait_names_of("&'a Wrapper<u8>"), vec!["Describe"]);
+    }
+}
@@ -0,0 +1,409 @@
+//! Type relationship graph
+//!
+//! Links items to the local types they mention, by how they mention them:
+//! - Struct fields and enum variant payloads
+//! - Function parameters and return types (methods as `Type::method`)
+//! - `impl Trait for Type` blocks and supertraits
+//! - Type aliases
+//!
+//! Only types defined in the analyzed crates are nodes, so `String` or
+//! `Vec` never link unrelated items. Types are matched by name, preferring
+//! one declared in the same file when several share it. Neighbors are
+//! ranked by the summed weight of their edges: a field type is a closer
+//! relative than a parameter type.
+
+use crate::diff;
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// How one item refers to a type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum EdgeKind {
+    /// Struct field type
+    Field,
+    /// Enum variant payload
+    Variant,
+    /// `impl Trait for Type`, from the type to the trait
+    Impl,
+    /// `trait Sub: Super`
+    Supertrait,
+    /// `type Alias = Type`
+    Alias,
+    /// Function return type
+    Return,
+    /// Function parameter type
+    Param,
+}
+
+impl EdgeKind {
+    /// How closely the edge ties its ends together
+    pub fn weight(self) -> usize {
+        match self {
+            EdgeKind::Field | EdgeKind::Variant => 5,
+            EdgeKind::Impl | EdgeKind::Supertrait | EdgeKind::Alias => 4,
+            EdgeKind::Return => 3,
+            EdgeKind::Param => 2,
+        }
+    }
+
+    /// What the other end is, seen from the source (`outgoing`) or the
+    /// target of the edge
+    pub fn label(self, outgoing: bool) -> &'static str {
+        match (self, outgoing) {
+            (EdgeKind::Field, true) => "field type",
+            (EdgeKind::Field, false) => "has it as a field",
+            (EdgeKind::Variant, true) => "variant payload",
+            (EdgeKind::Variant, false) => "holds it in a variant",
+            (EdgeKind::Impl, true) => "implemented trait",
+            (EdgeKind::Impl, false) => "implementor",
+            (EdgeKind::Supertrait, true) => "supertrait",
+            (EdgeKind::Supertrait, false) => "subtrait",
+            (EdgeKind::Alias, true) => "aliased type",
+            (EdgeKind::Alias, false) => "alias",
+            (EdgeKind::Return, true) => "return type",
+            (EdgeKind::Return, false) => "returns it",
+            (EdgeKind::Param, true) => "parameter type",
+            (EdgeKind::Param, false) => "takes it",
+        }
+    }
+}
+
+/// A type, trait or function in the graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeNode {
+    /// Item name; methods are `Type::method`
+    pub name: String,
+    /// Item kind label (`struct`, `fn`, ...)
+    pub kind: String,
+    pub file: PathBuf,
+    pub line: usize,
+}
+
+/// One mention of a type by another item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeEdge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: EdgeKind,
+    /// Line of the mentioning item
+    pub line: usize,
+}
+
+/// Graph of items and the local types they mention
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TypeGraph {
+    nodes: Vec<TypeNode>,
+    edges: Vec<TypeEdge>,
+    /// (file, start line) -> node, for looking items up
+    #[serde(skip)]
+    by_location: HashMap<(PathBuf, usize), usize>,
+}
+
+impl TypeGraph {
+    /// Build the graph from parsed files
+    pub fn build(files: &[ParsedFile]) -> Self {
+        let mut graph = TypeGraph::default();
+        let mut types: HashMap<String, Vec<usize>> = HashMap::new();
+
+        for file in files {
+            for item in file.items_and_methods() {
+                let name = match &item.kind {
+                    ItemKind::Struct { .. }
+                    | ItemKind::Enum { .. }
+                    | ItemKind::Trait { .. }
+                    | ItemKind::TypeAlias { .. } => {
+                        types
+                            .entry(item.name.clone())
+                            .or_default()
+                            .push(graph.nodes.len());
+                        item.name.clone()
+                    }
+                    ItemKind::Function { .. } => match diff::enclosing_impl(file, item) {
+                        Some(ItemKind::Impl { self_type, .. }) => {
+                            format!("{}::{}", base_type(self_type), item.name)
+                        }
+                        _ => item.name.clone(),
+                    },
+                    _ => continue,
+                };
+                graph.by_location.insert(
+                    (item.file_path.clone(), item.span.start_line),
+                    graph.nodes.len(),
+                );
+                graph.nodes.push(TypeNode {
+                    name,
+                    kind: diff::kind_label(&item.kind).to_string(),
+                    file: item.file_path.clone(),
+                    line: item.span.start_line,
+                });
+            }
+        }
+
+        let lookup = Lookup {
+            nodes: &graph.nodes,
+            types: &types,
+        };
+        let mut edges = Vec::new();
+        for file in files {
+            for item in file.items_and_methods() {
+                let line = item.span.start_line;
+                let from = match &item.kind {
+                    // Impl blocks link their self type to the trait
+                    ItemKind::Impl {
+                        self_type,
+                        trait_name: Some(_),
+                        ..
+                    } => lookup.types_in(self_type, &file.path).first().copied(),
+                    _ => graph
+                        .by_location
+                        .get(&(item.file_path.clone(), line))
+                        .copied(),
+                };
+                let Some(from) = from else {
+                    continue;
+                };
+
+                let mut mentions: Vec<(EdgeKind, &str)> = Vec::new();
+                match &item.kind {
+                    ItemKind::Struct { fields, .. } => {
+                        mentions.extend(fields.iter().map(|f| (EdgeKind::Field, f.ty.as_str())));
+                    }
+                    ItemKind::Enum { variants } => mentions.extend(
+                        variants
+                            .iter()
+                            .flat_map(|v| &v.fields)
+                            .map(|f| (EdgeKind::Variant, f.ty.as_str())),
+                    ),
+                    ItemKind::Function {
+                        parameters,
+                        return_type,
+                        ..
+                    } => {
+                        mentions
+                            .extend(parameters.iter().map(|p| (EdgeKind::Param, p.ty.as_str())));
+                        mentions.extend(return_type.iter().map(|r| (EdgeKind::Return, r.as_str())));
+                    }
+                    ItemKind::Trait { supertraits, .. } => mentions.extend(
+                        supertraits
+                            .iter()
+                            .map(|s| (EdgeKind::Supertrait, s.as_str())),
+                    ),
+                    ItemKind::TypeAlias { ty } => mentions.push((EdgeKind::Alias, ty.as_str())),
+                    ItemKind::Impl {
+                        trait_name: Some(trait_name),
+                        ..
+                    } => mentions.push((EdgeKind::Impl, trait_name.as_str())),
+                    _ => {}
+                }
+
+                for (kind, ty) in mentions {
+                    for to in lookup.types_in(ty, &file.path) {
+                        if to != from {
+                            edges.push(TypeEdge {
+                                from,
+                                to,
+                                kind,
+                                line,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        edges.sort_by_key(|e| (e.from, e.to, e.kind, e.line));
+        edges.dedup_by_key(|e| (e.from, e.to, e.kind));
+        graph.edges = edges;
+        graph
+    }
+
+    pub fn nodes(&self) -> &[TypeNode] {
+        &self.nodes
+    }
+
+    pub fn edges(&self) -> &[TypeEdge] {
+        &self.edges
+    }
+
+    /// The node of an item; impl blocks map to their self type's node
+    fn node_of(&self, item: &ParsedItem) -> Option<usize> {
+        if let Some(&node) = self
+            .by_location
+            .get(&(item.file_path.clone(), item.span.start_line))
+        {
+            return Some(node);
+        }
+        let ItemKind::Impl { self_type, .. } = &item.kind else {
+            return None;
+        };
+        let name = base_type(self_type);
+        self.nodes
+            .iter()
+            .position(|n| n.name == name && n.file == item.file_path)
+            .or_else(|| {
+                self.nodes
+                    .iter()
+                    .position(|n| n.name == name && n.kind != "fn")
+            })
+    }
+
+    /// Items connected to `item` in either direction, strongest first
+    pub fn related(&self, item: &ParsedItem) -> Vec<RelatedItem> {
+        let Some(node) = self.node_of(item) else {
+            return Vec::new();
+        };
+
+        let mut neighbors: BTreeMap<usize, Vec<(EdgeKind, bool)>> = BTreeMap::new();
+        for edge in &self.edges {
+            if edge.from == node {
+                neighbors
+                    .entry(edge.to)
+                    .or_default()
+                    .push((edge.kind, true));
+            } else if edge.to == node {
+                neighbors
+                    .entry(edge.from)
+                    .or_default()
+                    .push((edge.kind, false));
+            }
+        }
+
+        let mut related: Vec<RelatedItem> = neighbors
+            .into_iter()
+            .map(|(index, relations)| {
+                let other = &self.nodes[index];
+                let mut labels: Vec<String> = Vec::new();
+                for (kind, outgoing) in &relations {
+                    let label = kind.label(*outgoing).to_string();
+                    if !labels.contains(&label) {
+                        labels.push(label);
+                    }
+                }
+                RelatedItem {
+                    name: other.name.clone(),
+                    kind: other.kind.clone(),
+                    file: other.file.clone(),
+                    line: other.line,
+                    strength: relations.iter().map(|(kind, _)| kind.weight()).sum(),
+                    relations: labels,
+                }
+            })
+            .collect();
+        related.sort_by(|a, b| {
+            b.strength
+                .cmp(&a.strength)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        related
+    }
+}
+
+/// Resolves type strings to type nodes
+struct Lookup<'a> {
+    nodes: &'a [TypeNode],
+    types: &'a HashMap<String, Vec<usize>>,
+}
+
+impl Lookup<'_> {
+    /// Local types named in a type string, preferring ones declared in
+    /// `file` when a name is ambiguous
+    fn types_in(&self, ty: &str, file: &Path) -> Vec<usize> {
+        let mut found = Vec::new();
+        for word in ty.split(|c: char| !(c.is_alphanumeric() || c == '_')) {
+            let Some(candidates) = self.types.get(word) else {
+                continue;
+            };
+            let local: Vec<usize> = candidates
+                .iter()
+                .copied()
+                .filter(|&c| self.nodes[c].file == file)
+                .collect();
+            for candidate in if local.is_empty() {
+                candidates.clone()
+            } else {
+                local
+            } {
+                if !found.contains(&candidate) {
+                    found.push(candidate);
+                }
+            }
+        }
+        found
+    }
+}
+
+/// Type name without references, paths or generics (`& mut a :: Foo < T >`
+/// -> `Foo`)
+fn base_type(ty: &str) -> &str {
+    let ty = ty.split('<').next().unwrap_or(ty);
+    let ty = ty.rsplit("::").next().unwrap_or(ty);
+    ty.trim()
+        .trim_start_matches('&')
+        .trim_start()
+        .trim_start_matches("mut ")
+        .trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::PartialParser;
+
+    #[test]
+    fn test_related_types() {
+        let source = r#"
+pub struct Config { weights: Weights, name: String }
+pub struct Weights(f64);
+pub enum Event { Loaded(Config), Failed }
+pub trait Describe {}
+impl Describe for Config {}
+impl Config {
+    pub fn load(path: &str) -> Config { todo!() }
+    pub fn scale(&self, weights: &Weights) {}
+}
+"#;
+        let file = PartialParser::new()
+            .parse_source(Path::new("lib.rs"), source)
+            .unwrap();
+        let graph = TypeGraph::build(std::slice::from_ref(&file));
+        let item = |name: &str| file.items_and_methods().find(|i| i.name == name).unwrap();
+
+        let related: Vec<(String, usize, Vec<String>)> = graph
+            .related(item("Config"))
+            .into_iter()
+            .map(|r| (r.name, r.strength, r.relations))
+            .collect();
+        assert_eq!(
+            related,
+            vec![
+                (
+                    "Event".to_string(),
+                    5,
+                    vec!["holds it in a variant".to_string()]
+                ),
+                ("Weights".to_string(), 5, vec!["field type".to_string()]),
+                (
+                    "Describe".to_string(),
+                    4,
+                    vec!["implemented trait".to_string()]
+                ),
+                (
+                    "Config::load".to_string(),
+                    3,
+                    vec!["returns it".to_string()]
+                ),
+            ]
+        );
+
+        // Methods link to their parameter types; `String` is not a node
+        let scale: Vec<String> = graph
+            .related(item("scale"))
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+        assert_eq!(scale, vec!["Weights"]);
+        assert_eq!(graph.related(item("Weights"))[0].name, "Config");
+    }
+}
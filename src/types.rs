@@ -1,5 +1,6 @@
 //! Core types for the architecture analysis tool
 
+use crate::fuzzy::FuzzyMatch;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -22,6 +23,9 @@ pub enum ItemKind {
         is_async: bool,
         parameters: Vec<Parameter>,
         return_type: Option<String>,
+        /// Complexity metrics (absent for bodiless or unparsed functions)
+        #[serde(default)]
+        metrics: Option<FunctionMetrics>,
     },
     Struct {
         fields: Vec<StructField>,
@@ -33,11 +37,17 @@ pub enum ItemKind {
     Trait {
         methods: Vec<String>,
         supertraits: Vec<String>,
+        /// Methods without a default body
+        #[serde(default)]
+        required_methods: Vec<String>,
     },
     Impl {
         self_type: String,
         trait_name: Option<String>,
         methods: Vec<String>,
+        /// Type parameters of the impl and their bounds (`impl<T: Foo> ...`)
+        #[serde(default)]
+        generics: Vec<GenericBound>,
     },
     Mod {
         inline: bool,
@@ -72,11 +82,28 @@ pub struct Parameter {
     pub is_self: bool,
 }
 
+/// AST-derived complexity of a function body (see `metrics`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct FunctionMetrics {
+    pub cyclomatic: usize,
+    pub cognitive: usize,
+    /// Deepest nesting of control flow and closures
+    pub max_nesting: usize,
+    pub statements: usize,
+    /// Parameters, not counting `self`
+    pub parameters: usize,
+    /// `return`s before the end of the body, plus `?` operators
+    pub early_returns: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct StructField {
     pub name: Option<String>,
     pub ty: String,
     pub visibility: Visibility,
+    /// Field attributes such as `#[from]` or `#[serde(default)]`
+    #[serde(default)]
+    pub attributes: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -118,10 +145,20 @@ impl Default for Span {
 pub struct ParsedFile {
     pub path: PathBuf,
     pub items: Vec<ParsedItem>,
+    /// Methods of the file's impl blocks, kept out of `items` so only the
+    /// views that ask for them (query filters, per-function reports) see them
+    pub methods: Vec<ParsedItem>,
     pub parse_errors: Vec<ParseError>,
     pub module_path: Vec<String>,
 }
 
+impl ParsedFile {
+    /// Top-level items followed by impl methods
+    pub fn items_and_methods(&self) -> impl Iterator<Item = &ParsedItem> {
+        self.items.iter().chain(&self.methods)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParseError {
     pub message: String,
@@ -152,8 +189,12 @@ pub struct WorkSiteScore {
     pub item: ParsedItem,
     pub score: f64,
     pub factors: ScoreFactors,
+    /// Weighted contribution of each factor to `score`
+    pub breakdown: ScoreBreakdown,
     /// Recursive context window for richer search results
     pub context: ContextEnvelope,
+    /// How the search query matched this item (search results only)
+    pub matched: Option<FuzzyMatch>,
 }
 
 /// Recursive Context Window - enriched context for search results
@@ -161,26 +202,30 @@ pub struct WorkSiteScore {
 pub struct ContextEnvelope {
     /// Full module path breadcrumb (e.g., "crate::gravity::scoring")
     pub breadcrumbs: String,
-    /// Other types/functions in the same file that share generics or are closely related
-    pub siblings: Vec<SiblingInfo>,
+    /// Items linked through the type graph (fields, variants, signatures,
+    /// impls) from anywhere in the crate, strongest first
+    pub related: Vec<RelatedItem>,
     /// Expanded generic bounds without opening the file
     pub generic_bounds: Vec<GenericBound>,
     /// Parent item if this is nested (e.g., method inside impl block)
     pub parent_context: Option<String>,
 }
 
-/// Information about a sibling item in the same file
+/// An item linked to another through the type graph
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SiblingInfo {
+pub struct RelatedItem {
     pub name: String,
     pub kind: String,
+    pub file: PathBuf,
     pub line: usize,
-    /// Shared generic parameters with the main item
-    pub shared_generics: Vec<String>,
+    /// Summed weight of the edges between the two items
+    pub strength: usize,
+    /// What this item is to the other (e.g. "field type", "takes it")
+    pub relations: Vec<String>,
 }
 
 /// A generic bound extracted from a type signature
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct GenericBound {
     /// The generic parameter name (e.g., "T")
     pub param: String,
@@ -206,6 +251,94 @@ pub struct ScoreFactors {
     pub generic_depth: usize,
     /// Whether this item is a test function
     pub is_test: bool,
+    /// Git history of the item's lines (only when history is loaded)
+    pub history: Option<HistoryFactors>,
+    /// Cosine similarity to a semantic search query
+    pub semantic_similarity: Option<f32>,
+    /// Line coverage of the item (only when a coverage report is loaded)
+    pub coverage: Option<CoverageFactors>,
+}
+
+/// Line coverage of an item's span
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CoverageFactors {
+    /// Lines the coverage report has data for
+    pub instrumented_lines: usize,
+    /// Instrumented lines executed at least once
+    pub covered_lines: usize,
+    /// `covered_lines / instrumented_lines` as a percentage
+    pub percent: f64,
+}
+
+/// Git history signals for an item's line range
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct HistoryFactors {
+    /// Number of commits that touched the item's lines
+    pub churn: usize,
+    /// Days since the most recent of those commits
+    pub days_since_change: f64,
+    /// Number of distinct commit authors
+    pub author_count: usize,
+    /// Commits whose subject looks like a bug fix
+    pub bug_fix_count: usize,
+}
+
+/// Weighted contribution of each factor to a work-site score
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ScoreBreakdown {
+    /// Constant starting score
+    pub base: f64,
+    pub cross_module_usage: f64,
+    pub pub_visibility: f64,
+    pub generic_depth: f64,
+    pub is_test: f64,
+    pub entry_distance: f64,
+    /// Site bonus or utility penalty
+    pub call_pattern: f64,
+    pub impl_richness: f64,
+    pub trait_impls: f64,
+    pub churn: f64,
+    pub recency: f64,
+    pub author_count: f64,
+    pub bug_fixes: f64,
+    /// Fuzzy match quality against the search query
+    pub match_quality: f64,
+    /// Embedding similarity to a semantic search query
+    pub semantic_similarity: f64,
+    /// Cognitive complexity of the function body
+    pub complexity: f64,
+}
+
+impl ScoreBreakdown {
+    /// Sum of all contributions (before clamping at zero)
+    pub fn total(&self) -> f64 {
+        self.contributions().iter().map(|(_, value)| value).sum()
+    }
+
+    /// Named, non-zero contributions in display order
+    pub fn contributions(&self) -> Vec<(&'static str, f64)> {
+        [
+            ("base", self.base),
+            ("cross_module_usage", self.cross_module_usage),
+            ("pub_visibility", self.pub_visibility),
+            ("generic_depth", self.generic_depth),
+            ("is_test", self.is_test),
+            ("entry_distance", self.entry_distance),
+            ("call_pattern", self.call_pattern),
+            ("impl_richness", self.impl_richness),
+            ("trait_impls", self.trait_impls),
+            ("churn", self.churn),
+            ("recency", self.recency),
+            ("author_count", self.author_count),
+            ("bug_fixes", self.bug_fixes),
+            ("match_quality", self.match_quality),
+            ("semantic_similarity", self.semantic_similarity),
+            ("complexity", self.complexity),
+        ]
+        .into_iter()
+        .filter(|(_, value)| *value != 0.0)
+        .collect()
+    }
 }
 
 /// Reference to an external dependency usage in local code
@@ -223,20 +356,160 @@ pub struct ExternalReference {
     pub complexity: usize,
 }
 
+/// A path from one workspace member into another (`other_crate::Item`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrateReference {
+    /// Crate the reference is written in
+    pub from_crate: String,
+    /// Crate the path points into
+    pub to_crate: String,
+    /// The referenced path (e.g., "core_lib::Config::load")
+    pub path: String,
+    pub file: PathBuf,
+    pub line: usize,
+    /// The function/context where it's referenced
+    pub caller_context: String,
+}
+
+/// A module's use of another module or crate, from a `use` item or a
+/// qualified path in code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleDependency {
+    /// Module the reference is written in (e.g., "crate::types")
+    pub from_module: String,
+    /// Absolute path referenced (e.g., "crate::mcp::Tool", "sqlx::PgPool")
+    pub target: String,
+    pub file: PathBuf,
+    pub line: usize,
+}
+
 /// Map of external symbols to their local usages
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ReferenceMap {
     /// Maps "crate::path" -> list of local usages
     pub references: HashMap<String, Vec<ExternalReference>>,
+    /// Maps paths as written to the canonical path their usages are
+    /// kept under, once resolved (`tokio::task::spawn` -> `tokio::spawn`)
+    #[serde(default)]
+    pub aliases: HashMap<String, String>,
+}
+
+impl ReferenceMap {
+    /// Key the usages of a path are kept under
+    pub fn key<'a>(&'a self, path: &'a str) -> &'a str {
+        self.aliases.get(path).map_or(path, String::as_str)
+    }
+}
+
+/// Where a type is mentioned, from most to least structural
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TypeUsageKind {
+    /// `impl Trait for Type` / `impl Type` header
+    ImplHeader,
+    /// Struct field type
+    Field,
+    /// Enum variant payload
+    VariantPayload,
+    /// Function or method parameter
+    Parameter,
+    /// Function or method return type
+    ReturnType,
+    /// Right-hand side of a `type` alias
+    TypeAlias,
+    /// Trait bound or where clause
+    GenericBound,
+    /// Path inside a function body (constructors, casts, let annotations, macros)
+    Expression,
+    /// Anything else (const/static types, nested items)
+    Other,
+}
+
+impl TypeUsageKind {
+    /// Ranking weight: structural mentions outrank incidental ones
+    pub fn weight(self) -> f64 {
+        match self {
+            TypeUsageKind::ImplHeader => 40.0,
+            TypeUsageKind::Field | TypeUsageKind::VariantPayload => 30.0,
+            TypeUsageKind::Parameter | TypeUsageKind::ReturnType => 25.0,
+            TypeUsageKind::TypeAlias => 20.0,
+            TypeUsageKind::GenericBound => 15.0,
+            TypeUsageKind::Expression | TypeUsageKind::Other => 10.0,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TypeUsageKind::ImplHeader => "impl header",
+            TypeUsageKind::Field => "field",
+            TypeUsageKind::VariantPayload => "variant payload",
+            TypeUsageKind::Parameter => "parameter",
+            TypeUsageKind::ReturnType => "return type",
+            TypeUsageKind::TypeAlias => "type alias",
+            TypeUsageKind::GenericBound => "generic bound",
+            TypeUsageKind::Expression => "expression",
+            TypeUsageKind::Other => "other",
+        }
+    }
+}
+
+/// A place where a type is mentioned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeUsage {
+    pub kind: TypeUsageKind,
+    pub file: PathBuf,
+    pub line: usize,
+    /// Enclosing item (e.g. "SemanticGravity::search" or "ParsedItem")
+    pub context: String,
+    /// Trimmed source line
+    pub snippet: String,
+    /// Ranking score (higher first)
+    pub score: f64,
+}
+
+/// Complexity metrics aggregated over the functions of a module
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ModuleMetrics {
+    /// Module path (e.g. `crate::gravity`)
+    pub module: String,
+    pub file: PathBuf,
+    pub functions: usize,
+    pub total_cyclomatic: usize,
+    pub total_cognitive: usize,
+    pub max_cognitive: usize,
+    pub max_nesting: usize,
+    pub statements: usize,
+    /// Function with the highest cognitive complexity
+    pub most_complex: Option<String>,
+}
+
+impl ModuleMetrics {
+    pub fn mean_cyclomatic(&self) -> f64 {
+        self.total_cyclomatic as f64 / self.functions.max(1) as f64
+    }
+
+    pub fn mean_cognitive(&self) -> f64 {
+        self.total_cognitive as f64 / self.functions.max(1) as f64
+    }
 }
 
 /// Project-wide analysis result
+///
+/// Saved and loaded as a versioned snapshot (see [`crate::snapshot`]).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectAnalysis {
+    /// Snapshot format version
+    pub version: u32,
+    /// cargomap version that produced the analysis
+    pub generator: String,
+    /// Analyzed project root
+    pub root: PathBuf,
     pub files: Vec<ParsedFile>,
     pub dependencies: Vec<CrateDependency>,
     pub call_graph: CallGraph,
     pub module_tree: ModuleTree,
+    pub reference_map: ReferenceMap,
+    /// Paths from one workspace member into another
+    pub crate_references: Vec<CrateReference>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
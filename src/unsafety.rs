@@ -0,0 +1,704 @@
+//! Unsafe code audit - every `unsafe` in non-test code
+//!
+//! Walks the syntax tree of each file and records:
+//! - `unsafe fn`s, with the operations their bodies perform
+//! - `unsafe impl`s (`Send` / `Sync` especially) and `unsafe trait`s
+//! - `unsafe` blocks, with the operations inside them
+//!
+//! Operations are raw pointer dereferences, calls to foreign (`extern`)
+//! functions, `transmute`, `static mut` access, calls to other unsafe
+//! functions and inline assembly. Types aren't resolved: a dereference
+//! counts as a raw pointer dereference when the pointer is visibly one
+//! (a `*const`/`*mut` parameter or binding, a cast, `as_ptr()`, ...), and
+//! unsafe functions are known by name ([`Declarations`]) or from a list of
+//! common std ones. A site is documented when a `// SAFETY:` comment sits
+//! on or directly above it, or, for functions, when their docs have a
+//! `# Safety` section. Unsafe code inside macro bodies isn't seen.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+
+use crate::parser::{is_cfg_test, is_test_fn};
+
+/// Unsafe functions of std and core, by name. Names that are also common
+/// safe methods (`read`, `add`, ...) only count on a known raw pointer or
+/// under a `ptr::` path.
+const STD_UNSAFE_FNS: &[&str] = &[
+    "get_unchecked",
+    "get_unchecked_mut",
+    "from_raw_parts",
+    "from_raw_parts_mut",
+    "from_utf8_unchecked",
+    "from_utf8_unchecked_mut",
+    "set_len",
+    "assume_init",
+    "assume_init_ref",
+    "assume_init_mut",
+    "assume_init_read",
+    "unwrap_unchecked",
+    "from_raw",
+    "new_unchecked",
+    "zeroed",
+    "uninitialized",
+    "copy_nonoverlapping",
+    "read_unaligned",
+    "write_unaligned",
+    "read_volatile",
+    "write_volatile",
+    "drop_in_place",
+    "unreachable_unchecked",
+    "set_var",
+    "remove_var",
+];
+
+/// Raw pointer methods and `ptr::` functions with names too common to
+/// match on their own
+const POINTER_FNS: &[&str] = &[
+    "read", "write", "copy", "swap", "replace", "offset", "add", "sub", "as_ref", "as_mut",
+];
+
+/// Methods and functions that produce a raw pointer
+const POINTER_SOURCES: &[&str] = &[
+    "as_ptr",
+    "as_mut_ptr",
+    "null",
+    "null_mut",
+    "into_raw",
+    "cast",
+    "cast_mut",
+    "cast_const",
+    "offset",
+    "add",
+    "sub",
+    "wrapping_add",
+    "wrapping_sub",
+];
+
+/// Where `unsafe` appears
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum UnsafeKind {
+    Function,
+    Impl,
+    Trait,
+    Block,
+}
+
+impl UnsafeKind {
+    pub const ALL: [UnsafeKind; 4] = [
+        UnsafeKind::Function,
+        UnsafeKind::Impl,
+        UnsafeKind::Trait,
+        UnsafeKind::Block,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            UnsafeKind::Function => "fn",
+            UnsafeKind::Impl => "impl",
+            UnsafeKind::Trait => "trait",
+            UnsafeKind::Block => "block",
+        }
+    }
+
+    /// Parse a label as printed by [`UnsafeKind::label`]
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.label() == label)
+    }
+}
+
+/// What an unsafe context does that needs it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum UnsafeOp {
+    RawDeref,
+    FfiCall,
+    Transmute,
+    StaticMut,
+    UnsafeCall,
+    Asm,
+}
+
+impl UnsafeOp {
+    pub fn label(&self) -> &'static str {
+        match self {
+            UnsafeOp::RawDeref => "raw pointer deref",
+            UnsafeOp::FfiCall => "FFI call",
+            UnsafeOp::Transmute => "transmute",
+            UnsafeOp::StaticMut => "static mut access",
+            UnsafeOp::UnsafeCall => "unsafe call",
+            UnsafeOp::Asm => "inline asm",
+        }
+    }
+}
+
+/// An operation inside an unsafe function or block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsafeOperation {
+    pub op: UnsafeOp,
+    pub line: usize,
+    /// What is dereferenced, called or accessed (e.g. "ptr", "libc::free")
+    pub target: String,
+}
+
+/// An `unsafe` fn, impl, trait or block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsafeSite {
+    pub kind: UnsafeKind,
+    pub file: PathBuf,
+    pub line: usize,
+    /// The unsafe item ("Buffer::get", "Send for Buffer") or, for blocks,
+    /// the enclosing item ("<module>" at the top level)
+    pub name: String,
+    pub operations: Vec<UnsafeOperation>,
+    /// A `// SAFETY:` comment, or a `# Safety` doc section for functions
+    pub documented: bool,
+    /// Trimmed source line
+    pub snippet: String,
+}
+
+/// An unsafe site with its module and distance from the public API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankedUnsafeSite {
+    pub site: UnsafeSite,
+    /// Module path of the file ("crate" for the crate root)
+    pub module: String,
+    /// Calls from the nearest public function, 0 for public items
+    /// (`usize::MAX` if unreachable)
+    pub public_distance: usize,
+}
+
+/// Names declared across the project that make a use unsafe
+#[derive(Debug, Clone, Default)]
+pub struct Declarations {
+    pub unsafe_fns: HashSet<String>,
+    pub foreign_fns: HashSet<String>,
+    pub static_muts: HashSet<String>,
+}
+
+impl Declarations {
+    /// Add the declarations of a source file; files that don't parse add
+    /// nothing
+    pub fn add_source(&mut self, source: &str) {
+        if let Ok(file) = syn::parse_file(source) {
+            self.visit_file(&file);
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for Declarations {
+    fn visit_signature(&mut self, sig: &'ast syn::Signature) {
+        if sig.unsafety.is_some() {
+            self.unsafe_fns.insert(sig.ident.to_string());
+        }
+    }
+
+    fn visit_foreign_item_fn(&mut self, item: &'ast syn::ForeignItemFn) {
+        self.foreign_fns.insert(item.sig.ident.to_string());
+    }
+
+    fn visit_foreign_item_static(&mut self, item: &'ast syn::ForeignItemStatic) {
+        self.static_muts.insert(item.ident.to_string());
+    }
+
+    fn visit_item_static(&mut self, item: &'ast syn::ItemStatic) {
+        if matches!(item.mutability, syn::StaticMutability::Mut(_)) {
+            self.static_muts.insert(item.ident.to_string());
+        }
+        visit::visit_item_static(self, item);
+    }
+}
+
+/// Find unsafe sites in a source file, or `None` if it doesn't parse
+pub fn find_in_source(
+    source: &str,
+    path: &Path,
+    declarations: &Declarations,
+) -> Option<Vec<UnsafeSite>> {
+    let file = syn::parse_file(source).ok()?;
+
+    let mut visitor = UnsafeVisitor {
+        path,
+        lines: source.lines().collect(),
+        declarations,
+        context: Vec::new(),
+        impl_type: None,
+        pointers: HashSet::new(),
+        current: None,
+        sites: Vec::new(),
+    };
+    visitor.visit_file(&file);
+
+    let mut sites = visitor.sites;
+    sites.sort_by_key(|s| s.line);
+    Some(sites)
+}
+
+/// Whether a `// SAFETY:` comment is on `line` or in the comments directly
+/// above it (attributes in between are skipped)
+fn has_safety_comment(lines: &[&str], line: usize) -> bool {
+    let is_safety = |text: &str| text.to_ascii_uppercase().contains("SAFETY:");
+    if let Some(text) = lines.get(line.wrapping_sub(1))
+        && text.find("//").is_some_and(|at| is_safety(&text[at..]))
+    {
+        return true;
+    }
+
+    for text in lines[..line.saturating_sub(1).min(lines.len())]
+        .iter()
+        .rev()
+        .map(|l| l.trim())
+    {
+        if text.starts_with("//") || text.starts_with("/*") || text.starts_with('*') {
+            if is_safety(text) {
+                return true;
+            }
+        } else if !text.starts_with("#[") {
+            break;
+        }
+    }
+    false
+}
+
+/// Whether doc attributes have a `# Safety` section
+fn has_safety_section(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path().is_ident("doc")
+            && matches!(&attr.meta, syn::Meta::NameValue(nv)
+                if quote::quote!(#nv).to_string().contains("# Safety"))
+    })
+}
+
+/// A path as written, without generic arguments
+fn path_name(path: &syn::Path) -> String {
+    let segments: Vec<String> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+    segments.join("::")
+}
+
+fn is_pointer_type(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Ptr(_) => true,
+        syn::Type::Paren(paren) => is_pointer_type(&paren.elem),
+        syn::Type::Group(group) => is_pointer_type(&group.elem),
+        _ => false,
+    }
+}
+
+struct UnsafeVisitor<'a> {
+    path: &'a Path,
+    lines: Vec<&'a str>,
+    declarations: &'a Declarations,
+    /// Enclosing item names
+    context: Vec<String>,
+    /// Self type of the enclosing impl block
+    impl_type: Option<String>,
+    /// Bindings known to hold raw pointers in the current function
+    pointers: HashSet<String>,
+    /// Index of the innermost unsafe fn or block being visited
+    current: Option<usize>,
+    sites: Vec<UnsafeSite>,
+}
+
+impl UnsafeVisitor<'_> {
+    /// Visit inside a named item
+    fn nested(&mut self, name: String, f: impl FnOnce(&mut Self)) {
+        self.context.push(name);
+        f(self);
+        self.context.pop();
+    }
+
+    fn context_name(&self) -> String {
+        self.context
+            .last()
+            .cloned()
+            .unwrap_or_else(|| "<module>".to_string())
+    }
+
+    /// Qualify a method name with the enclosing impl's self type
+    fn method_name(&self, ident: &syn::Ident) -> String {
+        match &self.impl_type {
+            Some(ty) => format!("{}::{}", ty, ident),
+            None => ident.to_string(),
+        }
+    }
+
+    fn add_site(&mut self, kind: UnsafeKind, name: String, line: usize, documented: bool) -> usize {
+        let snippet = self
+            .lines
+            .get(line.wrapping_sub(1))
+            .map(|l| l.trim().chars().take(120).collect())
+            .unwrap_or_default();
+
+        self.sites.push(UnsafeSite {
+            kind,
+            file: self.path.to_path_buf(),
+            line,
+            name,
+            operations: Vec::new(),
+            documented: documented || has_safety_comment(&self.lines, line),
+            snippet,
+        });
+        self.sites.len() - 1
+    }
+
+    /// Visit an unsafe fn or block with its site as the current one
+    fn inside(&mut self, site: usize, f: impl FnOnce(&mut Self)) {
+        let previous = self.current.replace(site);
+        f(self);
+        self.current = previous;
+    }
+
+    fn record(&mut self, op: UnsafeOp, line: usize, target: String) {
+        if let Some(site) = self.current {
+            self.sites[site]
+                .operations
+                .push(UnsafeOperation { op, line, target });
+        }
+    }
+
+    /// Visit a function body with its own set of pointer bindings
+    fn function(
+        &mut self,
+        sig: &syn::Signature,
+        attrs: &[syn::Attribute],
+        name: String,
+        f: impl FnOnce(&mut Self),
+    ) {
+        let outer = std::mem::take(&mut self.pointers);
+        for input in &sig.inputs {
+            if let syn::FnArg::Typed(arg) = input
+                && let syn::Pat::Ident(ident) = &*arg.pat
+                && is_pointer_type(&arg.ty)
+            {
+                self.pointers.insert(ident.ident.to_string());
+            }
+        }
+
+        if sig.unsafety.is_some() {
+            let line = sig.fn_token.span.start().line;
+            let site = self.add_site(
+                UnsafeKind::Function,
+                name.clone(),
+                line,
+                has_safety_section(attrs),
+            );
+            self.nested(name, |v| v.inside(site, f));
+        } else {
+            // A safe function's body is only unsafe inside its blocks
+            let outer_site = self.current.take();
+            self.nested(name, f);
+            self.current = outer_site;
+        }
+        self.pointers = outer;
+    }
+
+    /// Whether an expression visibly evaluates to a raw pointer
+    fn is_pointer(&self, expr: &syn::Expr) -> bool {
+        match expr {
+            syn::Expr::Path(path) => path
+                .path
+                .get_ident()
+                .is_some_and(|ident| self.pointers.contains(&ident.to_string())),
+            syn::Expr::Cast(cast) => is_pointer_type(&cast.ty),
+            syn::Expr::RawAddr(_) => true,
+            syn::Expr::MethodCall(call) => {
+                POINTER_SOURCES.contains(&call.method.to_string().as_str())
+                    && (!matches!(call.method.to_string().as_str(), "offset" | "add" | "sub")
+                        || self.is_pointer(&call.receiver))
+            }
+            syn::Expr::Call(call) => matches!(&*call.func, syn::Expr::Path(path)
+                if path.path.segments.last().is_some_and(|s| s.ident == "null" || s.ident == "null_mut")),
+            syn::Expr::Macro(mac) => mac
+                .mac
+                .path
+                .segments
+                .last()
+                .is_some_and(|s| s.ident == "addr_of" || s.ident == "addr_of_mut"),
+            syn::Expr::Paren(paren) => self.is_pointer(&paren.expr),
+            _ => false,
+        }
+    }
+
+    /// The operation a call to `name` performs, if any
+    fn call_op(&self, path: &syn::Path) -> Option<UnsafeOp> {
+        let last = path.segments.last()?.ident.to_string();
+        let under_ptr = path.segments.iter().any(|s| s.ident == "ptr");
+        let first = path.segments.first()?.ident.to_string();
+
+        if last == "transmute" || last == "transmute_copy" {
+            Some(UnsafeOp::Transmute)
+        } else if first == "libc" || self.declarations.foreign_fns.contains(&last) {
+            Some(UnsafeOp::FfiCall)
+        } else if self.declarations.unsafe_fns.contains(&last)
+            || STD_UNSAFE_FNS.contains(&last.as_str())
+            || (under_ptr && POINTER_FNS.contains(&last.as_str()))
+        {
+            Some(UnsafeOp::UnsafeCall)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for UnsafeVisitor<'_> {
+    fn visit_item_mod(&mut self, item: &'ast syn::ItemMod) {
+        if !is_cfg_test(&item.attrs) {
+            visit::visit_item_mod(self, item);
+        }
+    }
+
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        if is_test_fn(&item.attrs) {
+            return;
+        }
+        let name = item.sig.ident.to_string();
+        self.function(&item.sig, &item.attrs, name, |v| {
+            visit::visit_item_fn(v, item)
+        });
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        if is_cfg_test(&item.attrs) {
+            return;
+        }
+        let self_ty = &item.self_ty;
+        let self_type = quote::quote!(#self_ty).to_string().replace(' ', "");
+        if let Some(unsafety) = item.unsafety {
+            let name = match &item.trait_ {
+                Some((_, path, _)) => format!("{} for {}", path_name(path), self_type),
+                None => self_type.clone(),
+            };
+            self.add_site(UnsafeKind::Impl, name, unsafety.span.start().line, false);
+        }
+        let previous = self.impl_type.replace(self_type);
+        visit::visit_item_impl(self, item);
+        self.impl_type = previous;
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        if is_test_fn(&item.attrs) {
+            return;
+        }
+        let name = self.method_name(&item.sig.ident);
+        self.function(&item.sig, &item.attrs, name, |v| {
+            visit::visit_impl_item_fn(v, item)
+        });
+    }
+
+    fn visit_item_trait(&mut self, item: &'ast syn::ItemTrait) {
+        if let Some(unsafety) = item.unsafety {
+            let documented = has_safety_section(&item.attrs);
+            self.add_site(
+                UnsafeKind::Trait,
+                item.ident.to_string(),
+                unsafety.span.start().line,
+                documented,
+            );
+        }
+        self.nested(item.ident.to_string(), |v| visit::visit_item_trait(v, item));
+    }
+
+    fn visit_trait_item_fn(&mut self, item: &'ast syn::TraitItemFn) {
+        let name = match self.context.last() {
+            Some(trait_name) => format!("{}::{}", trait_name, item.sig.ident),
+            None => item.sig.ident.to_string(),
+        };
+        self.function(&item.sig, &item.attrs, name, |v| {
+            visit::visit_trait_item_fn(v, item)
+        });
+    }
+
+    fn visit_expr_unsafe(&mut self, block: &'ast syn::ExprUnsafe) {
+        let name = self.context_name();
+        let line = block.unsafe_token.span.start().line;
+        let site = self.add_site(UnsafeKind::Block, name, line, false);
+        self.inside(site, |v| visit::visit_expr_unsafe(v, block));
+    }
+
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        visit::visit_local(self, local);
+        let (ident, ty) = match &local.pat {
+            syn::Pat::Ident(ident) => (&ident.ident, None),
+            syn::Pat::Type(typed) => match &*typed.pat {
+                syn::Pat::Ident(ident) => (&ident.ident, Some(&*typed.ty)),
+                _ => return,
+            },
+            _ => return,
+        };
+        let holds_pointer = ty.is_some_and(is_pointer_type)
+            || local
+                .init
+                .as_ref()
+                .is_some_and(|init| self.is_pointer(&init.expr));
+        if holds_pointer {
+            self.pointers.insert(ident.to_string());
+        } else {
+            // Shadowed by a non-pointer
+            self.pointers.remove(&ident.to_string());
+        }
+    }
+
+    fn visit_expr_unary(&mut self, unary: &'ast syn::ExprUnary) {
+        if matches!(unary.op, syn::UnOp::Deref(_)) && self.is_pointer(&unary.expr) {
+            let expr = &unary.expr;
+            let target = quote::quote!(#expr).to_string().replace(' ', "");
+            self.record(UnsafeOp::RawDeref, unary.span().start().line, target);
+        }
+        visit::visit_expr_unary(self, unary);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        if let syn::Expr::Path(path) = &*call.func
+            && let Some(op) = self.call_op(&path.path)
+        {
+            self.record(op, call.span().start().line, path_name(&path.path));
+        }
+        visit::visit_expr_call(self, call);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        let method = call.method.to_string();
+        let unsafe_method = self.declarations.unsafe_fns.contains(&method)
+            || STD_UNSAFE_FNS.contains(&method.as_str())
+            || (POINTER_FNS.contains(&method.as_str()) && self.is_pointer(&call.receiver));
+        if unsafe_method {
+            self.record(
+                UnsafeOp::UnsafeCall,
+                call.method.span().start().line,
+                method,
+            );
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_expr_path(&mut self, path: &'ast syn::ExprPath) {
+        if let Some(last) = path.path.segments.last()
+            && self
+                .declarations
+                .static_muts
+                .contains(&last.ident.to_string())
+        {
+            self.record(
+                UnsafeOp::StaticMut,
+                last.ident.span().start().line,
+                path_name(&path.path),
+            );
+        }
+        visit::visit_expr_path(self, path);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        if let Some(last) = mac.path.segments.last()
+            && matches!(
+                last.ident.to_string().as_str(),
+                "asm" | "global_asm" | "naked_asm"
+            )
+        {
+            self.record(
+                UnsafeOp::Asm,
+                last.ident.span().start().line,
+                format!("{}!", last.ident),
+            );
+        }
+        visit::visit_macro(self, mac);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_unsafe_sites() {
+        let source = r#"
+extern "C" {
+    fn free(ptr: *mut u8);
+}
+
+static mut COUNTER: u32 = 0;
+
+pub struct Buffer { data: *mut u8, len: usize }
+
+// SAFETY: the buffer owns its allocation
+unsafe impl Send for Buffer {}
+unsafe impl Sync for Buffer {}
+
+impl Buffer {
+    /// Read a byte without bounds checks
+    ///
+    /// # Safety
+    /// `index` must be less than `len`
+    pub unsafe fn get(&self, index: usize) -> u8 {
+        *self.data.add(index)
+    }
+
+    pub fn first(&self) -> u8 {
+        let ptr = self.data as *const u8;
+        // SAFETY: buffers are never empty
+        unsafe { *ptr }
+    }
+
+    pub fn release(&mut self) {
+        unsafe {
+            COUNTER += 1;
+            free(self.data);
+            let bits: u32 = std::mem::transmute(1.0f32);
+            self.get(bits as usize);
+        }
+    }
+}
+
+unsafe fn reset() {}
+
+#[cfg(test)]
+mod tests {
+    fn helper() { unsafe { super::reset() } }
+}
+"#;
+        let mut declarations = Declarations::default();
+        declarations.add_source(source);
+        assert!(declarations.foreign_fns.contains("free"));
+        assert!(declarations.static_muts.contains("COUNTER"));
+
+        let sites = find_in_source(source, Path::new("src/lib.rs"), &declarations).unwrap();
+        let found: Vec<_> = sites
+            .iter()
+            .map(|s| (s.kind, s.name.as_str(), s.line, s.documented))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (UnsafeKind::Impl, "Send for Buffer", 11, true),
+                (UnsafeKind::Impl, "Sync for Buffer", 12, false),
+                (UnsafeKind::Function, "Buffer::get", 19, true),
+                (UnsafeKind::Block, "Buffer::first", 26, true),
+                (UnsafeKind::Block, "Buffer::release", 30, false),
+                (UnsafeKind::Function, "reset", 39, false),
+            ]
+        );
+
+        // `self.data.add(index)` isn't a known pointer: fields aren't tracked
+        assert!(sites[2].operations.is_empty());
+        let ops = |site: &UnsafeSite| -> Vec<(UnsafeOp, String)> {
+            site.operations
+                .iter()
+                .map(|o| (o.op, o.target.clone()))
+                .collect()
+        };
+        assert_eq!(
+            ops(&sites[3]),
+            vec![(UnsafeOp::RawDeref, "ptr".to_string())]
+        );
+        assert_eq!(
+            ops(&sites[4]),
+            vec![
+                (UnsafeOp::StaticMut, "COUNTER".to_string()),
+                (UnsafeOp::FfiCall, "free".to_string()),
+                (UnsafeOp::Transmute, "std::mem::transmute".to_string()),
+                (UnsafeOp::UnsafeCall, "get".to_string()),
+            ]
+        );
+        assert_eq!(UnsafeKind::from_label("impl"), Some(UnsafeKind::Impl));
+    }
+}
@@ -0,0 +1,422 @@
+//! Type usage index - where a type is mentioned
+//!
+//! Walks the syntax tree of each file once and records every type-like
+//! name (one starting with an uppercase letter), categorized by where it
+//! appears: impl headers, struct fields, variant payloads, parameters,
+//! return types, type aliases, generic bounds and function bodies. Looking
+//! a type up is then a map lookup.
+//! Files that don't parse fall back to the type strings captured on their
+//! `ParsedItem`s, so broken code still reports its signature-level usages.
+
+use crate::types::*;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use syn::visit::{self, Visit};
+
+/// Usages found in one file, by the name used
+type FileUsages = HashMap<String, Vec<TypeUsage>>;
+
+/// Usages of every type-like name in the analyzed files, built once and
+/// patched per file as files change
+#[derive(Debug, Clone, Default)]
+pub struct UsageIndex {
+    files: HashMap<PathBuf, FileUsages>,
+}
+
+impl UsageIndex {
+    /// Index parsed files, reading their sources from disk
+    pub fn build(files: &[ParsedFile]) -> Self {
+        let mut index = UsageIndex::default();
+        for file in files {
+            index.update(&file.path, Some(file));
+        }
+        index
+    }
+
+    /// Re-index one file, or drop it when `file` is `None` (removed)
+    pub fn update(&mut self, path: &Path, file: Option<&ParsedFile>) {
+        self.files.remove(path);
+        if let Some(file) = file {
+            self.files.insert(path.to_path_buf(), index_file(file));
+        }
+    }
+
+    /// Usages of `type_name` in one file
+    pub fn in_file(&self, path: &Path, type_name: &str) -> &[TypeUsage] {
+        self.files
+            .get(path)
+            .and_then(|usages| usages.get(type_name))
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Order usages highest score first, then by file and line
+pub fn rank(usages: &mut [TypeUsage]) {
+    usages.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.file.cmp(&b.file))
+            .then_with(|| a.line.cmp(&b.line))
+    });
+}
+
+/// Usages in a file: from its source when it parses, otherwise from its
+/// parsed items
+fn index_file(file: &ParsedFile) -> FileUsages {
+    let from_source = if file.parse_errors.is_empty() {
+        std::fs::read_to_string(&file.path)
+            .ok()
+            .and_then(|source| index_source(&source, &file.path))
+    } else {
+        None
+    };
+    from_source.unwrap_or_else(|| index_items(file.items_and_methods()))
+}
+
+/// Find usages of `type_name` in a source file, or `None` if it doesn't parse
+pub fn find_in_source(source: &str, path: &Path, type_name: &str) -> Option<Vec<TypeUsage>> {
+    let mut usages = index_source(source, path)?;
+    Some(usages.remove(type_name).unwrap_or_default())
+}
+
+/// Usages of every type-like name in a source file, or `None` if it
+/// doesn't parse
+fn index_source(source: &str, path: &Path) -> Option<FileUsages> {
+    let file = syn::parse_file(source).ok()?;
+
+    let mut visitor = UsageVisitor {
+        path,
+        lines: source.lines().collect(),
+        kind: TypeUsageKind::Other,
+        context: Vec::new(),
+        impl_type: None,
+        usages: HashMap::new(),
+    };
+    visitor.visit_file(&file);
+
+    let mut usages = visitor.usages;
+    for found in usages.values_mut() {
+        found.dedup_by(|a, b| a.line == b.line && a.kind == b.kind);
+    }
+    Some(usages)
+}
+
+/// Usages of every type-like name in the type strings of already-parsed
+/// items
+fn index_items<'a>(items: impl IntoIterator<Item = &'a ParsedItem>) -> FileUsages {
+    let word = Regex::new(r"[A-Za-z_]\w*").expect("Invalid regex");
+
+    let mut usages: FileUsages = HashMap::new();
+    for item in items {
+        let mut types: Vec<(TypeUsageKind, &str)> = Vec::new();
+        match &item.kind {
+            ItemKind::Struct { fields, .. } => {
+                types.extend(fields.iter().map(|f| (TypeUsageKind::Field, f.ty.as_str())));
+            }
+            ItemKind::Enum { variants } => {
+                types.extend(
+                    variants
+                        .iter()
+                        .flat_map(|v| &v.fields)
+                        .map(|f| (TypeUsageKind::VariantPayload, f.ty.as_str())),
+                );
+            }
+            ItemKind::Function {
+                parameters,
+                return_type,
+                ..
+            } => {
+                types.extend(
+                    parameters
+                        .iter()
+                        .filter(|p| !p.is_self)
+                        .map(|p| (TypeUsageKind::Parameter, p.ty.as_str())),
+                );
+                if let Some(ret) = return_type {
+                    types.push((TypeUsageKind::ReturnType, ret));
+                }
+            }
+            ItemKind::Impl {
+                self_type,
+                trait_name,
+                ..
+            } => {
+                types.push((TypeUsageKind::ImplHeader, self_type));
+                if let Some(trait_name) = trait_name {
+                    types.push((TypeUsageKind::ImplHeader, trait_name));
+                }
+            }
+            ItemKind::TypeAlias { ty } => types.push((TypeUsageKind::TypeAlias, ty)),
+            ItemKind::Const { ty } | ItemKind::Static { ty, .. } => {
+                types.push((TypeUsageKind::Other, ty))
+            }
+            _ => {}
+        }
+
+        for (kind, ty) in types {
+            let mut names: Vec<&str> = word.find_iter(ty).map(|m| m.as_str()).collect();
+            names.sort_unstable();
+            names.dedup();
+            for name in names.into_iter().filter(|name| is_type_like(name)) {
+                usages.entry(name.to_string()).or_default().push(TypeUsage {
+                    kind,
+                    file: item.file_path.clone(),
+                    line: item.span.start_line,
+                    context: item.name.clone(),
+                    snippet: ty.to_string(),
+                    score: kind.weight(),
+                });
+            }
+        }
+    }
+
+    usages
+}
+
+/// Types, traits and variants are CamelCase; indexing only those keeps
+/// local variables and function calls out of the index
+fn is_type_like(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+}
+
+struct UsageVisitor<'a> {
+    path: &'a Path,
+    lines: Vec<&'a str>,
+    /// Category for paths found in the current position
+    kind: TypeUsageKind,
+    /// Enclosing item names
+    context: Vec<String>,
+    /// Self type of the enclosing impl block
+    impl_type: Option<String>,
+    usages: FileUsages,
+}
+
+impl UsageVisitor<'_> {
+    /// Visit with a category; everything inside a body stays `Expression`
+    fn scoped(&mut self, kind: TypeUsageKind, f: impl FnOnce(&mut Self)) {
+        if self.kind == TypeUsageKind::Expression {
+            f(self);
+            return;
+        }
+        let previous = std::mem::replace(&mut self.kind, kind);
+        f(self);
+        self.kind = previous;
+    }
+
+    /// Visit inside a named item
+    fn nested(&mut self, name: String, f: impl FnOnce(&mut Self)) {
+        self.context.push(name);
+        f(self);
+        self.context.pop();
+    }
+
+    fn record(&mut self, name: String, line: usize) {
+        if !is_type_like(&name) {
+            return;
+        }
+        let snippet = self
+            .lines
+            .get(line.wrapping_sub(1))
+            .map(|l| l.trim().chars().take(120).collect())
+            .unwrap_or_default();
+
+        self.usages.entry(name).or_default().push(TypeUsage {
+            kind: self.kind,
+            file: self.path.to_path_buf(),
+            line,
+            context: self
+                .context
+                .last()
+                .cloned()
+                .unwrap_or_else(|| "<module>".to_string()),
+            snippet,
+            score: self.kind.weight(),
+        });
+    }
+
+    /// Record identifiers inside macro tokens (`vec![Foo::new()]`)
+    fn scan_tokens(&mut self, tokens: proc_macro2::TokenStream) {
+        for token in tokens {
+            match token {
+                proc_macro2::TokenTree::Ident(ident) => {
+                    self.record(ident.to_string(), ident.span().start().line);
+                }
+                proc_macro2::TokenTree::Group(group) => self.scan_tokens(group.stream()),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for UsageVisitor<'_> {
+    fn visit_path(&mut self, path: &'ast syn::Path) {
+        for (index, segment) in path.segments.iter().enumerate() {
+            // A name repeated in one path (`Config::Config`) is one usage
+            if path
+                .segments
+                .iter()
+                .take(index)
+                .any(|s| s.ident == segment.ident)
+            {
+                continue;
+            }
+            self.record(segment.ident.to_string(), segment.ident.span().start().line);
+        }
+        visit::visit_path(self, path);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        visit::visit_macro(self, mac);
+        self.scan_tokens(mac.tokens.clone());
+    }
+
+    fn visit_item_struct(&mut self, item: &'ast syn::ItemStruct) {
+        self.nested(item.ident.to_string(), |v| {
+            visit::visit_item_struct(v, item)
+        });
+    }
+
+    fn visit_item_enum(&mut self, item: &'ast syn::ItemEnum) {
+        self.nested(item.ident.to_string(), |v| visit::visit_item_enum(v, item));
+    }
+
+    fn visit_variant(&mut self, variant: &'ast syn::Variant) {
+        self.scoped(TypeUsageKind::VariantPayload, |v| {
+            visit::visit_variant(v, variant)
+        });
+    }
+
+    fn visit_field(&mut self, field: &'ast syn::Field) {
+        let kind = if self.kind == TypeUsageKind::VariantPayload {
+            TypeUsageKind::VariantPayload
+        } else {
+            TypeUsageKind::Field
+        };
+        self.scoped(kind, |v| visit::visit_field(v, field));
+    }
+
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        self.nested(item.sig.ident.to_string(), |v| {
+            visit::visit_item_fn(v, item)
+        });
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        let name = match &self.impl_type {
+            Some(ty) => format!("{}::{}", ty, item.sig.ident),
+            None => item.sig.ident.to_string(),
+        };
+        self.nested(name, |v| visit::visit_impl_item_fn(v, item));
+    }
+
+    fn visit_trait_item_fn(&mut self, item: &'ast syn::TraitItemFn) {
+        let name = match self.context.last() {
+            Some(trait_name) => format!("{}::{}", trait_name, item.sig.ident),
+            None => item.sig.ident.to_string(),
+        };
+        self.nested(name, |v| visit::visit_trait_item_fn(v, item));
+    }
+
+    fn visit_fn_arg(&mut self, arg: &'ast syn::FnArg) {
+        self.scoped(TypeUsageKind::Parameter, |v| visit::visit_fn_arg(v, arg));
+    }
+
+    fn visit_return_type(&mut self, ret: &'ast syn::ReturnType) {
+        self.scoped(TypeUsageKind::ReturnType, |v| {
+            visit::visit_return_type(v, ret)
+        });
+    }
+
+    fn visit_generics(&mut self, generics: &'ast syn::Generics) {
+        self.scoped(TypeUsageKind::GenericBound, |v| {
+            visit::visit_generics(v, generics)
+        });
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        let self_ty = &item.self_ty;
+        let self_type = quote::quote!(#self_ty).to_string();
+        let previous = self.impl_type.replace(self_type.clone());
+
+        self.nested(self_type, |v| {
+            v.visit_generics(&item.generics);
+            v.scoped(TypeUsageKind::ImplHeader, |v| {
+                if let Some((_, path, _)) = &item.trait_ {
+                    v.visit_path(path);
+                }
+                v.visit_type(&item.self_ty);
+            });
+            for impl_item in &item.items {
+                v.visit_impl_item(impl_item);
+            }
+        });
+
+        self.impl_type = previous;
+    }
+
+    fn visit_item_trait(&mut self, item: &'ast syn::ItemTrait) {
+        self.nested(item.ident.to_string(), |v| {
+            v.visit_generics(&item.generics);
+            v.scoped(TypeUsageKind::GenericBound, |v| {
+                for bound in &item.supertraits {
+                    v.visit_type_param_bound(bound);
+                }
+            });
+            for trait_item in &item.items {
+                v.visit_trait_item(trait_item);
+            }
+        });
+    }
+
+    fn visit_item_type(&mut self, item: &'ast syn::ItemType) {
+        self.nested(item.ident.to_string(), |v| {
+            v.visit_generics(&item.generics);
+            v.scoped(TypeUsageKind::TypeAlias, |v| v.visit_type(&item.ty));
+        });
+    }
+
+    fn visit_block(&mut self, block: &'ast syn::Block) {
+        self.scoped(TypeUsageKind::Expression, |v| visit::visit_block(v, block));
+    }
+
+    fn visit_expr(&mut self, expr: &'ast syn::Expr) {
+        self.scoped(TypeUsageKind::Expression, |v| visit::visit_expr(v, expr));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_categories() {
+        let source = r#"
+struct Holder { inner: Target }
+enum Either { Left(Target), Right }
+impl Clone for Target { fn clone(&self) -> Target { Target::new() } }
+fn take<T: Into<Target>>(value: Vec<Target>) {}
+type Alias = Option<Target>;
+fn body() { let _ = vec![Target::default()]; }
+"#;
+        let usages = find_in_source(source, Path::new("lib.rs"), "Target").unwrap();
+        let kinds: Vec<(usize, TypeUsageKind)> = usages.iter().map(|u| (u.line, u.kind)).collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                (2, TypeUsageKind::Field),
+                (3, TypeUsageKind::VariantPayload),
+                (4, TypeUsageKind::ImplHeader),
+                (4, TypeUsageKind::ReturnType),
+                (4, TypeUsageKind::Expression),
+                (5, TypeUsageKind::GenericBound),
+                (5, TypeUsageKind::Parameter),
+                (6, TypeUsageKind::TypeAlias),
+                (7, TypeUsageKind::Expression),
+            ]
+        );
+        assert_eq!(usages[3].context, "Target::clone");
+    }
+}
@@ -0,0 +1,389 @@
+//! Watch mode - keep an analysis current as files change
+//!
+//! [`ProjectWatcher`] turns filesystem events under a project into batches
+//! of changed paths, and [`apply_changes`] feeds them to
+//! [`SemanticGravity::update_file`], which re-parses only those files.
+//! A `Cargo.toml` change can add or remove crates and targets, so it
+//! triggers a full `analyze_project` instead.
+//!
+//! [`LiveAnalysis`] runs that loop on a background thread and shares the
+//! result, which is how the MCP server answers from a current analysis
+//! without re-analyzing per request.
+
+use crate::gravity::{FileUpdate, GravityError, SemanticGravity};
+use crate::parser;
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, RwLock, RwLockReadGuard};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Quiet period that ends a batch of events (editors write in bursts)
+pub const DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Top-level directories that never hold analyzed sources
+const IGNORED_DIRS: &[&str] = &["target", ".git"];
+
+#[derive(Error, Debug)]
+pub enum WatchError {
+    #[error("Watch error: {0}")]
+    Notify(#[from] notify::Error),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Analysis error: {0}")]
+    Gravity(#[from] GravityError),
+    #[error("Watcher stopped")]
+    Stopped,
+}
+
+/// Filesystem watcher for the sources and manifests of a project
+pub struct ProjectWatcher {
+    // Dropping the watcher ends the event stream
+    watcher: notify::RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+    /// Watched root, canonical like the paths in events
+    root: PathBuf,
+    /// Top-level directories watched recursively
+    dirs: Vec<PathBuf>,
+}
+
+impl ProjectWatcher {
+    /// Watch `root`: its manifest non-recursively, and every top-level
+    /// directory except `target/` and hidden ones recursively, including
+    /// directories created later (`examples/`, a new member)
+    pub fn new(root: &Path) -> Result<Self, WatchError> {
+        let (tx, events) = mpsc::channel();
+        let root = root.canonicalize()?;
+        let mut watcher = ProjectWatcher {
+            watcher: notify::recommended_watcher(tx)?,
+            events,
+            root,
+            dirs: Vec::new(),
+        };
+
+        watcher
+            .watcher
+            .watch(&watcher.root, RecursiveMode::NonRecursive)?;
+        for entry in std::fs::read_dir(&watcher.root)? {
+            let path = entry?.path();
+            if watcher.is_unwatched_dir(&path) {
+                watcher.watcher.watch(&path, RecursiveMode::Recursive)?;
+                watcher.dirs.push(path);
+            }
+        }
+
+        Ok(watcher)
+    }
+
+    /// A top-level source directory not watched yet
+    fn is_unwatched_dir(&self, path: &Path) -> bool {
+        let Some(name) = path.file_name().map(|name| name.to_string_lossy()) else {
+            return false;
+        };
+        path.parent() == Some(self.root.as_path())
+            && path.is_dir()
+            && !name.starts_with('.')
+            && !IGNORED_DIRS.contains(&name.as_ref())
+            && !self.dirs.iter().any(|dir| dir == path)
+    }
+
+    /// Watch a directory that appeared at the root, returning the Rust
+    /// sources and manifests already in it: they may have been written
+    /// before the watch started
+    fn watch_new_dir(&mut self, path: &Path) -> Vec<PathBuf> {
+        if self.watcher.watch(path, RecursiveMode::Recursive).is_err() {
+            return Vec::new();
+        }
+        self.dirs.push(path.to_path_buf());
+
+        walkdir::WalkDir::new(path)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(walkdir::DirEntry::into_path)
+            .filter(|path| path.is_file() && is_relevant(&self.root, path))
+            .collect()
+    }
+
+    /// Block until a Rust source or manifest changes, then collect further
+    /// changes until `debounce` passes without one. Paths are deduplicated.
+    pub fn next_batch(&mut self, debounce: Duration) -> Result<Vec<PathBuf>, WatchError> {
+        let mut changed: Vec<PathBuf> = Vec::new();
+
+        loop {
+            let event = if changed.is_empty() {
+                self.events.recv().map_err(|_| WatchError::Stopped)?
+            } else {
+                match self.events.recv_timeout(debounce) {
+                    Ok(event) => event,
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => return Err(WatchError::Stopped),
+                }
+            };
+
+            // A failed event (e.g. an overflowed queue) shouldn't end watching
+            let Ok(event) = event else {
+                continue;
+            };
+            if matches!(event.kind, EventKind::Access(_)) {
+                continue;
+            }
+            let mut paths = event.paths;
+            for dir in paths.clone() {
+                if self.is_unwatched_dir(&dir) {
+                    paths.extend(self.watch_new_dir(&dir));
+                }
+            }
+            for path in paths {
+                if is_relevant(&self.root, &path) && !changed.contains(&path) {
+                    changed.push(path);
+                }
+            }
+        }
+
+        Ok(changed)
+    }
+}
+
+/// Rust sources and manifests outside build output
+fn is_relevant(root: &Path, path: &Path) -> bool {
+    !parser::is_build_output(root, path)
+        && (path.extension().is_some_and(|ext| ext == "rs") || is_manifest(path))
+}
+
+fn is_manifest(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == "Cargo.toml")
+}
+
+/// What applying a batch of changes did
+#[derive(Debug)]
+pub struct UpdateReport {
+    /// Files re-analyzed in place
+    pub updates: Vec<FileUpdate>,
+    /// Files that couldn't be updated, with why; the rest of the batch is
+    /// still applied
+    pub failures: Vec<(PathBuf, GravityError)>,
+    /// Whether a manifest change forced a full re-analysis
+    pub reanalyzed: bool,
+    pub elapsed: Duration,
+}
+
+/// Bring `gravity` up to date with `changed` paths: a full re-analysis if a
+/// manifest changed, otherwise an in-place update per file. A file that
+/// fails to update doesn't stop the others; it is listed in the report.
+pub fn apply_changes(
+    gravity: &mut SemanticGravity,
+    changed: &[PathBuf],
+) -> Result<UpdateReport, GravityError> {
+    let start = Instant::now();
+
+    if needs_reanalysis(changed) {
+        let root = gravity.root().to_path_buf();
+        gravity.analyze_project(&root)?;
+        return Ok(UpdateReport {
+            updates: Vec::new(),
+            failures: Vec::new(),
+            reanalyzed: true,
+            elapsed: start.elapsed(),
+        });
+    }
+
+    let mut updates = Vec::new();
+    let mut failures = Vec::new();
+    for path in changed {
+        match gravity.update_file(path) {
+            Ok(Some(update)) => updates.push(update),
+            Ok(None) => {}
+            Err(e) => failures.push((path.clone(), e)),
+        }
+    }
+
+    Ok(UpdateReport {
+        updates,
+        failures,
+        reanalyzed: false,
+        elapsed: start.elapsed(),
+    })
+}
+
+/// Whether a batch changes a manifest, so [`apply_changes`] re-analyzes
+/// the whole project
+pub fn needs_reanalysis(changed: &[PathBuf]) -> bool {
+    changed.iter().any(|path| is_manifest(path))
+}
+
+/// An analysis kept current by a background watcher thread
+#[derive(Clone)]
+pub struct LiveAnalysis {
+    gravity: Arc<RwLock<SemanticGravity>>,
+}
+
+impl LiveAnalysis {
+    /// Start watching the root of an analyzed `gravity`. File updates are
+    /// applied in place under the write lock, so readers wait for a batch
+    /// rather than see half of it; a full re-analysis runs on a copy while
+    /// readers keep the old analysis. Files that fail to update are
+    /// reported on stderr after the rest of their batch is applied; a
+    /// failed re-analysis leaves the previous analysis in place.
+    pub fn start(gravity: SemanticGravity) -> Result<Self, WatchError> {
+        let mut watcher = ProjectWatcher::new(gravity.root())?;
+        let shared = Arc::new(RwLock::new(gravity));
+
+        let live = Arc::clone(&shared);
+        std::thread::spawn(move || {
+            while let Ok(changed) = watcher.next_batch(DEBOUNCE) {
+                let result = if needs_reanalysis(&changed) {
+                    let mut gravity = match live.read() {
+                        Ok(current) => current.clone(),
+                        Err(_) => return,
+                    };
+                    apply_changes(&mut gravity, &changed).inspect(|_| {
+                        if let Ok(mut current) = live.write() {
+                            *current = gravity;
+                        }
+                    })
+                } else {
+                    match live.write() {
+                        Ok(mut current) => apply_changes(&mut current, &changed),
+                        Err(_) => return,
+                    }
+                };
+                match result {
+                    Ok(report) => {
+                        for (path, e) in report.failures {
+                            eprintln!("Warning: Could not update {}: {}", path.display(), e);
+                        }
+                    }
+                    Err(e) => eprintln!("Warning: Could not update analysis: {}", e),
+                }
+            }
+        });
+
+        Ok(Self { gravity: shared })
+    }
+
+    /// The current analysis
+    pub fn read(&self) -> RwLockReadGuard<'_, SemanticGravity> {
+        self.gravity
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Root of the watched project
+    pub fn root(&self) -> PathBuf {
+        self.read().root().to_path_buf()
+    }
+
+    /// Load the semantic index into the shared analysis unless it has one.
+    /// From then on each update embeds only the items it changes, so
+    /// searches don't re-embed the project. Returns the number embedded.
+    pub fn load_semantic_index(&self) -> Result<usize, GravityError> {
+        if self.read().get_semantic_index().is_some() {
+            return Ok(0);
+        }
+        let mut gravity = self
+            .gravity
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // Another search may have loaded it while this one waited
+        if gravity.get_semantic_index().is_some() {
+            return Ok(0);
+        }
+        let root = gravity.root().to_path_buf();
+        gravity.refresh_semantic_index(&root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_incremental_matches_full_analysis() {
+        let root = std::env::temp_dir().join(format!("cargomap-watch-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src/lib.rs"),
+            "mod util;\npub fn run() { util::helper(); }\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/util.rs"), "pub fn helper() {}\n").unwrap();
+
+        let mut gravity = SemanticGravity::new();
+        gravity.analyze_project(&root).unwrap();
+
+        std::fs::write(
+            root.join("src/util.rs"),
+            "pub struct Cache;\nimpl Cache { pub fn get(&self) { helper(); } }\npub fn helper() { regex::Regex::new(\"x\"); }\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/extra.rs"), "pub fn spare() {}\n").unwrap();
+        // Unreadable as a file, so its update fails without stopping the batch
+        std::fs::create_dir_all(root.join("src/broken.rs")).unwrap();
+        let report = apply_changes(
+            &mut gravity,
+            &[
+                root.join("src/broken.rs"),
+                root.join("src/util.rs"),
+                root.join("src/extra.rs"),
+            ],
+        )
+        .unwrap();
+        std::fs::remove_dir(root.join("src/broken.rs")).unwrap();
+        let changes: Vec<_> = report.updates.iter().map(|u| u.change).collect();
+        assert_eq!(
+            changes,
+            vec![
+                crate::gravity::FileChange::Modified,
+                crate::gravity::FileChange::Added
+            ]
+        );
+        let failed: Vec<&PathBuf> = report.failures.iter().map(|(path, _)| path).collect();
+        assert_eq!(failed, vec![&root.join("src/broken.rs")]);
+
+        let mut fresh = SemanticGravity::new();
+        fresh.analyze_project(&root).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let item_names = |g: &SemanticGravity| {
+            let mut names: Vec<String> = g
+                .get_files()
+                .iter()
+                .flat_map(|f| f.items.iter().map(|i| i.name.clone()))
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(item_names(&gravity), item_names(&fresh));
+        assert_eq!(gravity.get_impls_for_type("Cache").len(), 1);
+        assert_eq!(
+            gravity.find_call_sites("helper").len(),
+            fresh.find_call_sites("helper").len()
+        );
+        assert_eq!(
+            gravity.get_external_usages("regex::Regex::new").len(),
+            fresh.get_external_usages("regex::Regex::new").len()
+        );
+        assert_eq!(gravity.get_external_usages("regex::Regex::new").len(), 1);
+    }
+
+    #[test]
+    fn test_watch_directories_created_later() {
+        let root = std::env::temp_dir().join(format!("cargomap-newdir-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        let mut watcher = ProjectWatcher::new(&root).unwrap();
+
+        std::fs::create_dir_all(root.join("examples")).unwrap();
+        std::fs::write(root.join("examples/demo.rs"), "fn main() {}\n").unwrap();
+        let changed = watcher.next_batch(DEBOUNCE).unwrap();
+        let canonical = root.canonicalize().unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(changed, vec![canonical.join("examples/demo.rs")]);
+    }
+}
@@ -0,0 +1,326 @@
+//! Cargo workspace discovery
+//!
+//! Finds the crates that make up the analyzed project so each member gets
+//! its own module paths and entry point, and paths into sibling members are
+//! treated as internal cross-crate references rather than external symbols.
+//!
+//! Members come from `cargo metadata --no-deps` when cargo is available,
+//! falling back to reading `[workspace] members` (with trailing `*` globs)
+//! and each member's manifest directly (see `targets`). A directory
+//! without a manifest is analyzed as a single crate.
+
+use crate::targets::{self, CrateTarget, TargetKind};
+use cargo_metadata::MetadataCommand;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum WorkspaceError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("TOML parse error: {0}")]
+    Toml(#[from] toml::de::Error),
+}
+
+/// One crate of the workspace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceMember {
+    /// Package name as written in Cargo.toml (e.g. `my-crate`)
+    pub package: String,
+    /// Name the crate is referred to by in paths (e.g. `my_crate`)
+    pub crate_name: String,
+    /// Directory containing the member's Cargo.toml
+    pub root: PathBuf,
+    /// Library root, or the main binary if there is no library
+    pub entry: PathBuf,
+    /// Every build target (lib, bins, examples, benches, tests)
+    pub targets: Vec<CrateTarget>,
+    /// Crates its manifest depends on, by the name paths use (renames
+    /// applied, `-` as `_`), dev and build dependencies included
+    pub dependencies: Vec<String>,
+}
+
+impl WorkspaceMember {
+    fn new(
+        package: String,
+        root: PathBuf,
+        targets: Vec<CrateTarget>,
+        dependencies: Vec<String>,
+    ) -> Self {
+        let primary = targets
+            .iter()
+            .find(|t| t.kind == TargetKind::Lib)
+            .or_else(|| targets.iter().find(|t| t.kind == TargetKind::Bin));
+        // Paths name the library (or binary) crate, not the package
+        let crate_name = primary
+            .map_or(package.as_str(), |t| t.name.as_str())
+            .replace('-', "_");
+        let entry = primary
+            .map(|t| t.entry.clone())
+            .unwrap_or_else(|| root.join("src/lib.rs"));
+
+        Self {
+            package,
+            crate_name,
+            root,
+            entry,
+            targets,
+            dependencies,
+        }
+    }
+
+    /// Targets of the given kinds
+    pub fn targets_of<'a>(
+        &'a self,
+        kinds: &'a [TargetKind],
+    ) -> impl Iterator<Item = &'a CrateTarget> {
+        self.targets.iter().filter(|t| kinds.contains(&t.kind))
+    }
+}
+
+/// The crates under an analyzed directory
+#[derive(Debug, Clone, Default)]
+pub struct Workspace {
+    members: Vec<WorkspaceMember>,
+}
+
+impl Workspace {
+    /// Discover the members under `root`
+    pub fn discover(root: &Path) -> Result<Self, WorkspaceError> {
+        if !root.join("Cargo.toml").exists() {
+            return Ok(Self::single(root));
+        }
+
+        match Self::from_metadata(root) {
+            Some(workspace) if !workspace.members.is_empty() => Ok(workspace),
+            _ => Self::from_manifests(root),
+        }
+    }
+
+    /// A lone crate rooted at `root`
+    fn single(root: &Path) -> Self {
+        let package = root
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "crate".to_string());
+        let targets = targets::from_manifest(root, None);
+        Self {
+            members: vec![WorkspaceMember::new(
+                package,
+                root.to_path_buf(),
+                targets,
+                Vec::new(),
+            )],
+        }
+    }
+
+    /// Members reported by `cargo metadata`, limited to those under `root`
+    fn from_metadata(root: &Path) -> Option<Self> {
+        let metadata = MetadataCommand::new()
+            .manifest_path(root.join("Cargo.toml"))
+            .no_deps()
+            .exec()
+            .ok()?;
+        let canonical_root = root.canonicalize().ok()?;
+
+        let members = metadata
+            .workspace_packages()
+            .into_iter()
+            .filter_map(|package| {
+                let member_root = package.manifest_path.parent()?.as_std_path();
+                // Keep paths spelled relative to `root`, like the parsed files
+                let relative = member_root.strip_prefix(&canonical_root).ok()?;
+                let member_root = root.join(relative);
+
+                let targets = targets::from_metadata(&package.targets, |path| {
+                    Some(root.join(path.strip_prefix(&canonical_root).ok()?))
+                });
+
+                let mut dependencies: Vec<String> = package
+                    .dependencies
+                    .iter()
+                    .map(|dep| dep.rename.as_ref().unwrap_or(&dep.name).replace('-', "_"))
+                    .collect();
+                dependencies.sort();
+                dependencies.dedup();
+
+                Some(WorkspaceMember::new(
+                    package.name.to_string(),
+                    member_root,
+                    targets,
+                    dependencies,
+                ))
+            })
+            .collect();
+
+        Some(Self { members })
+    }
+
+    /// Members read straight from the manifests, for when cargo isn't usable
+    pub fn from_manifests(root: &Path) -> Result<Self, WorkspaceError> {
+        let manifest: toml::Value =
+            toml::from_str(&std::fs::read_to_string(root.join("Cargo.toml"))?)?;
+
+        let mut member_dirs = Vec::new();
+        if manifest.get("package").is_some() {
+            member_dirs.push(root.to_path_buf());
+        }
+        if let Some(workspace) = manifest.get("workspace") {
+            let excluded: Vec<PathBuf> = string_list(workspace.get("exclude"))
+                .iter()
+                .map(|p| root.join(p))
+                .collect();
+            for pattern in string_list(workspace.get("members")) {
+                for dir in expand_member(root, &pattern)? {
+                    if !excluded.contains(&dir) && !member_dirs.contains(&dir) {
+                        member_dirs.push(dir);
+                    }
+                }
+            }
+        }
+
+        let mut members = Vec::new();
+        for dir in member_dirs {
+            let Ok(content) = std::fs::read_to_string(dir.join("Cargo.toml")) else {
+                continue;
+            };
+            let manifest: toml::Value = toml::from_str(&content)?;
+            let Some(package) = manifest
+                .get("package")
+                .and_then(|p| p.get("name"))
+                .and_then(|n| n.as_str())
+            else {
+                continue;
+            };
+
+            let targets = targets::from_manifest(&dir, Some(&manifest));
+            let dependencies = manifest_dependencies(&manifest);
+            members.push(WorkspaceMember::new(
+                package.to_string(),
+                dir,
+                targets,
+                dependencies,
+            ));
+        }
+
+        if members.is_empty() {
+            return Ok(Self::single(root));
+        }
+        Ok(Self { members })
+    }
+
+    /// All members
+    pub fn members(&self) -> &[WorkspaceMember] {
+        &self.members
+    }
+
+    /// Whether there is more than one crate
+    pub fn is_multi_crate(&self) -> bool {
+        self.members.len() > 1
+    }
+
+    /// The member a file belongs to (innermost member directory)
+    pub fn member_for(&self, path: &Path) -> Option<&WorkspaceMember> {
+        self.members
+            .iter()
+            .filter(|m| path.starts_with(&m.root))
+            .max_by_key(|m| m.root.components().count())
+    }
+
+    /// The member referred to by a crate name in paths (`my_crate::...`)
+    pub fn member_named(&self, crate_name: &str) -> Option<&WorkspaceMember> {
+        self.members.iter().find(|m| m.crate_name == crate_name)
+    }
+}
+
+fn string_list(value: Option<&toml::Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|i| i.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Dependency names declared in a manifest: `[dependencies]`,
+/// `[dev-dependencies]` and `[build-dependencies]`, also under `[target.*]`
+fn manifest_dependencies(manifest: &toml::Value) -> Vec<String> {
+    const TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+    let mut sections: Vec<&toml::Value> = vec![manifest];
+    if let Some(targets) = manifest.get("target").and_then(|t| t.as_table()) {
+        sections.extend(targets.values());
+    }
+    let mut dependencies: Vec<String> = sections
+        .iter()
+        .flat_map(|section| TABLES.iter().filter_map(|table| section.get(table)))
+        .filter_map(|table| table.as_table())
+        .flat_map(|table| table.keys().map(|name| name.replace('-', "_")))
+        .collect();
+    dependencies.sort();
+    dependencies.dedup();
+    dependencies
+}
+
+/// Expand a member entry; only a trailing `*` (`crates/*`) is supported
+fn expand_member(root: &Path, pattern: &str) -> Result<Vec<PathBuf>, WorkspaceError> {
+    let Some(parent) = pattern.strip_suffix("/*") else {
+        return Ok(vec![root.join(pattern)]);
+    };
+
+    let mut dirs: Vec<PathBuf> = std::fs::read_dir(root.join(parent))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.join("Cargo.toml").exists())
+        .collect();
+    dirs.sort();
+    Ok(dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_members() {
+        let root = std::env::temp_dir().join(format!("cargomap-ws-{}", std::process::id()));
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write(
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\", \"tools/cli\"]\nexclude = [\"crates/old\"]\n",
+        );
+        write(
+            "crates/core-lib/Cargo.toml",
+            "[package]\nname = \"core-lib\"\n[dependencies]\nserde-json = \"1\"\n[target.'cfg(unix)'.dev-dependencies]\nrusqlite = \"0.31\"\n",
+        );
+        write("crates/core-lib/src/lib.rs", "");
+        write("crates/old/Cargo.toml", "[package]\nname = \"old\"\n");
+        write(
+            "tools/cli/Cargo.toml",
+            "[package]\nname = \"cli\"\n[lib]\nname = \"cli_support\"\npath = \"src/support.rs\"\n",
+        );
+
+        let workspace = Workspace::from_manifests(&root).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let names: Vec<&str> = workspace
+            .members()
+            .iter()
+            .map(|m| m.crate_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["core_lib", "cli_support"]);
+
+        let core = workspace.member_named("core_lib").unwrap();
+        assert_eq!(core.entry, root.join("crates/core-lib/src/lib.rs"));
+        assert_eq!(core.dependencies, vec!["rusqlite", "serde_json"]);
+        let file = root.join("tools/cli/src/support.rs");
+        assert_eq!(workspace.member_for(&file).unwrap().package, "cli");
+    }
+}